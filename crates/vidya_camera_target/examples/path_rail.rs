@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use bevy::prelude::shape::Icosphere;
+
+// Corridor constants
+const LENGTH: f32 = 40.0;
+const WIDTH: f32 = 4.0;
+const SPEED: f32 = 0.3;
+
+/// Example where the camera glides along a rail beside a character walking down a corridor.
+/// Left and right arrow keys walk, and P switches between path modes.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::new(Duration::from_secs_f64(1.0/20.0)))
+        .add_plugin(CameraTargetPlugin)
+        .add_startup_system(startup)
+        .add_fixed_system(walk_character)
+        .add_system(switch_mode)
+        .run();
+}
+
+/// Marker component for the walking character
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Character;
+
+/// Spawns corridor, character and camera
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>
+) {
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns corridor floor and back wall
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(shape::Box::new(LENGTH, 0.2, WIDTH).into()),
+        material: materials.add(Color::GRAY.into()),
+        transform: Transform::from_xyz(0.0, -0.1, 0.0),
+        ..default()
+    });
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(shape::Box::new(LENGTH, 4.0, 0.2).into()),
+        material: materials.add(Color::DARK_GRAY.into()),
+        transform: Transform::from_xyz(0.0, 2.0, -WIDTH / 2.0),
+        ..default()
+    });
+
+    // Spawns character
+    let character = commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Icosphere { radius: 0.5, subdivisions: 3 }.into()),
+            material: materials.add(Color::RED.into()),
+            transform: Transform::from_xyz(-LENGTH / 2.0, 0.5, 0.0),
+            ..default()
+        })
+        .insert((
+            Character,
+            CurrentTransform(Transform::from_xyz(-LENGTH / 2.0, 0.5, 0.0)),
+            PreviousTransform::default()
+        ))
+        .id();
+
+    // Spawns camera on a rail that swings out toward the middle of the corridor
+    let path = CameraPath::new()
+        .with_point(Vec3::new(-LENGTH / 2.0, 2.0, 6.0))
+        .with_point(Vec3::new(-LENGTH / 4.0, 3.0, 8.0))
+        .with_point(Vec3::new(0.0, 5.0, 12.0))
+        .with_point(Vec3::new(LENGTH / 4.0, 3.0, 8.0))
+        .with_point(Vec3::new(LENGTH / 2.0, 2.0, 6.0));
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(character),
+            target_style: path.into_style(PathMode::Nearest),
+            ..default()
+        })
+        .insert(FollowSmoothing::new(8.0));
+}
+
+/// Walks the character down the corridor with the arrow keys
+fn walk_character(
+    keys: Res<Input<KeyCode>>,
+    mut characters: Query<&mut CurrentTransform, With<Character>>
+) {
+    let mut dir = 0.0;
+    if keys.pressed(KeyCode::Left) { dir -= 1.0; }
+    if keys.pressed(KeyCode::Right) { dir += 1.0; }
+    for mut trans in &mut characters {
+        let x = (trans.0.translation.x + dir * SPEED).clamp(-LENGTH / 2.0, LENGTH / 2.0);
+        trans.0.translation.x = x;
+    }
+}
+
+/// Switches between path modes when P is pressed
+fn switch_mode(keys: Res<Input<KeyCode>>, mut styles: Query<&mut TargetStyle>) {
+    if !keys.just_pressed(KeyCode::P) {
+        return;
+    }
+    for mut style in &mut styles {
+        if let TargetStyle::Path { mode, .. } = style.as_mut() {
+            *mode = match mode {
+                PathMode::Nearest => PathMode::Projected,
+                PathMode::Projected => PathMode::Nearest
+            };
+            info!("Switched to {mode:?}");
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+
+// Vehicle constants
+const SPEED: f32 = 0.4;
+const TURN_SPEED: f32 = 0.08;
+
+/// Example where the camera stays behind a box vehicle as it turns.
+/// Up and down arrow keys drive, left and right arrow keys steer, and F toggles following the full rotation.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::new(Duration::from_secs_f64(1.0/20.0)))
+        .add_plugin(CameraTargetPlugin)
+        .add_startup_system(startup)
+        .add_fixed_system(drive_vehicle)
+        .add_system(toggle_full_rotation)
+        .run();
+}
+
+/// Marker component for the vehicle
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Vehicle;
+
+/// Spawns ground, pillars, vehicle and camera
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>
+) {
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns ground with pillars to show off turning
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(shape::Plane { size: 200.0 }.into()),
+        material: materials.add(Color::DARK_GREEN.into()),
+        ..default()
+    });
+    let pillar = meshes.add(shape::Box::new(1.0, 6.0, 1.0).into());
+    let pillar_material = materials.add(Color::GRAY.into());
+    for x in (-50..=50).step_by(10) {
+        for z in (-50..=50).step_by(10) {
+            commands.spawn(PbrBundle {
+                mesh: pillar.clone(),
+                material: pillar_material.clone(),
+                transform: Transform::from_xyz(x as f32, 3.0, z as f32),
+                ..default()
+            });
+        }
+    }
+
+    // Spawns vehicle, which faces -Z
+    let vehicle = commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(shape::Box::new(2.0, 1.0, 4.0).into()),
+            material: materials.add(Color::RED.into()),
+            transform: Transform::from_xyz(5.0, 0.5, 5.0),
+            ..default()
+        })
+        .insert((
+            Vehicle,
+            CurrentTransform(Transform::from_xyz(5.0, 0.5, 5.0)),
+            PreviousTransform::default()
+        ))
+        .id();
+
+    // Spawns camera behind and above the vehicle
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(vehicle),
+            target_style: TargetStyle::rotated_offset(Vec3::new(0.0, 4.0, 10.0)),
+            ..default()
+        })
+        .insert(FollowSmoothing::new(4.0));
+}
+
+/// Drives and steers the vehicle with the arrow keys
+fn drive_vehicle(
+    keys: Res<Input<KeyCode>>,
+    mut vehicles: Query<&mut CurrentTransform, With<Vehicle>>
+) {
+    let mut turn = 0.0;
+    let mut drive = 0.0;
+    if keys.pressed(KeyCode::Left) { turn += 1.0; }
+    if keys.pressed(KeyCode::Right) { turn -= 1.0; }
+    if keys.pressed(KeyCode::Up) { drive += 1.0; }
+    if keys.pressed(KeyCode::Down) { drive -= 1.0; }
+    for mut trans in &mut vehicles {
+        trans.0.rotate_y(turn * TURN_SPEED);
+        let forward = trans.0.forward();
+        trans.0.translation += forward * drive * SPEED;
+    }
+}
+
+/// Toggles following the vehicle's full rotation when F is pressed
+fn toggle_full_rotation(keys: Res<Input<KeyCode>>, mut styles: Query<&mut TargetStyle>) {
+    if !keys.just_pressed(KeyCode::F) {
+        return;
+    }
+    for mut style in &mut styles {
+        if let TargetStyle::RotatedOffset { full_rotation, .. } = style.as_mut() {
+            *full_rotation = !*full_rotation;
+            info!("Full rotation: {full_rotation}");
+        }
+    }
+}
@@ -2,14 +2,29 @@ use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_ecs::query::QueryEntityError;
 use bevy_transform::{prelude::*, TransformSystem};
-use bevy_math::Vec3;
+use bevy_time::Time;
+use bevy_math::{Quat, Vec3};
+
+mod path;
+mod replay;
+pub use path::*;
+pub use replay::*;
 
 pub struct CameraTargetPlugin;
 impl Plugin for CameraTargetPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_to_stage(CoreStage::PostUpdate, update_cameras
-            .before(TransformSystem::TransformPropagate)
-        );
+        app
+            .add_system_to_stage(CoreStage::PostUpdate, update_cameras
+                .before(TransformSystem::TransformPropagate)
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, replay_cameras
+                .before(TransformSystem::TransformPropagate)
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, record_cameras
+                .after(update_cameras)
+                .after(replay_cameras)
+                .before(TransformSystem::TransformPropagate)
+            );
     }
 }
 
@@ -48,34 +63,124 @@ pub enum Target {
 }
 
 /// Component that determines how a camera should follow its target.
-#[derive(Component, Debug, Copy, Clone, PartialEq)]
+#[derive(Component, Debug, Clone, PartialEq)]
 pub enum TargetStyle {
-    Offset(Vec3)
+    /// Camera stays at a fixed offset from the target.
+    Offset(Vec3),
+    /// Camera stays at an offset that rotates with the target, like behind a vehicle.
+    /// Only the target's yaw is followed unless `full_rotation` is true.
+    RotatedOffset {
+        offset: Vec3,
+        full_rotation: bool
+    },
+    /// Camera moves along a Catmull-Rom path through `points` while looking at the target.
+    /// See [`CameraPath`] for building one.
+    Path {
+        points: Vec<Vec3>,
+        mode: PathMode
+    }
 }
 impl Default for TargetStyle {
     fn default() -> Self {
         Self::Offset(Vec3::ZERO)
     }
 }
+impl TargetStyle {
+    /// Offset that follows the target's yaw.
+    pub fn rotated_offset(offset: Vec3) -> Self {
+        Self::RotatedOffset { offset, full_rotation: false }
+    }
+}
 
 /// Optional component to add to targets. Determines the up vector of the camera when being targetted.
 /// If not included, camera's up vector will be [0.0, 1.0, 0.0].
 #[derive(Component, Debug, Copy, Clone, PartialEq)]
 pub struct Up(pub Vec3);
 
+/// Optional component to add to a camera. Smooths the camera's movement towards its desired position.
+/// Without it, the camera snaps to its desired position every frame.
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct FollowSmoothing {
+    /// Rate at which the camera closes the distance to its desired position, per second.
+    pub rate: f32,
+    /// How much of the target's velocity is compensated for.
+    /// 0.0 lets the camera trail behind a moving target, 1.0 keeps the target centered at constant speeds.
+    pub bias: f32,
+    last_desired: Option<Vec3>
+}
+impl FollowSmoothing {
+    pub fn new(rate: f32) -> Self {
+        Self {
+            rate,
+            bias: 0.0,
+            last_desired: None
+        }
+    }
+    pub fn with_bias(mut self, bias: f32) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Moves the current position towards the desired position over `dt` seconds and returns the result.
+    pub fn step(&mut self, current: Vec3, desired: Vec3, dt: f32) -> Vec3 {
+        let alpha = 1.0 - (-self.rate * dt).exp();
 
-/// Has cameras with a target follow their target
+        // Leads the desired position by the lag exponential smoothing would have at the desired position's velocity
+        let lead = match self.last_desired {
+            Some(last_desired) if alpha > 0.0 => (desired - last_desired) * (1.0 - alpha) / alpha * self.bias,
+            _ => Vec3::ZERO
+        };
+        self.last_desired = Some(desired);
+        current.lerp(desired + lead, alpha)
+    }
+
+    /// Jumps straight to the desired position, forgetting any motion from previous steps.
+    pub fn snap(&mut self, desired: Vec3) -> Vec3 {
+        self.last_desired = Some(desired);
+        desired
+    }
+}
+impl Default for FollowSmoothing {
+    fn default() -> Self {
+        Self::new(10.0)
+    }
+}
+
+/// Marker that has a camera jump to its target on the next update, skipping [`FollowSmoothing`].
+/// Removed once the camera has snapped. See [`snap_to_target`].
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct SnapToTarget;
+
+/// Has a camera jump to its target on the next update, like after the target was teleported.
+/// Cameras always snap on the update their [`Target`] is added.
+pub fn snap_to_target(commands: &mut Commands, camera: Entity) {
+    commands.entity(camera).insert(SnapToTarget);
+}
+
+/// Has cameras with a target follow their target, unless they're replaying a recording
+#[allow(clippy::type_complexity)]
 fn update_cameras(
-    mut cameras: Query<(&Target, &TargetStyle, &Up, &mut Transform)>,
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cameras: Query<(
+        Entity,
+        &Target,
+        ChangeTrackers<Target>,
+        &TargetStyle,
+        &Up,
+        &mut Transform,
+        Option<&mut FollowSmoothing>,
+        Option<&SnapToTarget>
+    ), Without<ReplayCamera>>,
     target_query: Query<(&Transform, Option<&Up>), Without<Target>>
 ) {
-    for (cam_target, cam_style, cam_up, mut cam_trans) in &mut cameras {
+    for (cam_entity, cam_target, cam_tracker, cam_style, cam_up, mut cam_trans, smoothing, snap) in &mut cameras {
         
-        // Gets position / up vectors of camera's target
-        let (target_pos, target_up) = match *cam_target {
+        // Gets position / rotation / up vectors of camera's target
+        let (target_pos, target_rot, target_up) = match *cam_target {
 
             // If target is a point, just grab the raw values
-            Target::Point { position, up } => (position, up),
+            Target::Point { position, up } => (position, Quat::IDENTITY, up),
 
             // Otherwise, grab the transform and optional up vector from the entity
             Target::Entity(entity) => {
@@ -92,20 +197,52 @@ fn update_cameras(
                     }
                 };
                 match target_up {
-                    Some(target_up) => (target_trans.translation, target_up.0),
-                    None => (target_trans.translation, cam_up.0)
+                    Some(target_up) => (target_trans.translation, target_trans.rotation, target_up.0),
+                    None => (target_trans.translation, target_trans.rotation, cam_up.0)
                 }
             }
         };
 
         // Follows target
-        match *cam_style {
-            TargetStyle::Offset(offset) => {
-                cam_trans.translation = target_pos + offset;
-                cam_trans.look_at(target_pos, target_up);
+        let desired = match cam_style {
+            TargetStyle::Offset(offset) => target_pos + *offset,
+            TargetStyle::RotatedOffset { offset, full_rotation } => {
+                let rotation = if *full_rotation { target_rot } else { yaw(target_rot) };
+                target_pos + rotation * *offset
+            },
+            TargetStyle::Path { points, mode } => {
+                let t = match mode {
+                    PathMode::Nearest => closest_on_path(points, target_pos),
+                    PathMode::Projected => project_on_path(points, target_pos)
+                };
+                match sample_path(points, t) {
+                    Some(desired) => desired,
+                    None => continue
+                }
             }
+        };
+
+        // Snaps to the desired position on the first update, or when requested
+        let snap = snap.is_some() || cam_tracker.is_added();
+        if snap {
+            commands.entity(cam_entity).remove::<SnapToTarget>();
         }
+        cam_trans.translation = match smoothing {
+            Some(mut smoothing) if snap => smoothing.snap(desired),
+            Some(mut smoothing) => smoothing.step(cam_trans.translation, desired, time.delta_seconds()),
+            None => desired
+        };
+        cam_trans.look_at(target_pos, target_up);
+    }
+}
+
+/// Rotation around the Y axis that points forward in the same direction as the rotation specified.
+fn yaw(rotation: Quat) -> Quat {
+    let forward = rotation * Vec3::NEG_Z;
+    if forward.x.abs() < f32::EPSILON && forward.z.abs() < f32::EPSILON {
+        return Quat::IDENTITY;
     }
+    Quat::from_rotation_y(f32::atan2(-forward.x, -forward.z))
 }
 
 pub mod prelude {
@@ -114,6 +251,116 @@ pub mod prelude {
         CameraTargetBundle,
         Target,
         TargetStyle,
-        Up  
+        CameraPath,
+        PathMode,
+        Up,
+        FollowSmoothing,
+        SnapToTarget,
+        snap_to_target,
+        CameraTrack,
+        RecordCamera,
+        ReplayCamera
     };
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+    use bevy_ecs::system::CommandQueue;
+    use bevy_math::{Quat, Vec3};
+    use bevy_time::Time;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    fn steady_state_offset(bias: f32) -> Vec3 {
+        let offset = Vec3::new(0.0, 5.0, 5.0);
+        let velocity = Vec3::new(12.0, 0.0, -3.0);
+        let dt = 1.0 / 60.0;
+        let mut smoothing = FollowSmoothing::new(5.0).with_bias(bias);
+        let mut target = Vec3::ZERO;
+        let mut camera = target + offset;
+        for _ in 0..600 {
+            target += velocity * dt;
+            camera = smoothing.step(camera, target + offset, dt);
+        }
+        camera - target
+    }
+
+    #[test]
+    fn velocity_matching() {
+        let offset = Vec3::new(0.0, 5.0, 5.0);
+        assert!(steady_state_offset(1.0).abs_diff_eq(offset, 0.0001));
+        assert!(!steady_state_offset(0.0).abs_diff_eq(offset, 0.1));
+    }
+
+    #[test]
+    fn rotated_offset() {
+        let mut app = App::new();
+        app
+            .init_resource::<Time>()
+            .add_plugin(CameraTargetPlugin);
+        let rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2) * Quat::from_rotation_x(0.5);
+        let target = app.world.spawn(Transform::from_xyz(1.0, 0.0, 0.0).with_rotation(rotation)).id();
+        let spawn_camera = |app: &mut App, full_rotation: bool| app.world
+            .spawn((
+                Transform::default(),
+                CameraTargetBundle {
+                    target: Target::Entity(target),
+                    target_style: TargetStyle::RotatedOffset { offset: Vec3::new(0.0, 5.0, 10.0), full_rotation },
+                    ..Default::default()
+                }
+            ))
+            .id();
+        let yaw_camera = spawn_camera(&mut app, false);
+        let full_camera = spawn_camera(&mut app, true);
+        app.update();
+
+        // Yaw only keeps the camera level behind the target, which faces -X after a quarter turn
+        let yaw_pos = app.world.get::<Transform>(yaw_camera).unwrap().translation;
+        assert!(yaw_pos.abs_diff_eq(Vec3::new(11.0, 5.0, 0.0), 0.0001), "{yaw_pos}");
+        let full_pos = app.world.get::<Transform>(full_camera).unwrap().translation;
+        let expected = Vec3::new(1.0, 0.0, 0.0) + rotation * Vec3::new(0.0, 5.0, 10.0);
+        assert!(full_pos.abs_diff_eq(expected, 0.0001), "{full_pos}");
+    }
+
+    #[test]
+    fn warm_start() {
+        let mut app = App::new();
+        app
+            .init_resource::<Time>()
+            .add_plugin(CameraTargetPlugin);
+        let offset = Vec3::new(0.0, 5.0, 5.0);
+        let target = app.world.spawn(Transform::from_xyz(100.0, 0.0, -50.0)).id();
+        let camera = app.world
+            .spawn((
+                Transform::default(),
+                FollowSmoothing::new(5.0).with_bias(1.0),
+                CameraTargetBundle {
+                    target: Target::Entity(target),
+                    target_style: TargetStyle::Offset(offset),
+                    ..Default::default()
+                }
+            ))
+            .id();
+        app.update();
+        let expected = Vec3::new(100.0, 0.0, -50.0) + offset;
+        assert_eq!(expected, app.world.get::<Transform>(camera).unwrap().translation);
+
+        // Smooths after the first update
+        app.world.get_mut::<Transform>(target).unwrap().translation = Vec3::new(-100.0, 0.0, 0.0);
+        app.update();
+        assert_eq!(expected, app.world.get::<Transform>(camera).unwrap().translation);
+
+        // Snaps again when requested
+        let mut queue = CommandQueue::default();
+        snap_to_target(&mut Commands::new(&mut queue, &app.world), camera);
+        queue.apply(&mut app.world);
+        app.update();
+        let expected = Vec3::new(-100.0, 0.0, 0.0) + offset;
+        assert_eq!(expected, app.world.get::<Transform>(camera).unwrap().translation);
+        assert!(app.world.get::<SnapToTarget>(camera).is_none());
+    }
+}
@@ -0,0 +1,158 @@
+use bevy_math::Vec3;
+
+use crate::TargetStyle;
+
+/// Number of samples taken per path segment when searching for the closest point.
+const SAMPLES_PER_SEGMENT: usize = 16;
+
+/// Number of refinement iterations after the closest sample is found.
+const REFINE_ITERATIONS: usize = 16;
+
+/// Determines where a camera following a [`TargetStyle::Path`] is placed along its path.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum PathMode {
+    /// Camera is placed at the point on the path closest to the target.
+    #[default]
+    Nearest,
+    /// Camera advances along the path in proportion to the target's progress from the first point to the last.
+    Projected
+}
+
+/// Builder for a Catmull-Rom path that cameras can follow with [`TargetStyle::Path`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CameraPath {
+    pub points: Vec<Vec3>
+}
+impl CameraPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_point(mut self, point: Vec3) -> Self {
+        self.points.push(point);
+        self
+    }
+    pub fn with_points(mut self, points: impl IntoIterator<Item = Vec3>) -> Self {
+        self.points.extend(points);
+        self
+    }
+
+    /// Creates a [`TargetStyle`] that follows this path.
+    pub fn into_style(self, mode: PathMode) -> TargetStyle {
+        TargetStyle::Path { points: self.points, mode }
+    }
+}
+
+/// Samples a Catmull-Rom path passing through `points` at `t`, where 0.0 is the first point and 1.0 is the last.
+/// `t` is clamped to the endpoints. Returns None if there are no points.
+pub fn sample_path(points: &[Vec3], t: f32) -> Option<Vec3> {
+    let (first, last) = (*points.first()?, *points.last()?);
+    if points.len() == 1 {
+        return Some(first);
+    }
+    let segments = points.len() - 1;
+    let u = t.clamp(0.0, 1.0) * segments as f32;
+    let idx = (u as usize).min(segments - 1);
+    let t = u - idx as f32;
+
+    // Duplicates endpoints so the path passes through every point
+    let p0 = if idx == 0 { first } else { points[idx - 1] };
+    let p1 = points[idx];
+    let p2 = points[idx + 1];
+    let p3 = points.get(idx + 2).copied().unwrap_or(last);
+    let (t2, t3) = (t * t, t * t * t);
+    Some(0.5 * (
+        2.0 * p1 +
+        (p2 - p0) * t +
+        (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2 +
+        (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3
+    ))
+}
+
+/// Finds the parameter of the point on the path through `points` closest to `position`.
+/// See [`sample_path`].
+pub fn closest_on_path(points: &[Vec3], position: Vec3) -> f32 {
+    if points.len() < 2 {
+        return 0.0;
+    }
+    let samples = (points.len() - 1) * SAMPLES_PER_SEGMENT;
+    let distance = |t: f32| sample_path(points, t).unwrap().distance_squared(position);
+
+    // Finds closest sample, then refines it between its neighbors
+    let step = 1.0 / samples as f32;
+    let closest = (0..=samples)
+        .map(|i| i as f32 * step)
+        .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+        .unwrap();
+    let (mut lo, mut hi) = ((closest - step).max(0.0), (closest + step).min(1.0));
+    for _ in 0..REFINE_ITERATIONS {
+        let a = lo + (hi - lo) / 3.0;
+        let b = hi - (hi - lo) / 3.0;
+        if distance(a) < distance(b) {
+            hi = b;
+        }
+        else {
+            lo = a;
+        }
+    }
+    [lo, (lo + hi) / 2.0, hi]
+        .into_iter()
+        .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+        .unwrap()
+}
+
+/// Progress of `position` from the first to the last of `points`, measured along the line between them.
+/// Clamped between 0.0 and 1.0.
+pub fn project_on_path(points: &[Vec3], position: Vec3) -> f32 {
+    let (first, last) = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return 0.0
+    };
+    let line = last - first;
+    let length_squared = line.length_squared();
+    if length_squared == 0.0 {
+        return 0.0;
+    }
+    ((position - first).dot(line) / length_squared).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_math::Vec3;
+
+    use crate::{sample_path, closest_on_path, project_on_path, CameraPath};
+
+    fn rail() -> CameraPath {
+        CameraPath::new()
+            .with_point(Vec3::new(0.0, 2.0, 5.0))
+            .with_point(Vec3::new(10.0, 3.0, 6.0))
+            .with_point(Vec3::new(20.0, 2.0, 5.0))
+    }
+
+    #[test]
+    fn passes_through_points() {
+        let points = rail().points;
+        assert_eq!(Some(points[0]), sample_path(&points, 0.0));
+        assert!(sample_path(&points, 0.5).unwrap().abs_diff_eq(points[1], 0.0001));
+        assert_eq!(Some(points[2]), sample_path(&points, 1.0));
+    }
+
+    #[test]
+    fn clamps_to_endpoints() {
+        let points = rail().points;
+        assert_eq!(0.0, closest_on_path(&points, Vec3::new(-50.0, 0.0, 0.0)));
+        assert_eq!(1.0, closest_on_path(&points, Vec3::new(50.0, 0.0, 0.0)));
+        assert_eq!(0.0, project_on_path(&points, Vec3::new(-50.0, 0.0, 0.0)));
+        assert_eq!(1.0, project_on_path(&points, Vec3::new(50.0, 0.0, 0.0)));
+        assert_eq!(Some(points[2]), sample_path(&points, 2.0));
+    }
+
+    #[test]
+    fn follows_target() {
+        let points = rail().points;
+        let target = Vec3::new(10.0, 1.0, 0.0);
+        let nearest = sample_path(&points, closest_on_path(&points, target)).unwrap();
+        assert!(nearest.abs_diff_eq(points[1], 0.01));
+        assert_eq!(0.25, project_on_path(&points, Vec3::new(5.0, 1.0, 0.0)));
+    }
+}
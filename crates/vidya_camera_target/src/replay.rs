@@ -0,0 +1,212 @@
+use bevy_ecs::prelude::*;
+use bevy_time::Time;
+use bevy_transform::prelude::*;
+
+/// Transforms of a camera over time, recorded with [`RecordCamera`] and played back with [`ReplayCamera`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CameraTrack {
+    samples: Vec<CameraSample>
+}
+
+/// Transform of a camera at a point in a [`CameraTrack`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CameraSample {
+    /// Seconds since the start of the track
+    pub time: f32,
+    pub transform: Transform
+}
+
+impl CameraTrack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sample to the end of the track.
+    /// Panics if the sample is earlier than the last one.
+    pub fn push(&mut self, time: f32, transform: Transform) {
+        if let Some(last) = self.samples.last() {
+            assert!(time >= last.time, "Camera sample at {time}s is earlier than the last one at {}s", last.time);
+        }
+        self.samples.push(CameraSample { time, transform });
+    }
+
+    pub fn samples(&self) -> &[CameraSample] {
+        &self.samples
+    }
+
+    /// Seconds between the first and last sample.
+    pub fn duration(&self) -> f32 {
+        self.samples.last().map_or(0.0, |last| last.time)
+    }
+
+    /// Transform at the time specified, interpolated between the samples around it.
+    /// Times outside of the track are clamped to its ends. Returns None if the track is empty.
+    pub fn sample(&self, time: f32) -> Option<Transform> {
+        let first = self.samples.first()?;
+        let next_idx = self.samples.partition_point(|sample| sample.time <= time);
+        if next_idx == 0 {
+            return Some(first.transform);
+        }
+        let prev = &self.samples[next_idx - 1];
+        let Some(next) = self.samples.get(next_idx) else { return Some(prev.transform) };
+        let t = (time - prev.time) / (next.time - prev.time);
+        Some(Transform {
+            translation: prev.transform.translation.lerp(next.transform.translation, t),
+            rotation: prev.transform.rotation.slerp(next.transform.rotation, t),
+            scale: prev.transform.scale.lerp(next.transform.scale, t)
+        })
+    }
+}
+
+/// Component that records the final [`Transform`] of a camera every frame, after it followed its target.
+#[derive(Component, Debug, Clone, PartialEq, Default)]
+pub struct RecordCamera {
+    pub track: CameraTrack,
+    elapsed: Option<f32>
+}
+impl RecordCamera {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Component that drives a camera's [`Transform`] from a recorded [`CameraTrack`], ignoring its target while present.
+/// Samples are picked by time rather than by frame, so replays look the same at any frame rate.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct ReplayCamera {
+    pub track: CameraTrack,
+    /// Starts over once the end of the track is reached
+    pub looping: bool,
+    elapsed: Option<f32>
+}
+impl ReplayCamera {
+    pub fn new(track: CameraTrack) -> Self {
+        Self { track, looping: false, elapsed: None }
+    }
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Seconds into the track the replay is.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed.unwrap_or(0.0)
+    }
+
+    /// True if the replay reached the end of a track that doesn't loop.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed() >= self.track.duration()
+    }
+}
+
+/// Seconds since the first frame, which happens at 0
+fn advance(elapsed: &mut Option<f32>, dt: f32) -> f32 {
+    let seconds = elapsed.map_or(0.0, |elapsed| elapsed + dt);
+    *elapsed = Some(seconds);
+    seconds
+}
+
+/// Records the transforms of cameras with a [`RecordCamera`].
+pub(crate) fn record_cameras(time: Res<Time>, mut cameras: Query<(&mut RecordCamera, &Transform)>) {
+    for (mut recorder, transform) in &mut cameras {
+        let recorder = recorder.as_mut();
+        let seconds = advance(&mut recorder.elapsed, time.delta_seconds());
+        recorder.track.push(seconds, *transform);
+    }
+}
+
+/// Moves cameras with a [`ReplayCamera`] along their tracks.
+pub(crate) fn replay_cameras(time: Res<Time>, mut cameras: Query<(&mut ReplayCamera, &mut Transform)>) {
+    for (mut replay, mut transform) in &mut cameras {
+        let replay = replay.as_mut();
+        let mut seconds = advance(&mut replay.elapsed, time.delta_seconds());
+        let duration = replay.track.duration();
+        if replay.looping && duration > 0.0 {
+            seconds %= duration;
+            replay.elapsed = Some(seconds);
+        }
+        if let Some(sampled) = replay.track.sample(seconds) {
+            *transform = sampled;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::time::{Duration, Instant};
+
+    use bevy_app::prelude::*;
+    use bevy_math::Vec3;
+    use bevy_time::Time;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    /// Runs an app at a fixed frame rate, calling a function with the seconds elapsed before each frame.
+    fn run(app: &mut App, frames: u32, frame_seconds: f32, mut before_frame: impl FnMut(&mut App, f32)) {
+        let start = Instant::now();
+        app.world.resource_mut::<Time>().update_with_instant(start);
+        for frame in 0..frames {
+            let seconds = frame as f32 * frame_seconds;
+            app.world.resource_mut::<Time>().update_with_instant(start + Duration::from_secs_f32(seconds));
+            before_frame(app, seconds);
+            app.update();
+        }
+    }
+
+    fn camera_app() -> App {
+        let mut app = App::new();
+        app
+            .init_resource::<Time>()
+            .add_plugin(CameraTargetPlugin);
+        app
+    }
+
+    #[test]
+    fn sample_interpolates() {
+        let mut track = CameraTrack::new();
+        assert_eq!(None, track.sample(0.0));
+        track.push(1.0, Transform::from_xyz(0.0, 0.0, 0.0));
+        track.push(3.0, Transform::from_xyz(4.0, 0.0, 0.0));
+        assert_eq!(Vec3::ZERO, track.sample(0.0).unwrap().translation);
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), track.sample(1.5).unwrap().translation);
+        assert_eq!(Vec3::new(4.0, 0.0, 0.0), track.sample(3.0).unwrap().translation);
+        assert_eq!(Vec3::new(4.0, 0.0, 0.0), track.sample(10.0).unwrap().translation);
+    }
+
+    #[test]
+    fn replays_at_different_frame_rate() {
+
+        // Records a camera following a target moving at 6 units per second, at 60 fps
+        let mut app = camera_app();
+        let target = app.world.spawn(Transform::default()).id();
+        let camera = app.world
+            .spawn((
+                Transform::default(),
+                RecordCamera::new(),
+                CameraTargetBundle {
+                    target: Target::Entity(target),
+                    target_style: TargetStyle::Offset(Vec3::new(0.0, 5.0, 5.0)),
+                    ..Default::default()
+                }
+            ))
+            .id();
+        run(&mut app, 61, 1.0 / 60.0, |app, seconds| {
+            app.world.get_mut::<Transform>(target).unwrap().translation.x = seconds * 6.0;
+        });
+        let track = app.world.entity_mut(camera).remove::<RecordCamera>().unwrap().track;
+        assert!((track.duration() - 1.0).abs() < 0.001);
+
+        // Replays at 24 fps, where the target standing still is ignored
+        app.world.entity_mut(camera).insert(ReplayCamera::new(track));
+        run(&mut app, 13, 1.0 / 24.0, |app, seconds| {
+            if seconds > 0.0 {
+                let x = app.world.get::<Transform>(camera).unwrap().translation.x;
+                let expected = (seconds - 1.0 / 24.0) * 6.0;
+                assert!((x - expected).abs() < 0.001, "{x} != {expected}");
+            }
+        });
+        assert!(!app.world.get::<ReplayCamera>(camera).unwrap().is_finished());
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+
+use bevy_ecs::prelude::*;
+
+use crate::CurrentTransform;
+
+/// Marker for entities whose [`CurrentTransform`] is intentionally written outside of the fixed stages.
+/// Silences the warnings logged for them.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct ExternalTransformWrites;
+
+/// Resource that lists the entities whose [`CurrentTransform`] was written outside of
+/// [`crate::FixedTimestepStages::FixedUpdate`] through [`crate::FixedTimestepStages::PostFixedUpdate`].
+/// Such writes make motion judder, since they skip interpolation.
+/// A warning is logged the first time each entity is found, so it doesn't flood the log every frame.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ExternalTransformWarnings {
+    warned: HashSet<Entity>,
+    last_check: u32,
+    fixed_start: Option<u32>
+}
+impl ExternalTransformWarnings {
+    /// True if a warning was logged for the entity specified.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.warned.contains(&entity)
+    }
+    /// Entities warnings were logged for.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.warned.iter().copied()
+    }
+}
+
+/// Remembers when the first fixed tick of the frame started.
+pub(crate) fn record_fixed_start(world: &mut World) {
+    let change_tick = world.read_change_tick();
+    let mut warnings = world.resource_mut::<ExternalTransformWarnings>();
+    if warnings.fixed_start.is_none() {
+        warnings.fixed_start = Some(change_tick);
+    }
+}
+
+/// Warns about [`CurrentTransform`]s that changed since the last check, but not during this frame's fixed ticks.
+/// Components that were just added don't count, since spawning entities is fine anywhere.
+pub(crate) fn warn_external_transform_writes(world: &mut World) {
+    let change_tick = world.read_change_tick();
+    let mut query = world.query_filtered::<Entity, (With<CurrentTransform>, Without<ExternalTransformWrites>)>();
+    let mut warnings = world.remove_resource::<ExternalTransformWarnings>().unwrap_or_default();
+    for entity in query.iter(world) {
+        let Some(ticks) = world.entity(entity).get_change_ticks::<CurrentTransform>() else { continue };
+        let written = ticks.is_changed(warnings.last_check, change_tick) && !ticks.is_added(warnings.last_check, change_tick);
+        let written_in_fixed = matches!(warnings.fixed_start, Some(start) if ticks.is_changed(start, change_tick));
+        if written && !written_in_fixed && warnings.warned.insert(entity) {
+            bevy_log::warn!(
+                "CurrentTransform of {entity:?} was written outside of the fixed stages, which makes it judder. \
+                Write it from a fixed system, or add ExternalTransformWrites to the entity if this is intentional."
+            );
+        }
+    }
+    warnings.last_check = change_tick;
+    warnings.fixed_start = None;
+    world.insert_resource(warnings);
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::time::{Duration, Instant};
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+    use bevy_time::{Time, FixedTimesteps};
+
+    use crate::*;
+
+    /// Marker for entities moved every tick
+    #[derive(Component)]
+    struct Fixed;
+
+    /// Marker for entities moved every frame
+    #[derive(Component)]
+    struct Frame;
+
+    fn move_fixed(mut query: Query<&mut CurrentTransform, With<Fixed>>) {
+        for mut trans in &mut query {
+            trans.0.translation.x += 1.0;
+        }
+    }
+
+    fn move_frame(mut query: Query<&mut CurrentTransform, With<Frame>>) {
+        for mut trans in &mut query {
+            trans.0.translation.x += 1.0;
+        }
+    }
+
+    #[test]
+    fn warns_about_external_writes() {
+        let mut app = App::new();
+        app
+            .add_plugin(FixedTimestepPlugin::new(Duration::from_millis(10)))
+            .insert_resource(Time::default())
+            .init_resource::<FixedTimesteps>()
+            .add_fixed_system(move_fixed)
+            .add_system(move_frame);
+        let fixed = app.world.spawn((CurrentTransform::default(), PreviousTransform::default(), Fixed)).id();
+        let frame = app.world.spawn((CurrentTransform::default(), PreviousTransform::default(), Frame)).id();
+        let silenced = app.world.spawn((CurrentTransform::default(), PreviousTransform::default(), Frame, ExternalTransformWrites)).id();
+
+        // Runs frames both with and without ticks
+        let start = Instant::now();
+        for millis in [0, 5, 10, 12, 20, 40, 45] {
+            app.world.resource_mut::<Time>().update_with_instant(start + Duration::from_millis(millis));
+            app.update();
+        }
+        let warnings = app.world.resource::<ExternalTransformWarnings>();
+        assert!(warnings.contains(frame));
+        assert!(!warnings.contains(fixed));
+        assert!(!warnings.contains(silenced));
+        assert_eq!(1, warnings.iter().count());
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_input::prelude::*;
+use bevy_input::gamepad::{Gamepads, GamepadButton, GamepadAxis};
+use bevy_math::prelude::*;
+
+use crate::FixedTimestepStages;
+
+/// User-defined action that inputs get mapped to, usually an enum.
+pub trait Action: Copy + Eq + Hash + Send + Sync + 'static {}
+impl<T: Copy + Eq + Hash + Send + Sync + 'static> Action for T {}
+
+/// Plugin that maps raw inputs to actions of type `A` using an [`InputMap`],
+/// and exposes them to fixed systems through the [`FixedActions`] resource.
+/// Requires bevy's `InputPlugin`.
+pub struct FixedInputPlugin<A: Action> {
+    map: InputMap<A>
+}
+impl<A: Action> FixedInputPlugin<A> {
+    pub fn new(map: InputMap<A>) -> Self {
+        Self { map }
+    }
+}
+impl<A: Action> Plugin for FixedInputPlugin<A> {
+    fn build(&self, app: &mut App) {
+        FixedTimestepStages::FixedUpdate.expect_added(app, "FixedInputPlugin");
+        app
+            .insert_resource(self.map.clone())
+            .insert_resource(FixedActions::<A>::default())
+            .insert_resource(ActionBuffer::<A>::default())
+            .add_system_to_stage(CoreStage::PreUpdate, buffer_actions::<A>)
+            .add_system_to_stage(FixedTimestepStages::FixedUpdate, apply_buffered_actions::<A>.at_start());
+    }
+}
+
+/// Raw input an action can be bound to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum InputBinding {
+    Key(KeyCode),
+    GamepadButton(GamepadButtonType),
+    /// Active while the axis is past the threshold, in the threshold's direction.
+    GamepadAxis {
+        axis: GamepadAxisType,
+        threshold: f32
+    }
+}
+
+/// Raw inputs an action can read a [`Vec2`] from.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum InputBinding2d {
+    /// Stick axes with a radial deadzone between 0.0 and 1.0.
+    Stick {
+        x: GamepadAxisType,
+        y: GamepadAxisType,
+        deadzone: f32
+    },
+    /// Four keys, each pushing the value to one side.
+    Keys {
+        up: KeyCode,
+        down: KeyCode,
+        left: KeyCode,
+        right: KeyCode
+    }
+}
+impl InputBinding2d {
+    /// Left stick with the deadzone specified.
+    pub fn left_stick(deadzone: f32) -> Self {
+        Self::Stick {
+            x: GamepadAxisType::LeftStickX,
+            y: GamepadAxisType::LeftStickY,
+            deadzone
+        }
+    }
+    /// Arrow keys.
+    pub fn arrow_keys() -> Self {
+        Self::Keys {
+            up: KeyCode::Up,
+            down: KeyCode::Down,
+            left: KeyCode::Left,
+            right: KeyCode::Right
+        }
+    }
+}
+
+/// Resource that maps raw inputs to user-defined actions.
+/// An action is pressed while any of its bindings are active, and its [`Vec2`] value is the longest of its 2d bindings.
+#[derive(Resource, Debug, Clone)]
+pub struct InputMap<A: Action> {
+    bindings: HashMap<A, Vec<InputBinding>>,
+    bindings_2d: HashMap<A, Vec<InputBinding2d>>,
+    /// Gamepad read from. If None, the first connected gamepad is used.
+    pub gamepad: Option<Gamepad>
+}
+impl<A: Action> InputMap<A> {
+    pub fn new() -> Self {
+        Self {
+            bindings: HashMap::new(),
+            bindings_2d: HashMap::new(),
+            gamepad: None
+        }
+    }
+    pub fn with_binding(mut self, action: A, binding: InputBinding) -> Self {
+        self.bindings.entry(action).or_default().push(binding);
+        self
+    }
+    pub fn with_binding_2d(mut self, action: A, binding: InputBinding2d) -> Self {
+        self.bindings_2d.entry(action).or_default().push(binding);
+        self
+    }
+    pub fn with_gamepad(mut self, gamepad: Gamepad) -> Self {
+        self.gamepad = Some(gamepad);
+        self
+    }
+}
+impl<A: Action> Default for InputMap<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Resource that stores the state of actions as seen by the current fixed tick.
+/// Presses that happen between ticks are buffered, so a quick tap is never missed,
+/// and `just_pressed` is only true during the first tick after the press.
+#[derive(Resource, Debug, Clone)]
+pub struct FixedActions<A: Action> {
+    pressed: HashSet<A>,
+    just_pressed: HashSet<A>,
+    values: HashMap<A, Vec2>
+}
+impl<A: Action> FixedActions<A> {
+    pub fn pressed(&self, action: A) -> bool {
+        self.pressed.contains(&action)
+    }
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.just_pressed.contains(&action)
+    }
+    /// Value of a 2d action. Zero if it has no 2d bindings.
+    pub fn value(&self, action: A) -> Vec2 {
+        self.values.get(&action).copied().unwrap_or(Vec2::ZERO)
+    }
+}
+impl<A: Action> Default for FixedActions<A> {
+    fn default() -> Self {
+        Self {
+            pressed: HashSet::new(),
+            just_pressed: HashSet::new(),
+            values: HashMap::new()
+        }
+    }
+}
+
+/// Actions read since the last fixed tick.
+#[derive(Resource)]
+struct ActionBuffer<A: Action> {
+    pressed: HashSet<A>,
+    just_pressed: HashSet<A>,
+    values: HashMap<A, Vec2>
+}
+impl<A: Action> Default for ActionBuffer<A> {
+    fn default() -> Self {
+        Self {
+            pressed: HashSet::new(),
+            just_pressed: HashSet::new(),
+            values: HashMap::new()
+        }
+    }
+}
+
+/// Reads raw inputs every frame, accumulating presses until the next fixed tick.
+fn buffer_actions<A: Action>(
+    map: Res<InputMap<A>>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+    mut buffer: ResMut<ActionBuffer<A>>
+) {
+    let gamepad = map.gamepad.or_else(|| gamepads.iter().next());
+    let axis = |axis_type| gamepad
+        .and_then(|gamepad| axes.get(GamepadAxis::new(gamepad, axis_type)))
+        .unwrap_or(0.0);
+
+    // Reads buttons, detecting presses of axis bindings from their previous state
+    let mut pressed_now = HashSet::new();
+    for (action, bindings) in &map.bindings {
+        let mut pressed = false;
+        let mut just_pressed = false;
+        for binding in bindings {
+            match *binding {
+                InputBinding::Key(key) => {
+                    pressed |= keys.pressed(key);
+                    just_pressed |= keys.just_pressed(key);
+                },
+                InputBinding::GamepadButton(button_type) => if let Some(gamepad) = gamepad {
+                    let button = GamepadButton::new(gamepad, button_type);
+                    pressed |= buttons.pressed(button);
+                    just_pressed |= buttons.just_pressed(button);
+                },
+                InputBinding::GamepadAxis { axis: axis_type, threshold } => {
+                    pressed |= axis_active(axis(axis_type), threshold);
+                }
+            }
+        }
+        if just_pressed || (pressed && !buffer.pressed.contains(action)) {
+            buffer.just_pressed.insert(*action);
+        }
+        if pressed {
+            pressed_now.insert(*action);
+        }
+    }
+    buffer.pressed = pressed_now;
+
+    // Reads 2d values
+    for (action, bindings) in &map.bindings_2d {
+        let value = bindings
+            .iter()
+            .map(|binding| match *binding {
+                InputBinding2d::Stick { x, y, deadzone } => apply_deadzone(Vec2::new(axis(x), axis(y)), deadzone),
+                InputBinding2d::Keys { up, down, left, right } => {
+                    let value = Vec2::new(
+                        keys.pressed(right) as u8 as f32 - keys.pressed(left) as u8 as f32,
+                        keys.pressed(up) as u8 as f32 - keys.pressed(down) as u8 as f32
+                    );
+                    value.normalize_or_zero()
+                }
+            })
+            .fold(Vec2::ZERO, |a, b| if b.length_squared() > a.length_squared() { b } else { a });
+        buffer.values.insert(*action, value);
+    }
+}
+
+/// Hands buffered actions to the current fixed tick.
+fn apply_buffered_actions<A: Action>(mut buffer: ResMut<ActionBuffer<A>>, mut actions: ResMut<FixedActions<A>>) {
+    let buffer = &mut *buffer;
+    actions.pressed.clone_from(&buffer.pressed);
+    actions.just_pressed = std::mem::take(&mut buffer.just_pressed);
+    actions.values.clone_from(&buffer.values);
+}
+
+fn axis_active(value: f32, threshold: f32) -> bool {
+    match threshold < 0.0 {
+        true => value <= threshold,
+        false => value >= threshold
+    }
+}
+
+/// Zeroes values within the deadzone, and rescales the rest to start from zero at its edge.
+pub fn apply_deadzone(value: Vec2, deadzone: f32) -> Vec2 {
+    let length = value.length();
+    if length <= deadzone || deadzone >= 1.0 {
+        return Vec2::ZERO;
+    }
+    let scaled = ((length - deadzone) / (1.0 - deadzone)).min(1.0);
+    value / length * scaled
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+    use bevy_ecs::schedule::ShouldRun;
+    use bevy_input::prelude::*;
+    use bevy_input::gamepad::{Gamepads, GamepadButton, GamepadAxis};
+    use bevy_math::prelude::*;
+
+    use crate::{FixedTimestepStages, FixedActions, FixedInputPlugin, InputMap, InputBinding, InputBinding2d, apply_deadzone};
+
+    #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+    enum Action {
+        Move,
+        Jump
+    }
+
+    const GAMEPAD: Gamepad = Gamepad { id: 0 };
+
+    /// Whether fixed ticks run during the next update
+    #[derive(Resource)]
+    struct Tick(bool);
+
+    /// App where each update is a render frame, and fixed ticks only run when `tick` is true.
+    fn input_test_app() -> App {
+        let mut app = App::new();
+        app
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .init_resource::<Gamepads>()
+            .insert_resource(Tick(true))
+            .add_stage_after(CoreStage::Update, FixedTimestepStages::FixedUpdate, SystemStage::parallel()
+                .with_run_criteria(|tick: Res<Tick>| match tick.0 {
+                    true => ShouldRun::Yes,
+                    false => ShouldRun::No
+                })
+            )
+            .add_plugin(FixedInputPlugin::new(InputMap::new()
+                .with_gamepad(GAMEPAD)
+                .with_binding(Action::Jump, InputBinding::Key(KeyCode::Space))
+                .with_binding(Action::Jump, InputBinding::GamepadButton(GamepadButtonType::South))
+                .with_binding_2d(Action::Move, InputBinding2d::arrow_keys())
+                .with_binding_2d(Action::Move, InputBinding2d::left_stick(0.2))
+            ));
+        app
+    }
+
+    fn set_stick(app: &mut App, value: Vec2) {
+        let mut axes = app.world.resource_mut::<Axis<GamepadAxis>>();
+        axes.set(GamepadAxis::new(GAMEPAD, GamepadAxisType::LeftStickX), value.x);
+        axes.set(GamepadAxis::new(GAMEPAD, GamepadAxisType::LeftStickY), value.y);
+    }
+
+    #[test]
+    fn stick_through_deadzone() {
+        let mut app = input_test_app();
+        let ticks = [
+            (Vec2::new(0.1, 0.1), Vec2::ZERO),
+            (Vec2::new(0.6, 0.0), Vec2::new(0.5, 0.0)),
+            (Vec2::new(0.0, -1.0), Vec2::new(0.0, -1.0))
+        ];
+        for (stick, expected) in ticks {
+            set_stick(&mut app, stick);
+            app.update();
+            let value = app.world.resource::<FixedActions<Action>>().value(Action::Move);
+            assert!(value.abs_diff_eq(expected, 0.0001));
+        }
+        assert_eq!(Vec2::ZERO, apply_deadzone(Vec2::new(0.5, 0.5), 1.0));
+    }
+
+    #[test]
+    fn buffers_presses_between_ticks() {
+        let mut app = input_test_app();
+
+        // Taps jump on a frame without a tick
+        app.world.resource_mut::<Tick>().0 = false;
+        app.world.resource_mut::<Input<GamepadButton>>().press(GamepadButton::new(GAMEPAD, GamepadButtonType::South));
+        app.update();
+        let mut buttons = app.world.resource_mut::<Input<GamepadButton>>();
+        buttons.clear();
+        buttons.release(GamepadButton::new(GAMEPAD, GamepadButtonType::South));
+        app.update();
+
+        // Next tick sees the press once
+        app.world.resource_mut::<Tick>().0 = true;
+        app.update();
+        let actions = app.world.resource::<FixedActions<Action>>();
+        assert!(actions.just_pressed(Action::Jump));
+        assert!(!actions.pressed(Action::Jump));
+        app.update();
+        assert!(!app.world.resource::<FixedActions<Action>>().just_pressed(Action::Jump));
+    }
+}
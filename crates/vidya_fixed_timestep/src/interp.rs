@@ -0,0 +1,61 @@
+use std::marker::PhantomData;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+use bevy_math::prelude::*;
+use bevy_time::FixedTimesteps;
+use bevy_transform::prelude::*;
+
+use crate::{lerp_transform, FixedClock, VIDYA_FIXED};
+
+/// Resource that stores the interpolation factor used to blend [`crate::PreviousTransform`]s and
+/// [`crate::CurrentTransform`]s this frame. Updated once per frame right before transforms get interpolated,
+/// so user systems can blend their own values consistently.
+#[derive(Resource, Debug, Copy, Clone, PartialEq, Default)]
+pub struct RenderInterpolation {
+    /// Blend factor between the previous and current tick, clamped between 0 and 1
+    pub t: f32,
+    /// Fixed tick being blended toward
+    pub last_tick: u64
+}
+
+/// Blends two values of a fixed-timestep [`Vec3`] the same way transforms are blended this frame.
+pub fn interp_vec3(prev: Vec3, curr: Vec3, interpolation: &RenderInterpolation) -> Vec3 {
+    prev.lerp(curr, interpolation.t)
+}
+
+/// System param for blending values the same way transforms are blended this frame.
+#[derive(SystemParam)]
+pub struct InterpFactor<'w, 's> {
+    interpolation: Res<'w, RenderInterpolation>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>
+}
+impl<'w, 's> InterpFactor<'w, 's> {
+    /// Blend factor between the previous and current tick
+    pub fn t(&self) -> f32 {
+        self.interpolation.t
+    }
+    pub fn vec3(&self, prev: Vec3, curr: Vec3) -> Vec3 {
+        interp_vec3(prev, curr, &self.interpolation)
+    }
+    pub fn transform(&self, prev: &Transform, curr: &Transform) -> Transform {
+        lerp_transform(prev, curr, self.interpolation.t)
+    }
+}
+
+/// Computes the [`RenderInterpolation`] of this frame.
+pub(crate) fn update_render_interpolation(
+    timesteps: Res<FixedTimesteps>,
+    clock: Res<FixedClock>,
+    mut interpolation: ResMut<RenderInterpolation>
+) {
+    let t = timesteps
+        .get(VIDYA_FIXED)
+        .expect("Missing timestep")
+        .overstep_percentage() as f32;
+    *interpolation = RenderInterpolation {
+        t: t.clamp(0.0, 1.0),
+        last_tick: clock.tick()
+    };
+}
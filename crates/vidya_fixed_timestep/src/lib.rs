@@ -1,11 +1,32 @@
 use std::time::Duration;
 
 use bevy_app::prelude::*;
-use bevy_ecs::schedule::IntoSystemDescriptor;
+use bevy_ecs::schedule::{IntoSystemDescriptor, StageLabelId};
 use bevy_transform::prelude::*;
 use bevy_ecs::prelude::*;
-use bevy_time::{FixedTimestep, FixedTimesteps};
+use bevy_time::FixedTimestep;
 use bevy_reflect::prelude::*;
+use bevy_math::prelude::*;
+
+mod remote;
+mod interp;
+mod input;
+mod rng;
+mod phase;
+pub use remote::*;
+pub use interp::*;
+pub use input::*;
+pub use rng::*;
+pub use phase::*;
+
+#[cfg(feature = "pbr")]
+mod material;
+#[cfg(feature = "pbr")]
+pub use material::*;
+#[cfg(feature = "debug")]
+mod debug;
+#[cfg(feature = "debug")]
+pub use debug::*;
 
 /// Label for fixed timestep
 static VIDYA_FIXED: &str = "VIDYA_FIXED";
@@ -13,23 +34,53 @@ static VIDYA_FIXED: &str = "VIDYA_FIXED";
 
 /// Plugin that interpolates [`Transform`] components between
 /// [`PreviousTransform`] and [`CurrentTransform`] components during the [`CoreStage::PostUpdate`] stage.
-/// Adds the following stages:
+/// Adds the following stages after [`CoreStage::Update`], or after the anchor stage specified:
 ///     [`FixedTimestepStages::FixedUpdate`],
 ///     [`FixedTimestepStages::SyncTransforms`],
 ///     [`FixedTimestepStages::PostFixedUpdate`],
+///     [`FixedTimestepStages::PreInterpolate`],
 ///     [`FixedTimestepStages::InterpolateTransforms`]
 pub struct FixedTimestepPlugin {
-    step: Duration
+    step: Duration,
+    anchor: StageLabelId,
+    snap_scale_sign: bool,
+    seed: u64
 }
 impl FixedTimestepPlugin {
     /// Creates the plugin with the desired timestep duration.
     pub fn new(step: Duration) -> Self {
-        Self { step }
+        Self { step, ..Self::default() }
+    }
+    /// Creates the plugin with its stages placed right after the stage specified instead of [`CoreStage::Update`].
+    /// The anchor stage must already exist when the plugin is added.
+    pub fn anchored_after(anchor: impl StageLabel) -> Self {
+        Self::default().with_anchor(anchor)
+    }
+    /// Places the plugin's stages right after the stage specified.
+    pub fn with_anchor(mut self, anchor: impl StageLabel) -> Self {
+        self.anchor = anchor.as_label();
+        self
+    }
+    /// Determines if scale axes that only flip sign between ticks snap instead of interpolating through zero.
+    /// Defaults to true, which keeps sprites flipped with a negative scale from squashing while they turn.
+    pub fn with_snap_scale_sign(mut self, snap_scale_sign: bool) -> Self {
+        self.snap_scale_sign = snap_scale_sign;
+        self
+    }
+    /// Seed of the [`SimRng`] resource. Defaults to 0.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
     }
 }
 impl Default for FixedTimestepPlugin {
     fn default() -> Self {
-        Self { step: Duration::from_secs_f64(1.0/60.0) }
+        Self {
+            step: Duration::from_secs_f64(1.0/60.0),
+            anchor: CoreStage::Update.as_label(),
+            snap_scale_sign: true,
+            seed: 0
+        }
     }
 }
 impl Plugin for FixedTimestepPlugin {
@@ -40,11 +91,16 @@ impl Plugin for FixedTimestepPlugin {
         app
             .register_type::<CurrentTransform>()
             .register_type::<PreviousTransform>()
+            .insert_resource(FixedClock::new(self.step))
+            .init_resource::<RenderInterpolation>()
+            .insert_resource(InterpolationConfig { snap_scale_sign: self.snap_scale_sign })
+            .insert_resource(SimRng::new(self.seed))
             .add_stage_after(
-                CoreStage::Update,
+                self.anchor,
                 FixedTimestepStages::FixedUpdate,
                 SystemStage::parallel()
                     .with_run_criteria(FixedTimestep::step(step).with_label(VIDYA_FIXED))
+                    .with_system(advance_clock.at_start())
             )
             .add_stage_after(
                 FixedTimestepStages::FixedUpdate,
@@ -54,23 +110,45 @@ impl Plugin for FixedTimestepPlugin {
             .add_stage_after(
                 FixedTimestepStages::SyncTransforms,
                 FixedTimestepStages::PostFixedUpdate,
-                SystemStage::parallel().with_run_criteria(FixedTimestep::step(step))
+                SystemStage::parallel()
+                    .with_run_criteria(FixedTimestep::step(step))
+                    .with_system(sample_remote_transforms.label(FixedTimestepSystems::SampleRemoteTransforms))
             )
             .add_stage_after(
                 FixedTimestepStages::PostFixedUpdate,
+                FixedTimestepStages::PreInterpolate,
+                SystemStage::parallel()
+            )
+            .add_stage_after(
+                FixedTimestepStages::PreInterpolate,
                 FixedTimestepStages::InterpolateTransforms,
                 SystemStage::single_threaded()
                     .with_system(sync_added_transforms.label(FixedTimestepSystems::SyncAddedTransforms))
+                    .with_system(update_render_interpolation.label(FixedTimestepSystems::UpdateRenderInterpolation))
                     .with_system(interpolate_transforms
                         .label(FixedTimestepSystems::InterpolateTransforms)
                         .after(FixedTimestepSystems::SyncAddedTransforms)
+                        .after(FixedTimestepSystems::UpdateRenderInterpolation)
                     )
             );
+
+        #[cfg(feature = "pbr")]
+        app.add_system_to_stage(FixedTimestepStages::InterpolateTransforms, interpolate_emissive
+            .label(FixedTimestepSystems::InterpolateEmissive)
+            .after(FixedTimestepSystems::UpdateRenderInterpolation)
+        );
+
+        #[cfg(feature = "debug")]
+        app
+            .init_resource::<ExternalTransformWarnings>()
+            .add_system_to_stage(FixedTimestepStages::FixedUpdate, record_fixed_start.at_start())
+            .add_system_to_stage(FixedTimestepStages::PreInterpolate, warn_external_transform_writes.at_start());
     }
 }
 
 /// Labels for stages used by the fixed timestep plugin.
-/// Each stage is positioned between [`CoreStage::Update`] and [`CoreStage::PostUpdate`] and in the order specified.
+/// Each stage is positioned between [`CoreStage::Update`] and [`CoreStage::PostUpdate`] and in the order specified,
+/// unless the plugin was anchored elsewhere.
 #[derive(StageLabel, Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum FixedTimestepStages {
     /// Fixed-timestep version of [`CoreStage::Update`].
@@ -84,44 +162,144 @@ pub enum FixedTimestepStages {
     /// Great place to put a physics engine.
     /// Manipulating a [`CurrentTransform`] here will cause the entity to "interpolate".
     PostFixedUpdate,
+    /// Stage that runs every frame after the fixed stages and before interpolation.
+    /// For systems that need to see the results of this frame's ticks before [`Transform`]s are written.
+    PreInterpolate,
     /// Stage where [`Transform`]s are interpolated between [`PreviousTransform`]s and [`CurrentTransform`]s.
     /// Do not touch.
     InterpolateTransforms
 }
 
+impl FixedTimestepStages {
+    const ALL: [Self; 5] = [
+        Self::FixedUpdate,
+        Self::SyncTransforms,
+        Self::PostFixedUpdate,
+        Self::PreInterpolate,
+        Self::InterpolateTransforms
+    ];
+
+    /// Checks if all of the [`FixedTimestepPlugin`]'s stages were added to the app.
+    pub fn exists(app: &App) -> bool {
+        Self::ALL.into_iter().all(|stage| stage.is_added(app))
+    }
+
+    /// Checks if this stage was added to the app.
+    pub fn is_added(self, app: &App) -> bool {
+        app.schedule.get_stage::<SystemStage>(self).is_some()
+    }
+
+    /// Panics with a helpful message if this stage was not added to the app.
+    /// Plugins that depend on a fixed timestep stage should call this before using it.
+    pub fn expect_added(self, app: &App, dependent: &str) {
+        if !self.is_added(app) {
+            panic!("{dependent} requires the {self:?} stage. Add FixedTimestepPlugin before {dependent}.");
+        }
+    }
+}
+
 /// Labels for systems.
 #[derive(SystemLabel, Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum FixedTimestepSystems {
     SyncAddedTransforms,
-    InterpolateTransforms
+    /// Updates the [`RenderInterpolation`] resource
+    UpdateRenderInterpolation,
+    InterpolateTransforms,
+    /// Writes [`RemoteTransformBuffer`] samples to [`CurrentTransform`]s during [`FixedTimestepStages::PostFixedUpdate`]
+    SampleRemoteTransforms,
+    /// Writes blended `InterpolatedEmissive` colors into materials. Requires the `pbr` feature.
+    InterpolateEmissive
+}
+
+/// Resource that keeps track of fixed ticks.
+/// Advanced at the start of every fixed tick, before any [`FixedTimestepStages::FixedUpdate`] system runs.
+#[derive(Resource, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FixedClock {
+    step: Duration,
+    tick: u64
+}
+impl FixedClock {
+    /// Creates a clock that hasn't ticked yet.
+    /// Useful for running systems that depend on the timestep without the plugin, like in tests.
+    pub fn new(step: Duration) -> Self {
+        Self { step, tick: 0 }
+    }
+    /// Number of the current fixed tick. The first tick is 1.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+    /// Duration of a single fixed tick.
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+    /// Duration of a single fixed tick in seconds.
+    pub fn step_seconds(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+    /// Fixed time elapsed since startup in seconds.
+    pub fn elapsed_seconds_f64(&self) -> f64 {
+        self.tick as f64 * self.step.as_secs_f64()
+    }
 }
 
 /// Transform of [`Entity`] during current game tick
 #[derive(Component, Default, Debug, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[reflect(Component)]
 pub struct CurrentTransform(pub Transform);
 
 /// Transform of [`Entity`] during previous game tick
 #[derive(Component, Default, Debug, PartialEq, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[reflect(Component)]
 pub struct PreviousTransform(pub Transform);
 
-/// Interpolates [`Transform`] components between [`PreviousTransform`] and [`CurrentTransform`]1
+/// Resource that configures how [`Transform`]s are interpolated.
+#[derive(Resource, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InterpolationConfig {
+    /// If true, scale axes that only flip sign between ticks snap to the current tick's value.
+    pub snap_scale_sign: bool
+}
+impl Default for InterpolationConfig {
+    fn default() -> Self {
+        Self { snap_scale_sign: true }
+    }
+}
+
+/// Interpolates [`Transform`] components between [`PreviousTransform`] and [`CurrentTransform`]
 fn interpolate_transforms(
-    timesteps: Res<FixedTimesteps>,
+    interpolation: Res<RenderInterpolation>,
+    config: Res<InterpolationConfig>,
     mut query: Query<(&PreviousTransform, &CurrentTransform, &mut Transform)>
 ) {
-    let t = timesteps
-        .get(VIDYA_FIXED)
-        .expect("Missing timestep")
-        .overstep_percentage() as f32;
     for (prev, current, mut trans) in &mut query {
-        trans.translation = prev.0.translation.lerp(current.0.translation, t);
-        trans.scale = prev.0.scale.lerp(current.0.scale, t);
-        trans.rotation = prev.0.rotation.lerp(current.0.rotation, t);
+        *trans = lerp_transform(&prev.0, &current.0, interpolation.t);
+        if config.snap_scale_sign {
+            trans.scale = snap_scale_sign(prev.0.scale, current.0.scale, trans.scale);
+        }
+    }
+}
+
+/// Replaces the axes of an interpolated scale with `b`'s where `a` and `b` only differ in sign.
+fn snap_scale_sign(a: Vec3, b: Vec3, interpolated: Vec3) -> Vec3 {
+    let flipped = a.cmpne(b) & (a.abs() - b.abs()).abs().cmplt(Vec3::splat(f32::EPSILON));
+    Vec3::select(flipped, b, interpolated)
+}
+
+/// Linearly interpolates between two [`Transform`]s.
+pub(crate) fn lerp_transform(a: &Transform, b: &Transform, t: f32) -> Transform {
+    Transform {
+        translation: a.translation.lerp(b.translation, t),
+        rotation: a.rotation.lerp(b.rotation, t),
+        scale: a.scale.lerp(b.scale, t)
     }
 }
 
+/// Advances the [`FixedClock`] by one tick.
+fn advance_clock(world: &mut World) {
+    world.resource_mut::<FixedClock>().tick += 1;
+}
+
 /// Reusable system that syncs the previous transform state with the current.
 /// Should run before updating [`CurrentTransform`].
 fn sync_transforms(mut query: Query<(&mut PreviousTransform, &CurrentTransform)>) {
@@ -151,16 +329,39 @@ fn sync_added_transforms(mut query: Query<
 
 /// This trait adds a helper method for adding fixed systems
 pub trait AppExt {
+    /// Adds a system that runs during the [`Phase`] specified.
+    fn add_phase_system<Params>(&mut self, phase: Phase, system: impl IntoSystemDescriptor<Params>) -> &mut Self;
+    /// Adds a system set that runs during the [`Phase`] specified.
+    fn add_phase_system_set(&mut self, phase: Phase, system_set: SystemSet) -> &mut Self;
     fn add_fixed_system<Params>(&mut self, system: impl IntoSystemDescriptor<Params>) -> &mut Self;
     fn add_fixed_system_set(&mut self, system_set: SystemSet) -> &mut Self;
+    /// Adds a system that runs every frame between the fixed stages and [`FixedTimestepStages::InterpolateTransforms`].
+    fn add_system_between_fixed_and_interp<Params>(&mut self, system: impl IntoSystemDescriptor<Params>) -> &mut Self;
 }
 impl AppExt for App {
+    fn add_phase_system<Params>(&mut self, phase: Phase, system: impl IntoSystemDescriptor<Params>) -> &mut Self {
+        phase.stage().expect_added(self, "add_phase_system");
+        self.add_system_to_stage(phase.stage(), system);
+        self
+    }
+    fn add_phase_system_set(&mut self, phase: Phase, system_set: SystemSet) -> &mut Self {
+        phase.stage().expect_added(self, "add_phase_system_set");
+        self.add_system_set_to_stage(phase.stage(), system_set);
+        self
+    }
     fn add_fixed_system<Params>(&mut self, system: impl IntoSystemDescriptor<Params>) -> &mut Self {
-        self.add_system_to_stage(FixedTimestepStages::FixedUpdate, system);
+        Phase::Update.stage().expect_added(self, "add_fixed_system");
+        self.add_system_to_stage(Phase::Update.stage(), system);
         self
     }
     fn add_fixed_system_set(&mut self, system_set: SystemSet) -> &mut Self {
-        self.add_system_set_to_stage(FixedTimestepStages::FixedUpdate, system_set);
+        Phase::Update.stage().expect_added(self, "add_fixed_system_set");
+        self.add_system_set_to_stage(Phase::Update.stage(), system_set);
+        self
+    }
+    fn add_system_between_fixed_and_interp<Params>(&mut self, system: impl IntoSystemDescriptor<Params>) -> &mut Self {
+        Phase::Interpolate.stage().expect_added(self, "add_system_between_fixed_and_interp");
+        self.add_system_to_stage(Phase::Interpolate.stage(), system);
         self
     }
 }
@@ -171,6 +372,131 @@ pub mod prelude {
         FixedTimestepPlugin,
         CurrentTransform,
         PreviousTransform,
+        FixedClock,
+        RemoteTransformBuffer,
+        RenderInterpolation,
+        InterpolationConfig,
+        InterpFactor,
+        SimRng,
+        Phase,
+        FixedInputPlugin,
+        FixedActions,
+        InputMap,
+        InputBinding,
+        InputBinding2d,
         AppExt
     };
-}
\ No newline at end of file
+    #[cfg(feature = "pbr")]
+    pub use crate::InterpolatedEmissive;
+}
+#[cfg(test)]
+mod test {
+
+    use std::time::{Duration, Instant};
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+    use bevy_time::{Time, FixedTimesteps};
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    #[test]
+    fn render_interpolation_matches_transforms() {
+        let mut app = App::new();
+        app
+            .add_plugin(FixedTimestepPlugin::new(Duration::from_millis(100)))
+            .insert_resource(Time::default())
+            .init_resource::<FixedTimesteps>();
+        let entity = app.world
+            .spawn((
+                Transform::default(),
+                PreviousTransform::default(),
+                CurrentTransform(Transform::from_xyz(10.0, 0.0, 0.0))
+            ))
+            .id();
+
+        // Advances by 1.25 ticks, then pauses for a few frames
+        let start = Instant::now();
+        let frames = [0, 50, 125, 125, 125];
+        for millis in frames {
+            app.world.resource_mut::<Time>().update_with_instant(start + Duration::from_millis(millis));
+            app.update();
+            let interpolation = *app.world.resource::<RenderInterpolation>();
+            let prev = app.world.get::<PreviousTransform>(entity).unwrap().0;
+            let current = app.world.get::<CurrentTransform>(entity).unwrap().0;
+            let applied = app.world.get::<Transform>(entity).unwrap().translation;
+            assert!((0.0..=1.0).contains(&interpolation.t));
+            assert_eq!(app.world.resource::<FixedClock>().tick(), interpolation.last_tick);
+            assert_eq!(interp_vec3(prev.translation, current.translation, &interpolation), applied);
+        }
+        let interpolation = app.world.resource::<RenderInterpolation>();
+        assert_eq!(1, interpolation.last_tick);
+        assert!((interpolation.t - 0.25).abs() < 0.0001);
+    }
+
+    fn flip_scale(snap_scale_sign: bool) -> Vec<f32> {
+        let mut app = App::new();
+        app
+            .add_plugin(FixedTimestepPlugin::new(Duration::from_millis(100)).with_snap_scale_sign(snap_scale_sign))
+            .insert_resource(Time::default())
+            .init_resource::<FixedTimesteps>();
+        let entity = app.world
+            .spawn((Transform::default(), PreviousTransform::default(), CurrentTransform::default()))
+            .id();
+
+        // Flips scale.x, then renders frames until the flip has fully ticked through
+        let start = Instant::now();
+        app.world.resource_mut::<Time>().update_with_instant(start);
+        app.update();
+        app.world.get_mut::<CurrentTransform>(entity).unwrap().0.scale.x = -1.0;
+        let mut scales = Vec::new();
+        for millis in (20..=240).step_by(20) {
+            app.world.resource_mut::<Time>().update_with_instant(start + Duration::from_millis(millis));
+            app.update();
+            scales.push(app.world.get::<Transform>(entity).unwrap().scale.x);
+        }
+        scales
+    }
+
+    #[test]
+    fn snap_scale_sign() {
+        let scales = flip_scale(true);
+        assert!(scales.iter().all(|scale| scale.abs() >= 1.0), "{scales:?}");
+        assert_eq!(-1.0, *scales.last().unwrap());
+        let scales = flip_scale(false);
+        assert!(scales.iter().any(|scale| scale.abs() < 1.0), "{scales:?}");
+    }
+
+    #[derive(StageLabel)]
+    struct CustomStage;
+
+    #[derive(Resource, Default)]
+    struct Order(Vec<&'static str>);
+
+    #[test]
+    fn anchored_after() {
+        let mut app = App::new();
+        app
+            .init_resource::<Order>()
+            .insert_resource(Time::default())
+            .init_resource::<FixedTimesteps>()
+            .add_stage_after(CoreStage::PostUpdate, CustomStage, SystemStage::parallel())
+            .add_plugin(FixedTimestepPlugin::anchored_after(CustomStage))
+            .add_system_to_stage(CoreStage::PostUpdate, |mut order: ResMut<Order>| order.0.push("post_update"))
+            .add_system_to_stage(CustomStage, |mut order: ResMut<Order>| order.0.push("custom"))
+            .add_system_between_fixed_and_interp(|mut order: ResMut<Order>| order.0.push("pre_interpolate"))
+            .add_system_to_stage(CoreStage::Last, |mut order: ResMut<Order>| order.0.push("last"));
+        app.update();
+        assert!(FixedTimestepStages::exists(&app));
+        assert_eq!(vec!["post_update", "custom", "pre_interpolate", "last"], app.world.resource::<Order>().0);
+    }
+
+    #[test]
+    #[should_panic(expected = "add_fixed_system requires the FixedUpdate stage. Add FixedTimestepPlugin before add_fixed_system.")]
+    fn missing_plugin() {
+        let mut app = App::new();
+        assert!(!FixedTimestepStages::exists(&app));
+        app.add_fixed_system(|| {});
+    }
+}
@@ -0,0 +1,85 @@
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_pbr::prelude::*;
+use bevy_render::prelude::*;
+
+use crate::RenderInterpolation;
+
+/// Emissive color of an [`Entity`]'s [`StandardMaterial`] during the previous and current fixed ticks.
+/// Blended into the material every frame the same way transforms are blended.
+/// The first time an entity's emissive gets written, its material is cloned so that shared materials don't get mutated.
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct InterpolatedEmissive {
+    pub previous: Color,
+    pub current: Color
+}
+impl InterpolatedEmissive {
+    pub fn new(color: Color) -> Self {
+        Self {
+            previous: color,
+            current: color
+        }
+    }
+
+    /// Sets the emissive color of this tick, shifting the current color to the previous.
+    /// Should be called once per fixed tick while the color is changing, like [`crate::CurrentTransform`]s being synced.
+    pub fn set_emissive(&mut self, current: Color) {
+        self.previous = self.current;
+        self.current = current;
+    }
+
+    /// Blends the previous and current colors in linear space.
+    pub fn lerp(&self, t: f32) -> Color {
+        let [pr, pg, pb, pa] = self.previous.as_linear_rgba_f32();
+        let [cr, cg, cb, ca] = self.current.as_linear_rgba_f32();
+        Color::rgba_linear(
+            pr + (cr - pr) * t,
+            pg + (cg - pg) * t,
+            pb + (cb - pb) * t,
+            pa + (ca - pa) * t
+        )
+    }
+}
+
+/// Marks an [`Entity`] whose material was cloned for its [`InterpolatedEmissive`].
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct UniqueEmissiveMaterial;
+
+/// Writes blended [`InterpolatedEmissive`] colors into materials.
+pub(crate) fn interpolate_emissive(
+    mut commands: Commands,
+    interpolation: Res<RenderInterpolation>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut query: Query<(Entity, &InterpolatedEmissive, &mut Handle<StandardMaterial>, Option<&UniqueEmissiveMaterial>)>
+) {
+    for (entity, emissive, mut handle, unique) in &mut query {
+        if unique.is_none() {
+            let material = match materials.get(&handle) {
+                Some(material) => material.clone(),
+                None => continue
+            };
+            *handle = materials.add(material);
+            commands.entity(entity).insert(UniqueEmissiveMaterial);
+        }
+        if let Some(material) = materials.get_mut(&handle) {
+            material.emissive = emissive.lerp(interpolation.t);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_render::prelude::*;
+
+    use crate::InterpolatedEmissive;
+
+    #[test]
+    fn shifts_and_blends() {
+        let mut emissive = InterpolatedEmissive::new(Color::BLACK);
+        emissive.set_emissive(Color::WHITE);
+        assert_eq!(Color::BLACK, emissive.previous);
+        assert_eq!(Color::WHITE, emissive.current);
+        assert_eq!([0.5, 0.5, 0.5, 1.0], emissive.lerp(0.5).as_linear_rgba_f32());
+    }
+}
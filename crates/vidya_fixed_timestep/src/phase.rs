@@ -0,0 +1,68 @@
+use crate::FixedTimestepStages;
+
+/// Logical phases of a fixed tick that systems can be added to with [`crate::AppExt::add_phase_system`].
+/// Downstream code should prefer phases over [`FixedTimestepStages`], which only exist because of how
+/// fixed timesteps currently work in Bevy and will go away once Bevy replaces stages.
+/// Stages are the only backend phases map onto, since Bevy 0.9 has no `FixedTime` schedule to map them onto instead.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Phase {
+    /// Runs every fixed tick. For general game logic.
+    Update,
+    /// Runs every fixed tick after [`Phase::Update`], once previous transforms were synced. For physics.
+    PostUpdate,
+    /// Runs every frame after the fixed ticks of that frame, before transforms get interpolated.
+    Interpolate
+}
+
+impl Phase {
+    /// Stage the phase runs in.
+    pub fn stage(self) -> FixedTimestepStages {
+        match self {
+            Self::Update => FixedTimestepStages::FixedUpdate,
+            Self::PostUpdate => FixedTimestepStages::PostFixedUpdate,
+            Self::Interpolate => FixedTimestepStages::PreInterpolate
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::time::{Duration, Instant};
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+    use bevy_time::{Time, FixedTimesteps};
+
+    use crate::*;
+
+    /// Phases that ran, in order
+    #[derive(Resource, Default)]
+    struct Ran(Vec<Phase>);
+
+    #[test]
+    fn phases_run_in_order() {
+        let mut app = App::new();
+        app
+            .add_plugin(FixedTimestepPlugin::new(Duration::from_millis(100)))
+            .insert_resource(Time::default())
+            .init_resource::<FixedTimesteps>()
+            .init_resource::<Ran>()
+            .add_phase_system(Phase::Interpolate, |mut ran: ResMut<Ran>| ran.0.push(Phase::Interpolate))
+            .add_phase_system(Phase::PostUpdate, |mut ran: ResMut<Ran>| ran.0.push(Phase::PostUpdate))
+            .add_phase_system_set(Phase::Update, SystemSet::new()
+                .with_system(|mut ran: ResMut<Ran>| ran.0.push(Phase::Update))
+            );
+
+        // Ticks on the second frame only
+        let start = Instant::now();
+        for millis in [0, 100, 150] {
+            app.world.resource_mut::<Time>().update_with_instant(start + Duration::from_millis(millis));
+            app.update();
+        }
+        assert_eq!(
+            vec![Phase::Interpolate, Phase::Update, Phase::PostUpdate, Phase::Interpolate, Phase::Interpolate],
+            app.world.resource::<Ran>().0
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+
+use bevy_ecs::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::{lerp_transform, CurrentTransform, FixedClock};
+
+/// Buffer of timestamped [`Transform`]s received from an external source, like a remote player over the network.
+/// Each fixed tick, the buffer is sampled `interpolation_delay` seconds in the past and the result is written to
+/// the entity's [`CurrentTransform`], so remote entities get interpolated like any other.
+/// Timestamps should be on the same clock as [`FixedClock::elapsed_seconds_f64`].
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct RemoteTransformBuffer {
+    samples: VecDeque<(f64, Transform)>,
+    /// Maximum number of samples stored. Oldest samples are evicted first.
+    pub capacity: usize,
+    /// How far in the past, in seconds, the buffer is sampled.
+    pub interpolation_delay: f64,
+    /// How far past the newest sample, in seconds, motion gets extrapolated before holding still.
+    pub max_extrapolation: f64
+}
+impl RemoteTransformBuffer {
+    pub fn new(interpolation_delay: f64) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            capacity: 32,
+            interpolation_delay,
+            max_extrapolation: 0.25
+        }
+    }
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+    pub fn with_max_extrapolation(mut self, max_extrapolation: f64) -> Self {
+        self.max_extrapolation = max_extrapolation;
+        self
+    }
+
+    /// Adds a sample. Samples that arrive out of order are sorted into place.
+    pub fn push(&mut self, time: f64, transform: Transform) {
+        let idx = self.samples.partition_point(|(sample_time, _)| *sample_time <= time);
+        match idx.checked_sub(1).map(|prev| self.samples[prev].0 == time) {
+            Some(true) => self.samples[idx - 1].1 = transform,
+            _ => self.samples.insert(idx, (time, transform))
+        }
+        while self.samples.len() > self.capacity.max(1) {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Samples the buffer at the time specified.
+    /// Interpolates between the surrounding samples, extrapolates past the newest sample up to `max_extrapolation`
+    /// seconds, and holds otherwise. Returns None if the buffer is empty.
+    pub fn sample(&self, time: f64) -> Option<Transform> {
+        let (first_time, first) = *self.samples.front()?;
+        let (last_time, last) = *self.samples.back()?;
+        if time <= first_time {
+            return Some(first);
+        }
+
+        // Extrapolates using the two newest samples
+        if time >= last_time {
+            if self.samples.len() < 2 {
+                return Some(last);
+            }
+            let (prev_time, prev) = self.samples[self.samples.len() - 2];
+            let time = time.min(last_time + self.max_extrapolation);
+            let t = ((time - prev_time) / (last_time - prev_time)) as f32;
+            return Some(Transform {
+                translation: prev.translation.lerp(last.translation, t),
+                rotation: last.rotation,
+                scale: prev.scale.lerp(last.scale, t)
+            });
+        }
+
+        // Interpolates between surrounding samples
+        let idx = self.samples.partition_point(|(sample_time, _)| *sample_time <= time);
+        let (a_time, a) = self.samples[idx - 1];
+        let (b_time, b) = self.samples[idx];
+        let t = ((time - a_time) / (b_time - a_time)) as f32;
+        Some(lerp_transform(&a, &b, t))
+    }
+
+    /// Removes samples that are no longer needed to sample at or after the time specified.
+    /// Keeps at least two samples so that extrapolation remains possible.
+    pub fn evict_before(&mut self, time: f64) {
+        while self.samples.len() > 2 && self.samples[1].0 <= time {
+            self.samples.pop_front();
+        }
+    }
+}
+
+/// Writes sampled remote transforms to [`CurrentTransform`]s.
+pub(crate) fn sample_remote_transforms(
+    clock: Res<FixedClock>,
+    mut query: Query<(&mut RemoteTransformBuffer, &mut CurrentTransform)>
+) {
+    let now = clock.elapsed_seconds_f64();
+    for (mut buffer, mut current) in &mut query {
+        let time = now - buffer.interpolation_delay;
+        if let Some(transform) = buffer.sample(time) {
+            current.0 = transform;
+        }
+        buffer.evict_before(time);
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_transform::prelude::*;
+
+    use crate::RemoteTransformBuffer;
+
+    #[test]
+    fn smooth_output() {
+        let speed = 3.0;
+        let mut buffer = RemoteTransformBuffer::new(0.15);
+        let mut positions = Vec::new();
+
+        // Receives samples at 10 hz while ticking at 60 hz
+        for tick in 0..120 {
+            let now = tick as f64 / 60.0;
+            if tick % 6 == 0 {
+                buffer.push(now, Transform::from_xyz(now as f32 * speed, 0.0, 0.0));
+            }
+            let time = now - buffer.interpolation_delay;
+            positions.push(buffer.sample(time).unwrap().translation.x);
+            buffer.evict_before(time);
+        }
+
+        // Once the delay has elapsed, motion advances the same amount every tick
+        for pair in positions[10..].windows(2) {
+            assert!((pair[1] - pair[0] - speed / 60.0).abs() < 0.0001);
+        }
+        assert!(buffer.len() <= 3);
+    }
+
+    #[test]
+    fn extrapolates_then_holds() {
+        let mut buffer = RemoteTransformBuffer::new(0.0).with_max_extrapolation(0.5);
+        buffer.push(0.0, Transform::from_xyz(0.0, 0.0, 0.0));
+        buffer.push(1.0, Transform::from_xyz(1.0, 0.0, 0.0));
+        assert_eq!(1.25, buffer.sample(1.25).unwrap().translation.x);
+        assert_eq!(1.5, buffer.sample(1.5).unwrap().translation.x);
+        assert_eq!(1.5, buffer.sample(10.0).unwrap().translation.x);
+    }
+
+    #[test]
+    fn sorts_out_of_order_samples() {
+        let mut buffer = RemoteTransformBuffer::new(0.0);
+        buffer.push(1.0, Transform::from_xyz(1.0, 0.0, 0.0));
+        buffer.push(0.0, Transform::from_xyz(0.0, 0.0, 0.0));
+        buffer.push(2.0, Transform::from_xyz(2.0, 0.0, 0.0));
+        assert_eq!(0.5, buffer.sample(0.5).unwrap().translation.x);
+        assert_eq!(1.5, buffer.sample(1.5).unwrap().translation.x);
+    }
+}
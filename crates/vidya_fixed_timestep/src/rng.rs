@@ -0,0 +1,162 @@
+use bevy_ecs::prelude::*;
+use rand::distributions::uniform::{SampleRange, SampleUniform};
+use rand::distributions::{Distribution, Standard};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Seeded random number generator for simulation code.
+/// Unlike `rand::thread_rng()`, results only depend on the seed and the order numbers are drawn in, so simulations replay identically.
+/// Should only be advanced by [`crate::FixedTimestepStages`] systems, since frame rate varies between runs.
+/// Systems that draw numbers in an unspecified order relative to each other should each use their own [`SimRng::fork`].
+/// Cloning takes a snapshot that can be restored later by assigning it back.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct SimRng {
+    seed: u64,
+    rng: ChaCha8Rng
+}
+
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed)
+        }
+    }
+
+    /// Seed this generator was created from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Child generator whose stream only depends on this generator's seed and the label specified.
+    /// Forking doesn't advance this generator, and forking twice with the same label yields identical streams.
+    pub fn fork(&self, label: &str) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(fnv1a(label.as_bytes()));
+        Self { seed: self.seed, rng }
+    }
+
+    /// Random value, like [`Rng::gen`].
+    pub fn gen<T>(&mut self) -> T where Standard: Distribution<T> {
+        self.rng.gen()
+    }
+
+    /// Random value in a range, like [`Rng::gen_range`].
+    /// Panics if the range is empty.
+    pub fn gen_range<T: SampleUniform, R: SampleRange<T>>(&mut self, range: R) -> T {
+        self.rng.gen_range(range)
+    }
+
+    /// True with the probability specified, like [`Rng::gen_bool`].
+    /// Panics if `p` is not in 0..=1.
+    pub fn gen_bool(&mut self, p: f64) -> bool {
+        self.rng.gen_bool(p)
+    }
+
+    /// Random index into a collection of the length specified. None if it's empty.
+    pub fn gen_index(&mut self, len: usize) -> Option<usize> {
+        if len == 0 {
+            None
+        }
+        else {
+            Some(self.rng.gen_range(0..len))
+        }
+    }
+}
+
+impl Default for SimRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Allows using the generator with the rest of rand, like shuffling a slice.
+impl RngCore for SimRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Hash that stays the same across platforms and Rust versions, unlike the std hashers.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::time::{Duration, Instant};
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+    use bevy_time::{Time, FixedTimesteps};
+
+    use crate::*;
+
+    /// Positions drawn from the rng every fixed tick
+    #[derive(Resource, Default)]
+    struct Positions(Vec<f32>);
+
+    fn scatter(mut rng: ResMut<SimRng>, mut positions: ResMut<Positions>) {
+        let position = rng.gen_range(-10.0..10.0);
+        positions.0.push(position);
+    }
+
+    /// Runs a simulation at an uneven frame rate, returning the positions drawn.
+    fn simulate(seed: u64, frame_millis: u64) -> Vec<f32> {
+        let mut app = App::new();
+        app
+            .add_plugin(FixedTimestepPlugin::new(Duration::from_millis(10)).with_seed(seed))
+            .insert_resource(Time::default())
+            .init_resource::<FixedTimesteps>()
+            .init_resource::<Positions>()
+            .add_fixed_system(scatter);
+        let start = Instant::now();
+        let mut millis = 0;
+        while millis < 1000 {
+            app.world.resource_mut::<Time>().update_with_instant(start + Duration::from_millis(millis));
+            app.update();
+            millis += frame_millis;
+        }
+        app.world.remove_resource::<Positions>().unwrap().0
+    }
+
+    #[test]
+    fn replays_identically() {
+        let positions = simulate(5, 16);
+        assert!(positions.len() >= 90);
+        let replayed = simulate(5, 33);
+        let len = positions.len().min(replayed.len());
+        assert_eq!(positions[..len], replayed[..len]);
+        assert_ne!(positions[..len], simulate(6, 16)[..len]);
+    }
+
+    #[test]
+    fn fork_and_restore() {
+        let mut rng = SimRng::new(5);
+        let snapshot = rng.clone();
+        let mut a = rng.fork("particles");
+        let first: Vec<u32> = (0..8).map(|_| a.gen()).collect();
+
+        // Forks don't depend on how far the parent has advanced
+        let drawn: Vec<u32> = (0..8).map(|_| rng.gen()).collect();
+        let mut b = rng.fork("particles");
+        assert_eq!(first, (0..8).map(|_| b.gen()).collect::<Vec<u32>>());
+        assert_ne!(first, drawn);
+        assert_ne!(rng.fork("particles"), rng.fork("enemies"));
+
+        // Restoring a snapshot repeats the numbers drawn since
+        rng = snapshot;
+        assert_eq!(drawn, (0..8).map(|_| rng.gen()).collect::<Vec<u32>>());
+        assert_eq!(None, rng.gen_index(0));
+    }
+}
@@ -1,44 +1,29 @@
-use bevy::prelude::shape::{Plane, Icosphere};
-use bevy_inspector_egui::WorldInspectorPlugin;
-use vidya_fixed_timestep::{CurrentTransform, PreviousTransform, FixedTimestepPlugin};
+use vidya_fixed_timestep::FixedTimestepPlugin;
 use vidya_physics::*;
+use vidya_physics::debug::*;
 use bevy::prelude::*;
 
-// Marks ball entity
-#[derive(Component, Debug)]
-struct Ball;
+// Room constants
+const ROOM_SIZE: f32 = 10.0;
+const WALL_HEIGHT: f32 = 2.0;
 
-// Wall constants
-const FLOOR: f32 = 0.0;
-const LEFT_WALL: f32 = -5.0;
-const RIGHT_WALL: f32 = 5.0;
-const NEAR_WALL: f32 = 5.0;
-const FAR_WALL: f32 = -5.0;
-const JUMP_SPEED: f32 = 0.2;
-
-/// Example where only a single falling entity is spawned.
+/// Example where a ball bounces around a walled in room, glancing off the corners of a box in the middle.
 pub fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugin(WorldInspectorPlugin::new())
         .add_plugin(FixedTimestepPlugin::default())
         .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .insert_resource(PhysicsDebugConfig { contact_points: true, ..default() })
         .add_startup_system(startup)
-        .add_system(bounce_ball)
         .run();
 }
 
-fn startup(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>
-) {
-
-    // Adds gravity
-    commands.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+fn startup(mut commands: Commands) {
 
     // Spawns light above scene
-    commands.spawn_bundle(PointLightBundle {
+    commands.spawn(PointLightBundle {
         point_light: PointLight {
             intensity: 1500.0,
             shadows_enabled: true,
@@ -48,79 +33,37 @@ fn startup(
         ..default()
     });
 
-    // Spawns plane
-    commands.spawn_bundle(PbrBundle {
-        mesh: meshes.add(Plane { size: 10.0 }.into()),
-        material: materials.add(Color::GREEN.into()),
-        transform: Transform::from_xyz(0.0, 0.0, 0.0),
-        ..default()
-    });
+    // Spawns floor, walls and a box in the middle
+    let half = ROOM_SIZE / 2.0;
+    let terrain = [
+        (Vec3::new(0.0, -0.5, 0.0), HalfExtents::new(ROOM_SIZE, 1.0, ROOM_SIZE), Color::GREEN),
+        (Vec3::new(-half - 0.5, WALL_HEIGHT / 2.0, 0.0), HalfExtents::new(1.0, WALL_HEIGHT, ROOM_SIZE), Color::GRAY),
+        (Vec3::new(half + 0.5, WALL_HEIGHT / 2.0, 0.0), HalfExtents::new(1.0, WALL_HEIGHT, ROOM_SIZE), Color::GRAY),
+        (Vec3::new(0.0, WALL_HEIGHT / 2.0, -half - 0.5), HalfExtents::new(ROOM_SIZE, WALL_HEIGHT, 1.0), Color::GRAY),
+        (Vec3::new(0.0, WALL_HEIGHT / 2.0, half + 0.5), HalfExtents::new(ROOM_SIZE, WALL_HEIGHT, 1.0), Color::GRAY),
+        (Vec3::new(0.0, 0.5, 0.0), HalfExtents::new(2.0, 1.0, 2.0), Color::BLUE)
+    ];
+    for (position, bounds, color) in terrain {
+        commands
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+                ..PhysicsBundle::new(Transform::from_translation(position), bounds, Shape::Cuboid)
+            })
+            .insert((AntiGravity, StaticBody, DebugRender(color)));
+    }
 
-    // Spawns ball
-    let start_transform = Transform::from_xyz(0.0, 0.5, 0.0);
+    // Spawns ball, which keeps all of its speed when bouncing
     commands
-        .spawn(PbrBundle {
-            mesh: meshes.add(Icosphere { radius: 0.5, subdivisions: 3 }.into()),
-            material: materials.add(Color::RED.into()),
-            ..default()
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            ..PhysicsBundle::new(Transform::from_xyz(-3.0, 3.0, -2.5), HalfExtents::new(1.0, 1.0, 1.0), Shape::Sphere)
+                .with_velocity(Velocity(Vec3::new(0.05, 0.0, 0.025)))
         })
-        .insert(
-            PhysicsBundle {
-                current_transform: CurrentTransform(start_transform),
-                previous_transform: PreviousTransform(start_transform),
-                bounds: HalfExtents::new(1.0, 1.0, 1.0),
-                velocity: Velocity(Vec3::new(0.05, JUMP_SPEED, 0.025)),
-                ..default()
-            }
-        )
-        .insert(Ball);
+        .insert((Restitution(1.0), DebugRender(Color::RED)));
 
     // Spawns camera
     commands.spawn(Camera3dBundle {
-        transform: Transform::from_xyz(0.0, 2.0, 10.0).looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Y),
+        transform: Transform::from_xyz(0.0, 8.0, 12.0).looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Y),
         ..default()
     });
 }
-
-fn bounce_ball(mut entities: Query<
-    (
-        &mut CurrentTransform,
-        &HalfExtents,
-        &mut Velocity
-    ),
-    With<Ball>>
-) {
-    for (mut trans, bounds, mut vel) in &mut entities {
-
-        // Bounces off floor
-        let trans = &mut trans.0.translation;
-        if trans.y - bounds.0.y <= FLOOR {
-            trans.y = FLOOR + bounds.0.y;
-            vel.0.y = JUMP_SPEED;
-        }
-
-        // Bounces off left wall
-        if trans.x - bounds.0.x <= LEFT_WALL {
-            trans.x = LEFT_WALL + bounds.0.x;
-            vel.0.x *= -1.0;
-        }
-
-        // Bounces off right wall
-        if trans.x + bounds.0.x >= RIGHT_WALL {
-            trans.x = RIGHT_WALL - bounds.0.x;
-            vel.0.x *= -1.0;
-        }
-
-        // Bounces off near wall
-        if trans.z + bounds.0.z >= NEAR_WALL {
-            trans.z = NEAR_WALL - bounds.0.z;
-            vel.0.z *= -1.0;
-        }
-
-        // Bounces off far wall
-        if trans.z - bounds.0.z <= FAR_WALL {
-            trans.z = FAR_WALL + bounds.0.z;
-            vel.0.z *= -1.0;
-        }
-    }
-}
\ No newline at end of file
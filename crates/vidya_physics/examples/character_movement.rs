@@ -0,0 +1,109 @@
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Character constants
+const ACCELERATION: f32 = 0.014;
+const AIR_CONTROL: f32 = 0.1;
+const JUMP_SPEED: f32 = 0.25;
+const DEADZONE: f32 = 0.2;
+
+/// Actions the character can take
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Action {
+    Move,
+    Jump
+}
+
+/// Example where a character moves and jumps using either the keyboard or a gamepad.
+/// Arrow keys or the left stick move, and space or the south button jump.
+/// The character moves with [`MoveAndSlide`], so walking diagonally into the rim slides along it.
+/// Strong ground [`Friction`] stops it quickly, while weak [`AirFriction`] lets jumps carry it far.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(FixedInputPlugin::new(InputMap::new()
+            .with_binding_2d(Action::Move, InputBinding2d::arrow_keys())
+            .with_binding_2d(Action::Move, InputBinding2d::left_stick(DEADZONE))
+            .with_binding(Action::Jump, InputBinding::Key(KeyCode::Space))
+            .with_binding(Action::Jump, InputBinding::GamepadButton(GamepadButtonType::South))
+        ))
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .add_startup_system(startup)
+        .add_fixed_system(control_character)
+        .run();
+}
+
+/// Marker for the character
+#[derive(Component)]
+struct Character;
+
+/// Spawns light, floor, character and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns floor chunk with a rim around its edges
+    let mut chunk = VoxelChunk::new(UVec3::new(20, 3, 20));
+    chunk
+        .set_voxel_box(UVec3::new(0, 1, 0), UVec3::new(20, 3, 20), VoxelData::new(Voxel::Cuboid))
+        .set_voxel_box(UVec3::new(1, 1, 1), UVec3::new(19, 3, 19), VoxelData::default())
+        .set_voxel_box(UVec3::ZERO, UVec3::new(20, 1, 20), VoxelData::new(Voxel::Cuboid));
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, 0.5, 0.0), HalfExtents::new(20.0, 3.0, 20.0), Shape::VoxelChunk(chunk))
+        })
+        .insert((AntiGravity, DebugRender::default()));
+
+    // Spawns character
+    let character = commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.0, 0.0), HalfExtents::new(1.0, 2.0, 1.0), Shape::Cuboid)
+        })
+        .insert((
+            Character,
+            CharacterController::default(),
+            MoveAndSlide,
+            Friction(Vec3::new(0.0005, 1.0, 0.0005)),
+            AirFriction(Vec3::new(0.5, 1.0, 0.5)),
+            DebugRender(Color::RED)
+        ))
+        .id();
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(character),
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 6.0, 12.0)),
+            ..default()
+        });
+}
+
+/// Accelerates the character, with less control in the air, and jumps when it's able to
+fn control_character(
+    actions: Res<FixedActions<Action>>,
+    mut characters: Query<(&mut Velocity, &mut CharacterController), With<Character>>
+) {
+    let dir = actions.value(Action::Move);
+    for (mut vel, mut controller) in &mut characters {
+        let acceleration = if controller.is_grounded() { ACCELERATION } else { ACCELERATION * AIR_CONTROL };
+        vel.0.x += dir.x * acceleration;
+        vel.0.z -= dir.y * acceleration;
+        if actions.just_pressed(Action::Jump) {
+            controller.try_jump(JUMP_SPEED);
+        }
+    }
+}
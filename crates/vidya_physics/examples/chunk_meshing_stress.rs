@@ -0,0 +1,105 @@
+use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Terrain constants
+const CHUNK_SIZE: UVec3 = UVec3::new(32, 32, 32);
+const SPEED: f32 = 0.5;
+
+/// Example where large hilly chunks stream in continuously around a fast-moving focus.
+/// Chunk meshes are generated off the main thread, so the logged frame rate should stay steady.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .add_plugin(ChunkStreamingPlugin::new(HillProvider, ChunkStreamingConfig {
+            radius: UVec3::new(3, 0, 3),
+            chunk_size: CHUNK_SIZE,
+            loads_per_tick: 2,
+            ..default()
+        }))
+        .add_startup_system(startup)
+        .add_system(render_loaded_chunks)
+        .add_system(log_in_flight_tasks)
+        .run();
+}
+
+/// Generates rolling hills in the chunks at y = 0
+struct HillProvider;
+impl ChunkProvider for HillProvider {
+    fn load(&self, coords: IVec3) -> Option<VoxelChunk> {
+        if coords.y != 0 {
+            return None;
+        }
+        let mut chunk = VoxelChunk::new(CHUNK_SIZE);
+        let origin = coords * CHUNK_SIZE.as_ivec3();
+        for x in 0..CHUNK_SIZE.x {
+            for z in 0..CHUNK_SIZE.z {
+                let world_x = (origin.x + x as i32) as f32;
+                let world_z = (origin.z + z as i32) as f32;
+                let height = 8.0 + 6.0 * (world_x * 0.1).sin() * (world_z * 0.1).cos();
+                for y in 0..(height as u32).min(CHUNK_SIZE.y) {
+                    chunk.set_voxel(UVec3::new(x, y, z), VoxelData::new(Voxel::Cuboid));
+                }
+            }
+        }
+        Some(chunk)
+    }
+}
+
+/// Spawns light, a moving focus and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns a focus that flies over the terrain forever
+    let focus = commands
+        .spawn(PhysicsBundle::new(
+            Transform::from_xyz(0.0, 24.0, 0.0),
+            HalfExtents::new(1.0, 1.0, 1.0),
+            Shape::Cuboid
+        ).with_velocity(Velocity(Vec3::new(SPEED, 0.0, SPEED))))
+        .insert((ChunkFocus, AntiGravity, CollisionConfig::new(GROUP_BASIC, GROUP_NONE), DebugRender(Color::RED)))
+        .id();
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(focus),
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 40.0, 40.0)),
+            ..default()
+        });
+}
+
+/// Allows newly streamed chunks to be rendered
+fn render_loaded_chunks(
+    mut commands: Commands,
+    mut loaded_reader: EventReader<ChunkLoaded>
+) {
+    for loaded in loaded_reader.iter() {
+        if let Some(mut chunk) = commands.get_entity(loaded.entity) {
+            chunk.insert(DebugRender::default());
+        }
+    }
+}
+
+/// Logs the number of chunk meshes being generated whenever it changes
+fn log_in_flight_tasks(tasks: Res<ChunkMeshTasks>, mut last: Local<usize>) {
+    if tasks.in_flight() != *last {
+        *last = tasks.in_flight();
+        info!("Chunk meshes in flight: {}", *last);
+    }
+}
@@ -0,0 +1,105 @@
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Floor chunk constants
+const CHUNK_SIZE: UVec3 = UVec3::new(8, 1, 8);
+const SPEED: f32 = 0.15;
+
+/// Example where an infinite floor is streamed in and out around a moving character.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .add_plugin(ChunkStreamingPlugin::new(FloorProvider, ChunkStreamingConfig {
+            radius: UVec3::new(2, 0, 2),
+            chunk_size: CHUNK_SIZE,
+            ..default()
+        }))
+        .add_startup_system(startup)
+        .add_fixed_system(move_character)
+        .add_system(render_loaded_chunks)
+        .run();
+}
+
+/// Marker for the character
+#[derive(Component)]
+struct Character;
+
+/// Generates a checkered floor for every chunk at y = 0
+struct FloorProvider;
+impl ChunkProvider for FloorProvider {
+    fn load(&self, coords: IVec3) -> Option<VoxelChunk> {
+        if coords.y != 0 {
+            return None;
+        }
+        let mut chunk = VoxelChunk::new(CHUNK_SIZE);
+        for (x, z) in (0..CHUNK_SIZE.x).flat_map(|x| (0..CHUNK_SIZE.z).map(move |z| (x, z))) {
+            if (x + z) % 2 == 0 {
+                chunk.set_voxel(UVec3::new(x, 0, z), VoxelData::new(Voxel::Cuboid));
+            }
+        }
+        Some(chunk)
+    }
+}
+
+/// Spawns light, character and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns character that chunks get loaded around
+    let character = commands
+        .spawn(PhysicsBundle::new(
+            Transform::from_xyz(0.0, 2.0, 0.0),
+            HalfExtents::new(1.0, 1.0, 1.0),
+            Shape::Cuboid
+        ))
+        .insert((Character, ChunkFocus, AntiGravity, DebugRender(Color::RED)))
+        .id();
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(character),
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 12.0, 12.0)),
+            ..default()
+        });
+}
+
+/// Moves the character with the arrow keys
+fn move_character(
+    keys: Res<Input<KeyCode>>,
+    mut characters: Query<&mut Velocity, With<Character>>
+) {
+    let mut dir = Vec3::ZERO;
+    if keys.pressed(KeyCode::Left) { dir.x -= 1.0; }
+    if keys.pressed(KeyCode::Right) { dir.x += 1.0; }
+    if keys.pressed(KeyCode::Up) { dir.z -= 1.0; }
+    if keys.pressed(KeyCode::Down) { dir.z += 1.0; }
+    for mut vel in &mut characters {
+        vel.0 = dir.normalize_or_zero() * SPEED;
+    }
+}
+
+/// Allows newly streamed chunks to be rendered
+fn render_loaded_chunks(
+    mut commands: Commands,
+    mut loaded_reader: EventReader<ChunkLoaded>
+) {
+    for loaded in loaded_reader.iter() {
+        if let Some(mut chunk) = commands.get_entity(loaded.entity) {
+            chunk.insert(DebugRender::default());
+        }
+    }
+}
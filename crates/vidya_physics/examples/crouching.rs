@@ -0,0 +1,102 @@
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Character constants
+const SPEED: f32 = 0.1;
+const STANDING: HalfExtents = HalfExtents(Vec3::new(0.5, 1.0, 0.5));
+const CROUCHING: HalfExtents = HalfExtents(Vec3::new(0.5, 0.5, 0.5));
+const RESIZE_SPEED: f32 = 0.05;
+
+/// Example where a character crouches under a low voxel overhang with C, and can't stand back up beneath it.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .add_startup_system(startup)
+        .add_fixed_system(control_character)
+        .add_system(log_blocked)
+        .run();
+}
+
+/// Marker for the character
+#[derive(Component)]
+struct Character;
+
+/// Spawns light, terrain, character and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns floor
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(20.0, 1.0, 8.0), Shape::Cuboid)
+        })
+        .insert((AntiGravity, DebugRender::default()));
+
+    // Spawns overhang low enough to only fit a crouching character beneath it
+    let mut chunk = VoxelChunk::new(UVec3::new(4, 1, 8));
+    chunk.set_voxel_plane(0, UVec2::ZERO, UVec2::new(4, 8), PlaneAxis::XZ, VoxelData::new(Voxel::Cuboid));
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(4.0, 1.75, 0.0), HalfExtents::new(4.0, 1.0, 8.0), Shape::VoxelChunk(chunk))
+        })
+        .insert((AntiGravity, DebugRender(Color::GRAY)));
+
+    // Spawns character
+    let character = commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            ..PhysicsBundle::new(Transform::from_xyz(-4.0, 1.0, 0.0), STANDING, Shape::Cuboid)
+        })
+        .insert((
+            Character,
+            ResizeBounds::new(STANDING, RESIZE_SPEED).with_anchor(BoundsAnchor::Bottom),
+            DebugRender(Color::RED)
+        ))
+        .id();
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(character),
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 4.0, 12.0)),
+            ..default()
+        });
+}
+
+/// Moves the character with the arrow keys and crouches while C is held
+fn control_character(
+    keys: Res<Input<KeyCode>>,
+    mut characters: Query<(&mut Velocity, &mut ResizeBounds), With<Character>>
+) {
+    let mut dir = 0.0;
+    if keys.pressed(KeyCode::Left) { dir -= 1.0; }
+    if keys.pressed(KeyCode::Right) { dir += 1.0; }
+    let target = if keys.pressed(KeyCode::C) { CROUCHING } else { STANDING };
+    for (mut vel, mut resize) in &mut characters {
+        vel.0.x = dir * SPEED;
+        resize.target = target.0;
+    }
+}
+
+/// Logs when the character fails to stand up
+fn log_blocked(mut blocked_reader: EventReader<ResizeBlocked>) {
+    if blocked_reader.iter().count() > 0 {
+        info!("Can't stand up here");
+    }
+}
@@ -0,0 +1,88 @@
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Spawning constants
+const COUNT: u32 = 20;
+const ROW: u32 = 5;
+
+/// Example where twenty identical boxes are labelled with their entity ids and sleep state while they fall and settle.
+/// Only boxes that are still awake get labelled. Press L to toggle labels.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(AssetPlugin {
+            // Borrows the font shipped with the dialog crate
+            asset_folder: "../vidya_dialog/assets".into(),
+            ..default()
+        }))
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .insert_resource(PhysicsConfig {
+            sleep_threshold: 0.001,
+            ..default()
+        })
+        .add_startup_system(startup)
+        .add_system(toggle_labels)
+        .run();
+}
+
+/// Spawns light, floor, boxes and camera, and enables labels
+fn startup(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut config: ResMut<PhysicsDebugConfig>
+) {
+    *config = PhysicsDebugConfig {
+        labels: true,
+        hide_sleeping_labels: true,
+        label_font: assets.load("yoster.ttf"),
+        ..default()
+    };
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns floor
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::default(), HalfExtents::new(20.0, 1.0, 20.0), Shape::Cuboid)
+        })
+        .insert((AntiGravity, DebugRender::default()));
+
+    // Spawns boxes at staggered heights, naming the first one
+    for i in 0..COUNT {
+        let x = (i % ROW) as f32 * 3.0 - 6.0;
+        let z = (i / ROW) as f32 * 3.0 - 4.5;
+        let transform = Transform::from_xyz(x, 2.0 + i as f32 * 0.5, z);
+        let label = match i {
+            0 => DebugLabel(Some("first".to_owned())),
+            _ => DebugLabel(None)
+        };
+        commands
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(transform, HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert((label, DebugRender(Color::ORANGE)));
+    }
+
+    // Spawns camera
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 14.0, 20.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+}
+
+/// Toggles labels when L is pressed
+fn toggle_labels(keys: Res<Input<KeyCode>>, mut config: ResMut<PhysicsDebugConfig>) {
+    if keys.just_pressed(KeyCode::L) {
+        config.labels = !config.labels;
+    }
+}
@@ -0,0 +1,81 @@
+use vidya_fixed_timestep::{CurrentTransform, FixedTimestepPlugin};
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+use bevy_inspector_egui::WorldInspectorPlugin;
+
+/// Example of a pool of water in a room with its own gravity, with the gravity field drawn around the camera.
+/// Press G to toggle the field. Gravity volumes can be edited in the inspector, and the field follows along.
+pub fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(WorldInspectorPlugin::new())
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .insert_resource(PhysicsDebugConfig { draw_gravity_field: true, gravity_field_spacing: 1.5, ..default() })
+        .add_startup_system(startup)
+        .add_system(toggle_gravity_field)
+        .run();
+}
+
+fn startup(mut commands: Commands) {
+
+    // Spawns light above scene
+    commands.spawn(PointLightBundle {
+        point_light: PointLight {
+            intensity: 1500.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        transform: Transform::from_xyz(0.0, 10.0, 0.0),
+        ..default()
+    });
+
+    // Spawns floor
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(16.0, 1.0, 8.0), Shape::Cuboid)
+        })
+        .insert((AntiGravity, StaticBody, DebugRender(Color::GRAY)));
+
+    // Spawns a pool of water on the left, and a room with stronger sideways gravity on the right that takes priority over it where they overlap
+    commands.spawn((
+        FluidVolume::new(1.0, 0.5),
+        GravityVolume::new(Vec3::new(0.0, -0.005, 0.0)),
+        CurrentTransform(Transform::from_xyz(-4.0, 1.5, 0.0)),
+        HalfExtents::new(8.0, 3.0, 8.0),
+        Name::new("Pool")
+    ));
+    commands.spawn((
+        GravityVolume::new(Vec3::new(0.02, -0.02, 0.0)).with_priority(1),
+        CurrentTransform(Transform::from_xyz(4.0, 2.0, 0.0)),
+        HalfExtents::new(6.0, 4.0, 8.0),
+        Name::new("Heavy room")
+    ));
+
+    // Spawns crates that float in the pool and slide along the room
+    for x in [-6.0, -4.0, -2.0, 3.0, 5.0] {
+        commands
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(x, 4.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert((Weight(0.5), DebugRender(Color::ORANGE)));
+    }
+
+    // Spawns camera
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 4.0, 14.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+        ..default()
+    });
+}
+
+/// Shows or hides the gravity field when G is pressed
+fn toggle_gravity_field(keys: Res<Input<KeyCode>>, mut config: ResMut<PhysicsDebugConfig>) {
+    if keys.just_pressed(KeyCode::G) {
+        config.draw_gravity_field = !config.draw_gravity_field;
+    }
+}
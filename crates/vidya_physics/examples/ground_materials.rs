@@ -0,0 +1,110 @@
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Character constants
+const JUMP_SPEED: f32 = 0.25;
+const DEADZONE: f32 = 0.2;
+
+// Surface tags
+const STONE: SurfaceTag = SurfaceTag(1);
+const ICE: SurfaceTag = SurfaceTag(2);
+const MUD: SurfaceTag = SurfaceTag(3);
+
+/// Actions the character can take
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Action {
+    Move,
+    Jump
+}
+
+/// Example where a character runs along a track of stone, ice and mud, each handling differently.
+/// Ice is slow to speed up and slow to stop, but reaches higher speeds. Mud is sluggish and stops quickly.
+/// Arrow keys or the left stick move, and space or the south button jump.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(FixedInputPlugin::new(InputMap::new()
+            .with_binding_2d(Action::Move, InputBinding2d::arrow_keys())
+            .with_binding_2d(Action::Move, InputBinding2d::left_stick(DEADZONE))
+            .with_binding(Action::Jump, InputBinding::Key(KeyCode::Space))
+            .with_binding(Action::Jump, InputBinding::GamepadButton(GamepadButtonType::South))
+        ))
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .insert_resource(GroundMaterialTable::default()
+            .with_material(STONE, GroundMaterial::default())
+            .with_material(ICE, GroundMaterial::new(0.15, 0.05, 1.8))
+            .with_material(MUD, GroundMaterial::new(0.5, 3.0, 0.4))
+            .with_air(GroundMaterial::new(0.3, 0.0, 1.0))
+        )
+        .add_startup_system(startup)
+        .add_fixed_system(control_character)
+        .run();
+}
+
+/// Marker for the character
+#[derive(Component)]
+struct Character;
+
+/// Spawns light, track, character and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns strips of track, each with its own surface
+    let strips = [(STONE, Color::GRAY), (ICE, Color::CYAN), (MUD, Color::rgb(0.4, 0.25, 0.1)), (STONE, Color::GRAY)];
+    for (i, (tag, color)) in strips.into_iter().enumerate() {
+        commands
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+                ..PhysicsBundle::new(
+                    Transform::from_xyz(i as f32 * 20.0 - 30.0, 0.0, 0.0),
+                    HalfExtents::new(20.0, 1.0, 8.0),
+                    Shape::Cuboid
+                )
+            })
+            .insert((AntiGravity, tag, DebugRender(color)));
+    }
+
+    // Spawns character
+    let character = commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            ..PhysicsBundle::new(Transform::from_xyz(-35.0, 2.0, 0.0), HalfExtents::new(1.0, 2.0, 1.0), Shape::Cuboid)
+        })
+        .insert((Character, CharacterController::default(), DebugRender(Color::RED)))
+        .id();
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(character),
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 6.0, 12.0)),
+            ..default()
+        });
+}
+
+/// Passes movement input to the character, and jumps when it's able to
+fn control_character(
+    actions: Res<FixedActions<Action>>,
+    mut characters: Query<&mut CharacterController, With<Character>>
+) {
+    let dir = actions.value(Action::Move);
+    for mut controller in &mut characters {
+        controller.set_move_input(Vec2::new(dir.x, -dir.y));
+        if actions.just_pressed(Action::Jump) {
+            controller.try_jump(JUMP_SPEED);
+        }
+    }
+}
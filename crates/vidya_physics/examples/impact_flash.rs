@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_fixed_timestep::FixedTimestepStages;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Flash constants
+const FLASH_DECAY: f32 = 0.7;
+const LIFT_HEIGHT: f32 = 6.0;
+
+/// Example where crates flash white when they land and fade over several ticks.
+/// Runs at a low tick rate to show that the fade stays smooth between ticks. Press space to lift the crates back up.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::new(Duration::from_secs_f64(1.0/10.0)))
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.05, 0.0)))
+        .add_startup_system(startup)
+        .add_fixed_system(lift_crates)
+        .add_system_to_stage(FixedTimestepStages::PostFixedUpdate, flash_on_impact.after(PhysicsSystems::Update))
+        .run();
+}
+
+/// Flash state of a crate
+#[derive(Component, Default)]
+struct Flash {
+    airborne: bool,
+    intensity: f32
+}
+
+/// Spawns light, floor, crates and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns floor
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(20.0, 1.0, 8.0), Shape::Cuboid)
+        })
+        .insert((AntiGravity, DebugRender::default()));
+
+    // Spawns crates at staggered heights so they land one after another
+    for i in 0..5 {
+        let transform = Transform::from_xyz(i as f32 * 2.5 - 5.0, 2.0 + i as f32 * 1.5, 0.0);
+        commands
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_STATIC_TERRAIN),
+                ..PhysicsBundle::new(transform, HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert((
+                Flash { airborne: true, ..default() },
+                InterpolatedEmissive::new(Color::BLACK),
+                DebugRender(Color::ORANGE)
+            ));
+    }
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Point {
+                position: Vec3::new(0.0, 2.0, 0.0),
+                up: Vec3::Y
+            },
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 4.0, 14.0)),
+            ..default()
+        });
+}
+
+/// Lifts the crates back up when space is pressed
+fn lift_crates(
+    keys: Res<Input<KeyCode>>,
+    mut crates: Query<(&mut CurrentTransform, &mut Velocity), With<Flash>>
+) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+    for (mut trans, mut vel) in &mut crates {
+        trans.0.translation.y += LIFT_HEIGHT;
+        vel.0 = Vec3::ZERO;
+    }
+}
+
+/// Flashes crates white on the tick they land, then fades the flash out
+fn flash_on_impact(mut crates: Query<(&Contacts, &mut Flash, &mut InterpolatedEmissive)>) {
+    for (contacts, mut flash, mut emissive) in &mut crates {
+        let landed = flash.airborne && !contacts.is_empty();
+        flash.airborne = contacts.is_empty();
+        flash.intensity = match landed {
+            true => 1.0,
+            false => flash.intensity * FLASH_DECAY
+        };
+        emissive.set_emissive(Color::WHITE * flash.intensity);
+    }
+}
@@ -0,0 +1,104 @@
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Character constants
+const SPEED: f32 = 0.1;
+const CLIMB_SPEED: f32 = 0.08;
+
+/// Example where a character climbs a column of climbable voxels with the up and down keys.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .add_startup_system(startup)
+        .add_fixed_system(control_character)
+        .run();
+}
+
+/// Marker for the character
+#[derive(Component)]
+struct Character;
+
+/// Spawns light, terrain, ladder, character and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns floor and a ledge at the top of the ladder
+    let terrain = [
+        (Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(20.0, 1.0, 4.0)),
+        (Transform::from_xyz(4.0, 5.5, 0.0), HalfExtents::new(6.0, 1.0, 4.0))
+    ];
+    for (transform, bounds) in terrain {
+        commands
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+                ..PhysicsBundle::new(transform, bounds, Shape::Cuboid)
+            })
+            .insert((AntiGravity, DebugRender::default()));
+    }
+
+    // Spawns a ladder column next to the ledge.
+    // Ladder cells are empty so that they can be walked through.
+    let mut chunk = VoxelChunk::new(UVec3::new(1, 6, 1));
+    let ladder = VoxelData::new(Voxel::Empty).with_flags(VoxelFlags::CLIMBABLE);
+    chunk.set_voxel_box(UVec3::ZERO, UVec3::new(1, 6, 1), ladder);
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(0.5, 3.0, 0.0), HalfExtents::new(1.0, 6.0, 1.0), Shape::VoxelChunk(chunk))
+        })
+        .insert((AntiGravity, DebugRender(Color::ORANGE)));
+
+    // Spawns character
+    let character = commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            ..PhysicsBundle::new(Transform::from_xyz(-4.0, 1.0, 0.0), HalfExtents::new(0.8, 2.0, 0.8), Shape::Cuboid)
+        })
+        .insert((Character, OverlappedVoxelFlags::default(), DebugRender(Color::RED)))
+        .id();
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(character),
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 4.0, 14.0)),
+            ..default()
+        });
+}
+
+/// Walks with left and right, and climbs with up and down while overlapping a climbable voxel
+fn control_character(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut characters: Query<(Entity, &mut Velocity, &OverlappedVoxelFlags), With<Character>>
+) {
+    let mut dir = Vec2::ZERO;
+    if keys.pressed(KeyCode::Left) { dir.x -= 1.0; }
+    if keys.pressed(KeyCode::Right) { dir.x += 1.0; }
+    if keys.pressed(KeyCode::Up) { dir.y += 1.0; }
+    if keys.pressed(KeyCode::Down) { dir.y -= 1.0; }
+    for (entity, mut vel, overlapped) in &mut characters {
+        vel.0.x = dir.x * SPEED;
+        if overlapped.0.contains(VoxelFlags::CLIMBABLE) {
+            vel.0.y = dir.y * CLIMB_SPEED;
+            commands.entity(entity).insert(AntiGravity);
+        }
+        else {
+            commands.entity(entity).remove::<AntiGravity>();
+        }
+    }
+}
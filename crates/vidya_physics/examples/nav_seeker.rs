@@ -0,0 +1,179 @@
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use vidya_physics::nav::*;
+use bevy::prelude::*;
+
+const CHUNK_SIZE: UVec3 = UVec3::new(16, 4, 16);
+const PLAYER_SPEED: f32 = 0.15;
+const SEEKER_SPEED: f32 = 0.08;
+
+/// Example where a seeker paths around a wall to reach the player.
+/// Move the player with the arrow keys. Press space to knock a hole in the wall.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .add_plugin(ChunkStreamingPlugin::new(WalledFloor, ChunkStreamingConfig {
+            radius: UVec3::new(1, 0, 1),
+            chunk_size: CHUNK_SIZE,
+            ..default()
+        }))
+        .init_resource::<Nav>()
+        .add_startup_system(startup)
+        .add_system(render_loaded_chunks)
+        .add_system(update_nav)
+        .add_system(knock_hole)
+        .add_fixed_system(move_player)
+        .add_fixed_system(seek_player)
+        .run();
+}
+
+/// Marker for the player
+#[derive(Component)]
+struct Player;
+
+/// Marker for the seeker
+#[derive(Component)]
+struct Seeker;
+
+/// Walkable grid of the loaded chunks
+#[derive(Resource, Default)]
+struct Nav(Option<WalkableGrid>);
+
+/// Single floor chunk split by a wall with a gap at the far end
+struct WalledFloor;
+impl ChunkProvider for WalledFloor {
+    fn load(&self, coords: IVec3) -> Option<VoxelChunk> {
+        if coords != IVec3::ZERO {
+            return None;
+        }
+        let mut chunk = VoxelChunk::new(CHUNK_SIZE);
+        chunk
+            .set_voxel_box(UVec3::ZERO, UVec3::new(16, 1, 16), VoxelData::new(Voxel::Cuboid))
+            .set_voxel_box(UVec3::new(8, 1, 0), UVec3::new(9, 4, 12), VoxelData::new(Voxel::Cuboid));
+        Some(chunk)
+    }
+}
+
+/// Spawns light, player, seeker and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns player and seeker on opposite sides of the wall
+    commands
+        .spawn(PhysicsBundle::new(
+            Transform::from_xyz(12.5, 1.5, 4.5),
+            HalfExtents::new(1.0, 1.0, 1.0),
+            Shape::Cuboid
+        ))
+        .insert((Player, ChunkFocus, AntiGravity, DebugRender(Color::GREEN)));
+    commands
+        .spawn(PhysicsBundle::new(
+            Transform::from_xyz(3.5, 1.5, 4.5),
+            HalfExtents::new(1.0, 1.0, 1.0),
+            Shape::Cuboid
+        ))
+        .insert((Seeker, AntiGravity, DebugRender(Color::RED)));
+
+    // Spawns camera looking down at the chunk
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Point { position: Vec3::new(8.0, 0.0, 8.0), up: Vec3::Y },
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 18.0, 14.0)),
+            ..default()
+        });
+}
+
+/// Allows newly streamed chunks to be rendered
+fn render_loaded_chunks(
+    mut commands: Commands,
+    mut loaded_reader: EventReader<ChunkLoaded>
+) {
+    for loaded in loaded_reader.iter() {
+        if let Some(mut chunk) = commands.get_entity(loaded.entity) {
+            chunk.insert(DebugRender::default());
+        }
+    }
+}
+
+/// Rebuilds the grid when chunks load or unload, and updates it when chunks are edited
+fn update_nav(
+    mut nav: ResMut<Nav>,
+    chunk_map: Res<VoxelChunkMap>,
+    mut loaded_reader: EventReader<ChunkLoaded>,
+    mut unloaded_reader: EventReader<ChunkUnloaded>,
+    shapes: Query<&Shape>,
+    edited: Query<&StreamedChunk, Changed<Shape>>
+) {
+    let area_changed = loaded_reader.iter().count() + unloaded_reader.iter().count() > 0;
+    if area_changed || nav.0.is_none() {
+        nav.0 = Some(build_walkable_grid(&chunk_map, &shapes, 1, 1));
+    }
+    else if let Some(grid) = &mut nav.0 {
+        update_walkable_grid(grid, &chunk_map, &shapes, edited.iter().map(|chunk| chunk.0));
+    }
+}
+
+/// Knocks a hole in the middle of the wall
+fn knock_hole(
+    keys: Res<Input<KeyCode>>,
+    mut chunks: Query<&mut Shape, With<StreamedChunk>>
+) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+    for mut shape in &mut chunks {
+        if let Shape::VoxelChunk(chunk) = shape.as_mut() {
+            chunk.set_voxel_box(UVec3::new(8, 1, 3), UVec3::new(9, 4, 6), VoxelData::default());
+        }
+    }
+}
+
+/// Moves the player with the arrow keys
+fn move_player(
+    keys: Res<Input<KeyCode>>,
+    mut players: Query<&mut Velocity, With<Player>>
+) {
+    let mut dir = Vec3::ZERO;
+    if keys.pressed(KeyCode::Left) { dir.x -= 1.0; }
+    if keys.pressed(KeyCode::Right) { dir.x += 1.0; }
+    if keys.pressed(KeyCode::Up) { dir.z -= 1.0; }
+    if keys.pressed(KeyCode::Down) { dir.z += 1.0; }
+    for mut vel in &mut players {
+        vel.0 = dir.normalize_or_zero() * PLAYER_SPEED;
+    }
+}
+
+/// Moves the seeker towards the next cell on its path to the player
+fn seek_player(
+    nav: Res<Nav>,
+    players: Query<&CurrentTransform, With<Player>>,
+    mut seekers: Query<(&CurrentTransform, &mut Velocity), With<Seeker>>
+) {
+    let (Some(grid), Ok(player)) = (&nav.0, players.get_single()) else { return };
+    let cell_of = |position: Vec3| grid.walkable_below(position.floor().as_ivec3());
+    for (seeker, mut vel) in &mut seekers {
+        let seeker_pos = seeker.0.translation;
+        let path = cell_of(seeker_pos)
+            .zip(cell_of(player.0.translation))
+            .and_then(|(start, goal)| find_path(grid, start, goal));
+        vel.0 = match path.as_deref() {
+            Some([_, next, ..]) => {
+                let target = next.as_vec3() + 0.5;
+                Vec3::new(target.x - seeker_pos.x, 0.0, target.z - seeker_pos.z).normalize_or_zero() * SEEKER_SPEED
+            },
+            _ => Vec3::ZERO
+        };
+    }
+}
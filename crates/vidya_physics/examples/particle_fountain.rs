@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+
+use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Fountain constants
+const MAX_PARTICLES: usize = 2000;
+const SPAWNS_PER_TICK: usize = 10;
+const LAUNCH_SPEED: f32 = 0.4;
+
+/// Example where a fountain of point particles bounces around inside a walled chunk.
+/// Oldest particles get recycled once there are too many. The logged frame time shows what they cost.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .init_resource::<Particles>()
+        .add_startup_system(startup)
+        .add_fixed_system(spawn_particles)
+        .run();
+}
+
+/// Particles in the order they were spawned, and what they look like
+#[derive(Resource, Default)]
+struct Particles {
+    spawned: VecDeque<Entity>,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>
+}
+
+/// Spawns light, walled chunk and camera
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut particles: ResMut<Particles>
+) {
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns floor chunk with a rim of walls
+    let mut chunk = VoxelChunk::new(UVec3::new(16, 3, 16));
+    chunk
+        .set_voxel_box(UVec3::new(0, 1, 0), UVec3::new(16, 3, 16), VoxelData::new(Voxel::Cuboid))
+        .set_voxel_box(UVec3::new(1, 1, 1), UVec3::new(15, 3, 15), VoxelData::default())
+        .set_voxel_box(UVec3::ZERO, UVec3::new(16, 1, 16), VoxelData::new(Voxel::Cuboid));
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, 0.5, 0.0), HalfExtents::new(16.0, 3.0, 16.0), Shape::VoxelChunk(chunk))
+        })
+        .insert((AntiGravity, DebugRender::default()));
+
+    // Particles are too small to debug render, so they share a tiny mesh instead
+    particles.mesh = meshes.add(shape::Cube { size: 0.1 }.into());
+    particles.material = materials.add(Color::CYAN.into());
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Point { position: Vec3::ZERO, up: Vec3::Y },
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 14.0, 18.0)),
+            ..default()
+        });
+}
+
+/// Launches particles in random directions from the middle of the floor, recycling the oldest ones
+fn spawn_particles(
+    mut commands: Commands,
+    mut rng: ResMut<SimRng>,
+    mut particles: ResMut<Particles>
+) {
+    for _ in 0..SPAWNS_PER_TICK {
+        if particles.spawned.len() >= MAX_PARTICLES {
+            if let Some(oldest) = particles.spawned.pop_front() {
+                commands.entity(oldest).despawn();
+            }
+        }
+        let dir = Vec3::new(rng.gen_range(-0.3..0.3), 1.0, rng.gen_range(-0.3..0.3)).normalize();
+        let particle = commands
+            .spawn(ParticleBundle::new(Vec3::new(0.0, 0.5, 0.0)).with_velocity(Velocity(dir * LAUNCH_SPEED)))
+            .insert((
+                Restitution(0.6),
+                particles.mesh.clone(),
+                particles.material.clone(),
+                Transform::from_xyz(0.0, 0.5, 0.0),
+                GlobalTransform::default(),
+                VisibilityBundle::default()
+            ))
+            .id();
+        particles.spawned.push_back(particle);
+    }
+}
@@ -0,0 +1,107 @@
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Character constants
+const SPEED: f32 = 0.1;
+const JUMP_SPEED: f32 = 0.25;
+const DEADZONE: f32 = 0.2;
+
+/// Actions the character can take
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Action {
+    Move,
+    Jump
+}
+
+/// Example of a side-view platformer with physics locked to the XY plane.
+/// Left and right arrow keys or the left stick move, and space or the south button jump.
+/// The thin platforms can be jumped through from below.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(FixedInputPlugin::new(InputMap::new()
+            .with_binding_2d(Action::Move, InputBinding2d::arrow_keys())
+            .with_binding_2d(Action::Move, InputBinding2d::left_stick(DEADZONE))
+            .with_binding(Action::Jump, InputBinding::Key(KeyCode::Space))
+            .with_binding(Action::Jump, InputBinding::GamepadButton(GamepadButtonType::South))
+        ))
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .insert_resource(PhysicsConfig { plane_lock: Some(PlaneAxis::XY), ..default() })
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .add_startup_system(startup)
+        .add_fixed_system(control_character)
+        .run();
+}
+
+/// Marker for the character
+#[derive(Component)]
+struct Character;
+
+/// Spawns light, level, character and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns light in front of the level
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(2.0, 4.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns a level one voxel deep, with walls, stairs and one-way platforms
+    let solid = VoxelData::new(Voxel::Cuboid);
+    let one_way = VoxelData::new(Voxel::Cuboid).with_flags(VoxelFlags::ONE_WAY_UP);
+    let mut level = VoxelChunk::new(UVec3::new(32, 16, 1));
+    level
+        .set_voxel_plane(0, UVec2::new(0, 0), UVec2::new(32, 1), PlaneAxis::XY, solid)
+        .set_voxel_plane(0, UVec2::new(0, 0), UVec2::new(1, 16), PlaneAxis::XY, solid)
+        .set_voxel_plane(0, UVec2::new(31, 0), UVec2::new(32, 16), PlaneAxis::XY, solid)
+        .set_voxel_plane(0, UVec2::new(20, 1), UVec2::new(31, 2), PlaneAxis::XY, solid)
+        .set_voxel_plane(0, UVec2::new(22, 2), UVec2::new(31, 3), PlaneAxis::XY, solid)
+        .set_voxel_plane(0, UVec2::new(24, 3), UVec2::new(31, 4), PlaneAxis::XY, solid)
+        .set_voxel_plane(0, UVec2::new(4, 3), UVec2::new(10, 4), PlaneAxis::XY, one_way)
+        .set_voxel_plane(0, UVec2::new(10, 6), UVec2::new(16, 7), PlaneAxis::XY, one_way)
+        .set_voxel_plane(0, UVec2::new(18, 9), UVec2::new(24, 10), PlaneAxis::XY, one_way);
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, 7.0, 0.0), HalfExtents::new(32.0, 16.0, 1.0), Shape::VoxelChunk(level))
+        })
+        .insert((AntiGravity, DebugRender::default()));
+
+    // Spawns character
+    let character = commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            ..PhysicsBundle::new(Transform::from_xyz(-12.0, 2.0, 0.0), HalfExtents::new(1.0, 2.0, 1.0), Shape::Cuboid)
+        })
+        .insert((Character, CharacterController::default(), DebugRender(Color::RED)))
+        .id();
+
+    // Spawns camera looking at the level from the side
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(character),
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 2.0, 24.0)),
+            ..default()
+        });
+}
+
+/// Moves the character, and jumps when it's able to
+fn control_character(
+    actions: Res<FixedActions<Action>>,
+    mut characters: Query<(&mut Velocity, &mut CharacterController), With<Character>>
+) {
+    let dir = actions.value(Action::Move);
+    for (mut vel, mut controller) in &mut characters {
+        vel.0.x = dir.x * SPEED;
+        if actions.just_pressed(Action::Jump) {
+            controller.try_jump(JUMP_SPEED);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+mod starter;
+
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+use starter::*;
+
+/// Starting point for a small game, wiring the fixed timestep, physics, character controller and camera together.
+/// The character walks up a ramp to a portal, which swaps the level for a second one with a portal leading back.
+/// Arrow keys or the left stick move, and space or the south button jump.
+/// The game itself lives in [`StarterPlugin`], while rendering is added here.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(StarterPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_startup_system(spawn_light)
+        .add_system(render_camera)
+        .add_system(render_debug_shapes)
+        .run();
+}
+
+/// Spawns light above scene
+fn spawn_light(mut commands: Commands) {
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+}
+
+/// Renders from the camera following the character
+fn render_camera(mut commands: Commands, cameras: Query<Entity, Added<MainCamera>>) {
+    for camera in &cameras {
+        commands.entity(camera).insert(Camera3dBundle::default());
+    }
+}
+
+/// Draws the character, levels and portals as debug shapes
+fn render_debug_shapes(
+    mut commands: Commands,
+    characters: Query<Entity, Added<Character>>,
+    portals: Query<Entity, Added<Portal>>,
+    terrain: Query<Entity, (Added<LevelEntity>, Without<Portal>)>
+) {
+    for character in &characters {
+        commands.entity(character).insert(DebugRender(Color::RED));
+    }
+    for portal in &portals {
+        commands.entity(portal).insert(DebugRender(Color::PURPLE));
+    }
+    for terrain in &terrain {
+        commands.entity(terrain).insert(DebugRender::default());
+    }
+}
@@ -0,0 +1,169 @@
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use bevy::prelude::*;
+
+// Character constants
+const JUMP_SPEED: f32 = 0.25;
+const DEADZONE: f32 = 0.2;
+
+/// Actions the character can take
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Action {
+    Move,
+    Jump
+}
+
+/// Game side of the starter, wiring the fixed timestep, physics, character controller and camera together.
+/// Doesn't render anything, so it also runs headless.
+pub struct StarterPlugin;
+impl Plugin for StarterPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugin(FixedTimestepPlugin::default())
+            .add_plugin(FixedInputPlugin::new(InputMap::new()
+                .with_binding_2d(Action::Move, InputBinding2d::arrow_keys())
+                .with_binding_2d(Action::Move, InputBinding2d::left_stick(DEADZONE))
+                .with_binding(Action::Jump, InputBinding::Key(KeyCode::Space))
+                .with_binding(Action::Jump, InputBinding::GamepadButton(GamepadButtonType::South))
+            ))
+            .add_plugin(PhysicsPlugin)
+            .add_plugin(CameraTargetPlugin)
+            .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+            .add_startup_system(startup)
+            .add_fixed_system(control_character)
+            .add_fixed_system(take_portals);
+    }
+}
+
+/// Marker for the character
+#[derive(Component)]
+pub struct Character;
+
+/// Marker for the camera following the character
+#[derive(Component)]
+pub struct MainCamera;
+
+/// Marker for entities that belong to the current level, and get despawned when leaving it
+#[derive(Component)]
+pub struct LevelEntity;
+
+/// Sensor that sends the character to another level
+#[derive(Component)]
+pub struct Portal {
+    level: Level
+}
+
+/// Levels of the game
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Level {
+    Hills,
+    Caves
+}
+
+/// Spawns first level, character and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns first level
+    spawn_level(&mut commands, Level::Hills);
+
+    // Spawns character
+    let character = commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, 2.0, 6.0), HalfExtents::new(1.0, 2.0, 1.0), Shape::Cuboid)
+        })
+        .insert((Character, CharacterController::default()))
+        .id();
+
+    // Spawns camera that smoothly follows the character
+    commands
+        .spawn(TransformBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(character),
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 6.0, 14.0)),
+            ..default()
+        })
+        .insert((MainCamera, FollowSmoothing::new(5.0).with_bias(0.5)));
+}
+
+/// Spawns the terrain and portal of a level
+fn spawn_level(commands: &mut Commands, level: Level) {
+    let solid = VoxelData::new(Voxel::Cuboid);
+    let slope = VoxelData::new(Voxel::Slope);
+    let mut chunk = VoxelChunk::new(UVec3::new(16, 4, 16));
+    let (portal_position, next_level) = match level {
+
+        // Floor with a ramp leading up to a ledge at the back
+        Level::Hills => {
+            chunk
+                .set_voxel_box(UVec3::ZERO, UVec3::new(16, 1, 16), solid)
+                .set_voxel_box(UVec3::new(4, 1, 0), UVec3::new(12, 2, 4), solid)
+                .set_voxel_box(UVec3::new(4, 1, 4), UVec3::new(12, 2, 5), slope);
+            (Vec3::new(0.0, 2.0, -6.0), Level::Caves)
+        },
+
+        // Floor walled in on every side, with a pillar in the middle
+        Level::Caves => {
+            chunk
+                .set_voxel_box(UVec3::ZERO, UVec3::new(16, 4, 16), solid)
+                .set_voxel_box(UVec3::new(1, 1, 1), UVec3::new(15, 4, 15), VoxelData::default())
+                .set_voxel_box(UVec3::new(7, 1, 7), UVec3::new(9, 4, 9), solid);
+            (Vec3::new(-5.0, 2.0, -5.0), Level::Hills)
+        }
+    };
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, 0.0, 0.0), HalfExtents::new(16.0, 4.0, 16.0), Shape::VoxelChunk(chunk))
+        })
+        .insert((AntiGravity, StaticBody, LevelEntity));
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_NONE, GROUP_BASIC),
+            ..PhysicsBundle::new(Transform::from_translation(portal_position), HalfExtents::new(2.0, 2.0, 2.0), Shape::Cuboid)
+        })
+        .insert((AntiGravity, Sensor, Portal { level: next_level }, LevelEntity));
+}
+
+/// Moves the character, and jumps when it's able to
+fn control_character(
+    actions: Res<FixedActions<Action>>,
+    mut characters: Query<&mut CharacterController, With<Character>>
+) {
+    let dir = actions.value(Action::Move);
+    for mut controller in &mut characters {
+        controller.set_move_input(Vec2::new(dir.x, -dir.y));
+        if actions.just_pressed(Action::Jump) {
+            controller.try_jump(JUMP_SPEED);
+        }
+    }
+}
+
+/// Swaps the level when the character walks into a portal
+fn take_portals(
+    mut commands: Commands,
+    mut events: EventReader<SensorEvent>,
+    portals: Query<&Portal>,
+    level_entities: Query<Entity, With<LevelEntity>>,
+    mut characters: Query<(&mut CurrentTransform, &mut PreviousTransform, &mut Velocity), With<Character>>,
+    cameras: Query<Entity, With<MainCamera>>
+) {
+    for event in events.iter() {
+        let Ok(portal) = portals.get(event.sensor) else { continue };
+        let Ok((mut current, mut previous, mut vel)) = characters.get_mut(event.other) else { continue };
+        for entity in &level_entities {
+            commands.entity(entity).despawn();
+        }
+        spawn_level(&mut commands, portal.level);
+
+        // Teleports the character to the start of the new level, without interpolating or smoothing the jump
+        current.0.translation = Vec3::new(0.0, 2.0, 6.0);
+        previous.0 = current.0;
+        vel.0 = Vec3::ZERO;
+        for camera in &cameras {
+            snap_to_target(&mut commands, camera);
+        }
+        return;
+    }
+}
@@ -0,0 +1,105 @@
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Explosion constants
+const EXPLOSION_STRENGTH: f32 = 0.3;
+const EXPLOSION_LIFT: f32 = 0.2;
+
+/// Example where pressing K explodes a three-part dummy into pieces that tumble onto the floor.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .add_startup_system(startup)
+        .add_system(explode_dummy)
+        .run();
+}
+
+/// Marker for the dummy
+#[derive(Component)]
+struct Dummy;
+
+/// Spawns light, floor, dummy and camera
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>
+) {
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns floor
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(20.0, 1.0, 20.0), Shape::Cuboid)
+        })
+        .insert((AntiGravity, DebugRender::default()));
+
+    // Spawns dummy made of a head, body and legs
+    let material = materials.add(Color::ORANGE.into());
+    let parts = [
+        (Vec3::new(0.0, 3.25, 0.0), HalfExtents::new(0.5, 0.5, 0.5), Weight(0.5)),
+        (Vec3::new(0.0, 2.0, 0.0), HalfExtents::new(1.0, 1.5, 0.6), Weight(2.0)),
+        (Vec3::new(0.0, 0.6, 0.0), HalfExtents::new(0.8, 1.2, 0.5), Weight(1.5))
+    ];
+    commands
+        .spawn(SpatialBundle::default())
+        .insert(Dummy)
+        .with_children(|builder| {
+            for (position, half_extents, weight) in parts {
+                let size = half_extents.0 * 2.0;
+                builder.spawn((
+                    PbrBundle {
+                        mesh: meshes.add(shape::Box::new(size.x, size.y, size.z).into()),
+                        material: material.clone(),
+                        transform: Transform::from_translation(position),
+                        ..default()
+                    },
+                    DetachableBody::new(half_extents, weight)
+                ));
+            }
+        });
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Point {
+                position: Vec3::new(0.0, 1.0, 0.0),
+                up: Vec3::Y
+            },
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 8.0, 12.0)),
+            ..default()
+        });
+}
+
+/// Explodes the dummy when K is pressed, pushing each part away from the dummy's center
+fn explode_dummy(
+    keys: Res<Input<KeyCode>>,
+    dummies: Query<Entity, With<Dummy>>,
+    mut commands: Commands
+) {
+    if !keys.just_pressed(KeyCode::K) {
+        return;
+    }
+    for dummy in &dummies {
+        detach_children_as_bodies(&mut commands, dummy, |parent, child| {
+            let center = parent.translation + Vec3::new(0.0, 2.0, 0.0);
+            let dir = (child.translation - center).normalize_or_zero();
+            dir * EXPLOSION_STRENGTH + Vec3::new(0.0, EXPLOSION_LIFT, 0.0)
+        });
+        commands.entity(dummy).remove::<Dummy>();
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::render::render_resource::PrimitiveTopology;
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+/// Example with a cable draped over the floor, and a chain strung between a post and a box sliding back and forth.
+/// Each rope is drawn as a line strip through its interpolated segment positions.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .add_startup_system(startup)
+        .add_fixed_system(slide_box)
+        .add_system(update_rope_meshes)
+        .run();
+}
+
+/// Marker for the box the chain hangs from
+#[derive(Component)]
+struct SlidingBox;
+
+/// Line strip mesh drawn through the segments of a rope
+#[derive(Component)]
+struct RopeMesh(Handle<Mesh>);
+
+/// Spawns light, floor, ropes and camera
+fn startup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>
+) {
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns floor
+    commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(20.0, 1.0, 8.0), Shape::Cuboid)
+        })
+        .insert((AntiGravity, DebugRender::default()));
+
+    // Spawns box that slides along the back of the floor
+    let sliding_box = commands
+        .spawn(PhysicsBundle::new(Transform::from_xyz(4.0, 4.0, -2.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid))
+        .insert((AntiGravity, SlidingBox, DebugRender(Color::ORANGE)))
+        .id();
+
+    // Spawns a cable that falls onto the floor, and a chain strung between a post and the box
+    let cable = spawn_rope(&mut commands, Rope::new(RopeAnchor::Point(Vec3::new(-6.0, 5.0, 2.0)), 30, 0.3)
+        .with_collision(GROUP_STATIC_TERRAIN)
+    );
+    let chain = spawn_rope(&mut commands, Rope::new(RopeAnchor::Point(Vec3::new(-4.0, 4.0, -2.0)), 16, 0.6)
+        .with_end(RopeAnchor::Entity(sliding_box))
+    );
+    for (rope, color) in [(cable, Color::CYAN), (chain, Color::YELLOW)] {
+        let mesh = meshes.add(Mesh::new(PrimitiveTopology::LineStrip));
+        commands.entity(rope).insert((
+            RopeMesh(mesh.clone()),
+            PbrBundle {
+                mesh,
+                material: materials.add(StandardMaterial { base_color: color, unlit: true, ..default() }),
+                ..default()
+            }
+        ));
+    }
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Point { position: Vec3::new(0.0, 2.0, 0.0), up: Vec3::Y },
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 6.0, 16.0)),
+            ..default()
+        });
+}
+
+/// Slides the box back and forth, dragging the chain with it
+fn slide_box(mut boxes: Query<&mut Velocity, With<SlidingBox>>, mut ticks: Local<u32>) {
+    *ticks += 1;
+    let direction = if (*ticks / 200) % 2 == 0 { -1.0 } else { 1.0 };
+    for mut vel in &mut boxes {
+        vel.0 = Vec3::new(direction * 0.02, 0.0, 0.0);
+    }
+}
+
+/// Rebuilds each rope's line strip from its anchors and the interpolated transforms of its segments
+fn update_rope_meshes(
+    ropes: Query<(&Rope, &RopeMesh)>,
+    transforms: Query<&Transform>,
+    mut meshes: ResMut<Assets<Mesh>>
+) {
+    let anchor_position = |anchor: RopeAnchor| match anchor {
+        RopeAnchor::Point(point) => Some(point),
+        RopeAnchor::Entity(entity) => transforms.get(entity).ok().map(|transform| transform.translation)
+    };
+    for (rope, rope_mesh) in &ropes {
+        let Some(mesh) = meshes.get_mut(&rope_mesh.0) else { continue };
+        let segments = rope.segments()
+            .iter()
+            .filter_map(|segment| transforms.get(*segment).ok())
+            .map(|transform| transform.translation);
+        let positions: Vec<[f32; 3]> = anchor_position(rope.start)
+            .into_iter()
+            .chain(segments)
+            .chain(rope.end.and_then(anchor_position))
+            .map(|position| position.to_array())
+            .collect();
+        let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    }
+}
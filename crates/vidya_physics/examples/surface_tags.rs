@@ -0,0 +1,114 @@
+use vidya_camera_target::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+use vidya_physics::*;
+use vidya_physics::debug::*;
+use bevy::prelude::*;
+
+// Surface constants
+const STONE: SurfaceTag = SurfaceTag(0);
+const METAL: SurfaceTag = SurfaceTag(1);
+const SPEED: f32 = 0.1;
+const TICKS_PER_STEP: u32 = 20;
+
+/// Example where a character logs different footsteps as it walks from a stone floor onto a metal platform.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FixedTimestepPlugin::default())
+        .add_plugin(PhysicsPlugin)
+        .add_plugin(PhysicsDebugPlugin)
+        .add_plugin(CameraTargetPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)))
+        .insert_resource(SurfaceTags::default()
+            .with_tag(STONE, "stone")
+            .with_tag(METAL, "metal")
+        )
+        .add_startup_system(startup)
+        .add_fixed_system(move_character)
+        .add_fixed_system(log_footsteps)
+        .run();
+}
+
+/// Marker for the character
+#[derive(Component)]
+struct Character;
+
+/// Spawns light, terrain, character and camera
+fn startup(mut commands: Commands) {
+
+    // Spawns light above scene
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(4.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    // Spawns stone floor and a slightly lower metal platform so the character steps down onto it
+    let terrain = [
+        (STONE, Transform::from_xyz(-4.0, 0.0, 0.0), Color::GRAY),
+        (METAL, Transform::from_xyz(4.0, -0.1, 0.0), Color::SILVER)
+    ];
+    for (tag, transform, color) in terrain {
+        commands
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+                ..PhysicsBundle::new(transform, HalfExtents::new(8.0, 1.0, 8.0), Shape::Cuboid)
+            })
+            .insert((tag, AntiGravity, DebugRender(color)));
+    }
+
+    // Spawns character
+    let character = commands
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            ..PhysicsBundle::new(Transform::from_xyz(-6.0, 2.0, 0.0), HalfExtents::new(1.0, 2.0, 1.0), Shape::Cuboid)
+        })
+        .insert((Character, DebugRender(Color::RED)))
+        .id();
+
+    // Spawns camera
+    commands
+        .spawn(Camera3dBundle::default())
+        .insert(CameraTargetBundle {
+            target: Target::Entity(character),
+            target_style: TargetStyle::Offset(Vec3::new(0.0, 6.0, 12.0)),
+            ..default()
+        });
+}
+
+/// Moves the character left and right with the arrow keys
+fn move_character(
+    keys: Res<Input<KeyCode>>,
+    mut characters: Query<&mut Velocity, With<Character>>
+) {
+    let mut dir = 0.0;
+    if keys.pressed(KeyCode::Left) { dir -= 1.0; }
+    if keys.pressed(KeyCode::Right) { dir += 1.0; }
+    for mut vel in &mut characters {
+        vel.0.x = dir * SPEED;
+    }
+}
+
+/// Logs a footstep named after the surface underfoot every few ticks while the character walks
+fn log_footsteps(
+    tags: Res<SurfaceTags>,
+    mut ticks: Local<u32>,
+    characters: Query<(&Velocity, &Contacts), With<Character>>
+) {
+    for (vel, contacts) in &characters {
+        if vel.0.x == 0.0 {
+            *ticks = 0;
+            continue;
+        }
+        *ticks += 1;
+        if *ticks % TICKS_PER_STEP != 1 {
+            continue;
+        }
+        let ground = contacts
+            .iter()
+            .filter(|contact| contact.normal.y > 0.5)
+            .find_map(|contact| contact.surface);
+        if let Some(name) = ground.and_then(|tag| tags.name(tag)) {
+            info!("Footstep: {name}");
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Checks that physics ticks don't allocate once a scene settles.
+//! Counting replaces the global allocator, so it's behind the `alloc-counter` feature:
+//! `cargo test -p vidya_physics --features alloc-counter`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use bevy_app::prelude::*;
+use bevy_ecs::event::Events;
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::Stage;
+use bevy_math::prelude::*;
+use bevy_transform::prelude::*;
+use vidya_fixed_timestep::Phase;
+
+use crate::*;
+
+thread_local! {
+    /// Allocations made on this thread while counting, or None when not counting
+    static ALLOCATIONS: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Allocator that counts the allocations made on the thread that asked for them.
+/// Counting per thread keeps tests running in parallel from counting each other's allocations.
+struct CountingAllocator;
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc_zeroed(layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count() {
+    // Thread locals can be gone while a thread shuts down
+    let _ = ALLOCATIONS.try_with(|allocations| {
+        if let Some(count) = allocations.get() {
+            allocations.set(Some(count + 1));
+        }
+    });
+}
+
+/// Number of allocations made on this thread while running the function specified.
+fn allocations_during(f: impl FnOnce()) -> usize {
+    ALLOCATIONS.with(|allocations| allocations.set(Some(0)));
+    f();
+    ALLOCATIONS.with(|allocations| allocations.take()).unwrap()
+}
+
+#[test]
+fn settled_ticks_dont_allocate() {
+
+    // Runs physics on a single thread, so that every allocation it makes is counted
+    let mut app = App::new();
+    app
+        .add_stage_after(CoreStage::Update, Phase::PostUpdate.stage(), SystemStage::single_threaded())
+        .add_plugin(PhysicsPlugin)
+        .insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+
+    // Floors, with boxes and a character resting on them
+    spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(10.0, 1.0, 10.0), Shape::Cuboid);
+    let mut chunk = VoxelChunk::new(UVec3::new(8, 1, 8));
+    chunk.set_voxel_box(UVec3::ZERO, UVec3::new(8, 1, 8), VoxelData::new(Voxel::Cuboid));
+    spawn_static_floor(&mut app.world, Transform::from_xyz(20.0, 0.0, 0.0), HalfExtents::new(8.0, 1.0, 8.0), Shape::VoxelChunk(chunk));
+    for x in [-2.0, 2.0, 20.0] {
+        app.world.spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            ..PhysicsBundle::new(Transform::from_xyz(x, 1.5, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+        });
+    }
+    app.world
+        .spawn(PhysicsBundle {
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.5, 3.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+        })
+        .insert(CharacterController::default());
+
+    // Lets the scene settle, which sizes buffers like contact lists
+    for _ in 0..10 {
+        app.update();
+    }
+    let stage = app.schedule.get_stage_mut::<SystemStage>(Phase::PostUpdate.stage()).unwrap();
+    let allocations = allocations_during(|| {
+        for _ in 0..10 {
+            app.world.resource_mut::<Events<CollisionEvent>>().update();
+            app.world.resource_mut::<Events<PhysicsStepEvent>>().update();
+            stage.run(&mut app.world);
+        }
+    });
+    assert_eq!(0, allocations);
+}
@@ -0,0 +1,129 @@
+use bevy_ecs::prelude::*;
+use bevy_transform::prelude::*;
+use vidya_fixed_timestep::{CurrentTransform, PreviousTransform};
+
+#[cfg(feature = "debug")]
+use crate::debug::DebugRender;
+use crate::{PhysicsBundle, Velocity};
+
+/// Per-instance values of an entity spawned by a [`PhysicsBatchSpawner`].
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct BatchInstance {
+    pub transform: Transform,
+    pub velocity: Velocity
+}
+impl BatchInstance {
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            transform,
+            velocity: Velocity::default()
+        }
+    }
+    pub fn with_velocity(mut self, velocity: Velocity) -> Self {
+        self.velocity = velocity;
+        self
+    }
+}
+
+/// Spawns many physics entities at once from a template [`PhysicsBundle`].
+/// Uses [`Commands::spawn_batch`], so all entities are spawned directly into their final archetype.
+#[derive(Debug, Clone)]
+pub struct PhysicsBatchSpawner {
+    template: PhysicsBundle,
+    #[cfg(feature = "debug")]
+    debug_render: Option<DebugRender>
+}
+impl PhysicsBatchSpawner {
+    pub fn new(template: PhysicsBundle) -> Self {
+        Self {
+            template,
+            #[cfg(feature = "debug")]
+            debug_render: None
+        }
+    }
+
+    /// Inserts the same [`DebugRender`] in every entity spawned.
+    #[cfg(feature = "debug")]
+    pub fn with_debug_render(mut self, debug_render: DebugRender) -> Self {
+        self.debug_render = Some(debug_render);
+        self
+    }
+
+    /// Spawns an entity for each instance specified.
+    /// Each entity's [`PreviousTransform`] starts synced with its [`CurrentTransform`].
+    pub fn spawn(&self, commands: &mut Commands, instances: impl IntoIterator<Item = BatchInstance>) {
+        let bundles: Vec<PhysicsBundle> = instances
+            .into_iter()
+            .map(|instance| self.instantiate(instance))
+            .collect();
+        #[cfg(feature = "debug")]
+        if let Some(debug_render) = self.debug_render {
+            let bundles: Vec<(PhysicsBundle, DebugRender)> = bundles
+                .into_iter()
+                .map(|bundle| (bundle, debug_render))
+                .collect();
+            commands.spawn_batch(bundles);
+            return;
+        }
+        commands.spawn_batch(bundles);
+    }
+
+    fn instantiate(&self, instance: BatchInstance) -> PhysicsBundle {
+        PhysicsBundle {
+            current_transform: CurrentTransform(instance.transform),
+            previous_transform: PreviousTransform(instance.transform),
+            velocity: instance.velocity,
+            ..self.template.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_ecs::system::CommandQueue;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+    use vidya_fixed_timestep::{CurrentTransform, PreviousTransform};
+
+    use crate::*;
+
+    #[test]
+    fn spawned_bodies_collide() {
+        let mut app = physics_test_app();
+        spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(100.0, 1.0, 100.0), Shape::Cuboid);
+
+        // Spawns a grid of boxes sinking into the floor
+        let spawner = PhysicsBatchSpawner::new(PhysicsBundle {
+            bounds: HalfExtents::new(1.0, 1.0, 1.0),
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_STATIC_TERRAIN),
+            ..Default::default()
+        });
+        let instances = (0..100).map(|i| {
+            let transform = Transform::from_xyz((i % 10) as f32 * 2.0, 1.0, (i / 10) as f32 * 2.0);
+            BatchInstance::new(transform).with_velocity(Velocity(Vec3::new(0.0, -0.5, 0.0)))
+        });
+        let mut queue = CommandQueue::default();
+        spawner.spawn(&mut Commands::new(&mut queue, &app.world), instances);
+        queue.apply(&mut app.world);
+
+        let mut bodies = app.world.query_filtered::<(&PreviousTransform, &CurrentTransform), With<Velocity>>();
+        let mut count = 0;
+        for (prev, current) in bodies.iter(&app.world) {
+            if current.0.translation.y == 1.0 {
+                assert_eq!(prev.0, current.0);
+                count += 1;
+            }
+        }
+        assert_eq!(100, count);
+
+        // Every box lands on the floor the next tick
+        app.update();
+        let mut bodies = app.world.query_filtered::<(&CurrentTransform, &Contacts), Without<AntiGravity>>();
+        assert_eq!(100, bodies.iter(&app.world).count());
+        for (current, contacts) in bodies.iter(&app.world) {
+            assert_eq!(1.0, current.0.translation.y);
+            assert_eq!(1, contacts.len());
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+
+use crate::{CollisionConfig, AABB};
+
+/// Objects spanning more cells than this are paired with every other object instead of being hashed.
+const MAX_CELLS_PER_OBJECT: i32 = 64;
+
+/// Padding added to bounds, so objects that only touch still share a cell despite rounding errors
+const MARGIN: f32 = 0.001;
+
+/// Uniform spatial hash that finds pairs of objects whose bounds could overlap.
+/// Objects stay in their cells between updates, and only get rehashed when their bounds cover different cells,
+/// so static and sleeping objects cost next to nothing. Buffers are kept too, so a scene that stays put doesn't allocate.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct Broadphase {
+    cell_size: f32,
+    verify: bool,
+    slots: Vec<Slot>,
+    free_slots: Vec<usize>,
+    slot_indices: HashMap<Entity, usize>,
+    cells: HashMap<IVec3, Vec<usize>>,
+    oversized: Vec<usize>,
+    pairs: Vec<(Entity, Entity)>,
+    counts: BroadphaseCounts
+}
+
+/// Object in the [`Broadphase`], with the range of cells it was hashed into.
+#[derive(Debug, Copy, Clone)]
+struct Slot {
+    entity: Entity,
+    config: CollisionConfig,
+    is_static: bool,
+    bounds: AABB,
+    min: IVec3,
+    max: IVec3,
+    live: bool,
+    updated: bool
+}
+
+impl Slot {
+    fn is_oversized(&self) -> bool {
+        let span = self.max - self.min + IVec3::ONE;
+        span.x * span.y * span.z > MAX_CELLS_PER_OBJECT
+    }
+}
+
+/// Number of objects the [`Broadphase`] hashed and reused since [`Broadphase::reset_counts`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) struct BroadphaseCounts {
+    /// Objects hashed into cells, because they were new or their bounds covered different cells
+    pub inserted: usize,
+    /// Objects that stayed in the cells they were in
+    pub reused: usize,
+    /// Objects dropped because they weren't updated, like despawned ones
+    pub removed: usize
+}
+
+impl Broadphase {
+
+    /// Starts an update, after which every object still around gets passed to [`Broadphase::update`].
+    /// Cells left empty since the last update are dropped, and changing the cell size forgets all objects. In debug builds, `verify` checks that [`Broadphase::pairs`]
+    /// matches what rebuilding the broadphase from scratch would find.
+    pub fn begin(&mut self, cell_size: f32, verify: bool) {
+        if cell_size != self.cell_size {
+            self.cell_size = cell_size;
+            self.slots.clear();
+            self.free_slots.clear();
+            self.slot_indices.clear();
+            self.cells.clear();
+            self.oversized.clear();
+        }
+        self.verify = verify;
+        self.cells.retain(|_, cell| !cell.is_empty());
+        for slot in &mut self.slots {
+            slot.updated = false;
+        }
+    }
+
+    /// Adds an object to every cell its bounds touch, or moves it if its bounds touch different cells than before.
+    /// Static objects are never paired with each other.
+    pub fn update(&mut self, entity: Entity, config: CollisionConfig, is_static: bool, bounds: AABB) {
+        let padded = bounds.expanded_by(Vec3::splat(MARGIN));
+        let min = ((padded.center - padded.half_extents) / self.cell_size).floor().as_ivec3();
+        let max = ((padded.center + padded.half_extents) / self.cell_size).floor().as_ivec3();
+        let slot = Slot { entity, config, is_static, bounds, min, max, live: true, updated: true };
+        match self.slot_indices.get(&entity).copied() {
+            Some(index) if self.slots[index].min == min && self.slots[index].max == max => {
+                self.slots[index] = slot;
+                self.counts.reused += 1;
+            },
+            Some(index) => {
+                self.unhash(index);
+                self.slots[index] = slot;
+                self.hash(index);
+                self.counts.inserted += 1;
+            },
+            None => {
+                let index = match self.free_slots.pop() {
+                    Some(index) => {
+                        self.slots[index] = slot;
+                        index
+                    },
+                    None => {
+                        self.slots.push(slot);
+                        self.slots.len() - 1
+                    }
+                };
+                self.slot_indices.insert(entity, index);
+                self.hash(index);
+                self.counts.inserted += 1;
+            }
+        }
+    }
+
+    /// Number of objects hashed and reused since the counts were last reset.
+    pub fn counts(&self) -> BroadphaseCounts {
+        self.counts
+    }
+
+    pub fn reset_counts(&mut self) {
+        self.counts = BroadphaseCounts::default();
+    }
+
+    fn hash(&mut self, index: usize) {
+        let slot = self.slots[index];
+        if slot.is_oversized() {
+            self.oversized.push(index);
+            return;
+        }
+        for z in slot.min.z..=slot.max.z {
+            for y in slot.min.y..=slot.max.y {
+                for x in slot.min.x..=slot.max.x {
+                    self.cells.entry(IVec3::new(x, y, z)).or_default().push(index);
+                }
+            }
+        }
+    }
+
+    fn unhash(&mut self, index: usize) {
+        let slot = self.slots[index];
+        if slot.is_oversized() {
+            self.oversized.retain(|other| *other != index);
+            return;
+        }
+        for z in slot.min.z..=slot.max.z {
+            for y in slot.min.y..=slot.max.y {
+                for x in slot.min.x..=slot.max.x {
+                    if let Some(cell) = self.cells.get_mut(&IVec3::new(x, y, z)) {
+                        cell.retain(|other| *other != index);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drops the objects that weren't updated since [`Broadphase::begin`].
+    fn remove_stale(&mut self) {
+        for index in 0..self.slots.len() {
+            let slot = self.slots[index];
+            if slot.live && !slot.updated {
+                self.unhash(index);
+                self.slots[index].live = false;
+                self.slot_indices.remove(&slot.entity);
+                self.free_slots.push(index);
+                self.counts.removed += 1;
+            }
+        }
+    }
+
+    /// Pairs of objects that share a cell, aren't both static, and have at least one of them affected by the other.
+    /// Sorted by entity index and without duplicates, so the same scene always yields pairs in the same order.
+    pub fn pairs(&mut self) -> &[(Entity, Entity)] {
+        self.remove_stale();
+        self.pairs.clear();
+        let slots = &self.slots;
+        let pairs = &mut self.pairs;
+        let mut add_pair = |a: usize, b: usize| {
+            let (a, b) = (&slots[a], &slots[b]);
+            if a.is_static && b.is_static {
+                return;
+            }
+            if a.config.affected_by(b.config.groups) || b.config.affected_by(a.config.groups) {
+                pairs.push(if a.entity.index() < b.entity.index() { (a.entity, b.entity) } else { (b.entity, a.entity) });
+            }
+        };
+        for cell in self.cells.values() {
+            for (i, a) in cell.iter().enumerate() {
+                for b in &cell[i+1..] {
+                    add_pair(*a, *b);
+                }
+            }
+        }
+        for a in &self.oversized {
+            for (b, slot) in slots.iter().enumerate() {
+                if b != *a && slot.live {
+                    add_pair(*a, b);
+                }
+            }
+        }
+        self.pairs.sort_unstable_by_key(|(a, b)| (a.index(), b.index()));
+        self.pairs.dedup();
+        if self.verify {
+            debug_assert_eq!(self.pairs, self.rebuilt().pairs().to_vec(), "Broadphase diverged from a full rebuild");
+        }
+        &self.pairs
+    }
+
+    /// Broadphase with the same objects, hashed from scratch.
+    fn rebuilt(&self) -> Broadphase {
+        let mut rebuilt = Broadphase::default();
+        rebuilt.begin(self.cell_size, false);
+        for slot in self.slots.iter().filter(|slot| slot.live) {
+            rebuilt.update(slot.entity, slot.config, slot.is_static, slot.bounds);
+        }
+        rebuilt
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_ecs::prelude::*;
+    use bevy_math::prelude::*;
+    use vidya_fixed_timestep::SimRng;
+
+    use bevy_transform::prelude::*;
+
+    use super::{Broadphase, BroadphaseCounts};
+    use crate::*;
+
+    #[test]
+    fn same_collisions_as_brute_force() {
+        let mut rng = SimRng::new(7);
+        let bodies: Vec<(Entity, CollisionConfig, AABB, Vec3)> = (0..1000)
+            .map(|i| {
+                let center = Vec3::new(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0));
+                let half_extents = Vec3::new(rng.gen_range(0.1..2.0), rng.gen_range(0.1..2.0), rng.gen_range(0.1..2.0));
+                let vel = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+                let config = if i % 10 == 0 {
+                    CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE)
+                }
+                else {
+                    CollisionConfig::new(GROUP_BASIC, GROUP_ALL)
+                };
+                (Entity::from_raw(i), config, AABB::new(center, half_extents), vel)
+            })
+            .collect();
+        let collides = |a: &(Entity, CollisionConfig, AABB, Vec3), b: &(Entity, CollisionConfig, AABB, Vec3)| {
+            let a_obj = PhysObj { aabb: a.2, shape: &Shape::Cuboid, vel: a.3 };
+            let b_obj = PhysObj { aabb: b.2, shape: &Shape::Cuboid, vel: b.3 };
+            collide(a_obj, b_obj, AxisPriority::YFirst).is_some()
+        };
+
+        // Checks every pair
+        let mut brute_force_pairs = 0;
+        let mut expected = Vec::new();
+        for (i, a) in bodies.iter().enumerate() {
+            for b in &bodies[i+1..] {
+                if !a.1.affected_by(b.1.groups) && !b.1.affected_by(a.1.groups) {
+                    continue;
+                }
+                brute_force_pairs += 1;
+                if collides(a, b) {
+                    expected.push((a.0, b.0));
+                }
+            }
+        }
+
+        // Only checks pairs sharing a cell of the grid
+        let mut broadphase = Broadphase::default();
+        broadphase.begin(4.0, false);
+        for (entity, config, aabb, vel) in &bodies {
+            let swept = aabb.union(&AABB::new(aabb.center + *vel, aabb.half_extents));
+            broadphase.update(*entity, *config, false, swept);
+        }
+        let pairs = broadphase.pairs().to_vec();
+        let found: Vec<(Entity, Entity)> = pairs
+            .iter()
+            .filter(|(a, b)| collides(&bodies[a.index() as usize], &bodies[b.index() as usize]))
+            .copied()
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(expected, found);
+        assert!(pairs.len() * 50 < brute_force_pairs, "{} vs {}", pairs.len(), brute_force_pairs);
+    }
+
+    #[test]
+    fn skips_static_pairs() {
+
+        // Level of wall segments packed next to each other, with boxes moving around on top
+        let wall_config = CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_ALL);
+        let box_config = CollisionConfig::new(GROUP_BASIC, GROUP_ALL);
+        let walls: Vec<AABB> = (0..500)
+            .map(|i| AABB::new(Vec3::new((i % 25) as f32, 0.0, (i / 25) as f32), Vec3::splat(0.5)))
+            .collect();
+        let boxes: Vec<AABB> = (0..20)
+            .map(|i| AABB::new(Vec3::new(i as f32, 1.0, i as f32 / 2.0), Vec3::splat(0.5)))
+            .collect();
+        let pair_count = |walls_static: bool| {
+            let mut broadphase = Broadphase::default();
+            broadphase.begin(4.0, false);
+            for (i, wall) in walls.iter().enumerate() {
+                broadphase.update(Entity::from_raw(i as u32), wall_config, walls_static, *wall);
+            }
+            for (i, aabb) in boxes.iter().enumerate() {
+                broadphase.update(Entity::from_raw(1000 + i as u32), box_config, false, *aabb);
+            }
+            broadphase.pairs().len()
+        };
+        let dynamic_pairs = pair_count(false);
+        let static_pairs = pair_count(true);
+        assert!(static_pairs * 10 < dynamic_pairs, "{static_pairs} vs {dynamic_pairs}");
+    }
+
+    #[test]
+    fn only_rehashes_objects_that_change_cells() {
+
+        // Level of 1000 static blocks, with a few boxes moving over it
+        let mut broadphase = Broadphase::default();
+        let wall_config = CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_ALL);
+        let box_config = CollisionConfig::new(GROUP_BASIC, GROUP_ALL);
+        let update = |broadphase: &mut Broadphase, tick: u32, despawned: Option<u32>| {
+            broadphase.reset_counts();
+            broadphase.begin(4.0, true);
+            for i in 0..1000 {
+                let wall = AABB::new(Vec3::new((i % 40) as f32, 0.0, (i / 40) as f32), Vec3::splat(0.5));
+                broadphase.update(Entity::from_raw(i), wall_config, true, wall);
+            }
+            for i in (0..10).filter(|i| Some(*i) != despawned) {
+                let position = Vec3::new(i as f32 * 4.0 + tick as f32 * 0.5, 1.0, i as f32 * 2.0);
+                broadphase.update(Entity::from_raw(1000 + i), box_config, false, AABB::new(position, Vec3::splat(0.5)));
+            }
+            let pairs = broadphase.pairs().to_vec();
+            assert_eq!(pairs, broadphase.rebuilt().pairs().to_vec());
+            (broadphase.counts(), pairs)
+        };
+        let (counts, _) = update(&mut broadphase, 0, None);
+        assert_eq!(BroadphaseCounts { inserted: 1010, reused: 0, removed: 0 }, counts);
+
+        // Only boxes crossing into other cells get rehashed
+        let mut inserted = 0;
+        for tick in 1..=8 {
+            let (counts, _) = update(&mut broadphase, tick, None);
+            assert_eq!(1010, counts.inserted + counts.reused);
+            assert!(counts.inserted <= 10, "{counts:?}");
+            inserted += counts.inserted;
+        }
+        assert!(inserted > 0 && inserted < 80, "{inserted}");
+
+        // Objects that stop getting updated get dropped
+        let (counts, pairs) = update(&mut broadphase, 8, Some(3));
+        assert_eq!(BroadphaseCounts { inserted: 0, reused: 1009, removed: 1 }, counts);
+        assert!(pairs.iter().all(|(a, b)| a.index() != 1003 && b.index() != 1003));
+        assert!(!pairs.is_empty());
+    }
+
+    #[test]
+    fn matches_full_rebuild_while_simulating() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        app.insert_resource(PhysicsConfig { verify_broadphase: true, sleep_threshold: 0.001, sleep_ticks: 5, ..PhysicsConfig::default() });
+        spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(40.0, 1.0, 40.0), Shape::Cuboid).insert(StaticBody);
+        let mut rng = SimRng::new(3);
+        let bodies: Vec<Entity> = (0..40)
+            .map(|_| {
+                let position = Vec3::new(rng.gen_range(-15.0..15.0), rng.gen_range(0.5..6.0), rng.gen_range(-15.0..15.0));
+                let vel = Vec3::new(rng.gen_range(-0.3..0.3), 0.0, rng.gen_range(-0.3..0.3));
+                app.world
+                    .spawn(PhysicsBundle {
+                        config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                        ..PhysicsBundle::new(Transform::from_translation(position), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                            .with_velocity(Velocity(vel))
+                    })
+                    .insert(Friction(Vec3::splat(0.2)))
+                    .id()
+            })
+            .collect();
+
+        // Bodies fall, slide, fall asleep and get despawned, with every substep checked against a full rebuild
+        for tick in 0..60 {
+            if tick % 10 == 9 {
+                app.world.despawn(bodies[tick / 10]);
+            }
+            app.update();
+        }
+        let counts = app.world.resource::<Broadphase>().counts();
+        assert!(counts.reused > counts.inserted, "{counts:?}");
+    }
+}
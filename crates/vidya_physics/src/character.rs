@@ -0,0 +1,436 @@
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_reflect::prelude::*;
+
+use crate::{Contacts, GroundMaterial, GroundMaterialTable, SurfaceTag, Velocity};
+
+/// Handles grounded state and jumping for a platformer character.
+/// Jumps requested with [`CharacterController::try_jump`] are buffered for a few ticks, so a jump pressed slightly
+/// before landing still happens. Jumps are also allowed for a few ticks after walking off a ledge.
+/// Grounded, wall and ceiling state come from the entity's [`Contacts`].
+/// Adding [`crate::MoveAndSlide`] has the character slide along what it runs into instead of going through collision responses.
+/// Optionally controls horizontal movement too, with handling that depends on the [`GroundMaterial`] stood on.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct CharacterController {
+    /// Ticks after leaving the ground during which jumping is still allowed
+    pub coyote_ticks: u8,
+    /// Ticks a jump requested in the air waits for the character to be able to jump
+    pub jump_buffer_ticks: u8,
+    /// Jumps allowed before touching the ground again, not counting the first one
+    pub max_air_jumps: u8,
+    /// Minimum Y component of a contact normal for the surface touched to count as ground
+    pub min_ground_normal_y: f32,
+    /// Horizontal speed gained per tick while moving
+    pub acceleration: f32,
+    /// Horizontal speed lost per tick while not moving
+    pub deceleration: f32,
+    /// Maximum horizontal speed reached by moving
+    pub max_speed: f32,
+    /// Ticks it takes for handling to change to that of new ground, which avoids jerks mid-stride
+    pub material_blend_ticks: u8,
+    grounded: bool,
+    on_wall: bool,
+    on_ceiling: bool,
+    #[reflect(ignore)]
+    ground_surface: Option<SurfaceTag>,
+    ticks_since_grounded: u8,
+    air_jumps: u8,
+    #[reflect(ignore)]
+    buffered_jump: Option<BufferedJump>,
+    #[reflect(ignore)]
+    move_input: Option<Vec2>,
+    material: GroundMaterial,
+    blend_from: GroundMaterial,
+    blend_target: GroundMaterial,
+    blend_ticks_left: u8
+}
+
+/// Jump waiting to happen
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct BufferedJump {
+    strength: f32,
+    ticks_left: u8
+}
+
+impl CharacterController {
+    pub fn with_coyote_ticks(mut self, coyote_ticks: u8) -> Self {
+        self.coyote_ticks = coyote_ticks;
+        self
+    }
+    pub fn with_jump_buffer_ticks(mut self, jump_buffer_ticks: u8) -> Self {
+        self.jump_buffer_ticks = jump_buffer_ticks;
+        self
+    }
+    pub fn with_max_air_jumps(mut self, max_air_jumps: u8) -> Self {
+        self.max_air_jumps = max_air_jumps;
+        self
+    }
+    pub fn with_min_ground_normal_y(mut self, min_ground_normal_y: f32) -> Self {
+        self.min_ground_normal_y = min_ground_normal_y;
+        self
+    }
+    pub fn with_acceleration(mut self, acceleration: f32) -> Self {
+        self.acceleration = acceleration;
+        self
+    }
+    pub fn with_deceleration(mut self, deceleration: f32) -> Self {
+        self.deceleration = deceleration;
+        self
+    }
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+    pub fn with_material_blend_ticks(mut self, material_blend_ticks: u8) -> Self {
+        self.material_blend_ticks = material_blend_ticks;
+        self
+    }
+
+    /// True if the character stood on the ground during the last tick.
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// True if the character touched a wall during the last tick.
+    pub fn on_wall(&self) -> bool {
+        self.on_wall
+    }
+
+    /// True if the character bumped into a ceiling during the last tick.
+    pub fn on_ceiling(&self) -> bool {
+        self.on_ceiling
+    }
+
+    /// Surface tag of the ground stood on during the last tick, if any.
+    pub fn ground_surface(&self) -> Option<SurfaceTag> {
+        self.ground_surface
+    }
+
+    /// Handling currently applied to movement, which may be partway through blending to that of new ground.
+    pub fn ground_material(&self) -> GroundMaterial {
+        self.material
+    }
+
+    /// Sets the direction to move in, where X moves along X and Y moves along Z. Inputs longer than 1 get shortened.
+    /// Horizontal velocity is only controlled by the character controller once this has been called.
+    pub fn set_move_input(&mut self, input: Vec2) {
+        self.move_input = Some(input.clamp_length_max(1.0));
+    }
+
+    /// Ticks since the character last stood on the ground, or since it last jumped off of it.
+    pub fn ticks_since_grounded(&self) -> u8 {
+        self.ticks_since_grounded
+    }
+
+    /// True if a jump requested now would happen this tick.
+    pub fn can_jump(&self) -> bool {
+        self.ticks_since_grounded <= self.coyote_ticks || self.air_jumps < self.max_air_jumps
+    }
+
+    /// Requests a jump that sets the character's upward velocity to `strength`.
+    /// If the character can't jump this tick, the jump is buffered and happens as soon as it can within [`Self::jump_buffer_ticks`].
+    /// Returns true if the jump happens this tick.
+    pub fn try_jump(&mut self, strength: f32) -> bool {
+        self.buffered_jump = Some(BufferedJump { strength, ticks_left: self.jump_buffer_ticks });
+        self.can_jump()
+    }
+
+    /// Forgets a buffered jump, like when the jump button is released early.
+    pub fn cancel_jump(&mut self) {
+        self.buffered_jump = None;
+    }
+
+    /// Consumes the buffered jump if it can happen, returning its strength.
+    pub(crate) fn take_jump(&mut self) -> Option<f32> {
+        if !self.can_jump() {
+            return None;
+        }
+        let jump = self.buffered_jump.take()?;
+        if self.ticks_since_grounded <= self.coyote_ticks {
+            // Prevents coyote time from allowing a second jump
+            self.ticks_since_grounded = u8::MAX;
+        }
+        else {
+            self.air_jumps += 1;
+        }
+        Some(jump.strength)
+    }
+
+    /// Steps the handling applied to movement towards that of the material specified.
+    pub(crate) fn blend_material(&mut self, target: GroundMaterial) {
+        if target != self.blend_target {
+            self.blend_from = self.material;
+            self.blend_target = target;
+            self.blend_ticks_left = self.material_blend_ticks;
+        }
+        if self.blend_ticks_left <= 1 {
+            self.blend_ticks_left = 0;
+            self.material = target;
+        }
+        else {
+            self.blend_ticks_left -= 1;
+            let t = 1.0 - self.blend_ticks_left as f32 / self.material_blend_ticks.max(1) as f32;
+            self.material = self.blend_from.lerp(&target, t.clamp(0.0, 1.0));
+        }
+    }
+
+    /// Velocity after a tick of movement, accelerating towards the input direction or slowing down without input.
+    pub(crate) fn move_velocity(&self, vel: Vec3) -> Vec3 {
+        let Some(input) = self.move_input else { return vel };
+        let horizontal = Vec2::new(vel.x, vel.z);
+        let (target, rate) = if input == Vec2::ZERO {
+            (Vec2::ZERO, self.deceleration * self.material.friction_scale)
+        }
+        else {
+            (input * self.max_speed * self.material.max_speed_scale, self.acceleration * self.material.acceleration_scale)
+        };
+        let diff = target - horizontal;
+        let horizontal = if diff.length() <= rate {
+            target
+        }
+        else {
+            horizontal + diff.normalize() * rate
+        };
+        Vec3::new(horizontal.x, vel.y, horizontal.y)
+    }
+
+    /// Updates grounded state and ages the buffered jump at the end of a tick.
+    pub(crate) fn end_tick(&mut self, grounded: bool) {
+        self.grounded = grounded;
+        if grounded {
+            self.ticks_since_grounded = 0;
+            self.air_jumps = 0;
+        }
+        else {
+            self.ticks_since_grounded = self.ticks_since_grounded.saturating_add(1);
+        }
+        if let Some(jump) = &mut self.buffered_jump {
+            match jump.ticks_left.checked_sub(1) {
+                Some(ticks_left) => jump.ticks_left = ticks_left,
+                None => self.buffered_jump = None
+            }
+        }
+    }
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            coyote_ticks: 4,
+            jump_buffer_ticks: 4,
+            max_air_jumps: 0,
+            min_ground_normal_y: 0.7,
+            acceleration: 0.02,
+            deceleration: 0.02,
+            max_speed: 0.1,
+            material_blend_ticks: 3,
+            grounded: false,
+            on_wall: false,
+            on_ceiling: false,
+            ground_surface: None,
+            ticks_since_grounded: u8::MAX,
+            air_jumps: 0,
+            buffered_jump: None,
+            move_input: None,
+            material: GroundMaterial::default(),
+            blend_from: GroundMaterial::default(),
+            blend_target: GroundMaterial::default(),
+            blend_ticks_left: 0
+        }
+    }
+}
+
+/// Performs buffered jumps that are allowed this tick.
+pub(crate) fn apply_jumps(mut characters: Query<(&mut CharacterController, &mut Velocity)>) {
+    for (mut controller, mut vel) in &mut characters {
+        if let Some(strength) = controller.take_jump() {
+            vel.0.y = strength;
+        }
+    }
+}
+
+/// Moves characters that were given movement input, with the handling of the ground they stand on.
+pub(crate) fn apply_movement(
+    materials: Option<Res<GroundMaterialTable>>,
+    mut characters: Query<(&mut CharacterController, &mut Velocity)>
+) {
+    let default_materials = GroundMaterialTable::default();
+    let materials = materials.as_deref().unwrap_or(&default_materials);
+    for (mut controller, mut vel) in &mut characters {
+        let target = if controller.grounded {
+            materials.ground(controller.ground_surface)
+        }
+        else {
+            materials.air
+        };
+        controller.blend_material(target);
+        let moved = controller.move_velocity(vel.0);
+        if moved != vel.0 {
+            vel.0 = moved;
+        }
+    }
+}
+
+/// Updates grounded, wall and ceiling state from the contacts made during the tick.
+pub(crate) fn update_grounded(mut characters: Query<(&mut CharacterController, &Contacts)>) {
+    for (mut controller, contacts) in &mut characters {
+        let min_normal_y = controller.min_ground_normal_y;
+        let ground = contacts
+            .iter()
+            .find(|contact| contact.normal.y >= min_normal_y);
+        controller.on_ceiling = contacts.iter().any(|contact| contact.normal.y <= -min_normal_y);
+        controller.on_wall = contacts.iter().any(|contact| contact.normal.y.abs() < min_normal_y);
+        controller.ground_surface = ground.and_then(|contact| contact.surface);
+        controller.end_tick(ground.is_some());
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    /// Simulates a single tick, optionally pressing jump. Returns true if a jump happened.
+    fn tick(controller: &mut CharacterController, press_jump: bool, grounded: bool) -> bool {
+        if press_jump {
+            controller.try_jump(1.0);
+        }
+        let jumped = controller.take_jump().is_some();
+        controller.end_tick(grounded && !jumped);
+        jumped
+    }
+
+    #[test]
+    fn coyote_time() {
+        for (coyote_ticks, expected) in [(3, true), (2, false)] {
+            let mut controller = CharacterController::default()
+                .with_coyote_ticks(coyote_ticks)
+                .with_jump_buffer_ticks(0);
+            tick(&mut controller, false, true);
+
+            // Walks off a ledge, then presses jump 3 ticks later
+            for _ in 0..3 {
+                tick(&mut controller, false, false);
+            }
+            assert_eq!(expected, tick(&mut controller, true, false));
+
+            // Coyote time only allows one jump
+            assert!(!tick(&mut controller, true, false));
+        }
+    }
+
+    #[test]
+    fn jump_buffering() {
+        for (jump_buffer_ticks, expected) in [(3, true), (2, false)] {
+            let mut controller = CharacterController::default()
+                .with_coyote_ticks(0)
+                .with_jump_buffer_ticks(jump_buffer_ticks);
+
+            // Presses jump 3 ticks before landing
+            assert!(!tick(&mut controller, true, false));
+            tick(&mut controller, false, false);
+            tick(&mut controller, false, true);
+            assert_eq!(expected, tick(&mut controller, false, true));
+        }
+    }
+
+    #[test]
+    fn air_jumps() {
+        let mut controller = CharacterController::default().with_max_air_jumps(1);
+        tick(&mut controller, false, true);
+        assert!(tick(&mut controller, true, true));
+        assert!(tick(&mut controller, true, false));
+        assert!(!tick(&mut controller, true, false));
+
+        // Landing restores air jumps
+        tick(&mut controller, false, true);
+        assert!(tick(&mut controller, true, false));
+        assert!(tick(&mut controller, true, false));
+    }
+
+    #[test]
+    fn ground_materials_scale_movement() {
+        let ice = GroundMaterial::new(0.25, 0.1, 2.0);
+        let mud = GroundMaterial::new(0.5, 3.0, 0.5);
+        let table = GroundMaterialTable::default()
+            .with_material(SurfaceTag(1), ice)
+            .with_material(SurfaceTag(2), mud)
+            .with_air(GroundMaterial::new(0.0, 0.0, 1.0));
+        for (tag, material) in [(SurfaceTag(0), GroundMaterial::default()), (SurfaceTag(1), ice), (SurfaceTag(2), mud)] {
+            let mut app = physics_test_app();
+            app
+                .insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)))
+                .insert_resource(table.clone());
+            spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(100.0, 1.0, 100.0), Shape::Cuboid).insert(tag);
+            let mut controller = CharacterController::default().with_material_blend_ticks(0);
+            controller.set_move_input(Vec2::X);
+            let body = app.world
+                .spawn(PhysicsBundle {
+                    config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                    ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                })
+                .insert(controller)
+                .id();
+
+            // Can't accelerate in the air, then accelerates once on the ground
+            app.update();
+            assert_eq!(0.0, app.world.get::<Velocity>(body).unwrap().0.x);
+            app.update();
+            let controller = *app.world.get::<CharacterController>(body).unwrap();
+            assert_eq!(Some(tag), controller.ground_surface());
+            assert_eq!(material, controller.ground_material());
+            let expected = controller.acceleration * material.acceleration_scale;
+            assert!((app.world.get::<Velocity>(body).unwrap().0.x - expected).abs() < 0.0001);
+
+            // Tops out at the scaled max speed
+            for _ in 0..50 {
+                app.update();
+            }
+            let expected = controller.max_speed * material.max_speed_scale;
+            assert!((app.world.get::<Velocity>(body).unwrap().0.x - expected).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn blends_between_materials() {
+        let ice = GroundMaterial::new(0.25, 0.1, 2.0);
+        let mut controller = CharacterController::default().with_material_blend_ticks(2);
+        controller.blend_material(ice);
+        assert_eq!(GroundMaterial::default().lerp(&ice, 0.5), controller.ground_material());
+        controller.blend_material(ice);
+        assert_eq!(ice, controller.ground_material());
+        controller.blend_material(ice);
+        assert_eq!(ice, controller.ground_material());
+    }
+
+    #[test]
+    fn jumps_on_landing() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(10.0, 1.0, 10.0), Shape::Cuboid);
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.6, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert(CharacterController::default())
+            .id();
+
+        // Presses jump while falling, which happens once the body lands
+        app.update();
+        let mut controller = app.world.get_mut::<CharacterController>(body).unwrap();
+        assert!(!controller.is_grounded());
+        assert!(!controller.try_jump(0.5));
+        for _ in 0..4 {
+            app.update();
+            if app.world.get::<Velocity>(body).unwrap().0.y > 0.0 {
+                return;
+            }
+        }
+        panic!("Buffered jump never happened");
+    }
+}
@@ -3,7 +3,7 @@ use bevy_math::prelude::*;
 //use bevy_macro_utils::*;
 use bevy_reflect::prelude::*;
 
-use crate::{PhysObj, AABB, Shape, VoxelChunk};
+use crate::{Orientation, PhysObj, AABB, Shape, Voxel, VoxelChunk, VoxelData, VoxelFlags, SurfaceTag, ResponseMode};
 
 /// Represents a group that a physics object can belong to.
 pub type CollisionGroups = u32;
@@ -17,6 +17,9 @@ pub const GROUP_BASIC: CollisionGroups =                0b00000000_00000000_0000
 
 const EPSILON: f32 = 0.00001;
 
+/// Collisions on different axes this close together in time are considered simultaneous.
+const TIE_EPSILON: f32 = 0.0001;
+
 
 /// Represents a collision that occurred between two physics objects
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -30,41 +33,92 @@ pub struct Collision {
     /// Normal of the surface hit on object A
     pub normal_a: Vec3,
     /// Normal of the surface hit on object B
-    pub normal_b: Vec3
+    pub normal_b: Vec3,
+    /// World space point where the objects touched, on the surface of object A
+    pub point: Vec3,
+    /// How deep object B would have ended up inside object A without a response
+    pub depth: f32
+}
+impl Collision {
+    /// Same collision with the roles of objects A and B swapped.
+    pub fn flipped(&self) -> Self {
+        Self {
+            t: self.t,
+            position_delta: -self.position_delta,
+            velocity_delta: -self.velocity_delta,
+            normal_a: self.normal_b,
+            normal_b: self.normal_a,
+            point: self.point,
+            depth: self.depth
+        }
+    }
+
+    /// Fills in the contact point and depth of a collision between box `a` and box `b` moving at `b_vel`.
+    /// The point is the center of where the boxes overlap when they touch, moved onto the face hit if it lines up with the axes.
+    fn with_contact(mut self, a: AABB, b: AABB, b_vel: Vec3) -> Self {
+        let bi = b.interp(self.t, b_vel);
+        let (a_min, a_max) = (a.center - a.half_extents, a.center + a.half_extents);
+        let (b_min, b_max) = (bi.center - bi.half_extents, bi.center + bi.half_extents);
+        let mut point = (a_min.max(b_min) + a_max.min(b_max)) / 2.0;
+        if self.normal_a.abs().max_element() >= 1.0 - EPSILON {
+            let face = a.center + self.normal_a * a.half_extents;
+            point += self.normal_a * (face - point).dot(self.normal_a);
+        }
+        self.point = point;
+        self.depth = self.position_delta.dot(self.normal_a).max(0.0);
+        self
+    }
 }
 
 impl CollisionResponse {
-    pub fn weighted(collision: &Collision, weight_a: f32, weight_b: f32) -> (CollisionResponse, CollisionResponse) {
-        let total = weight_a + weight_b;
-        let a_ratio = weight_a / total;
-        let b_ratio = weight_b / total;
+
+    /// Responses of both objects in a collision, split by weight so the heavier object moves less.
+    /// An infinitely heavy object leaves the whole response to the other one, and two of them split it evenly.
+    /// Each object's velocity changes according to its own [`ResponseMode`], with B moving at `relative_vel` compared to A.
+    pub fn weighted(
+        collision: &Collision,
+        weight_a: f32,
+        weight_b: f32,
+        modes: (ResponseMode, ResponseMode),
+        relative_vel: Vec3
+    ) -> (CollisionResponse, CollisionResponse) {
+        let (a_ratio, b_ratio) = match (weight_a.is_infinite(), weight_b.is_infinite()) {
+            (true, true) => (0.5, 0.5),
+            (true, false) => (1.0, 0.0),
+            (false, true) => (0.0, 1.0),
+            (false, false) => (weight_a / (weight_a + weight_b), weight_b / (weight_a + weight_b))
+        };
         let a_response = CollisionResponse::Value {
             t: collision.t,
             position_delta: -collision.position_delta * b_ratio,
-            velocity_delta: -collision.velocity_delta * b_ratio,
+            velocity_delta: modes.0.velocity_delta(&collision.flipped(), -relative_vel) * b_ratio,
             surface_normal: collision.normal_b
         };
         let b_response = CollisionResponse::Value {
             t: collision.t,
             position_delta: collision.position_delta * a_ratio,
-            velocity_delta: collision.velocity_delta * a_ratio,
+            velocity_delta: modes.1.velocity_delta(collision, relative_vel) * a_ratio,
             surface_normal: collision.normal_a
         };
         (a_response, b_response)
     }
-    pub fn for_a(collision: &Collision) -> CollisionResponse {
+
+    /// Response of object A when it takes the whole collision.
+    pub fn for_a(collision: &Collision, mode: ResponseMode, relative_vel: Vec3) -> CollisionResponse {
         CollisionResponse::Value {
             t: collision.t,
             position_delta: -collision.position_delta,
-            velocity_delta: -collision.velocity_delta,
+            velocity_delta: mode.velocity_delta(&collision.flipped(), -relative_vel),
             surface_normal: collision.normal_b
         }
     }
-    pub fn for_b(collision: &Collision) -> CollisionResponse {
+
+    /// Response of object B when it takes the whole collision.
+    pub fn for_b(collision: &Collision, mode: ResponseMode, relative_vel: Vec3) -> CollisionResponse {
         CollisionResponse::Value {
             t: collision.t,
             position_delta: collision.position_delta,
-            velocity_delta: collision.velocity_delta,
+            velocity_delta: mode.velocity_delta(collision, relative_vel),
             surface_normal: collision.normal_a
         }
     }
@@ -98,8 +152,90 @@ impl CollisionResponse {
 }
 
 
+/// How collisions that happen at the same time on different axes get resolved, like when a box lands exactly on a platform's corner.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Reflect)]
+pub enum AxisPriority {
+    /// Vertical collisions win, so boxes land on corners instead of getting pushed off of them
+    #[default]
+    YFirst,
+    /// The axis the box would sink into the least by the end of the substep wins
+    ByPenetration,
+    /// The axis the box moves along the fastest wins
+    ByVelocityDominance
+}
+
+/// Something a physics object touched during the last tick.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Contact {
+    /// Entity touched
+    pub entity: Entity,
+    /// Normal of the surface touched
+    pub normal: Vec3,
+    /// Surface tag of what was touched, if any
+    pub surface: Option<SurfaceTag>
+}
+
+/// Event fired when two physics objects collide, even if only one of them is affected by the other.
+/// Fires at most once per pair per tick, describing the first substep they collided in.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CollisionEvent {
+    pub entity_a: Entity,
+    pub entity_b: Entity,
+    /// Normal of the surface hit on entity A
+    pub normal: Vec3,
+    /// Value between 0 and 1 describing when during the substep the collision happened
+    pub t: f32,
+    /// World space point where the objects touched, on the surface of entity A
+    pub point: Vec3,
+    /// How deep entity B would have ended up inside entity A without a response
+    pub depth: f32
+}
+
+/// Contacts a physics object made with other objects during the last tick.
+/// Holds at most one contact per entity touched, which is the latest one.
+/// [`Sleeping`](crate::Sleeping) bodies keep the contacts they fell asleep with, until whatever they touch starts moving.
+#[derive(Component, Clone, PartialEq, Debug, Default)]
+pub struct Contacts(Vec<Contact>);
+impl Contacts {
+    pub fn iter(&self) -> impl Iterator<Item = &Contact> {
+        self.0.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Contact made with the entity specified.
+    pub fn get(&self, entity: Entity) -> Option<&Contact> {
+        self.0.iter().find(|contact| contact.entity == entity)
+    }
+    pub(crate) fn add(&mut self, contact: Contact) {
+        match self.0.iter_mut().find(|existing| existing.entity == contact.entity) {
+            Some(existing) => *existing = contact,
+            None => self.0.push(contact)
+        }
+    }
+    pub(crate) fn remove(&mut self, entity: Entity) {
+        self.0.retain(|contact| contact.entity != entity);
+    }
+    pub(crate) fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+
+/// Flags of all voxels a physics object overlapped at the end of the last tick, including non-solid ones.
+/// Opt-in. Lets characters know when they can climb, for instance.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct OverlappedVoxelFlags(pub VoxelFlags);
+
+
 /// Stores information about how a physics object should behave during a collision.
-#[derive(Component, Copy, Clone, Eq, PartialEq, Default, Hash, Debug)]
+/// Groups are plain bit masks, so they show up as numbers in inspectors. See [`CollisionGroupRegistry::name`](crate::CollisionGroupRegistry::name) to tell them apart.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Default, Hash, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
 pub struct CollisionConfig {
     /// Group(s) a physics object belongs to. It's typically only one.
     pub groups: CollisionGroups,
@@ -113,6 +249,21 @@ impl CollisionConfig {
             affected_by
         }
     }
+    /// Adds the groups specified to the ones this object belongs to.
+    /// See [`CollisionGroupRegistry`](crate::CollisionGroupRegistry) for groups allocated by name.
+    pub fn in_group(&self, groups: CollisionGroups) -> Self {
+        Self {
+            groups: self.groups | groups,
+            affected_by: self.affected_by
+        }
+    }
+    /// Adds the groups specified to the ones this object is affected by.
+    pub fn also_affected_by(&self, affected_by: CollisionGroups) -> Self {
+        Self {
+            groups: self.groups,
+            affected_by: self.affected_by | affected_by
+        }
+    }
     pub fn not_affected_by(&self, affected_by: CollisionGroups) -> Self {
         Self {
             groups: self.groups,
@@ -124,101 +275,506 @@ impl CollisionConfig {
     }
 }
 
-pub(crate) fn collide(a: PhysObj<'_>, b: PhysObj<'_>) -> Option<Collision> {
+pub(crate) fn collide(a: PhysObj<'_>, b: PhysObj<'_>, priority: AxisPriority) -> Option<Collision> {
     let b_vel = b.vel - a.vel;
     match (a.shape, b.shape) {
-        (Shape::Cuboid, Shape::Cuboid) => collide_cuboid_cuboid(a.aabb, b.aabb, b_vel),
-        (Shape::VoxelChunk(chunk), Shape::Cuboid) => collide_chunk_cuboid(a.aabb, chunk, b.aabb, b_vel),
+        (Shape::Cuboid, Shape::Cuboid) => collide_cuboid_cuboid_prioritized(a.aabb, b.aabb, b_vel, priority),
+        (Shape::VoxelChunk(chunk), Shape::Cuboid) => collide_chunk_cuboid(a.aabb, chunk, b.aabb, b_vel, priority),
+        (Shape::Cuboid, Shape::VoxelChunk(chunk)) => collide_chunk_cuboid(b.aabb, chunk, a.aabb, -b_vel, priority).map(|coll| coll.flipped()),
+        (Shape::Cuboid, Shape::Capsule | Shape::Sphere) => collide_rounded(a.aabb, 0.0, b.aabb, b.shape, b_vel),
+        (Shape::Capsule | Shape::Sphere, Shape::Cuboid) => collide_rounded(b.aabb, 0.0, a.aabb, a.shape, -b_vel).map(|coll| coll.flipped()),
+        (Shape::Capsule | Shape::Sphere, Shape::Capsule | Shape::Sphere) => {
+            let (a_core, a_radius) = rounded_core(a.aabb, a.shape);
+            collide_rounded(a_core, a_radius, b.aabb, b.shape, b_vel)
+        },
+        (Shape::VoxelChunk(chunk), Shape::Capsule | Shape::Sphere) => collide_chunk_rounded(a.aabb, chunk, b.aabb, b.shape, b_vel, priority),
+        (Shape::Capsule | Shape::Sphere, Shape::VoxelChunk(chunk)) => collide_chunk_rounded(b.aabb, chunk, a.aabb, a.shape, -b_vel, priority).map(|coll| coll.flipped()),
         _ => None
     }
 }
 
+/// Checks if a shape's bounds `a` overlap the box `b`.
+/// Voxel chunks only overlap where they have solid voxels, with slopes treated as full voxels.
+pub(crate) fn overlaps(a: AABB, a_shape: &Shape, b: AABB) -> bool {
+    if !a.intersects(&b) {
+        return false;
+    }
+    match a_shape {
+        Shape::VoxelChunk(chunk) => overlapped_voxels(a, chunk, b).any(|data| data.is_solid()),
+        _ => true
+    }
+}
+
+/// Checks if a shape's bounds `a` contain the point specified, surface included.
+/// Voxel chunks only contain points in their solid voxels, with slopes treated as full voxels.
+pub(crate) fn contains(a: AABB, a_shape: &Shape, point: Vec3) -> bool {
+    if !a.contains_point(point) {
+        return false;
+    }
+    match a_shape {
+        Shape::VoxelChunk(chunk) => {
+            let voxel_size = a.size() / chunk.size().as_vec3();
+            let coords = ((point - a.center + a.half_extents) / voxel_size).floor().min(chunk.size().as_vec3() - 1.0);
+            chunk.get_voxel(coords.as_uvec3()).is_some_and(VoxelData::is_solid)
+        },
+        _ => true
+    }
+}
+
+/// Iterates over the voxels of a chunk with bounds `a` that overlap the box `b`.
+pub(crate) fn overlapped_voxels(a: AABB, chunk: &VoxelChunk, b: AABB) -> impl Iterator<Item = &VoxelData> {
+    overlapped_voxel_bounds(a, chunk, b).map(|(_, data)| data)
+}
+
+/// Same as [`overlapped_voxels`], but also yields the bounds of each voxel.
+pub(crate) fn overlapped_voxel_bounds(a: AABB, chunk: &VoxelChunk, b: AABB) -> impl Iterator<Item = (AABB, &VoxelData)> {
+    overlapped_voxel_cells(a, chunk, b).map(|(_, bounds, data)| (bounds, data))
+}
+
+/// Same as [`overlapped_voxel_bounds`], but also yields the coordinates of each voxel.
+fn overlapped_voxel_cells(a: AABB, chunk: &VoxelChunk, b: AABB) -> impl Iterator<Item = (UVec3, AABB, &VoxelData)> {
+    let voxel_size = a.size() / chunk.size().as_vec3();
+    let a_min = a.center - a.half_extents;
+    let b_min = b.center - b.half_extents - a_min;
+    let b_max = b.center + b.half_extents - a_min;
+    let start = (b_min / voxel_size).floor().max(Vec3::ZERO).as_uvec3();
+    let end = (b_max / voxel_size).ceil().max(Vec3::ZERO).as_uvec3().min(chunk.size());
+    (start.z..end.z).flat_map(move |z| (start.y..end.y).flat_map(move |y| (start.x..end.x)
+        .filter_map(move |x| {
+            let coords = UVec3::new(x, y, z);
+            let center = a_min + (coords.as_vec3() + 0.5) * voxel_size;
+            chunk.get_voxel(coords).map(|data| (coords, AABB::new(center, voxel_size / 2.0), data))
+        })
+    ))
+}
+
 pub(crate) fn collide_cuboid_cuboid(a: AABB, b: AABB, b_vel: Vec3) -> Option<Collision> {
-    
-    let mut closest_coll = None;
+    collide_cuboid_cuboid_prioritized(a, b, b_vel, AxisPriority::default())
+}
+
+/// Same as [`collide_cuboid_cuboid`], but resolves collisions that happen at the same time on different axes with the priority specified.
+pub(crate) fn collide_cuboid_cuboid_prioritized(a: AABB, b: AABB, b_vel: Vec3, priority: AxisPriority) -> Option<Collision> {
+
+    // Treats boxes with zero size as points
+    if b.half_extents == Vec3::ZERO {
+        return collide_cuboid_point(a, b.center, b_vel);
+    }
+    if a.half_extents == Vec3::ZERO {
+        return collide_cuboid_point(b, a.center, -b_vel).map(|coll| coll.flipped());
+    }
+
+    // Boxes that already overlap get pushed apart instead of swept
+    if let Some(coll) = depenetrate(a, b, b_vel) {
+        return Some(coll);
+    }
 
-    // Computes b + vel
+    // Computes b + vel, and quits early if b's path doesn't cross a.
+    // The path is checked rather than b's end position, so fast boxes can't skip past thin ones.
     let bn = AABB::new(
         b.center + b_vel,
         b.half_extents
     );
+    if !a.intersects(&b.union(&bn)) {
+        return None;
+    }
 
-    if a.intersects(&bn) {
+    // Handles collisions for top and bottom.
+    // Collisions that only touch the face's edge are kept as candidates in case they tie with a collision on the other axis.
+    let collide_xz = |ay: f32, by: f32, byn: f32, na: Vec3, nb: Vec3| -> Option<Candidate> {
+        let t = compute_t(ay, by, byn);
+        if !(-TIE_EPSILON..=1.0).contains(&t) {
+            return None;
+        }
+        let t = t.max(0.0);
+        let bi = b.interp(t, b_vel);
+        let coll = Collision {
+            t,
+            position_delta: Vec3::new(0.0, ay - byn, 0.0),
+            velocity_delta: Vec3::new(0.0, -b_vel.y, 0.0),
+            normal_a: na,
+            normal_b: nb,
+            point: Vec3::ZERO,
+            depth: 0.0
+        }.with_contact(a, b, b_vel);
+        if bi.intersects_xz(&a) {
+            Some(Candidate { coll, touching_edge: false })
+        }
+        else if bi.expanded_by(Vec3::splat(TIE_EPSILON)).intersects_xz(&a) {
+            Some(Candidate { coll, touching_edge: true })
+        }
+        else {
+            None
+        }
+    };
 
-        // Handles collisions for top and bottom
-        let collide_xz = |ay: f32, by: f32, byn: f32, na: Vec3, nb: Vec3| -> Option<Collision> {
-            let t = compute_t(ay, by, byn);
-            if t < 0.0 || t > 1.0 {
+    // Handles collisions for left and right
+    let collide_yz = |ax: f32, bx: f32, bxn: f32, na: Vec3, nb: Vec3| -> Option<Candidate> {
+        let t = compute_t(ax, bx, bxn);
+        if !(-TIE_EPSILON..=1.0).contains(&t) {
+            return None;
+        }
+        let t = t.max(0.0);
+        let bi = b.interp(t, b_vel);
+        let coll = Collision {
+            t,
+            position_delta: Vec3::new(ax - bxn, 0.0, 0.0),
+            velocity_delta: Vec3::new(-b_vel.x, 0.0, 0.0),
+            normal_a: na,
+            normal_b: nb,
+            point: Vec3::ZERO,
+            depth: 0.0
+        }.with_contact(a, b, b_vel);
+        if bi.intersects_yz(&a) {
+            Some(Candidate { coll, touching_edge: false })
+        }
+        else if bi.expanded_by(Vec3::splat(TIE_EPSILON)).intersects_yz(&a) {
+            Some(Candidate { coll, touching_edge: true })
+        }
+        else {
+            None
+        }
+    };
+
+    // TOP / BOTTOM
+    let y_candidate = if b_vel.y < 0.0 {
+        collide_xz(a.top(), b.bottom(), bn.bottom(), Vec3::Y, Vec3::NEG_Y)
+    }
+    else if b_vel.y > 0.0 {
+        collide_xz(a.bottom(), b.top(), bn.top(), Vec3::NEG_Y, Vec3::Y)
+    }
+    else {
+        None
+    };
+
+    // LEFT / RIGHT
+    let x_candidate = if b_vel.x > 0.0 {
+        collide_yz(a.left(), b.right(), bn.right(), Vec3::NEG_X, Vec3::X)
+    }
+    else if b_vel.x < 0.0 {
+        collide_yz(a.right(), b.left(), bn.left(), Vec3::X, Vec3::NEG_X)
+    }
+    else {
+        None
+    };
+
+    match (y_candidate, x_candidate) {
+
+        // Collisions at the same time, like when hitting a corner, are resolved by priority
+        (Some(y), Some(x)) if (y.coll.t - x.coll.t).abs() <= TIE_EPSILON => {
+            let y_wins = match priority {
+                AxisPriority::YFirst => true,
+                AxisPriority::ByPenetration => y.coll.position_delta.y.abs() <= x.coll.position_delta.x.abs(),
+                AxisPriority::ByVelocityDominance => b_vel.y.abs() >= b_vel.x.abs()
+            };
+            if y_wins { Some(y.coll) } else { Some(x.coll) }
+        },
+
+        // Otherwise, the first collision that hit a face squarely wins
+        (y, x) => {
+            let y = y.filter(|y| !y.touching_edge).map(|y| y.coll);
+            let x = x.filter(|x| !x.touching_edge).map(|x| x.coll);
+            if is_coll_closer(&x, &y) { x } else { y }
+        }
+    }
+}
+
+/// Collision that pushes box `b` out of box `a` along the axis they overlap the least on, if they overlap by more than [`EPSILON`].
+/// Also stops `b` from moving any further into `a`.
+fn depenetrate(a: AABB, b: AABB, b_vel: Vec3) -> Option<Collision> {
+    let offset = b.center - a.center;
+    let overlap = a.half_extents + b.half_extents - offset.abs();
+
+    // Ties favor Y, since min_by picks the last of equal elements
+    let (axis, depth) = [(Vec3::Z, overlap.z), (Vec3::X, overlap.x), (Vec3::Y, overlap.y)]
+        .into_iter()
+        .min_by(|(_, x), (_, y)| x.total_cmp(y))
+        .unwrap();
+    if depth <= EPSILON {
+        return None;
+    }
+    let normal = if offset.dot(axis) < 0.0 { -axis } else { axis };
+    let approach = b_vel.dot(normal).min(0.0);
+    Some(Collision {
+        t: 0.0,
+        position_delta: normal * (depth - approach),
+        velocity_delta: -normal * approach,
+        normal_a: normal,
+        normal_b: -normal,
+        point: Vec3::ZERO,
+        depth: 0.0
+    }.with_contact(a, b, b_vel))
+}
+
+/// Collision found by a box sweep
+struct Candidate {
+    coll: Collision,
+    /// True if the boxes only touched along the edge of the face hit
+    touching_edge: bool
+}
+
+/// Sweeps point `b` along its velocity against box `a`, stopping it on the first face it enters.
+/// Unlike box sweeps, points touching a face count as hitting it, since they'd never overlap anything otherwise.
+pub(crate) fn collide_cuboid_point(a: AABB, b: Vec3, b_vel: Vec3) -> Option<Collision> {
+    let a_min = a.center - a.half_extents;
+    let a_max = a.center + a.half_extents;
+    let mut t_enter = f32::NEG_INFINITY;
+    let mut t_exit = f32::INFINITY;
+    let mut enter_axis = None;
+    for axis in 0..3 {
+        let (pos, vel, min, max) = (b[axis], b_vel[axis], a_min[axis], a_max[axis]);
+        if vel.abs() <= EPSILON {
+            if pos < min || pos > max {
                 return None;
             }
-            let bi = b.interp(t, b_vel);
-            if bi.intersects_xz(&a) {
-                return Some(Collision {
-                    t,
-                    position_delta: Vec3::new(0.0, ay - byn, 0.0),
-                    velocity_delta: Vec3::new(0.0, -b_vel.y, 0.0),
-                    normal_a: na,
-                    normal_b: nb,
-                })
-            }
-            return None
+            continue;
+        }
+        let (t_near, t_far) = if vel > 0.0 {
+            ((min - pos) / vel, (max - pos) / vel)
+        }
+        else {
+            ((max - pos) / vel, (min - pos) / vel)
         };
+        if t_near > t_enter {
+            t_enter = t_near;
+            enter_axis = Some(axis);
+        }
+        t_exit = t_exit.min(t_far);
+    }
 
-        // Handles collisions for left and right
-        let collide_yz = |ax: f32, bx: f32, bxn: f32, na: Vec3, nb: Vec3| -> Option<Collision> {
-            let t = compute_t(ax, bx, bxn);
-            let bi = b.interp(t, b_vel);
-            if bi.intersects_yz(&a) {
-                return Some(Collision {
-                    t,
-                    position_delta: Vec3::new(ax - bxn, 0.0, 0.0),
-                    velocity_delta: Vec3::new(-b_vel.x, 0.0, 0.0),
-                    normal_a: na,
-                    normal_b: nb,
-                })
-            }
-            return None
-        };
+    // Only collides if a face gets entered during this substep
+    let axis = enter_axis?;
+    if t_enter > t_exit || !(0.0..=1.0).contains(&t_enter) {
+        return None;
+    }
+    let face = if b_vel[axis] > 0.0 { a_min[axis] } else { a_max[axis] };
+    let mut normal_a = Vec3::ZERO;
+    normal_a[axis] = -b_vel[axis].signum();
+    let mut position_delta = Vec3::ZERO;
+    position_delta[axis] = face - (b[axis] + b_vel[axis]);
+    let mut velocity_delta = Vec3::ZERO;
+    velocity_delta[axis] = -b_vel[axis];
+    Some(Collision {
+        t: t_enter,
+        position_delta,
+        velocity_delta,
+        normal_a,
+        normal_b: -normal_a,
+        point: Vec3::ZERO,
+        depth: 0.0
+    }.with_contact(a, AABB::new(b, Vec3::ZERO), b_vel))
+}
 
-        // TOP
-        if b_vel.y < 0.0 {
-            let coll = collide_xz(a.top(), b.bottom(), bn.bottom(), Vec3::Y, Vec3::NEG_Y);
-            if is_coll_closer(&coll, &closest_coll) {
-                closest_coll = coll;
-            }
+/// Core of a capsule or sphere with the bounds specified, as a box with no width or depth, and its radius.
+/// A capsule's core is the segment running vertically through its middle, and its radius is the smallest of the bounds' horizontal half extents.
+/// A sphere's core is its center, and its radius is the smallest of the bounds' half extents.
+fn rounded_core(bounds: AABB, shape: &Shape) -> (AABB, f32) {
+    let (radius, half_height) = match shape {
+        Shape::Sphere => (bounds.half_extents.min_element(), 0.0),
+        _ => {
+            let radius = bounds.half_extents.x.min(bounds.half_extents.z);
+            (radius, (bounds.half_extents.y - radius).max(0.0))
         }
+    };
+    (AABB::new(bounds.center, Vec3::new(0.0, half_height, 0.0)), radius)
+}
 
-        // BOTTOM
-        if b_vel.y > 0.0 {
-            let coll = collide_xz(a.bottom(), b.top(), bn.top(), Vec3::NEG_Y, Vec3::Y);
-            if is_coll_closer(&coll, &closest_coll) {
-                closest_coll = coll;
-            }
-        }
+/// Collides capsule or sphere `b` with box `a` grown by `a_radius`, which is 0 for boxes and the radius of `a` for rounded shapes.
+/// `b` gets pushed out along the line between the closest points of `a` and the core of `b`,
+/// so it rounds corners instead of catching on them. Only the end of `b`'s movement is checked.
+fn collide_rounded(a: AABB, a_radius: f32, b: AABB, b_shape: &Shape, b_vel: Vec3) -> Option<Collision> {
+    let bn = AABB::new(b.center + b_vel, b.half_extents);
+    if !a.expanded_by(Vec3::splat(a_radius)).intersects(&b.union(&bn)) {
+        return None;
+    }
+    let (b_core, b_radius) = rounded_core(b, b_shape);
+    let radius = a_radius + b_radius;
+    let bn_core = AABB::new(bn.center, b_core.half_extents);
+    let (end_gap, normal) = rounded_separation(a, bn_core, radius);
+    if end_gap >= 0.0 {
+        return None;
+    }
+    let (start_gap, _) = rounded_separation(a, b_core, radius);
+    let t = if start_gap <= 0.0 { 0.0 } else { start_gap / (start_gap - end_gap) };
+    Some(Collision {
+        t,
+        position_delta: normal * -end_gap,
+        velocity_delta: -normal * b_vel.dot(normal).min(0.0),
+        normal_a: normal,
+        normal_b: -normal,
+        point: Vec3::ZERO,
+        depth: 0.0
+    }.with_contact(a.expanded_by(Vec3::splat(a_radius)), b, b_vel))
+}
 
-        // LEFT
-        if b_vel.x > 0.0 {
-            let coll = collide_yz(a.left(), b.right(), bn.right(), Vec3::NEG_X, Vec3::X);
-            if is_coll_closer(&coll, &closest_coll) {
-                closest_coll = coll;
-            }
+/// Distance between box `a` and the core `b` of a capsule or sphere, minus their combined `radius`.
+/// Negative if they overlap. Also returns the direction to push `b` in to separate them.
+fn rounded_separation(a: AABB, b: AABB, radius: f32) -> (f32, Vec3) {
+    let (a_min, a_max) = (a.center - a.half_extents, a.center + a.half_extents);
+    let (b_bottom, b_top) = (b.bottom(), b.top());
+
+    // Closest points between a and the core of b
+    let mut on_a = b.center.clamp(a_min, a_max);
+    let mut on_b = b.center;
+    (on_a.y, on_b.y) = if b_top < a_min.y {
+        (a_min.y, b_top)
+    }
+    else if b_bottom > a_max.y {
+        (a_max.y, b_bottom)
+    }
+    else {
+        let y = b.center.y.clamp(b_bottom.max(a_min.y), b_top.min(a_max.y));
+        (y, y)
+    };
+    let diff = on_b - on_a;
+    let distance = diff.length();
+    if distance > EPSILON {
+        return (distance - radius, diff / distance);
+    }
+
+    // Core runs through a, so pushes out along the axis it's least deep in. Ties favor Y.
+    let above = a_max + radius - on_b;
+    let below = on_b - (a_min - radius);
+    let (depth, normal) = [
+        (above.z, Vec3::Z), (below.z, Vec3::NEG_Z),
+        (above.x, Vec3::X), (below.x, Vec3::NEG_X),
+        (above.y, Vec3::Y), (below.y, Vec3::NEG_Y)
+    ]
+        .into_iter()
+        .min_by(|(x, _), (y, _)| x.total_cmp(y))
+        .unwrap();
+    (-depth, normal)
+}
+
+/// Collides capsule or sphere `b` with the solid voxels of a chunk with bounds `a`, returning the earliest collision.
+/// Slope voxels collide with the bounds of `b`, like they would with a box.
+fn collide_chunk_rounded(a_bounds: AABB, a_chunk: &VoxelChunk, b_bounds: AABB, b_shape: &Shape, b_vel: Vec3, priority: AxisPriority) -> Option<Collision> {
+    let swept = AABB::new(b_bounds.center + b_vel / 2.0, b_bounds.half_extents + b_vel.abs() / 2.0 + TIE_EPSILON);
+    let mut closest_coll = None;
+    for (coords, voxel_bounds, data) in overlapped_voxel_cells(a_bounds, a_chunk, swept) {
+        if !data.is_solid() {
+            continue;
         }
+        let coll = match data.voxel {
+            Voxel::Slope => collide_slope_cuboid(voxel_bounds, data.orientation, b_bounds, b_vel, priority),
+            _ => collide_rounded(voxel_bounds, 0.0, b_bounds, b_shape, b_vel).filter(|coll| {
+                let hit_top = coll.normal_a.y >= 1.0 - EPSILON;
+                let from_above = b_bounds.bottom() >= voxel_bounds.top() - EPSILON;
+                let one_way_blocked = data.flags.contains(VoxelFlags::ONE_WAY_UP) && !(hit_top && from_above);
+                let top_blocked = data.flags.contains(VoxelFlags::NO_COLLIDE_TOP) && hit_top;
+                let axis_normal = coll.normal_a.abs().max_element() >= 1.0 - EPSILON;
+                let inner_face = axis_normal && is_inner_face(a_chunk, coords, coll.normal_a.round());
+                !one_way_blocked && !top_blocked && !inner_face
+            })
+        };
+        if is_coll_closer(&coll, &closest_coll) {
+            closest_coll = coll;
+        }
+    }
+    closest_coll
+}
 
-        // RIGHT
-        if b_vel.x < 0.0 {
-            let coll = collide_yz(a.right(), b.left(), bn.left(), Vec3::X, Vec3::NEG_X);
+/// Sweeps box `b` against the solid voxels of a chunk with bounds `a`, returning the earliest collision.
+/// Only voxels overlapped by the swept box are checked. Faces shared by two cuboid voxels are skipped,
+/// so boxes slide across flat terrain without snagging on the seams between voxels. See [`collide_slope_cuboid`] for slopes.
+pub(crate) fn collide_chunk_cuboid(a_bounds: AABB, a_chunk: &VoxelChunk, b_bounds: AABB, b_vel: Vec3, priority: AxisPriority) -> Option<Collision> {
+    // Includes voxels that are only touched, so boxes resting on a slope keep following it
+    let swept = AABB::new(b_bounds.center + b_vel / 2.0, b_bounds.half_extents + b_vel.abs() / 2.0 + TIE_EPSILON);
+    let mut closest_coll = None;
+    for (coords, voxel_bounds, data) in overlapped_voxel_cells(a_bounds, a_chunk, swept) {
+        if !data.is_solid() {
+            continue;
+        }
+        if data.voxel == Voxel::Slope {
+            let coll = collide_slope_cuboid(voxel_bounds, data.orientation, b_bounds, b_vel, priority);
             if is_coll_closer(&coll, &closest_coll) {
                 closest_coll = coll;
-                bevy_log::info!("RIGHT: {:?}\nb_vel: {}", coll, b_vel);
             }
+            continue;
+        }
+        let coll = collide_cuboid_cuboid_prioritized(voxel_bounds, b_bounds, b_vel, priority).filter(|coll| {
+            let hit_top = coll.normal_a == Vec3::Y;
+            let from_above = b_bounds.bottom() >= voxel_bounds.top() - EPSILON;
+            let one_way_blocked = data.flags.contains(VoxelFlags::ONE_WAY_UP) && !(hit_top && from_above);
+            let top_blocked = data.flags.contains(VoxelFlags::NO_COLLIDE_TOP) && hit_top;
+            !one_way_blocked && !top_blocked && !is_inner_face(a_chunk, coords, coll.normal_a)
+        });
+        if is_coll_closer(&coll, &closest_coll) {
+            closest_coll = coll;
         }
     }
-
     closest_coll
 }
 
-pub(crate) fn collide_chunk_cuboid(a_bounds: AABB, a_chunk: &VoxelChunk, b_bounds: AABB, b_vel: Vec3) -> Option<Collision> {
-    None
+/// Collides box `b` with a slope voxel filling the bounds `a`.
+/// Slopes facing up clamp the bottom of `b` to their surface, so boxes glide up them instead of climbing a staircase.
+/// Boxes resting on one also get kept on its surface while moving down it, unless they're moving up.
+/// Boxes coming from below the surface, and slopes that don't face up, collide as if the voxel was a cuboid.
+fn collide_slope_cuboid(a: AABB, orientation: Orientation, b: AABB, b_vel: Vec3, priority: AxisPriority) -> Option<Collision> {
+
+    // Scales the normal along with the voxel, in case it isn't a cube
+    let normal = (orientation * Vec3::new(0.0, 1.0, 1.0) / a.half_extents).normalize();
+    if normal.y <= EPSILON {
+        return collide_cuboid_cuboid_prioritized(a, b, b_vel, priority);
+    }
+    let bn = AABB::new(b.center + b_vel, b.half_extents);
+    let path = b.union(&bn);
+    let (a_min, a_max) = (a.center - a.half_extents, a.center + a.half_extents);
+    let (path_min, path_max) = (path.center - path.half_extents, path.center + path.half_extents);
+    if path_max.x - a_min.x <= EPSILON || a_max.x - path_min.x <= EPSILON || path_max.z - a_min.z <= EPSILON || a_max.z - path_min.z <= EPSILON {
+        return None;
+    }
+
+    // Compares the bottom of b to the highest point of the surface beneath it, before and after moving
+    let start_gap = b.bottom() - slope_height(a, normal, b);
+    let end_gap = bn.bottom() - slope_height(a, normal, bn);
+    let tolerance = a.half_extents.y * 0.1;
+    if start_gap < -tolerance {
+        return collide_cuboid_cuboid_prioritized(a, b, b_vel, priority);
+    }
+    let t = if end_gap < 0.0 {
+        (start_gap / (start_gap - end_gap)).clamp(0.0, 1.0)
+    }
+    else if b_vel.y <= 0.0 && start_gap <= tolerance && end_gap > EPSILON {
+        1.0
+    }
+    else {
+        return None;
+    };
+
+    // Contact point sits on the surface, right beneath b
+    let mut coll = Collision {
+        t,
+        position_delta: Vec3::new(0.0, -end_gap, 0.0),
+        velocity_delta: Vec3::new(0.0, -b_vel.y.min(0.0), 0.0),
+        normal_a: normal,
+        normal_b: -normal,
+        point: Vec3::ZERO,
+        depth: 0.0
+    }.with_contact(a, b, b_vel);
+    coll.point.y = slope_height(a, normal, b.interp(t, b_vel));
+    Some(coll)
+}
+
+/// Height of the surface of slope voxel `a` at the highest point beneath box `b`.
+fn slope_height(a: AABB, normal: Vec3, b: AABB) -> f32 {
+    let (a_min, a_max) = (a.center - a.half_extents, a.center + a.half_extents);
+    let (b_min, b_max) = (b.center - b.half_extents, b.center + b.half_extents);
+    let x = if normal.x < 0.0 { b_max.x } else { b_min.x }.clamp(a_min.x, a_max.x);
+    let z = if normal.z < 0.0 { b_max.z } else { b_min.z }.clamp(a_min.z, a_max.z);
+    let height = a.center.y - (normal.x * (x - a.center.x) + normal.z * (z - a.center.z)) / normal.y;
+    height.clamp(a_min.y, a_max.y)
+}
+
+/// Checks if the face of a voxel with the normal specified is covered by a neighboring cuboid voxel.
+fn is_inner_face(chunk: &VoxelChunk, coords: UVec3, normal: Vec3) -> bool {
+    let neighbor = coords.as_ivec3() + normal.as_ivec3();
+    if neighbor.cmplt(IVec3::ZERO).any() {
+        return false;
+    }
+    match chunk.get_voxel(neighbor.as_uvec3()) {
+        Some(data) => data.voxel == Voxel::Cuboid && data.is_solid() && !data.flags.contains(VoxelFlags::ONE_WAY_UP),
+        None => false
+    }
 }
 
 fn compute_t(a_val: f32, b_val: f32, b_next_val: f32) -> f32 {
@@ -247,7 +803,11 @@ fn is_coll_closer(
 #[cfg(test)]
 mod test {
 
+    use bevy_app::prelude::*;
+    use bevy_transform::prelude::*;
+
     use super::*;
+    use crate::{physics_test_app, spawn_static_floor, AntiGravity, CurrentTransform, Degree, Gravity, GroundState, HalfExtents, PhysicsBundle, PhysicsConfig, Restitution, CarriesRiders, OneWay, Kinematic, StaticBody, Velocity, Weight};
 
     #[test]
     fn affected_by() {
@@ -260,5 +820,642 @@ mod test {
         assert!(config.affected_by(GROUP_STATIC_TERRAIN));
         assert!(config.affected_by(GROUP_MOVING_TERRAIN));
     }
+
+    #[test]
+    fn point_hits_faces() {
+        let a = AABB::new(Vec3::ZERO, Vec3::ONE);
+
+        // Lands on top
+        let coll = collide_cuboid_cuboid(a, AABB::new(Vec3::new(0.5, 1.5, 0.5), Vec3::ZERO), Vec3::new(0.0, -1.0, 0.0)).unwrap();
+        assert_eq!(0.5, coll.t);
+        assert_eq!(Vec3::new(0.0, 0.5, 0.0), coll.position_delta);
+        assert_eq!(Vec3::Y, coll.normal_a);
+
+        // Hits the near face, and still touches the edge of a face it grazes
+        let coll = collide_cuboid_cuboid(a, AABB::new(Vec3::new(1.0, 0.0, 1.5), Vec3::ZERO), Vec3::new(0.0, 0.0, -1.0)).unwrap();
+        assert_eq!(Vec3::Z, coll.normal_a);
+        assert_eq!(Vec3::new(0.0, 0.0, 0.5), coll.position_delta);
+
+        // Misses when moving past, or starting inside
+        assert_eq!(None, collide_cuboid_cuboid(a, AABB::new(Vec3::new(1.5, 1.5, 0.0), Vec3::ZERO), Vec3::new(0.0, -1.0, 0.0)));
+        assert_eq!(None, collide_cuboid_cuboid(a, AABB::new(Vec3::ZERO, Vec3::ZERO), Vec3::new(0.0, -1.0, 0.0)));
+
+        // Moving box hits a point in its way
+        let coll = collide_cuboid_cuboid(AABB::new(Vec3::new(0.0, -1.5, 0.0), Vec3::ZERO), a, Vec3::new(0.0, -1.0, 0.0)).unwrap();
+        assert_eq!(Vec3::new(0.0, 0.5, 0.0), coll.position_delta);
+        assert_eq!(Vec3::Y, coll.normal_a);
+        assert_eq!(Vec3::NEG_Y, coll.normal_b);
+    }
+
+    #[test]
+    fn contact_point_and_depth() {
+        // Box lands off-center on top of another, sinking 1.5 units in by the end of its move
+        let a = AABB::new(Vec3::ZERO, Vec3::ONE);
+        let coll = collide_cuboid_cuboid(a, AABB::new(Vec3::new(0.5, 2.0, 0.0), Vec3::splat(0.5)), Vec3::new(0.0, -2.0, 0.0)).unwrap();
+        assert_eq!(Vec3::new(0.5, 1.0, 0.0), coll.point);
+        assert_eq!(1.5, coll.depth);
+        assert_eq!(coll.point, coll.flipped().point);
+
+        // Points on chunks are in world space
+        let mut chunk = VoxelChunk::new(UVec3::ONE);
+        chunk.set_voxel(UVec3::ZERO, VoxelData::new(Voxel::Cuboid));
+        let chunk_bounds = AABB::new(Vec3::new(10.0, 0.0, 0.0), Vec3::splat(0.5));
+        let b = AABB::new(Vec3::new(10.25, 1.5, 0.25), Vec3::splat(0.5));
+        let coll = collide_chunk_cuboid(chunk_bounds, &chunk, b, Vec3::new(0.0, -1.0, 0.0), AxisPriority::YFirst).unwrap();
+        assert!(coll.point.abs_diff_eq(Vec3::new(10.125, 0.5, 0.125), 0.0001));
+        assert!((coll.depth - 0.5).abs() < 0.0001);
+    }
+
+    /// App with a chunk that has a 1-voxel-thick floor at y = 0, and a one-way platform over one corner at y = 3.
+    fn floor_chunk_app() -> App {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        let mut chunk = VoxelChunk::new(UVec3::new(8, 4, 8));
+        chunk
+            .set_voxel_box(UVec3::ZERO, UVec3::new(8, 1, 8), VoxelData::new(Voxel::Cuboid))
+            .set_voxel_box(UVec3::new(0, 3, 0), UVec3::new(2, 4, 2), VoxelData::new(Voxel::Cuboid).with_flags(VoxelFlags::ONE_WAY_UP));
+        spawn_static_floor(&mut app.world, Transform::from_xyz(4.0, 2.0, 4.0), HalfExtents::new(8.0, 4.0, 8.0), Shape::VoxelChunk(chunk));
+        app
+    }
+
+    fn spawn_box(app: &mut App, position: Vec3, vel: Vec3) -> Entity {
+        app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_translation(position), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(vel))
+            })
+            .id()
+    }
+
+    #[test]
+    fn box_rests_on_chunk_floor() {
+        let mut app = floor_chunk_app();
+        let dropped = spawn_box(&mut app, Vec3::new(4.5, 3.5, 6.5), Vec3::ZERO);
+        let sliding = spawn_box(&mut app, Vec3::new(4.5, 1.5, 4.5), Vec3::new(0.05, 0.0, 0.0));
+        for _ in 0..30 {
+            app.update();
+        }
+        assert_eq!(1.5, app.world.get::<CurrentTransform>(dropped).unwrap().0.translation.y);
+        assert_eq!(Vec3::ZERO, app.world.get::<Velocity>(dropped).unwrap().0);
+
+        // Slides across the seams between voxels without snagging
+        let sliding_trans = app.world.get::<CurrentTransform>(sliding).unwrap().0.translation;
+        assert_eq!(1.5, sliding_trans.y);
+        assert!((sliding_trans.x - 6.0).abs() < 0.001);
+        assert_eq!(Vec3::new(0.05, 0.0, 0.0), app.world.get::<Velocity>(sliding).unwrap().0);
+    }
+
+    #[test]
+    fn box_walks_up_and_down_slopes() {
+        for y_rot in [Degree::Zero, Degree::Ninty, Degree::OneEighty, Degree::TwoSeventy] {
+            let orientation = Orientation { y_rot, ..Default::default() };
+            let normal = orientation * Vec3::new(0.0, 1.0, 1.0);
+            let uphill = -Vec3::new(normal.x, 0.0, normal.z);
+
+            // Floor with a row of slopes leading up to a raised platform
+            let mut chunk = VoxelChunk::new(UVec3::new(9, 3, 9));
+            chunk.set_voxel_box(UVec3::ZERO, UVec3::new(9, 1, 9), VoxelData::new(Voxel::Cuboid));
+            for x in 0..9 {
+                for z in 0..9 {
+                    let progress = (Vec3::new(x as f32 - 4.0, 0.0, z as f32 - 4.0)).dot(uphill);
+                    let coords = UVec3::new(x, 1, z);
+                    if progress == 0.0 {
+                        chunk.set_voxel(coords, VoxelData::new(Voxel::Slope).with_orientation(orientation));
+                    }
+                    else if progress > 0.0 {
+                        chunk.set_voxel(coords, VoxelData::new(Voxel::Cuboid));
+                    }
+                }
+            }
+            let mut app = physics_test_app();
+            app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+            spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, 1.5, 0.0), HalfExtents::new(9.0, 3.0, 9.0), Shape::VoxelChunk(chunk));
+            let body = spawn_box(&mut app, uphill * -3.0 + Vec3::new(0.0, 1.5, 0.0), Vec3::ZERO);
+
+            // Rises and falls no faster than it moves sideways, staying on the ground the whole time
+            for (direction, end_y) in [(uphill, 2.5), (-uphill, 1.5)] {
+                for _ in 0..80 {
+                    let before = app.world.get::<CurrentTransform>(body).unwrap().0.translation.y;
+                    let mut vel = app.world.get_mut::<Velocity>(body).unwrap();
+                    vel.0 = direction * 0.05 + Vec3::new(0.0, vel.0.y, 0.0);
+                    app.update();
+                    let after = app.world.get::<CurrentTransform>(body).unwrap().0.translation.y;
+                    assert!((after - before).abs() <= 0.0501, "{y_rot:?}: {before} -> {after}");
+                    assert!((after - before) * direction.dot(uphill) >= -0.0001, "{y_rot:?}: {before} -> {after}");
+                    let contacts = app.world.get::<Contacts>(body).unwrap();
+                    assert!(contacts.iter().any(|contact| contact.normal.y > 0.5), "{y_rot:?}: {after}");
+                }
+                let end = app.world.get::<CurrentTransform>(body).unwrap().0.translation.y;
+                assert!((end - end_y).abs() < 0.001, "{y_rot:?}: {end}");
+            }
+        }
+    }
+
+    fn spawn_capsule(app: &mut App, position: Vec3, vel: Vec3) -> Entity {
+        app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_translation(position), HalfExtents::new(1.0, 2.0, 1.0), Shape::Capsule)
+                    .with_velocity(Velocity(vel))
+            })
+            .id()
+    }
+
+    #[test]
+    fn capsule_rests_on_floor() {
+        let mut app = floor_chunk_app();
+        spawn_static_floor(&mut app.world, Transform::from_xyz(-10.0, 0.5, 0.0), HalfExtents::new(8.0, 1.0, 8.0), Shape::Cuboid);
+        let on_chunk = spawn_capsule(&mut app, Vec3::new(4.5, 3.5, 4.5), Vec3::ZERO);
+        let on_box = spawn_capsule(&mut app, Vec3::new(-10.0, 3.5, 0.0), Vec3::ZERO);
+        let post = spawn_capsule(&mut app, Vec3::new(10.0, 2.0, 0.0), Vec3::ZERO);
+        app.world.entity_mut(post).insert((AntiGravity, CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE)));
+        let on_post = spawn_capsule(&mut app, Vec3::new(10.0, 6.0, 0.0), Vec3::ZERO);
+        for _ in 0..30 {
+            app.update();
+        }
+
+        // Bottoms of capsules rest on what's beneath them
+        assert_eq!(2.0, app.world.get::<CurrentTransform>(on_chunk).unwrap().0.translation.y);
+        assert_eq!(2.0, app.world.get::<CurrentTransform>(on_box).unwrap().0.translation.y);
+        assert!((app.world.get::<CurrentTransform>(on_post).unwrap().0.translation.y - 4.0).abs() < 0.0001);
+        assert_eq!(Vec3::ZERO, app.world.get::<Velocity>(on_chunk).unwrap().0);
+    }
+
+    #[test]
+    fn capsule_slides_around_wall_edge() {
+        for (shape, passes) in [(Shape::Capsule, true), (Shape::Cuboid, false)] {
+            let mut app = physics_test_app();
+            spawn_static_floor(&mut app.world, Transform::from_xyz(3.0, 0.0, -5.0), HalfExtents::new(2.0, 4.0, 10.0), Shape::Cuboid);
+            let body = app.world
+                .spawn(PhysicsBundle {
+                    config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                    ..PhysicsBundle::new(Transform::from_xyz(0.0, 0.0, 0.2), HalfExtents::new(1.0, 2.0, 1.0), shape)
+                })
+                .insert(AntiGravity)
+                .id();
+
+            // Clips the wall's edge while moving along it, getting nudged past it only if rounded
+            for _ in 0..60 {
+                app.world.get_mut::<Velocity>(body).unwrap().0.x = 0.1;
+                app.update();
+            }
+            let pos = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+            if passes {
+                assert!(pos.x > 5.0 && pos.z >= 0.5 - 0.0001, "{pos}");
+            }
+            else {
+                assert!((pos.x - 1.5).abs() < 0.0001, "{pos}");
+            }
+        }
+    }
+
+    #[test]
+    fn ball_bounces_off_corner_diagonally() {
+        let mut app = physics_test_app();
+        app.insert_resource(PhysicsConfig { substeps: 1, ..PhysicsConfig::default() });
+        spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, 0.0, 0.0), HalfExtents::new(2.0, 2.0, 2.0), Shape::Cuboid);
+        let spawn_ball = |app: &mut App, shape: Shape| app.world
+            .spawn((
+                PhysicsBundle {
+                    config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                    ..PhysicsBundle::new(Transform::from_xyz(-1.6, 1.6, 0.0), HalfExtents::new(1.0, 1.0, 1.0), shape)
+                        .with_velocity(Velocity(Vec3::new(0.5, -0.5, 0.0)))
+                },
+                AntiGravity,
+                Restitution(1.0)
+            ))
+            .id();
+        let ball = spawn_ball(&mut app, Shape::Sphere);
+        app.update();
+
+        // Hits the corner head on, so bounces straight back the way it came
+        let vel = app.world.get::<Velocity>(ball).unwrap().0;
+        assert!((vel - Vec3::new(-0.5, 0.5, 0.0)).length() < 0.0001, "{vel}");
+
+        // Boxes only ever bounce off one face of the corner
+        app.world.despawn(ball);
+        let cube = spawn_ball(&mut app, Shape::Cuboid);
+        app.update();
+        let vel = app.world.get::<Velocity>(cube).unwrap().0;
+        assert!(vel.x == 0.5 || vel.y == -0.5, "{vel}");
+    }
+
+    #[test]
+    fn ball_rests_on_chunk_floor() {
+        let mut app = floor_chunk_app();
+        let ball = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(4.0, 3.0, 5.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Sphere)
+            })
+            .id();
+        for _ in 0..30 {
+            app.update();
+        }
+        let trans = app.world.get::<CurrentTransform>(ball).unwrap().0.translation;
+        assert_eq!(Vec3::new(4.0, 1.5, 5.0), trans);
+    }
+
+    #[test]
+    fn box_jumps_through_one_way_platform() {
+        let mut app = floor_chunk_app();
+        let jumper = spawn_box(&mut app, Vec3::new(1.0, 1.5, 1.0), Vec3::new(0.0, 1.0, 0.0));
+
+        // Passes through from below, then lands on top
+        for _ in 0..40 {
+            app.update();
+        }
+        assert_eq!(4.5, app.world.get::<CurrentTransform>(jumper).unwrap().0.translation.y);
+    }
+
+    #[test]
+    fn box_walks_across_flat_chunk_seams() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.02, 0.0)));
+        app.insert_resource(PhysicsConfig { substeps: 3, ..PhysicsConfig::default() });
+
+        // Flat 16-voxel floor, with a stretch of one-way voxels in its top layer
+        let mut chunk = VoxelChunk::new(UVec3::new(16, 2, 1));
+        chunk
+            .set_voxel_box(UVec3::ZERO, UVec3::new(16, 2, 1), VoxelData::new(Voxel::Cuboid))
+            .set_voxel_box(UVec3::new(5, 1, 0), UVec3::new(9, 2, 1), VoxelData::new(Voxel::Cuboid).with_flags(VoxelFlags::ONE_WAY_UP));
+        spawn_static_floor(&mut app.world, Transform::from_xyz(8.0, 1.0, 0.0), HalfExtents::new(16.0, 2.0, 1.0), Shape::VoxelChunk(chunk));
+        let walker = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.25, 2.65, 0.0), HalfExtents::new(0.5, 1.3, 0.8), Shape::Cuboid)
+            })
+            .insert(GroundState::default())
+            .id();
+
+        // Ground stays flat the whole way, without the box snagging on or sinking into a seam
+        for tick in 0..110 {
+            app.world.get_mut::<Velocity>(walker).unwrap().0.x = 0.13;
+            app.update();
+            let ground = app.world.get::<GroundState>(walker).unwrap().ground();
+            assert_eq!(Some(Vec3::Y), ground.map(|ground| ground.normal), "Tick {tick}");
+            assert!(app.world.get::<Contacts>(walker).unwrap().iter().all(|contact| contact.normal == Vec3::Y), "Tick {tick}");
+        }
+        let trans = app.world.get::<CurrentTransform>(walker).unwrap().0.translation;
+        assert!(trans.x > 14.0, "{trans}");
+        assert!((trans.y - 2.65).abs() < 0.0001, "{trans}");
+    }
+
+    #[test]
+    fn box_falls_back_through_one_way_platform() {
+        let mut app = floor_chunk_app();
+        let jumper = spawn_box(&mut app, Vec3::new(0.5, 1.5, 0.5), Vec3::new(0.0, 0.75, 0.0));
+
+        // Gets its center past the middle of the platform without clearing it, so it shouldn't get bumped on top
+        let mut highest = 0.0_f32;
+        for _ in 0..60 {
+            app.update();
+            highest = highest.max(app.world.get::<CurrentTransform>(jumper).unwrap().0.translation.y);
+        }
+        assert!(highest > 3.5 && highest < 4.5, "{highest}");
+        assert_eq!(1.5, app.world.get::<CurrentTransform>(jumper).unwrap().0.translation.y);
+    }
+
+    #[test]
+    fn box_jumps_through_one_way_body() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, 3.0, 0.0), HalfExtents::new(4.0, 0.2, 4.0), Shape::Cuboid).insert(OneWay::default());
+        let jumper = spawn_box(&mut app, Vec3::new(0.0, 0.5, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+        // Passes through from below, then lands on top
+        let mut highest = 0.0_f32;
+        for _ in 0..40 {
+            app.update();
+            highest = highest.max(app.world.get::<CurrentTransform>(jumper).unwrap().0.translation.y);
+        }
+        assert!(highest > 5.0, "{highest}");
+        assert!((app.world.get::<CurrentTransform>(jumper).unwrap().0.translation.y - 3.6).abs() < 0.0001);
+    }
+
+    #[test]
+    fn corner_tie_break() {
+
+        // Box reaches the platform's corner halfway through the step, sinking further into it vertically
+        let platform = AABB::new(Vec3::ZERO, Vec3::new(1.0, 0.5, 1.0));
+        let b = AABB::new(Vec3::new(1.51, 1.05, 0.0), Vec3::splat(0.5));
+        let vel = Vec3::new(-0.02, -0.1, 0.0);
+        for (priority, expected) in [
+            (AxisPriority::YFirst, Vec3::Y),
+            (AxisPriority::ByPenetration, Vec3::X),
+            (AxisPriority::ByVelocityDominance, Vec3::Y)
+        ] {
+            let coll = collide_cuboid_cuboid_prioritized(platform, b, vel, priority).unwrap();
+            assert_eq!(expected, coll.normal_a, "{priority:?}");
+        }
+
+        // Box moving mostly sideways gets stopped sideways when velocity dominates
+        let b = AABB::new(Vec3::new(1.55, 1.01, 0.0), Vec3::splat(0.5));
+        let vel = Vec3::new(-0.1, -0.02, 0.0);
+        let coll = collide_cuboid_cuboid_prioritized(platform, b, vel, AxisPriority::ByVelocityDominance).unwrap();
+        assert_eq!(Vec3::X, coll.normal_a);
+        let coll = collide_cuboid_cuboid_prioritized(platform, b, vel, AxisPriority::YFirst).unwrap();
+        assert_eq!(Vec3::Y, coll.normal_a);
+    }
+
+    #[test]
+    fn lands_on_platform_corner() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::ZERO));
+        spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(2.0, 1.0, 2.0), Shape::Cuboid);
+
+        // Corner reaches the platform's corner halfway through the first substep
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(1.5005, 1.05, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(-0.004, -0.4, 0.0)))
+            })
+            .id();
+        for _ in 0..5 {
+            app.update();
+        }
+        let translation = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert_eq!(1.0, translation.y);
+        assert!(translation.x < 1.5005);
+    }
+
+    #[test]
+    fn fast_body_doesnt_tunnel() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::ZERO));
+        app.insert_resource(PhysicsConfig { substeps: 1, ..PhysicsConfig::default() });
+        spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(0.1, 10.0, 10.0), Shape::Cuboid);
+
+        // Moves several times its own size in a single step, ending well past the wall
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(-3.0, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(5.0, 0.0, 0.0)))
+            })
+            .id();
+        app.update();
+        let translation = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert!(translation.abs_diff_eq(Vec3::new(-0.55, 0.0, 0.0), 0.0001), "{translation}");
+        assert_eq!(Vec3::ZERO, app.world.get::<Velocity>(body).unwrap().0);
+    }
+
+    #[test]
+    fn overlapping_boxes_separate() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::ZERO));
+        let light = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::default(), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+        let heavy = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                weight: Weight(3.0),
+                ..PhysicsBundle::new(Transform::from_xyz(0.6, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+
+        // Pushed apart along the shallowest axis, with the lighter box moving further
+        for _ in 0..3 {
+            app.update();
+        }
+        let light_pos = app.world.get::<CurrentTransform>(light).unwrap().0.translation;
+        let heavy_pos = app.world.get::<CurrentTransform>(heavy).unwrap().0.translation;
+        assert!(light_pos.abs_diff_eq(Vec3::new(-0.3, 0.0, 0.0), 0.0001), "{light_pos}");
+        assert!(heavy_pos.abs_diff_eq(Vec3::new(0.7, 0.0, 0.0), 0.0001), "{heavy_pos}");
+        assert_eq!(Vec3::ZERO, app.world.get::<Velocity>(light).unwrap().0);
+        assert_eq!(Vec3::ZERO, app.world.get::<Velocity>(heavy).unwrap().0);
+    }
+
+    #[test]
+    fn immovable_doors_dont_get_pushed() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::ZERO));
+        let door = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::default(), HalfExtents::new(1.0, 4.0, 4.0), Shape::Cuboid)
+                    .with_weight(Weight::IMMOVABLE)
+            })
+            .id();
+        let pusher = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(1.5, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_weight(Weight(1000.0))
+            })
+            .id();
+
+        // Heavy box keeps pushing into the door, which stays put
+        for _ in 0..5 {
+            app.world.get_mut::<Velocity>(pusher).unwrap().0 = Vec3::new(-1.0, 0.0, 0.0);
+            app.update();
+        }
+        assert_eq!(Vec3::ZERO, app.world.get::<CurrentTransform>(door).unwrap().0.translation);
+        assert_eq!(Vec3::ZERO, app.world.get::<Velocity>(door).unwrap().0);
+        assert_eq!(1.0, app.world.get::<CurrentTransform>(pusher).unwrap().0.translation.x);
+
+        // Still moves on its own
+        app.world.get_mut::<Velocity>(door).unwrap().0 = Vec3::new(-1.0, 0.0, 0.0);
+        app.update();
+        assert_eq!(-1.0, app.world.get::<CurrentTransform>(door).unwrap().0.translation.x);
+    }
+
+    #[test]
+    fn immovable_weights_split_responses() {
+        let coll = Collision {
+            t: 0.5,
+            position_delta: Vec3::X,
+            velocity_delta: Vec3::X,
+            normal_a: Vec3::X,
+            normal_b: Vec3::NEG_X,
+            point: Vec3::ZERO,
+            depth: 1.0
+        };
+        let position_deltas = |weight_a: f32, weight_b: f32| {
+            match CollisionResponse::weighted(&coll, weight_a, weight_b, Default::default(), Vec3::NEG_X) {
+                (
+                    CollisionResponse::Value { position_delta: a, .. },
+                    CollisionResponse::Value { position_delta: b, .. }
+                ) => (a.x, b.x),
+                _ => unreachable!()
+            }
+        };
+        assert_eq!((-0.75, 0.25), position_deltas(1.0, 3.0));
+        assert_eq!((0.0, 1.0), position_deltas(f32::INFINITY, 3.0));
+        assert_eq!((-1.0, 0.0), position_deltas(3.0, f32::INFINITY));
+        assert_eq!((-0.5, 0.5), position_deltas(f32::INFINITY, f32::INFINITY));
+    }
+
+    #[test]
+    fn static_bodies_dont_get_pushed() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::ZERO));
+        let wall = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::default(), HalfExtents::new(1.0, 10.0, 10.0), Shape::Cuboid)
+            })
+            .insert(StaticBody)
+            .id();
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(1.5, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(-1.0, 0.0, 0.0)))
+            })
+            .id();
+        app.update();
+        assert_eq!(Vec3::ZERO, app.world.get::<CurrentTransform>(wall).unwrap().0.translation);
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), app.world.get::<CurrentTransform>(body).unwrap().0.translation);
+    }
+
+    #[test]
+    fn kinematic_platform_lifts_box() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+        let platform = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_MOVING_TERRAIN, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::default(), HalfExtents::new(4.0, 1.0, 4.0), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(0.0, 0.1, 0.0)))
+            })
+            .insert(Kinematic)
+            .id();
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+
+        // Platform keeps its speed despite the box's weight and gravity, carrying the box up with it
+        for _ in 0..5 {
+            app.update();
+        }
+        let platform_pos = app.world.get::<CurrentTransform>(platform).unwrap().0.translation;
+        let body_pos = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert!(platform_pos.abs_diff_eq(Vec3::new(0.0, 0.5, 0.0), 0.0001), "{platform_pos}");
+        assert!(body_pos.abs_diff_eq(Vec3::new(0.0, 1.5, 0.0), 0.0001), "{body_pos}");
+        assert_eq!(Vec3::new(0.0, 0.1, 0.0), app.world.get::<Velocity>(platform).unwrap().0);
+    }
+
+    #[test]
+    fn platform_carries_riders() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+        app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_MOVING_TERRAIN, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::default(), HalfExtents::new(8.0, 1.0, 4.0), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(0.1, 0.0, 0.0)))
+            })
+            .insert((Kinematic, CarriesRiders));
+        let rider = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(-2.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+        let walker = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(2.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+
+        // Platform moves 1 unit right, taking the rider along, while the walker walks on top of it
+        for _ in 0..10 {
+            app.world.get_mut::<Velocity>(walker).unwrap().0.x = 0.05;
+            app.update();
+        }
+        let rider_pos = app.world.get::<CurrentTransform>(rider).unwrap().0.translation;
+        let walker_pos = app.world.get::<CurrentTransform>(walker).unwrap().0.translation;
+        assert!(rider_pos.abs_diff_eq(Vec3::new(-1.0, 1.0, 0.0), 0.0001), "{rider_pos}");
+        assert!(walker_pos.abs_diff_eq(Vec3::new(3.5, 1.0, 0.0), 0.0001), "{walker_pos}");
+
+        // Walker steps off the edge, and stops being carried
+        for _ in 0..10 {
+            app.world.get_mut::<Velocity>(walker).unwrap().0.x = 0.3;
+            app.update();
+        }
+        let walker_pos = app.world.get::<CurrentTransform>(walker).unwrap().0.translation;
+        assert!(walker_pos.y < 1.0, "{walker_pos}");
+        let x_before = walker_pos.x;
+        app.update();
+        let walker_pos = app.world.get::<CurrentTransform>(walker).unwrap().0.translation;
+        assert!((walker_pos.x - x_before - 0.3).abs() < 0.0001, "{walker_pos}");
+    }
+
+    #[test]
+    fn collision_events() {
+        use bevy_ecs::event::Events;
+
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        let floor = spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(10.0, 1.0, 10.0), Shape::Cuboid).id();
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.15, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+
+        // Falling doesn't collide with anything
+        app.update();
+        assert!(app.world.resource::<Events<CollisionEvent>>().iter_current_update_events().next().is_none());
+
+        // Landing fires an event, even though the floor isn't affected by the body
+        for _ in 0..3 {
+            app.update();
+            let events: Vec<CollisionEvent> = app.world
+                .resource::<Events<CollisionEvent>>()
+                .iter_current_update_events()
+                .copied()
+                .collect();
+            assert_eq!(1, events.len());
+            let event = events[0];
+            let (entities, normal) = if event.entity_a == floor {
+                ((event.entity_a, event.entity_b), event.normal)
+            }
+            else {
+                ((event.entity_b, event.entity_a), -event.normal)
+            };
+            assert_eq!((floor, body), entities);
+            assert_eq!(Vec3::Y, normal);
+        }
+    }
+
+    #[test]
+    fn particles_land_on_floor() {
+        use crate::{ParticleBundle, Restitution};
+
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(10.0, 1.0, 10.0), Shape::Cuboid);
+
+        // Particles on top of each other, one of them bouncy and one on the floor's edge
+        let resting = app.world.spawn(ParticleBundle::new(Vec3::new(0.0, 2.0, 0.0))).id();
+        let stacked = app.world.spawn(ParticleBundle::new(Vec3::new(0.0, 2.05, 0.0))).id();
+        let edge = app.world.spawn(ParticleBundle::new(Vec3::new(5.0, 1.0, 5.0))).id();
+        let bouncy = app.world
+            .spawn(ParticleBundle::new(Vec3::new(1.0, 0.6, 0.0)).with_velocity(Velocity(Vec3::new(0.0, -0.2, 0.0))))
+            .insert(Restitution(1.0))
+            .id();
+        app.update();
+        assert!(app.world.get::<Velocity>(bouncy).unwrap().0.y > 0.0);
+        for _ in 0..20 {
+            app.update();
+        }
+        for particle in [resting, stacked, edge] {
+            assert_eq!(0.5, app.world.get::<CurrentTransform>(particle).unwrap().0.translation.y);
+            assert_eq!(Vec3::ZERO, app.world.get::<HalfExtents>(particle).unwrap().0);
+        }
+    }
     
 }
\ No newline at end of file
@@ -1,27 +1,57 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 
 use bevy_reflect::prelude::*;
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_asset::prelude::*;
-use bevy_math::Vec3;
+use bevy_math::{IVec3, Quat, Vec2, Vec3};
 use bevy_render::mesh::Indices;
 use bevy_render::prelude::*;
 use bevy_render::mesh::shape;
+use bevy_render::primitives::Aabb;
+use bevy_render::view::VisibilitySystems;
 use bevy_pbr::prelude::*;
 use bevy_render::render_resource::PrimitiveTopology;
+use bevy_render::camera::Camera;
+use bevy_tasks::{AsyncComputeTaskPool, Task};
+use bevy_text::prelude::*;
+use bevy_transform::prelude::*;
+use bevy_ui::prelude::*;
+use futures_lite::future;
 use vidya_fixed_timestep::prelude::*;
 use crate::Shape;
 
-use crate::{VoxelChunk, HalfExtents, VoxelData, Voxel, Orientation};
+use crate::{VoxelChunk, HalfExtents, VisualBounds, VoxelData, Voxel, Orientation, Velocity, Error, PhysicsConfig, PlaneAxis, CollisionEvent, Sleeping, Gravity, GravityVolume, SortedGravityVolumes, require_resource};
 
 /// Plugin that adds debug graphics objects in the physics engine.
 pub struct PhysicsDebugPlugin;
+impl PhysicsDebugPlugin {
+    /// Checks that the resources this plugin depends on were added to the app.
+    pub fn check_dependencies(app: &bevy_app::App) -> Result<(), Error> {
+        require_resource::<Assets<Mesh>>(app)?;
+        require_resource::<Assets<StandardMaterial>>(app)
+    }
+}
 impl Plugin for  PhysicsDebugPlugin {
     fn build(&self, app: &mut bevy_app::App) {
+        if let Err(err) = Self::check_dependencies(app) {
+            panic!("PhysicsDebugPlugin: {err}");
+        }
         app
             .init_resource::<DebugMaterials>()
-            .add_fixed_system(add_mesh_to_debug_shapes);
+            .init_resource::<ChunkMeshTasks>()
+            .init_resource::<PhysicsDebugConfig>()
+            .init_resource::<DebugLabels>()
+            .init_resource::<DebugContactPoints>()
+            .init_resource::<DebugGravityField>()
+            .add_phase_system(Phase::Update, add_mesh_to_debug_shapes)
+            .add_system(queue_chunk_meshes)
+            .add_system(finish_chunk_meshes.after(queue_chunk_meshes))
+            .add_system_to_stage(CoreStage::PostUpdate, update_render_aabbs.before(VisibilitySystems::CalculateBounds))
+            .add_system_to_stage(CoreStage::PostUpdate, update_debug_labels)
+            .add_system_to_stage(CoreStage::PostUpdate, draw_contact_points)
+            .add_system_to_stage(CoreStage::PostUpdate, draw_gravity_field);
     }
 }
 
@@ -43,18 +73,48 @@ pub struct DebugMaterials {
     materials: HashMap<RgbaColor, Handle<StandardMaterial>>
 }
 
-/// Scans for voxel chunk entities without a mesh + material, and if found, generates those and inserts them
+/// Resource that stores chunk meshes being generated off the main thread.
+#[derive(Resource, Default)]
+pub struct ChunkMeshTasks {
+    tasks: HashMap<Entity, Task<Mesh>>
+}
+impl ChunkMeshTasks {
+    /// Number of chunk meshes currently being generated.
+    pub fn in_flight(&self) -> usize {
+        self.tasks.len()
+    }
+}
+
+/// Render bounds of a debug entity. Uses its [`VisualBounds`] if present, and its [`HalfExtents`] otherwise.
+pub fn render_aabb(extents: &HalfExtents, visual_bounds: Option<&VisualBounds>) -> Aabb {
+    match visual_bounds {
+        Some(bounds) => Aabb {
+            center: bounds.0.center.into(),
+            half_extents: bounds.0.half_extents.into()
+        },
+        None => Aabb {
+            center: Vec3::ZERO.into(),
+            half_extents: extents.0.into()
+        }
+    }
+}
+
+/// Scans for debug entities without a mesh + material, and if found, inserts them.
+/// Voxel chunks receive an empty mesh that gets swapped out once their real mesh is generated.
+/// Render bounds get inserted up front, since Bevy would otherwise compute them from the empty mesh and never update them.
+/// Boxes are drawn as flat quads when physics is locked to the XY plane.
 fn add_mesh_to_debug_shapes(
     mut commands: Commands,
+    physics_config: Option<Res<PhysicsConfig>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut debug_materials: ResMut<DebugMaterials>,
     mut debug_shapes: Query<
-        (Entity, &Shape, &HalfExtents, &DebugRender),
+        (Entity, &Shape, &HalfExtents, Option<&VisualBounds>, &DebugRender),
         (Without<Handle<Mesh>>, Without<Handle<StandardMaterial>>)
     >
 ) {
-    for (entity, shape, extents, debug) in &mut debug_shapes {
+    for (entity, shape, extents, visual_bounds, debug) in &mut debug_shapes {
 
         // Gets material for insertion
         let color = debug.0;
@@ -63,53 +123,448 @@ fn add_mesh_to_debug_shapes(
             .or_insert_with(|| materials.add(color.into()));
 
         match shape {
-            Shape::VoxelChunk(chunk) => {
+            Shape::VoxelChunk(_) => {
                 commands.entity(entity).insert_bundle(PbrBundle {
-                    mesh: meshes.add(create_mesh_from_chunk(
-                        chunk,
-                        extents.size()
-                    )),
+                    mesh: meshes.add(Mesh::new(PrimitiveTopology::TriangleList)),
                     material: material.clone(),
                     ..Default::default()
-                });
+                }).insert(render_aabb(extents, visual_bounds));
             }
             Shape::Cuboid => {
-                let mesh: Mesh = shape::Box::new(
-                    extents.width(),
-                    extents.height(),
-                    extents.depth()
-                ).into();
+                let flat = physics_config.as_ref().and_then(|config| config.plane_lock) == Some(PlaneAxis::XY);
+                let mesh: Mesh = if flat {
+                    shape::Quad::new(Vec2::new(extents.width(), extents.height())).into()
+                }
+                else {
+                    shape::Box::new(
+                        extents.width(),
+                        extents.height(),
+                        extents.depth()
+                    ).into()
+                };
                 commands.entity(entity).insert_bundle(PbrBundle {
                     mesh: meshes.add(mesh),
                     material: material.clone(),
                     ..Default::default()
-                });
+                }).insert(render_aabb(extents, visual_bounds));
+            },
+            Shape::Sphere => {
+                let radius = extents.0.min_element();
+                commands.entity(entity).insert_bundle(PbrBundle {
+                    mesh: meshes.add(shape::UVSphere { radius, ..Default::default() }.into()),
+                    material: material.clone(),
+                    ..Default::default()
+                }).insert(render_aabb(extents, visual_bounds));
             },
             _ => {}
         };
     }
 }
 
-fn create_mesh_from_chunk(chunk: &VoxelChunk, size: Vec3) -> Mesh {
-    
+/// Spawns a meshing task for every debug chunk that was just given a mesh, or whose voxels changed.
+/// A chunk that changes again while its task is in flight has that task replaced, which cancels it.
+fn queue_chunk_meshes(
+    mut tasks: ResMut<ChunkMeshTasks>,
+    chunks: Query<
+        (Entity, &Shape, &HalfExtents),
+        (With<DebugRender>, With<Handle<Mesh>>, Or<(Changed<Shape>, Added<Handle<Mesh>>)>)
+    >
+) {
+    let pool = AsyncComputeTaskPool::get();
+    for (entity, shape, extents) in &chunks {
+        if let Shape::VoxelChunk(chunk) = shape {
+            let chunk = chunk.clone();
+            let size = extents.size();
+            let task = pool.spawn(async move {
+                MESH_BUFFERS.with(|buffers| create_mesh_from_chunk_into(&chunk, size, &mut buffers.borrow_mut()))
+            });
+            tasks.tasks.insert(entity, task);
+        }
+    }
+}
+
+/// Swaps in the meshes of finished tasks.
+/// Meshes of chunks despawned while their task was in flight are discarded.
+fn finish_chunk_meshes(
+    mut tasks: ResMut<ChunkMeshTasks>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut handles: Query<&mut Handle<Mesh>>
+) {
+    tasks.tasks.retain(|entity, task| {
+        let mesh = match future::block_on(future::poll_once(task)) {
+            Some(mesh) => mesh,
+            None => return true
+        };
+        if let Ok(mut handle) = handles.get_mut(*entity) {
+            *handle = meshes.add(mesh);
+        }
+        false
+    });
+}
+
+/// Keeps render bounds in sync with [`VisualBounds`] and [`HalfExtents`] as they change.
+#[allow(clippy::type_complexity)]
+fn update_render_aabbs(
+    mut aabbs: Query<
+        (&mut Aabb, &HalfExtents, Option<&VisualBounds>),
+        (With<DebugRender>, Or<(Changed<HalfExtents>, Changed<VisualBounds>)>)
+    >
+) {
+    for (mut aabb, extents, visual_bounds) in &mut aabbs {
+        *aabb = render_aabb(extents, visual_bounds);
+    }
+}
+
+/// Runtime configuration of the [`PhysicsDebugPlugin`].
+#[derive(Resource, Debug, Clone)]
+pub struct PhysicsDebugConfig {
+    /// Shows the labels of entities with a [`DebugLabel`]. Hidden labels are kept around, so toggling is cheap.
+    pub labels: bool,
+    /// Only labels entities moving at least this fast. Useful to only show bodies that haven't settled.
+    pub label_min_speed: f32,
+    /// Hides the labels of [`Sleeping`] bodies, so only awake ones get labelled.
+    pub hide_sleeping_labels: bool,
+    /// Font labels are drawn with
+    pub label_font: Handle<Font>,
+    /// Draws a small marker at the contact point of every [`CollisionEvent`].
+    pub contact_points: bool,
+    /// Draws arrows on a grid around the camera, pointing along the gravity a body there would get from [`Gravity`] and [`GravityVolume`]s.
+    /// Arrows get longer and go from blue to red the stronger gravity is, compared to the strongest in view.
+    pub draw_gravity_field: bool,
+    /// Distance between the points the gravity field is drawn at
+    pub gravity_field_spacing: f32,
+    /// Number of points the gravity field is drawn at from the camera along each axis
+    pub gravity_field_radius: u32
+}
+impl Default for PhysicsDebugConfig {
+    fn default() -> Self {
+        Self {
+            labels: false,
+            label_min_speed: 0.0,
+            hide_sleeping_labels: false,
+            label_font: Handle::default(),
+            contact_points: false,
+            draw_gravity_field: false,
+            gravity_field_spacing: 2.0,
+            gravity_field_radius: 4
+        }
+    }
+}
+
+/// Shows a text label above a [`DebugRender`] entity with its id, velocity, sleep state and optional custom text.
+/// Only drawn while [`PhysicsDebugConfig::labels`] is enabled.
+#[derive(Component, Debug, Clone, Default)]
+pub struct DebugLabel(pub Option<String>);
+
+/// UI text entities of labelled debug entities.
+#[derive(Resource, Default)]
+struct DebugLabels {
+    texts: HashMap<Entity, Entity>
+}
+
+/// Spawns, positions and despawns the UI text of [`DebugLabel`]s.
+#[allow(clippy::type_complexity)]
+fn update_debug_labels(
+    mut commands: Commands,
+    config: Res<PhysicsDebugConfig>,
+    mut labels: ResMut<DebugLabels>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    labelled: Query<(Entity, &DebugLabel, &Velocity, &HalfExtents, &GlobalTransform, Option<&Sleeping>), With<DebugRender>>,
+    mut texts: Query<(&mut Text, &mut Style, &mut Visibility)>
+) {
+    // Despawns texts of entities that are gone or no longer labelled
+    labels.texts.retain(|entity, text| {
+        let keep = labelled.contains(*entity);
+        if !keep {
+            commands.entity(*text).despawn();
+        }
+        keep
+    });
+
+    let camera = cameras.iter().find(|(camera, _)| camera.is_active);
+    for (entity, label, vel, extents, transform, sleeping) in &labelled {
+        let speed = vel.0.length();
+        let asleep = sleeping.is_some();
+        let top = transform.translation() + Vec3::new(0.0, extents.0.y, 0.0);
+        let shown = config.labels && speed >= config.label_min_speed && !(asleep && config.hide_sleeping_labels);
+        let position = match camera {
+            Some((camera, cam_transform)) if shown => camera.world_to_viewport(cam_transform, top),
+            _ => None
+        };
+        let state = if asleep { "asleep" } else { "awake" };
+        let value = match &label.0 {
+            Some(name) => format!("{name} ({entity:?})\n|v| {speed:.3}, {state}"),
+            None => format!("{entity:?}\n|v| {speed:.3}, {state}")
+        };
+
+        // Updates existing text
+        if let Some((mut text, mut style, mut visibility)) = labels.texts.get(&entity).and_then(|text| texts.get_mut(*text).ok()) {
+            visibility.is_visible = position.is_some();
+            if let Some(position) = position {
+                style.position.left = Val::Px(position.x);
+                style.position.bottom = Val::Px(position.y);
+                text.sections[0].value = value;
+            }
+            continue;
+        }
+
+        // Spawns text the first time a label is shown
+        if let Some(position) = position {
+            let style = TextStyle {
+                font: config.label_font.clone(),
+                font_size: 14.0,
+                color: Color::WHITE
+            };
+            let text = commands
+                .spawn(TextBundle::from_section(value, style).with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(position.x),
+                        bottom: Val::Px(position.y),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }))
+                .id();
+            labels.texts.insert(entity, text);
+        }
+    }
+}
+
+/// Marker entities drawn at contact points, reused from frame to frame.
+#[derive(Resource, Default)]
+struct DebugContactPoints {
+    markers: Vec<Entity>,
+    mesh: Option<(Handle<Mesh>, Handle<StandardMaterial>)>
+}
+
+/// Moves a marker to the contact point of every [`CollisionEvent`] sent since the last frame, and hides the rest.
+fn draw_contact_points(
+    mut commands: Commands,
+    config: Res<PhysicsDebugConfig>,
+    mut points: ResMut<DebugContactPoints>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut collision_reader: EventReader<CollisionEvent>,
+    mut markers: Query<(&mut Transform, &mut Visibility)>
+) {
+    let DebugContactPoints { markers: marker_entities, mesh } = &mut *points;
+    let mut used = 0;
+    for event in collision_reader.iter() {
+        if !config.contact_points {
+            continue;
+        }
+
+        // Reuses a marker from a previous frame
+        if let Some(marker) = marker_entities.get(used) {
+            if let Ok((mut transform, mut visibility)) = markers.get_mut(*marker) {
+                transform.translation = event.point;
+                visibility.is_visible = true;
+            }
+            used += 1;
+            continue;
+        }
+
+        // Spawns a new marker
+        let (marker_mesh, marker_material) = mesh.get_or_insert_with(|| (
+            meshes.add(shape::UVSphere { radius: 0.05, sectors: 8, stacks: 8 }.into()),
+            materials.add(Color::YELLOW.into())
+        ));
+        let marker = commands
+            .spawn(PbrBundle {
+                mesh: marker_mesh.clone(),
+                material: marker_material.clone(),
+                transform: Transform::from_translation(event.point),
+                ..Default::default()
+            })
+            .id();
+        marker_entities.push(marker);
+        used += 1;
+    }
+    for marker in &marker_entities[used..] {
+        if let Ok((_, mut visibility)) = markers.get_mut(*marker) {
+            visibility.is_visible = false;
+        }
+    }
+}
+
+/// Number of colors gravity field arrows get, from the weakest gravity in view to the strongest
+const GRAVITY_FIELD_COLORS: usize = 8;
+
+/// Arrows of the gravity field, reused from frame to frame.
+#[derive(Resource, Default)]
+struct DebugGravityField {
+    arrows: Vec<Entity>,
+    mesh: Option<Handle<Mesh>>,
+    colors: Vec<Handle<StandardMaterial>>
+}
+
+/// Points an arrow along the gravity at each point of a grid around the active camera, and hides the rest.
+/// Gravity is looked up the same way [`GravityVolume`]s apply it to bodies, so editing volumes updates the field.
+#[allow(clippy::too_many_arguments)]
+fn draw_gravity_field(
+    mut commands: Commands,
+    config: Res<PhysicsDebugConfig>,
+    mut field: ResMut<DebugGravityField>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    gravity: Option<Res<Gravity>>,
+    volumes: Query<(&GravityVolume, &CurrentTransform, &HalfExtents)>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut arrows: Query<(&mut Transform, &mut Visibility, &mut Handle<StandardMaterial>)>,
+    (mut sorted_volumes, mut samples): (Local<SortedGravityVolumes>, Local<Vec<(Vec3, Vec3)>>)
+) {
+    // Samples gravity on the grid cells around the camera
+    samples.clear();
+    let spacing = config.gravity_field_spacing.max(0.01);
+    let camera = cameras.iter().find(|(camera, _)| camera.is_active);
+    if let (true, Some((_, cam_transform))) = (config.draw_gravity_field, camera) {
+        sorted_volumes.update(&volumes);
+        let global = gravity.map(|gravity| gravity.0);
+        let center = (cam_transform.translation() / spacing).round().as_ivec3();
+        let radius = config.gravity_field_radius as i32;
+        for z in -radius..=radius {
+            for y in -radius..=radius {
+                for x in -radius..=radius {
+                    let point = (center + IVec3::new(x, y, z)).as_vec3() * spacing;
+                    match sorted_volumes.gravity_at(point, global) {
+                        Some(gravity) if gravity != Vec3::ZERO => samples.push((point, gravity)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    // Creates the arrow mesh and colors the first time the field is drawn
+    let DebugGravityField { arrows: arrow_entities, mesh, colors } = &mut *field;
+    if samples.is_empty() && arrow_entities.is_empty() {
+        return;
+    }
+    let mesh = mesh.get_or_insert_with(|| meshes.add(arrow_mesh())).clone();
+    if colors.is_empty() {
+        for i in 0..GRAVITY_FIELD_COLORS {
+            let strength = i as f32 / (GRAVITY_FIELD_COLORS - 1) as f32;
+            colors.push(materials.add(StandardMaterial {
+                base_color: Color::rgb(strength, 0.2, 1.0 - strength),
+                unlit: true,
+                ..Default::default()
+            }));
+        }
+    }
+
+    // Reuses arrows from previous frames, spawning more when needed
+    let strongest = samples.iter().map(|(_, gravity)| gravity.length()).fold(0.0, f32::max);
+    for (i, (point, gravity)) in samples.iter().enumerate() {
+        let strength = gravity.length() / strongest;
+        let color = colors[(strength * (GRAVITY_FIELD_COLORS - 1) as f32).round() as usize].clone();
+        let transform = Transform {
+            translation: *point,
+            rotation: Quat::from_rotation_arc(Vec3::Y, gravity.normalize()),
+            scale: Vec3::splat(spacing * 0.8 * strength.max(0.2))
+        };
+        match arrow_entities.get(i).and_then(|arrow| arrows.get_mut(*arrow).ok()) {
+            Some((mut arrow_transform, mut visibility, mut material)) => {
+                *arrow_transform = transform;
+                visibility.is_visible = true;
+                *material = color;
+            },
+            None => {
+                let arrow = commands
+                    .spawn(PbrBundle {
+                        mesh: mesh.clone(),
+                        material: color,
+                        transform,
+                        ..Default::default()
+                    })
+                    .id();
+                arrow_entities.push(arrow);
+            }
+        }
+    }
+    for arrow in arrow_entities.iter().skip(samples.len()) {
+        if let Ok((_, mut visibility, _)) = arrows.get_mut(*arrow) {
+            visibility.is_visible = false;
+        }
+    }
+}
+
+/// Arrow 1 unit long, pointing up from the origin.
+fn arrow_mesh() -> Mesh {
+    const SHAFT_WIDTH: f32 = 0.03;
+    const HEAD_WIDTH: f32 = 0.1;
+    const HEAD_START: f32 = 0.7;
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut add_triangle = |a: Vec3, b: Vec3, c: Vec3| {
+        let normal = (b - a).cross(c - a).normalize();
+        for corner in [a, b, c] {
+            positions.push(corner.to_array());
+            normals.push(normal.to_array());
+        }
+    };
+
+    // Square shaft, with a pyramid head on top
+    let corners = [Vec3::new(1.0, 0.0, 1.0), Vec3::new(1.0, 0.0, -1.0), Vec3::new(-1.0, 0.0, -1.0), Vec3::new(-1.0, 0.0, 1.0)];
+    let head_base = Vec3::Y * HEAD_START;
+    for (a, b) in corners.into_iter().zip(corners.into_iter().cycle().skip(1)) {
+        let (shaft_a, shaft_b) = (a * SHAFT_WIDTH, b * SHAFT_WIDTH);
+        add_triangle(shaft_a, shaft_b, shaft_b + head_base);
+        add_triangle(shaft_a, shaft_b + head_base, shaft_a + head_base);
+        add_triangle(a * HEAD_WIDTH + head_base, b * HEAD_WIDTH + head_base, Vec3::Y);
+    }
+    let base = corners.map(|corner| corner * HEAD_WIDTH + head_base);
+    add_triangle(base[0], base[2], base[1]);
+    add_triangle(base[0], base[3], base[2]);
+
+    let indices = Indices::U32((0..positions.len() as u32).collect());
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.set_indices(Some(indices));
+    mesh
+}
+
+thread_local! {
+    /// Buffers reused by the meshing tasks that run on a thread
+    static MESH_BUFFERS: RefCell<MeshBuffers> = RefCell::new(MeshBuffers::default());
+}
+
+/// Scratch space for meshing chunks, so that meshing many chunks doesn't grow fresh buffers for each one.
+#[derive(Default)]
+pub struct MeshBuffers {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>
+}
+
+/// Creates a mesh for a chunk of the size specified.
+pub fn create_mesh_from_chunk(chunk: &VoxelChunk, size: Vec3) -> Mesh {
+    create_mesh_from_chunk_into(chunk, size, &mut MeshBuffers::default())
+}
+
+/// Creates a mesh for a chunk of the size specified, building vertex data in the buffers specified.
+/// Only the mesh's own attributes get allocated once the buffers are large enough.
+pub fn create_mesh_from_chunk_into(chunk: &VoxelChunk, size: Vec3, buffers: &mut MeshBuffers) -> Mesh {
+
     // Creates vertex data
     let voxel_size = size / chunk.size().as_vec3();
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
+    let MeshBuffers { vertices, indices } = buffers;
+    vertices.clear();
+    indices.clear();
     let half_size = size / 2.0;
     for (voxel_data, coords) in chunk.iter() {
-        let VoxelData { voxel, orientation } = *voxel_data;
+        let VoxelData { voxel, orientation, .. } = *voxel_data;
         let voxel_pos = coords.as_vec3() * voxel_size - half_size;
         match voxel {
             Voxel::Cuboid => write_cuboid(
-                &mut vertices,
-                &mut indices,
+                vertices,
+                indices,
                 voxel_pos,
                 voxel_size
             ),
             Voxel::Slope => write_slope(
-                &mut vertices,
-                &mut indices,
+                vertices,
+                indices,
                 voxel_pos,
                 voxel_size,
                 orientation
@@ -121,7 +576,7 @@ fn create_mesh_from_chunk(chunk: &VoxelChunk, size: Vec3) -> Mesh {
     // Adds vertex data to mesh
     let positions: Vec<[f32; 3]> = vertices.iter().map(|vertex| vertex.pos).collect();
     let normals: Vec<[f32; 3]> = vertices.iter().map(|vertex| vertex.norm).collect();
-    let indices = Indices::U32(indices);
+    let indices = Indices::U32(indices.clone());
     let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
@@ -280,4 +735,28 @@ impl Vertex {
     pub fn new(pos: [f32; 3], norm: [f32; 3]) -> Self {
         Self { pos, norm }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_math::prelude::*;
+    use bevy_math::Vec3A;
+
+    use crate::*;
+    use crate::debug::render_aabb;
+
+    #[test]
+    fn visual_bounds_override_extents() {
+        let extents = HalfExtents::new(1.0, 0.5, 1.0);
+        let aabb = render_aabb(&extents, None);
+        assert_eq!(Vec3A::new(-0.5, -0.25, -0.5), aabb.min());
+        assert_eq!(Vec3A::new(0.5, 0.25, 0.5), aabb.max());
+
+        // Tall grass sprite with a tiny collision box
+        let visual_bounds = VisualBounds::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.5, 1.25, 0.5));
+        let aabb = render_aabb(&extents, Some(&visual_bounds));
+        assert_eq!(Vec3A::new(-0.5, -0.25, -0.5), aabb.min());
+        assert_eq!(Vec3A::new(0.5, 2.25, 0.5), aabb.max());
+    }
+}
@@ -0,0 +1,142 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
+use bevy_hierarchy::{BuildWorldChildren, Children};
+use bevy_math::prelude::*;
+use bevy_transform::prelude::*;
+use vidya_fixed_timestep::CurrentTransform;
+
+use crate::{PhysicsBundle, HalfExtents, Weight, Shape, Velocity, CollisionConfig, GROUP_BASIC, GROUP_STATIC_TERRAIN};
+
+/// Marks a child [`Entity`] that can be detached from its parent and turned into a free physics body.
+/// See [`detach_children_as_bodies`].
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct DetachableBody {
+    pub half_extents: HalfExtents,
+    pub weight: Weight,
+    /// Collision config given to the body once detached
+    pub config: CollisionConfig
+}
+impl DetachableBody {
+    pub fn new(half_extents: HalfExtents, weight: Weight) -> Self {
+        Self {
+            half_extents,
+            weight,
+            config: CollisionConfig::new(GROUP_BASIC, GROUP_STATIC_TERRAIN)
+        }
+    }
+    pub fn with_config(mut self, config: CollisionConfig) -> Self {
+        self.config = config;
+        self
+    }
+}
+
+/// Detaches every child of `parent` that has a [`DetachableBody`] component and turns it into an independent cuboid physics body
+/// at its current world transform. Children keep the rest of their components, so they continue rendering.
+/// `impulse_fn` receives the world transforms of the parent and the child, and returns the impulse applied to the child.
+/// The child's velocity changes by the impulse divided by its weight.
+pub fn detach_children_as_bodies(
+    commands: &mut Commands,
+    parent: Entity,
+    impulse_fn: impl Fn(&Transform, &Transform) -> Vec3 + Send + Sync + 'static
+) {
+    commands.add(DetachChildrenAsBodies { parent, impulse_fn });
+}
+
+struct DetachChildrenAsBodies<F> {
+    parent: Entity,
+    impulse_fn: F
+}
+impl<F> Command for DetachChildrenAsBodies<F>
+where
+    F: Fn(&Transform, &Transform) -> Vec3 + Send + Sync + 'static
+{
+    fn write(self, world: &mut World) {
+        let parent_transform = match world_transform(world, self.parent) {
+            Some(transform) => transform,
+            None => return
+        };
+        let children: Vec<(Entity, DetachableBody)> = match world.get::<Children>(self.parent) {
+            Some(children) => children
+                .iter()
+                .filter_map(|child| world.get::<DetachableBody>(*child).map(|body| (*child, *body)))
+                .collect(),
+            None => return
+        };
+        for (child, body) in children {
+            let transform = world_transform(world, child).unwrap_or_default();
+            let impulse = (self.impulse_fn)(&parent_transform, &transform);
+            world.entity_mut(self.parent).remove_children(&[child]);
+            let mut child = world.entity_mut(child);
+            child.remove::<DetachableBody>();
+            child
+                .insert(transform)
+                .insert(PhysicsBundle {
+                    weight: body.weight,
+                    config: body.config,
+                    ..PhysicsBundle::new(transform, body.half_extents, Shape::Cuboid)
+                        .with_velocity(Velocity(impulse / body.weight.0))
+                });
+        }
+    }
+}
+
+/// World transform of an entity, preferring its [`GlobalTransform`], then its [`CurrentTransform`].
+fn world_transform(world: &World, entity: Entity) -> Option<Transform> {
+    let entity = world.get_entity(entity)?;
+    entity
+        .get::<GlobalTransform>()
+        .map(|global| global.compute_transform())
+        .or_else(|| entity.get::<CurrentTransform>().map(|current| current.0))
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_ecs::system::CommandQueue;
+    use bevy_hierarchy::{BuildWorldChildren, Parent};
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+    use vidya_fixed_timestep::{CurrentTransform, PreviousTransform};
+
+    use crate::*;
+
+    #[test]
+    fn detaches_at_world_pose() {
+        let mut world = World::new();
+        let parent_transform = Transform::from_xyz(10.0, 0.0, 0.0);
+        let child_transform = Transform::from_xyz(0.0, 2.0, 0.0);
+        let parent = world
+            .spawn((parent_transform, GlobalTransform::from(parent_transform)))
+            .id();
+        let mut children = Vec::new();
+        world.entity_mut(parent).with_children(|builder| {
+            let body = DetachableBody::new(HalfExtents::new(1.0, 1.0, 1.0), Weight(2.0));
+            children.push(builder
+                .spawn((child_transform, GlobalTransform::from(parent_transform * child_transform), body))
+                .id()
+            );
+            children.push(builder
+                .spawn((child_transform, GlobalTransform::from(parent_transform * child_transform)))
+                .id()
+            );
+        });
+
+        // Pushes detached children away from the parent
+        let mut queue = CommandQueue::default();
+        detach_children_as_bodies(&mut Commands::new(&mut queue, &world), parent, |parent, child| {
+            (child.translation - parent.translation).normalize() * 4.0
+        });
+        queue.apply(&mut world);
+
+        // Only the detachable child was detached
+        let detached = world.entity(children[0]);
+        let expected = Transform::from_xyz(10.0, 2.0, 0.0);
+        assert!(detached.get::<Parent>().is_none());
+        assert!(detached.get::<DetachableBody>().is_none());
+        assert_eq!(expected, detached.get::<CurrentTransform>().unwrap().0);
+        assert_eq!(expected, detached.get::<PreviousTransform>().unwrap().0);
+        assert_eq!(expected, *detached.get::<Transform>().unwrap());
+        assert_eq!(Vec3::new(0.0, 2.0, 0.0), detached.get::<Velocity>().unwrap().0);
+        assert_eq!(Some(parent), world.get::<Parent>(children[1]).map(|p| p.get()));
+    }
+}
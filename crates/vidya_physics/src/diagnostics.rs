@@ -0,0 +1,57 @@
+use bevy_app::prelude::*;
+use bevy_diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_ecs::prelude::*;
+use vidya_fixed_timestep::{AppExt, Phase};
+
+use crate::{Broadphase, PhysicsSystems};
+
+/// Reports how much work the broadphase did each physics tick through Bevy's [`Diagnostics`], like for `LogDiagnosticsPlugin` to print.
+/// Objects that stay in the same cells get reused instead of rehashed, so scenes that mostly sit still should show few insertions.
+/// Add after [`crate::PhysicsPlugin`].
+pub struct PhysicsDiagnosticsPlugin;
+impl PhysicsDiagnosticsPlugin {
+    /// Objects hashed into cells during the last tick, because they were new or their bounds covered different cells
+    pub const BROADPHASE_INSERTED: DiagnosticId = DiagnosticId::from_u128(270911462310839146531178367612540387729);
+    /// Objects left in the cells they were already in during the last tick
+    pub const BROADPHASE_REUSED: DiagnosticId = DiagnosticId::from_u128(58617233760128514904337622098531826514);
+}
+impl Plugin for PhysicsDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Diagnostics>();
+        let mut diagnostics = app.world.resource_mut::<Diagnostics>();
+        diagnostics.add(Diagnostic::new(Self::BROADPHASE_INSERTED, "broadphase_inserted", 20));
+        diagnostics.add(Diagnostic::new(Self::BROADPHASE_REUSED, "broadphase_reused", 20));
+        app.add_phase_system(Phase::PostUpdate, measure_broadphase.after(PhysicsSystems::Update));
+    }
+}
+
+fn measure_broadphase(mut diagnostics: ResMut<Diagnostics>, broadphase: Res<Broadphase>) {
+    let counts = broadphase.counts();
+    diagnostics.add_measurement(PhysicsDiagnosticsPlugin::BROADPHASE_INSERTED, || counts.inserted as f64);
+    diagnostics.add_measurement(PhysicsDiagnosticsPlugin::BROADPHASE_REUSED, || counts.reused as f64);
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_diagnostic::Diagnostics;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    #[test]
+    fn measures_broadphase() {
+        let mut app = physics_test_app();
+        app.add_plugin(PhysicsDiagnosticsPlugin);
+        for x in 0..5 {
+            spawn_static_floor(&mut app.world, Transform::from_xyz(x as f32 * 10.0, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid);
+        }
+        app.update();
+        app.update();
+
+        // Each of the 4 substeps reuses the cells of every object
+        let diagnostics = app.world.resource::<Diagnostics>();
+        assert_eq!(Some(0.0), diagnostics.get(PhysicsDiagnosticsPlugin::BROADPHASE_INSERTED).unwrap().value());
+        assert_eq!(Some(20.0), diagnostics.get(PhysicsDiagnosticsPlugin::BROADPHASE_REUSED).unwrap().value());
+    }
+}
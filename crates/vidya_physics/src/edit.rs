@@ -0,0 +1,216 @@
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use vidya_fixed_timestep::CurrentTransform;
+
+use crate::{flatten_to_plane, overlaps, overlapped_voxel_bounds, CollisionConfig, HalfExtents, PhysicsConfig, Shape, Velocity, VoxelChunk, AABB};
+
+/// How far a box may sink into voxels before getting pushed out.
+const TOLERANCE: f32 = 0.0001;
+
+/// Directions bodies can get pushed out of voxels in, from most to least preferred.
+const PUSH_DIRECTIONS: [Vec3; 6] = [Vec3::Y, Vec3::NEG_Y, Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z];
+
+/// Event fired when a voxel chunk edit leaves an [`Entity`] so deep inside solid voxels that it can't be pushed out.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CrushedEvent {
+    /// Entity that got crushed
+    pub entity: Entity,
+    /// Chunk that crushed it
+    pub chunk: Entity
+}
+
+/// After a voxel chunk's [`Shape`] changes, pushes the entities it affects out of newly solid voxels along the shortest axis.
+/// Entities that would need to move further than their own size get a [`CrushedEvent`] instead.
+#[allow(clippy::type_complexity)]
+pub(crate) fn resolve_chunk_edits(
+    physics_config: Res<PhysicsConfig>,
+    mut objects: Query<(
+        Entity,
+        &mut CurrentTransform,
+        &mut Velocity,
+        &HalfExtents,
+        &Shape,
+        &CollisionConfig,
+        ChangeTrackers<Shape>
+    )>,
+    mut crushed_writer: EventWriter<CrushedEvent>
+) {
+    let edited: Vec<Entity> = objects
+        .iter()
+        .filter(|(_, _, _, _, shape, _, tracker)| tracker.is_changed() && matches!(shape, Shape::VoxelChunk(_)))
+        .map(|(entity, ..)| entity)
+        .collect();
+    if edited.is_empty() {
+        return;
+    }
+    let bodies: Vec<Entity> = objects
+        .iter()
+        .filter(|(_, _, _, _, shape, _, _)| !matches!(shape, Shape::VoxelChunk(_)))
+        .map(|(entity, ..)| entity)
+        .collect();
+
+    // Bodies never get pushed off of the plane physics is locked to
+    let locked_axis = physics_config.plane_lock.map_or(Vec3::ZERO, |plane| plane.normal());
+
+    for chunk_entity in edited {
+        for body_entity in bodies.iter().copied() {
+            let [chunk_item, body_item] = match objects.get_many_mut([chunk_entity, body_entity]) {
+                Ok(items) => items,
+                Err(_) => continue
+            };
+            let (_, chunk_trans, _, chunk_extents, chunk_shape, chunk_config, _) = chunk_item;
+            let (_, mut trans, mut vel, extents, _, config, _) = body_item;
+            let chunk = match chunk_shape {
+                Shape::VoxelChunk(chunk) => chunk,
+                _ => continue
+            };
+            if !config.affected_by(chunk_config.groups) {
+                continue;
+            }
+
+            // Finds the shortest way out
+            let mut chunk_bounds = AABB::new(chunk_trans.0.translation, chunk_extents.0);
+            let mut bounds = AABB::new(trans.0.translation, extents.0);
+            if let Some(plane) = physics_config.plane_lock {
+                chunk_bounds = flatten_to_plane(chunk_bounds, chunk_shape, plane);
+                bounds = flatten_to_plane(bounds, &Shape::Cuboid, plane);
+            }
+            let shrunk = AABB::new(bounds.center, (bounds.half_extents - TOLERANCE).max(Vec3::ZERO));
+            if !overlaps(chunk_bounds, chunk_shape, shrunk) {
+                continue;
+            }
+            let push = PUSH_DIRECTIONS
+                .iter()
+                .filter(|dir| dir.dot(locked_axis) == 0.0)
+                .filter_map(|dir| {
+                    let max_distance = bounds.size().dot(dir.abs());
+                    push_distance(chunk_bounds, chunk, bounds, *dir, max_distance).map(|distance| (*dir, distance))
+                })
+                .fold(None, |best: Option<(Vec3, f32)>, (dir, distance)| match best {
+                    Some((_, best_distance)) if best_distance <= distance => best,
+                    _ => Some((dir, distance))
+                });
+
+            // Pushes out, cancelling velocity into the voxels
+            match push {
+                Some((dir, distance)) => {
+                    trans.0.translation += dir * distance;
+                    let into = vel.0.dot(dir);
+                    if into < 0.0 {
+                        vel.0 -= dir * into;
+                    }
+                },
+                None => crushed_writer.send(CrushedEvent { entity: body_entity, chunk: chunk_entity })
+            }
+        }
+    }
+}
+
+/// Distance `bounds` needs to move along `dir` to stop overlapping solid voxels.
+/// Returns None if further than `max_distance`.
+fn push_distance(chunk_bounds: AABB, chunk: &VoxelChunk, bounds: AABB, dir: Vec3, max_distance: f32) -> Option<f32> {
+    let mut distance = 0.0;
+    loop {
+        let center = bounds.center + dir * distance;
+        let shrunk = AABB::new(center, (bounds.half_extents - TOLERANCE).max(Vec3::ZERO));
+        let penetration = overlapped_voxel_bounds(chunk_bounds, chunk, shrunk)
+            .filter(|(voxel_bounds, data)| data.is_solid() && voxel_bounds.intersects(&shrunk))
+            .map(|(voxel_bounds, _)| {
+                (voxel_bounds.center - center).dot(dir) + (voxel_bounds.half_extents + bounds.half_extents).dot(dir.abs())
+            })
+            .reduce(f32::max);
+        match penetration {
+            Some(penetration) => distance += penetration,
+            None => return Some(distance)
+        }
+        if distance > max_distance {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_ecs::prelude::*;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+    use vidya_fixed_timestep::CurrentTransform;
+
+    use crate::*;
+
+    /// Spawns a 4x4x4 chunk spanning (0, 0, 0) to (4, 4, 4), with its bottom layer filled.
+    fn spawn_chunk(app: &mut App) -> Entity {
+        let mut chunk = VoxelChunk::new(UVec3::new(4, 4, 4));
+        chunk.set_voxel_plane(0, UVec2::ZERO, UVec2::new(4, 4), PlaneAxis::XZ, VoxelData::new(Voxel::Cuboid));
+        spawn_static_floor(&mut app.world, Transform::from_xyz(2.0, 2.0, 2.0), HalfExtents::new(4.0, 4.0, 4.0), Shape::VoxelChunk(chunk)).id()
+    }
+
+    fn spawn_body(app: &mut App, transform: Transform) -> Entity {
+        app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_STATIC_TERRAIN),
+                ..PhysicsBundle::new(transform, HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert(AntiGravity)
+            .id()
+    }
+
+    fn edit_chunk(app: &mut App, chunk: Entity, edit: impl FnOnce(&mut VoxelChunk)) {
+        if let Some(mut shape) = app.world.get_mut::<Shape>(chunk) {
+            if let Shape::VoxelChunk(chunk) = shape.as_mut() {
+                edit(chunk);
+            }
+        }
+    }
+
+    #[test]
+    fn fill_inside_body() {
+        let mut app = physics_test_app();
+        let chunk = spawn_chunk(&mut app);
+        let body = spawn_body(&mut app, Transform::from_xyz(1.5, 1.5, 1.5));
+        app.update();
+
+        // Fills the voxel the body is in while it moves down
+        edit_chunk(&mut app, chunk, |chunk| { chunk.set_voxel(UVec3::new(1, 1, 1), VoxelData::new(Voxel::Cuboid)); });
+        app.world.get_mut::<Velocity>(body).unwrap().0 = Vec3::new(0.1, -0.2, 0.0);
+        app.update();
+        let trans = app.world.get::<CurrentTransform>(body).unwrap().0;
+        assert!((trans.translation - Vec3::new(1.6, 2.5, 1.5)).length() < 0.001);
+        assert_eq!(Vec3::new(0.1, 0.0, 0.0), app.world.get::<Velocity>(body).unwrap().0);
+        assert!(app.world.resource::<Events<CrushedEvent>>().is_empty());
+    }
+
+    #[test]
+    fn fill_encloses_body() {
+        let mut app = physics_test_app();
+        let chunk = spawn_chunk(&mut app);
+        let body = spawn_body(&mut app, Transform::from_xyz(2.0, 2.0, 2.0));
+        app.update();
+
+        // Fills everything around the body
+        edit_chunk(&mut app, chunk, |chunk| { chunk.fill_sphere(Vec3::splat(2.0), 3.0, VoxelData::new(Voxel::Cuboid)); });
+        app.update();
+        let trans = app.world.get::<CurrentTransform>(body).unwrap().0;
+        assert_eq!(Vec3::splat(2.0), trans.translation);
+        let events = app.world.resource::<Events<CrushedEvent>>();
+        let mut reader = events.get_reader();
+        assert_eq!(vec![&CrushedEvent { entity: body, chunk }], reader.iter(events).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn carve_under_feet() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        let chunk = spawn_chunk(&mut app);
+        let body = spawn_body(&mut app, Transform::from_xyz(1.5, 1.5, 1.5));
+        app.world.entity_mut(body).remove::<AntiGravity>();
+
+        // Carves the floor out from under the body, which then falls without getting pushed back up
+        edit_chunk(&mut app, chunk, |chunk| { chunk.carve_sphere(Vec3::new(1.5, 0.5, 1.5), 1.5); });
+        app.update();
+        let trans = app.world.get::<CurrentTransform>(body).unwrap().0;
+        assert!(trans.translation.y < 1.5);
+        assert!(app.world.resource::<Events<CrushedEvent>>().is_empty());
+    }
+}
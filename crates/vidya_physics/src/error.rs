@@ -0,0 +1,101 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use vidya_fixed_timestep::Phase;
+
+/// Error returned by the fallible (`try_`) variants of physics APIs.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Voxel coordinates were outside of a chunk.
+    #[error("Voxel coordinates {coords} out of bounds of chunk with size {size}")]
+    OutOfBounds { coords: UVec3, size: UVec3 },
+    /// Chunk size had a zero dimension, or too many voxels to index.
+    #[error("Invalid chunk size {size}")]
+    InvalidSize { size: UVec3 },
+    /// Two chunks, or a chunk and a patch, had different sizes.
+    #[error("Chunk size {found} does not match expected size {expected}")]
+    SizeMismatch { expected: UVec3, found: UVec3 },
+    /// Resource required by a plugin was not found in the app.
+    #[error("Missing resource {name}")]
+    MissingResource { name: &'static str },
+    /// Fixed timestep phase required by a plugin was not found in the app.
+    #[error("Missing phase {name}. Add FixedTimestepPlugin first")]
+    MissingPhase { name: String },
+    /// All 32 collision group bits were already taken.
+    #[error("No collision group bits left for group {name}")]
+    TooManyCollisionGroups { name: String }
+}
+
+/// Fails with [`Error::MissingResource`] if the app does not have the resource specified.
+pub fn require_resource<R: Resource>(app: &App) -> Result<(), Error> {
+    match app.world.contains_resource::<R>() {
+        true => Ok(()),
+        false => Err(Error::MissingResource { name: std::any::type_name::<R>() })
+    }
+}
+
+/// Fails with [`Error::MissingPhase`] if the app does not have the fixed timestep phase specified.
+pub fn require_phase(app: &App, phase: Phase) -> Result<(), Error> {
+    match phase.stage().is_added(app) {
+        true => Ok(()),
+        false => Err(Error::MissingPhase { name: format!("{phase:?}") })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+    use bevy_math::prelude::*;
+    use vidya_fixed_timestep::Phase;
+
+    use crate::*;
+
+    #[test]
+    fn out_of_bounds() {
+        let mut chunk = VoxelChunk::new(UVec3::new(4, 2, 4));
+        let data = VoxelData::new(Voxel::Cuboid);
+        let expected = Error::OutOfBounds { coords: UVec3::new(4, 0, 0), size: UVec3::new(4, 2, 4) };
+        assert_eq!(Some(&expected), chunk.try_set_voxel(UVec3::new(4, 0, 0), data).err().as_ref());
+        assert_eq!("Voxel coordinates [4, 0, 0] out of bounds of chunk with size [4, 2, 4]", expected.to_string());
+
+        // Regions fail without writing any voxel
+        let expected = Error::OutOfBounds { coords: UVec3::new(3, 2, 3), size: UVec3::new(4, 2, 4) };
+        assert_eq!(Some(expected), chunk.try_set_voxel_box(UVec3::ZERO, UVec3::new(4, 3, 4), data).err());
+        assert!(chunk.try_set_voxel_plane(2, UVec2::ZERO, UVec2::new(4, 4), PlaneAxis::XZ, data).is_err());
+        assert!(chunk.iter().all(|(data, _)| data.voxel == Voxel::Empty));
+        assert!(chunk.try_set_voxel_box(UVec3::ZERO, UVec3::new(4, 2, 4), data).is_ok());
+    }
+
+    #[test]
+    fn invalid_size() {
+        let size = UVec3::new(4, 0, 4);
+        let expected = Error::InvalidSize { size };
+        assert_eq!(Some(&expected), VoxelChunk::try_new(size).err().as_ref());
+        assert_eq!("Invalid chunk size [4, 0, 4]", expected.to_string());
+        assert!(VoxelChunk::try_new(UVec3::splat(u32::MAX)).is_err());
+    }
+
+    #[test]
+    fn missing_resource() {
+        #[derive(Resource, Default)]
+        struct Needed;
+
+        let mut app = App::new();
+        let error = require_resource::<Needed>(&app).unwrap_err();
+        assert!(matches!(error, Error::MissingResource { name } if name.ends_with("Needed")));
+        assert!(error.to_string().starts_with("Missing resource "));
+        app.init_resource::<Needed>();
+        assert_eq!(Ok(()), require_resource::<Needed>(&app));
+    }
+
+    #[test]
+    fn missing_phase() {
+        let app = App::new();
+        let expected = Error::MissingPhase { name: "PostUpdate".to_owned() };
+        assert_eq!(Err(expected.clone()), PhysicsPlugin::check_dependencies(&app));
+        assert_eq!(Err(expected.clone()), require_phase(&app, Phase::PostUpdate));
+        assert_eq!("Missing phase PostUpdate. Add FixedTimestepPlugin first", expected.to_string());
+    }
+}
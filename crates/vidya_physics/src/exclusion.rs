@@ -0,0 +1,254 @@
+use bevy_ecs::entity::Entities;
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
+use smallvec::SmallVec;
+
+/// Entities a physics object never collides with, no matter how long they overlap.
+/// Kept in sync on both entities by [`exclude_collision`] and [`remove_exclusion`].
+/// Excluding a pair only takes one side though, so a projectile can be spawned with [`CollisionExclusions::new`] to ignore its shooter right away.
+#[derive(Component, Clone, PartialEq, Eq, Debug, Default)]
+pub struct CollisionExclusions(SmallVec<[Entity; 4]>);
+impl CollisionExclusions {
+    pub fn new(entities: impl IntoIterator<Item = Entity>) -> Self {
+        let mut exclusions = Self::default();
+        for entity in entities {
+            if !exclusions.contains(entity) {
+                exclusions.0.push(entity);
+            }
+        }
+        exclusions
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &Entity> {
+        self.0.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.0.contains(&entity)
+    }
+}
+
+/// True if either entity excludes the other.
+pub(crate) fn is_excluded(
+    a: Entity,
+    a_exclusions: Option<&CollisionExclusions>,
+    b: Entity,
+    b_exclusions: Option<&CollisionExclusions>
+) -> bool {
+    a_exclusions.is_some_and(|exclusions| exclusions.contains(b)) ||
+    b_exclusions.is_some_and(|exclusions| exclusions.contains(a))
+}
+
+/// Stops `a` and `b` from colliding with each other until [`remove_exclusion`] is called.
+pub fn exclude_collision(commands: &mut Commands, a: Entity, b: Entity) {
+    commands.add(ExcludeCollision { a, b });
+}
+
+/// Lets `a` and `b` collide with each other again.
+pub fn remove_exclusion(commands: &mut Commands, a: Entity, b: Entity) {
+    commands.add(RemoveExclusion { a, b });
+}
+
+struct ExcludeCollision {
+    a: Entity,
+    b: Entity
+}
+impl Command for ExcludeCollision {
+    fn write(self, world: &mut World) {
+        if world.get_entity(self.a).is_none() || world.get_entity(self.b).is_none() || self.a == self.b {
+            return;
+        }
+        for (entity, other) in [(self.a, self.b), (self.b, self.a)] {
+            let mut entity = world.entity_mut(entity);
+            match entity.get_mut::<CollisionExclusions>() {
+                Some(mut exclusions) => if !exclusions.contains(other) {
+                    exclusions.0.push(other);
+                },
+                None => {
+                    entity.insert(CollisionExclusions(SmallVec::from_slice(&[other])));
+                }
+            }
+        }
+    }
+}
+
+struct RemoveExclusion {
+    a: Entity,
+    b: Entity
+}
+impl Command for RemoveExclusion {
+    fn write(self, world: &mut World) {
+        for (entity, other) in [(self.a, self.b), (self.b, self.a)] {
+            let mut entity = match world.get_entity_mut(entity) {
+                Some(entity) => entity,
+                None => continue
+            };
+            let empty = match entity.get_mut::<CollisionExclusions>() {
+                Some(mut exclusions) => {
+                    exclusions.0.retain(|excluded| *excluded != other);
+                    exclusions.is_empty()
+                },
+                None => continue
+            };
+            if empty {
+                entity.remove::<CollisionExclusions>();
+            }
+        }
+    }
+}
+
+/// Forgets exclusions with despawned entities.
+pub(crate) fn prune_collision_exclusions(
+    mut commands: Commands,
+    entities: &Entities,
+    mut query: Query<(Entity, &mut CollisionExclusions)>
+) {
+    for (entity, mut exclusions) in &mut query {
+        if exclusions.iter().all(|excluded| entities.contains(*excluded)) {
+            continue;
+        }
+        exclusions.0.retain(|excluded| entities.contains(*excluded));
+        if exclusions.is_empty() {
+            commands.entity(entity).remove::<CollisionExclusions>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_ecs::system::CommandQueue;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+    use vidya_fixed_timestep::CurrentTransform;
+
+    use crate::*;
+
+    fn apply(app: &mut App, command: impl FnOnce(&mut Commands)) {
+        let mut queue = CommandQueue::default();
+        command(&mut Commands::new(&mut queue, &app.world));
+        queue.apply(&mut app.world);
+    }
+
+    #[test]
+    fn rider_inside_vehicle() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.05, 0.0)));
+        let floor = spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(20.0, 1.0, 20.0), Shape::Cuboid).id();
+        let vehicle = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 2.0, 0.0), HalfExtents::new(4.0, 4.0, 4.0), Shape::Cuboid)
+            })
+            .insert(AntiGravity)
+            .id();
+        let rider = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 2.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+        apply(&mut app, |commands| exclude_collision(commands, rider, vehicle));
+        assert!(app.world.get::<CollisionExclusions>(vehicle).unwrap().contains(rider));
+
+        // Rider falls through the vehicle and lands on the floor
+        for _ in 0..60 {
+            app.update();
+            assert!(app.world.get::<Contacts>(rider).unwrap().get(vehicle).is_none());
+        }
+        assert!(app.world.get::<Contacts>(rider).unwrap().get(floor).is_some());
+        let trans = app.world.get::<CurrentTransform>(rider).unwrap().0;
+        assert!((trans.translation.y - 0.5).abs() < 0.001);
+
+        // Removing the exclusion cleans up both sides
+        apply(&mut app, |commands| remove_exclusion(commands, vehicle, rider));
+        assert!(app.world.get::<CollisionExclusions>(rider).is_none());
+        assert!(app.world.get::<CollisionExclusions>(vehicle).is_none());
+    }
+
+    #[test]
+    fn projectile_ignores_shooter() {
+        let mut app = physics_test_app();
+        let shooter = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::default(), HalfExtents::new(2.0, 2.0, 2.0), Shape::Cuboid)
+            })
+            .insert(AntiGravity)
+            .id();
+        spawn_static_floor(&mut app.world, Transform::from_xyz(5.0, 0.0, 0.0), HalfExtents::new(1.0, 4.0, 4.0), Shape::Cuboid).insert(StaticBody);
+
+        // Projectile starts inside of its shooter, which only the projectile knows to ignore
+        let projectile = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::default(), HalfExtents::new(0.5, 0.5, 0.5), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(0.5, 0.0, 0.0)))
+            })
+            .insert((AntiGravity, CollisionExclusions::new([shooter, shooter])))
+            .id();
+        assert_eq!(1, app.world.get::<CollisionExclusions>(projectile).unwrap().len());
+        for _ in 0..20 {
+            app.update();
+        }
+        assert_eq!(Vec3::ZERO, app.world.get::<CurrentTransform>(shooter).unwrap().0.translation);
+        assert!(app.world.get::<Contacts>(projectile).unwrap().get(shooter).is_none());
+
+        // Still hits the wall
+        let trans = app.world.get::<CurrentTransform>(projectile).unwrap().0;
+        assert!((trans.translation.x - 4.25).abs() < 0.001);
+        assert_eq!(0.0, app.world.get::<Velocity>(projectile).unwrap().0.x);
+
+        // Forgets the shooter once it's gone
+        app.world.despawn(shooter);
+        app.update();
+        assert!(app.world.get::<CollisionExclusions>(projectile).is_none());
+    }
+
+    #[test]
+    fn sliding_body_ignores_excluded_wall() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+        let floor = spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(40.0, 1.0, 40.0), Shape::Cuboid).id();
+        let wall = spawn_static_floor(&mut app.world, Transform::from_xyz(3.0, 2.5, 0.0), HalfExtents::new(2.0, 4.0, 20.0), Shape::Cuboid).id();
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert((MoveAndSlide, CharacterController::default(), CollisionExclusions::new([wall])))
+            .id();
+
+        // Walks through the wall as if it wasn't there, still standing on the floor
+        for _ in 0..60 {
+            app.world.get_mut::<Velocity>(body).unwrap().0.x = 0.1;
+            app.update();
+            assert!(app.world.get::<Contacts>(body).unwrap().get(wall).is_none());
+        }
+        let trans = app.world.get::<CurrentTransform>(body).unwrap().0;
+        assert!(trans.translation.abs_diff_eq(Vec3::new(6.0, 1.0, 0.0), 0.001), "{}", trans.translation);
+        assert!(app.world.get::<Contacts>(body).unwrap().get(floor).is_some());
+    }
+
+    #[test]
+    fn despawn_cleans_up() {
+        let mut app = physics_test_app();
+        let a = app.world.spawn(PhysicsBundle::default()).id();
+        let b = app.world.spawn(PhysicsBundle::default()).id();
+        let c = app.world.spawn(PhysicsBundle::default()).id();
+        apply(&mut app, |commands| {
+            exclude_collision(commands, a, b);
+            exclude_collision(commands, a, c);
+        });
+        app.world.despawn(b);
+        app.update();
+        assert_eq!(vec![&c], app.world.get::<CollisionExclusions>(a).unwrap().iter().collect::<Vec<_>>());
+        app.world.despawn(c);
+        app.update();
+        assert!(app.world.get::<CollisionExclusions>(a).is_none());
+    }
+}
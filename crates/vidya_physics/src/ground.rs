@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_reflect::prelude::*;
+
+use crate::SurfaceTag;
+
+/// How a surface changes the handling of [`crate::CharacterController`]s moving on it.
+/// Every scale defaults to 1, which leaves handling unchanged.
+#[derive(Debug, Copy, Clone, PartialEq, Reflect)]
+pub struct GroundMaterial {
+    /// Scales how quickly characters speed up
+    pub acceleration_scale: f32,
+    /// Scales how quickly characters slow down when not moving
+    pub friction_scale: f32,
+    /// Scales how fast characters can go
+    pub max_speed_scale: f32
+}
+
+impl GroundMaterial {
+    pub fn new(acceleration_scale: f32, friction_scale: f32, max_speed_scale: f32) -> Self {
+        Self { acceleration_scale, friction_scale, max_speed_scale }
+    }
+
+    /// Material a fraction `t` of the way from this one to `other`.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            acceleration_scale: self.acceleration_scale + (other.acceleration_scale - self.acceleration_scale) * t,
+            friction_scale: self.friction_scale + (other.friction_scale - self.friction_scale) * t,
+            max_speed_scale: self.max_speed_scale + (other.max_speed_scale - self.max_speed_scale) * t
+        }
+    }
+}
+
+/// Surface a physics object is standing on.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Grounded {
+    /// Normal of the surface stood on
+    pub normal: Vec3,
+    /// Entity stood on
+    pub entity: Entity
+}
+
+/// Component that tracks what a physics object is standing on, updated every tick by the physics update.
+/// Objects are grounded when they collide with a surface facing against [`crate::Gravity`], according to [`crate::PhysicsConfig::ground_threshold`].
+#[derive(Component, Debug, Copy, Clone, PartialEq, Default)]
+pub struct GroundState(Option<Grounded>);
+impl GroundState {
+    pub fn is_grounded(&self) -> bool {
+        self.0.is_some()
+    }
+    /// Surface stood on during the last tick, if any.
+    pub fn ground(&self) -> Option<Grounded> {
+        self.0
+    }
+    pub(crate) fn set(&mut self, ground: Option<Grounded>) {
+        self.0 = ground;
+    }
+}
+
+impl Default for GroundMaterial {
+    fn default() -> Self {
+        Self::new(1.0, 1.0, 1.0)
+    }
+}
+
+/// Resource that maps [`SurfaceTag`]s to [`GroundMaterial`]s.
+/// Ground without a tag, or with a tag missing from the table, uses the default material.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct GroundMaterialTable {
+    materials: HashMap<SurfaceTag, GroundMaterial>,
+    /// Material of untagged ground, and of tags missing from the table
+    pub default: GroundMaterial,
+    /// Material used while airborne, which controls air control
+    pub air: GroundMaterial
+}
+
+impl GroundMaterialTable {
+    /// Sets the material of a tag and returns self.
+    pub fn with_material(mut self, tag: SurfaceTag, material: GroundMaterial) -> Self {
+        self.insert(tag, material);
+        self
+    }
+    pub fn with_default(mut self, material: GroundMaterial) -> Self {
+        self.default = material;
+        self
+    }
+    pub fn with_air(mut self, material: GroundMaterial) -> Self {
+        self.air = material;
+        self
+    }
+    /// Sets the material of a tag, replacing its previous material if any.
+    pub fn insert(&mut self, tag: SurfaceTag, material: GroundMaterial) {
+        self.materials.insert(tag, material);
+    }
+    /// Material of ground with the tag specified.
+    pub fn ground(&self, tag: Option<SurfaceTag>) -> GroundMaterial {
+        tag
+            .and_then(|tag| self.materials.get(&tag).copied())
+            .unwrap_or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    /// Drops a box onto the floor specified, then removes the floor.
+    fn stand_on(floor: Shape) {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        let floor = spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(4.0, 1.0, 4.0), floor).id();
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.05, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert(GroundState::default())
+            .id();
+
+        // Stays grounded while resting
+        app.update();
+        for _ in 0..5 {
+            app.update();
+            let ground = app.world.get::<GroundState>(body).unwrap().ground();
+            assert_eq!(Some(Grounded { normal: Vec3::Y, entity: floor }), ground);
+        }
+
+        // Loses it once the floor is gone
+        app.world.despawn(floor);
+        app.update();
+        assert!(!app.world.get::<GroundState>(body).unwrap().is_grounded());
+    }
+
+    #[test]
+    fn grounded_on_cuboid() {
+        stand_on(Shape::Cuboid);
+    }
+
+    #[test]
+    fn grounded_on_chunk() {
+        let mut chunk = VoxelChunk::new(UVec3::new(4, 1, 4));
+        chunk.set_voxel_box(UVec3::ZERO, UVec3::new(4, 1, 4), VoxelData::new(Voxel::Cuboid));
+        stand_on(Shape::VoxelChunk(chunk));
+    }
+
+    #[test]
+    fn walls_are_not_ground() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::ZERO));
+        spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(1.0, 10.0, 10.0), Shape::Cuboid);
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(1.5, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(-1.0, 0.0, 0.0)))
+            })
+            .insert(GroundState::default())
+            .id();
+        app.update();
+        assert!(!app.world.get::<GroundState>(body).unwrap().is_grounded());
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+
+use crate::{CollisionGroups, Error, GROUP_BASIC, GROUP_MOVING_TERRAIN, GROUP_PARTICLES, GROUP_STATIC_TERRAIN};
+
+/// Resource that hands out [`CollisionGroups`] bits by name, so plugins can agree on groups without picking bits by hand.
+/// The built-in groups come pre-registered as `"particles"`, `"static_terrain"`, `"moving_terrain"` and `"basic"`.
+#[derive(Resource, Debug, Clone)]
+pub struct CollisionGroupRegistry {
+    groups: HashMap<String, CollisionGroups>,
+    used: CollisionGroups
+}
+impl CollisionGroupRegistry {
+    /// Registry without any groups, not even the built-in ones.
+    pub fn empty() -> Self {
+        Self {
+            groups: HashMap::new(),
+            used: 0
+        }
+    }
+
+    /// Allocates the next free bit for the group named, or returns the bit it already has.
+    /// Fails with [`Error::TooManyCollisionGroups`] once all 32 bits are taken.
+    pub fn register(&mut self, name: &str) -> Result<CollisionGroups, Error> {
+        if let Some(group) = self.groups.get(name) {
+            return Ok(*group);
+        }
+        if self.used == u32::MAX {
+            return Err(Error::TooManyCollisionGroups { name: name.to_owned() });
+        }
+        let group = 1 << (!self.used).trailing_zeros();
+        self.used |= group;
+        self.groups.insert(name.to_owned(), group);
+        Ok(group)
+    }
+
+    /// Group registered under the name specified.
+    pub fn get(&self, name: &str) -> Option<CollisionGroups> {
+        self.groups.get(name).copied()
+    }
+
+    /// Union of the groups named. Names that weren't registered are skipped.
+    pub fn get_all<'a>(&self, names: impl IntoIterator<Item = &'a str>) -> CollisionGroups {
+        names.into_iter().filter_map(|name| self.get(name)).fold(0, |groups, group| groups | group)
+    }
+
+    /// Name of a single group.
+    pub fn name(&self, group: CollisionGroups) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|(_, registered)| **registered == group)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Reserves a group under a fixed bit, so that it matches one of the hard-coded constants.
+    fn with_fixed(mut self, name: &str, group: CollisionGroups) -> Self {
+        self.used |= group;
+        self.groups.insert(name.to_owned(), group);
+        self
+    }
+}
+impl Default for CollisionGroupRegistry {
+    fn default() -> Self {
+        Self::empty()
+            .with_fixed("particles", GROUP_PARTICLES)
+            .with_fixed("static_terrain", GROUP_STATIC_TERRAIN)
+            .with_fixed("moving_terrain", GROUP_MOVING_TERRAIN)
+            .with_fixed("basic", GROUP_BASIC)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::*;
+
+    #[test]
+    fn groups_are_allocated_by_name() {
+        let mut registry = CollisionGroupRegistry::default();
+        assert_eq!(Some(GROUP_BASIC), registry.get("basic"));
+
+        // New groups take the bits after the built-in ones, and registering twice is a lookup
+        let enemies = registry.register("enemies").unwrap();
+        assert_eq!(0b1_0000, enemies);
+        assert_eq!(Ok(enemies), registry.register("enemies"));
+        assert_eq!(Some("enemies"), registry.name(enemies));
+        assert_eq!(enemies | GROUP_BASIC, registry.get_all(["enemies", "basic", "missing"]));
+
+        let config = CollisionConfig::new(GROUP_NONE, GROUP_NONE)
+            .in_group(enemies)
+            .also_affected_by(registry.get_all(["basic", "static_terrain"]));
+        assert_eq!(CollisionConfig::new(enemies, GROUP_BASIC | GROUP_STATIC_TERRAIN), config);
+
+        // Runs out of bits
+        for i in 0..27 {
+            registry.register(&format!("group {i}")).unwrap();
+        }
+        assert_eq!(Some(0x8000_0000), registry.get("group 26"));
+        assert_eq!(
+            Err(Error::TooManyCollisionGroups { name: "one too many".to_owned() }),
+            registry.register("one too many")
+        );
+    }
+}
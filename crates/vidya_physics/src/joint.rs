@@ -0,0 +1,145 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::entity::Entities;
+use bevy_math::prelude::*;
+
+/// Keeps an entity within a maximum distance of another, like a crate hanging off a winch by a rope.
+/// The pair only gets pulled together once stretched past `max_length`, with the lighter body moving more.
+/// [`StaticBody`](crate::StaticBody), [`Kinematic`](crate::Kinematic) and [`Weight::IMMOVABLE`](crate::Weight::IMMOVABLE) ends don't move. Removed if the other entity despawns.
+/// Solved at the start of every substep, so the collisions that follow push jointed bodies back out of anything they got pulled into.
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct DistanceJoint {
+    pub other: Entity,
+    pub max_length: f32,
+    /// Fraction of the stretch past `max_length` corrected per tick, between 0 and 1
+    pub stiffness: f32
+}
+impl DistanceJoint {
+    pub fn new(other: Entity, max_length: f32) -> Self {
+        Self {
+            other,
+            max_length,
+            stiffness: 1.0
+        }
+    }
+    pub fn with_stiffness(mut self, stiffness: f32) -> Self {
+        self.stiffness = stiffness;
+        self
+    }
+
+    /// How far each end has to move to get back within `max_length`, given the inverse of their weights.
+    /// The stiffness gets spread across `substeps`, so that the same fraction of the stretch is corrected per tick.
+    pub(crate) fn corrections(&self, a: Vec3, b: Vec3, a_inv: f32, b_inv: f32, substeps: usize) -> Option<(Vec3, Vec3)> {
+        let stiffness = 1.0 - (1.0 - self.stiffness.clamp(0.0, 1.0)).powf(1.0 / substeps.max(1) as f32);
+        distance_corrections(a, b, a_inv, b_inv, self.max_length, stiffness)
+    }
+}
+
+/// How far two points have to move to get back within `max_length` of each other, given the inverse of their weights.
+/// Only the `stiffness` fraction of the stretch gets corrected. Returns None if they're within range or both immovable.
+pub(crate) fn distance_corrections(a: Vec3, b: Vec3, a_inv: f32, b_inv: f32, max_length: f32, stiffness: f32) -> Option<(Vec3, Vec3)> {
+    let total_inv = a_inv + b_inv;
+    let diff = b - a;
+    let length = diff.length();
+    if total_inv == 0.0 || length <= max_length {
+        return None;
+    }
+    let correction = diff * ((length - max_length) / length * stiffness / total_inv);
+    Some((correction * a_inv, -correction * b_inv))
+}
+
+/// Removes [`DistanceJoint`]s attached to despawned entities.
+pub(crate) fn prune_distance_joints(
+    mut commands: Commands,
+    entities: &Entities,
+    joints: Query<(Entity, &DistanceJoint)>
+) {
+    for (entity, joint) in &joints {
+        if !entities.contains(joint.other) {
+            bevy_log::warn!("Removing DistanceJoint of {entity:?}, since {:?} is gone", joint.other);
+            commands.entity(entity).remove::<DistanceJoint>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    fn pendulum_app() -> (App, Entity, Entity) {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.05, 0.0)));
+        let winch = app.world
+            .spawn(PhysicsBundle::new(Transform::from_xyz(0.0, 10.0, 0.0), HalfExtents::new(0.5, 0.5, 0.5), Shape::Cuboid).with_weight(Weight::IMMOVABLE))
+            .insert(AntiGravity)
+            .id();
+        let load = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_NONE),
+                ..PhysicsBundle::new(Transform::from_xyz(3.0, 10.0, 0.0), HalfExtents::new(0.5, 0.5, 0.5), Shape::Cuboid)
+            })
+            .insert(DistanceJoint::new(winch, 3.0))
+            .id();
+        (app, winch, load)
+    }
+
+    #[test]
+    fn pendulum_keeps_its_length() {
+        let (mut app, winch, load) = pendulum_app();
+        let mut min_x = f32::MAX;
+        for _ in 0..500 {
+            app.update();
+            let position = app.world.get::<CurrentTransform>(load).unwrap().0.translation;
+            let length = position.distance(Vec3::new(0.0, 10.0, 0.0));
+            assert!(length <= 3.0 * 1.01, "{length}");
+            min_x = min_x.min(position.x);
+        }
+
+        // Swings over to the other side, and the winch never moves
+        assert!(min_x < -2.0, "{min_x}");
+        assert_eq!(Vec3::new(0.0, 10.0, 0.0), app.world.get::<CurrentTransform>(winch).unwrap().0.translation);
+    }
+
+    #[test]
+    fn joint_doesnt_pull_bodies_into_walls() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::ZERO));
+        spawn_static_floor(&mut app.world, Transform::from_xyz(-1.0, 0.0, 0.0), HalfExtents::new(2.0, 10.0, 10.0), Shape::Cuboid).insert(StaticBody);
+        let anchor = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_NONE, GROUP_NONE),
+                ..PhysicsBundle::new(Transform::from_xyz(-4.0, 0.0, 0.0), HalfExtents::new(0.5, 0.5, 0.5), Shape::Cuboid)
+                    .with_weight(Weight::IMMOVABLE)
+            })
+            .id();
+        let load = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.5, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert(DistanceJoint::new(anchor, 3.0))
+            .id();
+
+        // Rests against the wall, even though the joint keeps pulling it in
+        for _ in 0..100 {
+            app.update();
+            let position = app.world.get::<CurrentTransform>(load).unwrap().0.translation;
+            assert!(position.x >= 0.5 - 0.001, "{position}");
+        }
+        let position = app.world.get::<CurrentTransform>(load).unwrap().0.translation;
+        assert!(position.x < 0.5 + 0.001, "{position}");
+    }
+
+    #[test]
+    fn joint_removed_when_other_despawns() {
+        let (mut app, winch, load) = pendulum_app();
+        app.update();
+        app.world.despawn(winch);
+        app.update();
+        assert!(app.world.get::<DistanceJoint>(load).is_none());
+    }
+}
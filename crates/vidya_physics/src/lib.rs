@@ -1,48 +1,266 @@
+use std::collections::{HashMap, HashSet};
 use std::ops::{Neg, Sub, Add};
+use std::time::Duration;
 
-use vidya_fixed_timestep::FixedTimestepStages;
+use vidya_fixed_timestep::{AppExt, FixedClock, Phase};
 pub use vidya_fixed_timestep::{CurrentTransform, PreviousTransform};
 use bevy_transform::prelude::*;
 use bevy_app::prelude::*;
 use bevy_math::prelude::*;
 use bevy_reflect::prelude::*;
 use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::ShouldRun;
 
 
 mod voxel;
 mod collision;
+mod streaming;
+mod surface;
+mod resize;
+mod batch;
+mod detach;
+mod error;
+mod edit;
+mod exclusion;
+mod material;
+mod patch;
+mod undo;
+mod character;
+mod reaction;
+mod plane;
+mod ground;
+mod touching;
+mod sensor;
+mod broadphase;
+mod rope;
+mod slide;
+mod raycast;
+mod sleep;
+mod groups;
+mod snapshot;
+mod world_bounds;
+mod joint;
+mod diagnostics;
 pub use voxel::*;
 pub use collision::*;
+pub use streaming::*;
+pub use surface::*;
+pub use resize::*;
+pub use batch::*;
+pub use detach::*;
+pub use error::*;
+pub use edit::*;
+pub use exclusion::*;
+pub use material::*;
+pub use patch::*;
+pub use undo::*;
+pub use character::*;
+pub use reaction::*;
+pub(crate) use plane::*;
+pub use ground::*;
+pub use touching::*;
+pub use sensor::*;
+pub use rope::*;
+pub use slide::*;
+pub use raycast::*;
+pub use sleep::*;
+pub use groups::*;
+pub use snapshot::*;
+pub use world_bounds::*;
+pub use joint::*;
+pub use diagnostics::*;
+use broadphase::Broadphase;
 
 #[cfg(feature = "debug")]
 pub mod debug;
+pub mod predict;
+pub mod nav;
+#[cfg(all(test, feature = "alloc-counter"))]
+mod allocations;
 
 /// Adds a simple platformer voxel-based physics engine.
 pub struct PhysicsPlugin;
+impl PhysicsPlugin {
+    /// Checks that the phases this plugin depends on were added to the app.
+    pub fn check_dependencies(app: &App) -> Result<(), Error> {
+        require_phase(app, Phase::PostUpdate)
+    }
+}
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
+        if let Err(err) = Self::check_dependencies(app) {
+            panic!("PhysicsPlugin: {err}");
+        }
+
         // Runs physics systems before interpolation
         app
             .register_type::<Velocity>()
             .register_type::<Weight>()
             .register_type::<HalfExtents>()
             .register_type::<Friction>()
+            .register_type::<AirFriction>()
+            .register_type::<ExternalForce>()
+            .register_type::<ExternalImpulse>()
+            .register_type::<VelocityLimit>()
             .register_type::<PhysicsInterpolate>()
             .register_type::<CollisionResponse>()
             .register_type::<AntiGravity>()
+            .register_type::<GravityScale>()
+            .register_type::<LocalGravity>()
+            .register_type::<GravityVolume>()
+            .register_type::<ForceVolume>()
+            .register_type::<FluidVolume>()
+            .register_type::<StaticBody>()
+            .register_type::<Kinematic>()
+            .register_type::<CarriesRiders>()
+            .register_type::<OneWay>()
+            .register_type::<MoveAndSlide>()
+            .register_type::<StepHeight>()
+            .register_type::<SurfaceTag>()
+            .register_type::<ResizeBounds>()
+            .register_type::<Restitution>()
+            .register_type::<ContactFriction>()
+            .register_type::<RestitutionCombine>()
+            .register_type::<FrictionCombine>()
+            .register_type::<ResponseMode>()
+            .register_type::<Sleeping>()
+            .register_type::<CharacterController>()
+            .register_type::<GroundMaterial>()
+            .register_type::<CollisionConfig>()
+            .register_type::<Gravity>()
+            .register_type::<PhysicsConfig>()
+            .register_type::<CombineRule>()
+            .register_type::<PlaneAxis>()
+            .register_type::<Option<PlaneAxis>>()
+            .register_type::<AxisPriority>()
             .init_resource::<PhysicsConfig>()
-            .add_system_set_to_stage(FixedTimestepStages::PostFixedUpdate, SystemSet::new()
+            .init_resource::<PhysicsTick>()
+            .init_resource::<SurfaceTags>()
+            .init_resource::<CollisionGroupRegistry>()
+            .init_resource::<TouchingPairs>()
+            .init_resource::<Broadphase>()
+            .add_event::<PhysicsStepEvent>()
+            .add_event::<ResizeBlocked>()
+            .add_event::<CrushedEvent>()
+            .add_event::<CollisionEvent>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionEnded>()
+            .add_event::<SensorEvent>()
+            .add_event::<NamedCollisionEvent>()
+            .add_event::<OutOfBoundsEvent>()
+            .add_phase_system_set(Phase::PostUpdate, SystemSet::new()
+                .with_run_criteria(physics_running)
+                .with_system(begin_step
+                    .label(PhysicsSystems::BeginStep)
+                )
+                .with_system(resolve_chunk_edits
+                    .label(PhysicsSystems::ResolveChunkEdits)
+                    .after(PhysicsSystems::BeginStep)
+                )
+                .with_system(prune_collision_exclusions
+                    .after(PhysicsSystems::BeginStep)
+                    .before(PhysicsSystems::Update)
+                )
+                .with_system(prune_distance_joints
+                    .label(PhysicsSystems::PruneJoints)
+                    .after(PhysicsSystems::BeginStep)
+                    .before(PhysicsSystems::Update)
+                )
+                .with_system(validate_weights
+                    .label(PhysicsSystems::ValidateWeights)
+                    .after(PhysicsSystems::BeginStep)
+                    .before(PhysicsSystems::Update)
+                )
+                .with_system(apply_jumps
+                    .label(PhysicsSystems::ApplyJumps)
+                    .after(PhysicsSystems::ResolveChunkEdits)
+                )
+                .with_system(apply_movement
+                    .label(PhysicsSystems::ApplyMovement)
+                    .after(PhysicsSystems::ApplyJumps)
+                )
                 .with_system(apply_gravity
                     .label(PhysicsSystems::ApplyGravity)
+                    .after(PhysicsSystems::ApplyMovement)
+                )
+                .with_system(apply_forces
+                    .label(PhysicsSystems::ApplyForces)
+                    .after(PhysicsSystems::ApplyGravity)
+                )
+                .with_system(apply_force_volumes
+                    .label(PhysicsSystems::ApplyForceVolumes)
+                    .after(PhysicsSystems::ApplyForces)
+                )
+                .with_system(apply_fluid_volumes
+                    .label(PhysicsSystems::ApplyFluidVolumes)
+                    .after(PhysicsSystems::ApplyForceVolumes)
                 )
                 .with_system(apply_friction
                     .label(PhysicsSystems::ApplyFriction)
-                    .after(PhysicsSystems::ApplyGravity)
+                    .after(PhysicsSystems::ApplyFluidVolumes)
+                )
+                .with_system(snap_resting_velocities
+                    .label(PhysicsSystems::SnapRestingVelocities)
+                    .after(PhysicsSystems::ApplyFriction)
+                    .before(PhysicsSystems::LimitVelocity)
+                )
+                .with_system(limit_velocities
+                    .label(PhysicsSystems::LimitVelocity)
+                    .after(PhysicsSystems::ApplyFriction)
+                    .before(PhysicsSystems::LockToPlane)
+                )
+                .with_system(lock_velocities_to_plane
+                    .label(PhysicsSystems::LockToPlane)
+                    .after(PhysicsSystems::ApplyFriction)
+                    .before(PhysicsSystems::Update)
+                )
+                .with_system(record_incoming_velocities
+                    .after(PhysicsSystems::LockToPlane)
+                    .before(PhysicsSystems::Update)
+                )
+                .with_system(slide_bodies
+                    .label(PhysicsSystems::SlideBodies)
+                    .after(PhysicsSystems::LockToPlane)
+                    .before(PhysicsSystems::Update)
                 )
                 .with_system(update
                     .label(PhysicsSystems::Update)
                     .after(PhysicsSystems::ApplyFriction)
                 )
+                .with_system(enforce_world_bounds
+                    .label(PhysicsSystems::EnforceWorldBounds)
+                    .after(PhysicsSystems::Update)
+                )
+                .with_system(track_touching_pairs
+                    .label(PhysicsSystems::TrackTouchingPairs)
+                    .after(PhysicsSystems::Update)
+                )
+                .with_system(run_collision_reactions
+                    .label(PhysicsSystems::RunCollisionReactions)
+                    .after(PhysicsSystems::Update)
+                )
+                .with_system(update_grounded
+                    .label(PhysicsSystems::UpdateGrounded)
+                    .after(PhysicsSystems::Update)
+                )
+                .with_system(solve_ropes
+                    .label(PhysicsSystems::SolveRopes)
+                    .after(PhysicsSystems::Update)
+                    .before(PhysicsSystems::ResizeBounds)
+                )
+                .with_system(update_sleeping
+                    .label(PhysicsSystems::UpdateSleeping)
+                    .after(PhysicsSystems::RunCollisionReactions)
+                    .after(PhysicsSystems::SolveRopes)
+                )
+                .with_system(resize_bounds
+                    .label(PhysicsSystems::ResizeBounds)
+                    .after(PhysicsSystems::Update)
+                )
+                .with_system(detect_voxel_overlaps
+                    .label(PhysicsSystems::DetectVoxelOverlaps)
+                    .after(PhysicsSystems::ResizeBounds)
+                )
             );
     }
 }
@@ -50,12 +268,54 @@ impl Plugin for PhysicsPlugin {
 //////////////////////////////////////////////// Labels ////////////////////////////////////////////////
 #[derive(Debug, Copy, Clone, Eq, PartialEq, SystemLabel)]
 pub enum PhysicsSystems {
+    /// Advances the [`PhysicsTick`] and fires a [`PhysicsStepEvent`]
+    BeginStep,
+    /// Pushes entities out of voxels filled in by chunk edits
+    ResolveChunkEdits,
+    /// Performs jumps buffered in [`CharacterController`]s
+    ApplyJumps,
+    /// Moves [`CharacterController`]s that were given movement input
+    ApplyMovement,
     /// Applies friction to velocity
     ApplyFriction,
     /// Applies gravity to velocity
     ApplyGravity,
+    /// Applies [`ExternalForce`]s and [`ExternalImpulse`]s to velocity
+    ApplyForces,
+    /// Applies the acceleration of [`ForceVolume`]s to velocity
+    ApplyForceVolumes,
+    /// Applies the buoyancy and drag of [`FluidVolume`]s to velocity
+    ApplyFluidVolumes,
+    /// Snaps velocities slower than [`PhysicsConfig::rest_velocity_epsilon`] to zero
+    SnapRestingVelocities,
+    /// Clamps velocity to [`VelocityLimit`]s and [`PhysicsConfig::max_speed`], and zeroes non-finite velocity
+    LimitVelocity,
+    /// Zeroes velocity perpendicular to [`PhysicsConfig::plane_lock`]
+    LockToPlane,
+    /// Moves [`MoveAndSlide`] bodies
+    SlideBodies,
+    /// Clamps invalid [`Weight`]s
+    ValidateWeights,
     /// Applies velocity to position
     Update,
+    /// Despawns, clamps or reports bodies outside of the [`WorldBounds`]
+    EnforceWorldBounds,
+    /// Fires [`CollisionStarted`] and [`CollisionEnded`] events
+    TrackTouchingPairs,
+    /// Runs the [`CollisionReactions`] of entities that touched something
+    RunCollisionReactions,
+    /// Updates the grounded state of [`CharacterController`]s from their contacts
+    UpdateGrounded,
+    /// Pulls [`Rope`] segments back within range of each other
+    SolveRopes,
+    /// Removes [`DistanceJoint`]s attached to despawned entities. The joints themselves get solved during [`PhysicsSystems::Update`].
+    PruneJoints,
+    /// Puts still bodies to sleep and wakes up [`Sleeping`] bodies that got struck or pushed
+    UpdateSleeping,
+    /// Resizes bounds of entities with a [`ResizeBounds`] component
+    ResizeBounds,
+    /// Updates [`OverlappedVoxelFlags`] components
+    DetectVoxelOverlaps,
     /// Applies voxel collisions (moving entities w/ static terrain chunks)
     ApplyVoxelCollisions,
     /// Linearly interpolates transform components between Positions and PreviousPositions
@@ -66,7 +326,8 @@ pub enum PhysicsSystems {
 //////////////////////////////////////////////// Resources ////////////////////////////////////////////////
 
 /// Resource that stores the gravity of the situation ;)
-#[derive(Resource, Debug, Copy, Clone, PartialEq)]
+#[derive(Resource, Debug, Copy, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
 pub struct Gravity(pub Vec3);
 impl Default for Gravity {
     fn default() -> Self {
@@ -75,25 +336,58 @@ impl Default for Gravity {
 }
 
 
+/// Resource that counts physics steps. Advances once per fixed tick the physics runs, and not while paused.
+/// The first step is 1.
+#[derive(Resource, Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct PhysicsTick(pub u64);
+
+/// Event fired at the start of every physics step.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PhysicsStepEvent {
+    /// Value of the [`PhysicsTick`] during the step
+    pub tick: u64,
+    /// Duration of the step in seconds, or 1 without a [`FixedClock`]
+    pub dt: f32,
+    /// Number of substeps the step runs
+    pub substeps: usize
+}
+
+
 //////////////////////////////////////////////// Components ////////////////////////////////////////////////
 
 /// Velocity of an [`Entity`].
 #[derive(Component, Debug, Copy, Clone, PartialEq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[reflect(Component)]
 pub struct Velocity(pub Vec3);
 
 /// Represents the shape of an [`Entity`].
 #[derive(Component, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Shape {
     #[default]
     Cuboid,
+    /// Upright capsule with the radius of the smaller of its X and Z [`HalfExtents`]
     Capsule,
+    /// Sphere with the radius of the smallest of its [`HalfExtents`]
+    Sphere,
     VoxelChunk(VoxelChunk)
 }
 
-/// Weight of an [`Entity`].
+/// Weight of an [`Entity`], which acts as its mass.
+/// When two objects collide, the heavier one gets pushed less. A [`Weight::IMMOVABLE`] object never gets pushed by a finite weight one,
+/// like a heavy door, but still falls and moves by its own velocity unlike a [`StaticBody`].
+/// Weights that aren't positive get clamped to [`Weight::MIN`] with a warning.
 #[derive(Component, Debug, Copy, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
 pub struct Weight(pub f32);
+impl Weight {
+    /// Weight of objects that can't be pushed by finite weight objects
+    pub const IMMOVABLE: Self = Self(f32::INFINITY);
+    /// Smallest weight allowed
+    pub const MIN: f32 = 0.001;
+}
 impl Default for Weight {
     fn default() -> Self {
         Weight(1.0)
@@ -102,6 +396,7 @@ impl Default for Weight {
 
 /// Represents the bounds of an unscaled [`Entity`].
 #[derive(Component, Debug, Copy, Clone, PartialEq, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[reflect(Component)]
 pub struct HalfExtents(pub Vec3);
 impl HalfExtents {
@@ -122,21 +417,156 @@ impl HalfExtents {
     }
 }
 
+/// Bounds of an [`Entity`]'s visuals relative to its position, when they differ from its [`HalfExtents`].
+/// Like a tall grass sprite with a tiny collision box. Used for frustum culling by debug rendering.
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct VisualBounds(pub AABB);
+impl VisualBounds {
+    pub fn new(center: Vec3, half_extents: Vec3) -> Self {
+        Self(AABB::new(center, half_extents))
+    }
+}
+
 // Marker component that prevents an [`Entity`] from being affected by gravity.
 #[derive(Component, Debug, Copy, Clone, Default, Reflect)]
 #[reflect(Component)]
 pub struct AntiGravity;
 
+/// Scales the [`Gravity`] applied to an [`Entity`], like 0.1 for a feather or 2.0 for an anvil.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct GravityScale(pub f32);
+impl Default for GravityScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Gravity of an [`Entity`] that replaces the global [`Gravity`] resource, like for enemies walking on walls.
+/// Takes priority over [`GravityVolume`]s and [`GravityScale`], but not over [`AntiGravity`].
+#[derive(Component, Debug, Copy, Clone, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+pub struct LocalGravity(pub Vec3);
+
+/// Region that replaces the global [`Gravity`] for bodies whose center is inside it, like an underwater cave or a low gravity room.
+/// The region is the entity's [`HalfExtents`] around its [`CurrentTransform`]. Where volumes overlap, the highest priority one wins.
+/// Bodies inside still get their [`GravityScale`] applied. Volumes don't collide with anything on their own, so give them a [`CollisionConfig`] that doesn't either.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+pub struct GravityVolume {
+    pub gravity: Vec3,
+    pub priority: i32
+}
+impl GravityVolume {
+    pub fn new(gravity: Vec3) -> Self {
+        Self { gravity, priority: 0 }
+    }
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Region that accelerates bodies whose center is inside it, like a wind tunnel, a river current or a fan.
+/// The region is the entity's [`HalfExtents`] around its [`CurrentTransform`], and overlapping volumes add up.
+/// Acceleration is measured per second and ignores [`Weight`]. [`Sleeping`] bodies inside get woken up.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct ForceVolume {
+    pub force: Vec3,
+    /// Only bodies in these groups get pushed
+    pub affects: CollisionGroups
+}
+impl ForceVolume {
+    pub fn new(force: Vec3) -> Self {
+        Self { force, affects: GROUP_ALL }
+    }
+    pub fn not_affecting(mut self, groups: CollisionGroups) -> Self {
+        self.affects &= !groups;
+        self
+    }
+}
+impl Default for ForceVolume {
+    fn default() -> Self {
+        Self::new(Vec3::ZERO)
+    }
+}
+
+/// Region filled with a fluid like water, that pushes bodies inside it up against the global [`Gravity`] and slows them down.
+/// The region is the entity's [`HalfExtents`] around its [`CurrentTransform`].
+/// Buoyancy grows with the part of a body's box that's submerged, so bodies lighter than the fluid for their size float at the surface, and heavier ones sink.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct FluidVolume {
+    /// [`Weight`] of the fluid per unit of volume. Bodies with a lower weight per unit of volume float.
+    pub density: f32,
+    /// Fraction of velocity lost per second when fully submerged
+    pub drag: f32
+}
+impl FluidVolume {
+    pub fn new(density: f32, drag: f32) -> Self {
+        Self { density, drag }
+    }
+}
+impl Default for FluidVolume {
+    fn default() -> Self {
+        Self::new(1.0, 0.5)
+    }
+}
+
+/// Marker component for bodies that never move, like level geometry.
+/// Collisions never push them, and pairs of static bodies are skipped entirely, which keeps levels made of many pieces cheap.
+#[derive(Component, Debug, Copy, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct StaticBody;
+
+/// Marker component for bodies driven purely by game code, like moving platforms and doors.
+/// Collisions never push them, but they shove the bodies they run into. Gravity doesn't apply to them either.
+#[derive(Component, Debug, Copy, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct Kinematic;
+
+/// Marker component for platforms that carry the bodies standing on them.
+/// Bodies grounded on one get moved along with it each tick, on top of their own movement.
+#[derive(Component, Debug, Copy, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct CarriesRiders;
+
+/// Component for bodies that only block objects coming at them from the side `normal` faces, like platforms that can be jumped up through.
+/// Objects moving along or away from `normal` pass through them.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct OneWay {
+    pub normal: Vec3
+}
+impl Default for OneWay {
+    fn default() -> Self {
+        Self { normal: Vec3::Y }
+    }
+}
+impl OneWay {
+    /// True if an object hitting this body's surface with the normal specified, while moving at `incoming_vel` compared to it, gets blocked.
+    pub fn blocks(&self, surface_normal: Vec3, incoming_vel: Vec3) -> bool {
+        surface_normal.dot(self.normal) > 0.0 && incoming_vel.dot(self.normal) < 0.0
+    }
+}
+
 
 /// Frictional value of an [`Entity`].
-/// Used to dampen movement.
+/// Used to dampen movement. Each axis is the fraction of velocity kept after one second, so it behaves the same at any timestep.
 #[derive(Component, Debug, Copy, Clone, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[reflect(Component)]
 pub struct Friction(pub Vec3);
 impl Friction {
     pub fn new(value: f32) -> Self {
         Self(Vec3::new(value, value, value))
     }
+    /// Converts a friction that used to be applied once per tick into one applied per second.
+    #[deprecated(note = "Friction is kept per second now. Use Friction::new with the fraction of velocity kept after a second.")]
+    pub fn per_tick(value: Vec3, step: Duration) -> Self {
+        Self(value.powf(1.0 / step.as_secs_f32()))
+    }
 }
 impl Default for Friction {
     fn default() -> Self {
@@ -144,6 +574,59 @@ impl Default for Friction {
     }
 }
 
+/// Friction used instead of [`Friction`] while an [`Entity`] isn't grounded, like air drag.
+/// Whether it's grounded comes from its [`GroundState`] or [`CharacterController`] as of the previous tick.
+/// Entities with neither always use [`Friction`].
+#[derive(Component, Debug, Copy, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct AirFriction(pub Vec3);
+impl AirFriction {
+    pub fn new(value: f32) -> Self {
+        Self(Vec3::new(value, value, value))
+    }
+}
+impl Default for AirFriction {
+    fn default() -> Self {
+        Self(Vec3::ONE)
+    }
+}
+
+/// Force continuously pushing an [`Entity`], like wind.
+/// Every tick, velocity changes by the force scaled by the fixed timestep and divided by [`Weight`].
+#[derive(Component, Debug, Copy, Clone, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+pub struct ExternalForce(pub Vec3);
+
+/// One-off push of an [`Entity`], like an explosion or knockback.
+/// Velocity changes by the impulse divided by [`Weight`] on the next tick, after which the impulse is cleared.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+pub struct ExternalImpulse(pub Vec3);
+
+/// Limits how fast an [`Entity`] can move, like a terminal velocity for falling objects.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum VelocityLimit {
+    /// Clamps the length of the velocity
+    Linear(f32),
+    /// Clamps each axis of the velocity separately
+    PerAxis(Vec3)
+}
+impl VelocityLimit {
+    /// Clamps a velocity to this limit
+    pub fn clamp(self, vel: Vec3) -> Vec3 {
+        match self {
+            Self::Linear(max) => vel.clamp_length_max(max),
+            Self::PerAxis(max) => vel.clamp(-max, max)
+        }
+    }
+}
+impl Default for VelocityLimit {
+    fn default() -> Self {
+        Self::Linear(f32::INFINITY)
+    }
+}
+
 /// Marker component that lets the interpolation plugin select the correct entities.
 /// If an [`Entity`] has this, users of that entity should not manipulate [`Transform`]
 /// directly and should instead manipulate [`CurrentTransform`] (and sometimes [`PreviousTransform`]).
@@ -165,7 +648,8 @@ pub struct PhysicsBundle {
     pub velocity: Velocity,
     pub friction: Friction,
     pub physics_marker: PhysicsInterpolate,
-    pub collision_response: CollisionResponse
+    pub collision_response: CollisionResponse,
+    pub contacts: Contacts
 }
 impl PhysicsBundle {
     pub fn new(transform: Transform, bounds: HalfExtents, shape: Shape) -> Self {
@@ -185,6 +669,37 @@ impl PhysicsBundle {
         self.friction = friction;
         self
     }
+    pub fn with_weight(mut self, weight: Weight) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+/// Bundle for a point particle, which has zero [`HalfExtents`] and collides with everything but other particles.
+/// Skipping particle pairs keeps thousands of particles cheap.
+#[derive(Bundle, Debug, Clone)]
+pub struct ParticleBundle {
+    #[bundle]
+    pub physics: PhysicsBundle
+}
+impl ParticleBundle {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            physics: PhysicsBundle {
+                config: CollisionConfig::new(GROUP_PARTICLES, GROUP_ALL).not_affected_by(GROUP_PARTICLES),
+                ..PhysicsBundle::new(Transform::from_translation(position), HalfExtents(Vec3::ZERO), Shape::Cuboid)
+            }
+        }
+    }
+    pub fn with_velocity(mut self, velocity: Velocity) -> Self {
+        self.physics.velocity = velocity;
+        self
+    }
+}
+impl Default for ParticleBundle {
+    fn default() -> Self {
+        Self::new(Vec3::ZERO)
+    }
 }
 
 //////////////////////////////////////////////// Helper struct(s) ////////////////////////////////////////////////
@@ -253,12 +768,32 @@ impl AABB {
     pub fn interp(&self, t: f32, dir: Vec3) -> Self {
         Self::new(self.center + dir * t, self.half_extents)
     }
+    /// Volume of the box.
+    pub fn volume(&self) -> f32 {
+        let size = self.size();
+        size.x * size.y * size.z
+    }
+    /// Volume of the part of this box that's inside the other one.
+    pub fn overlap_volume(&self, other: &Self) -> f32 {
+        let min = (self.center - self.half_extents).max(other.center - other.half_extents);
+        let max = (self.center + self.half_extents).min(other.center + other.half_extents);
+        let size = (max - min).max(Vec3::ZERO);
+        size.x * size.y * size.z
+    }
     pub fn intersects_xz(&self, other: &Self) -> bool {
         self.left() < other.right() &&
         self.right() > other.left() &&
         self.far() < other.near() &&
         self.near() > other.far()
     }
+    /// True if the boxes overlap when viewed along the Z axis.
+    /// Boxes that only touch don't overlap, like in the other intersection tests.
+    pub fn intersects_xy(&self, other: &Self) -> bool {
+        self.left() < other.right() &&
+        self.right() > other.left() &&
+        self.bottom() < other.top() &&
+        self.top() > other.bottom()
+    }
     pub fn intersects_yz(&self, other: &Self) -> bool {
         self.bottom() < other.top() &&
         self.top() > other.bottom() &&
@@ -273,91 +808,538 @@ impl AABB {
         self.far() < other.near() &&
         self.near() > other.far()
     }
+    /// True if the point is inside the box or on its surface.
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        let offset = (point - self.center).abs();
+        offset.cmple(self.half_extents).all()
+    }
+    /// Smallest box containing both boxes.
+    pub fn union(&self, other: &Self) -> Self {
+        let min = (self.center - self.half_extents).min(other.center - other.half_extents);
+        let max = (self.center + self.half_extents).max(other.center + other.half_extents);
+        Self::new((min + max) / 2.0, (max - min) / 2.0)
+    }
+    /// Box grown by the amount specified on every side.
+    /// Negative amounts shrink the box, but never past zero size.
+    pub fn expanded_by(&self, amount: Vec3) -> Self {
+        Self::new(self.center, (self.half_extents + amount).max(Vec3::ZERO))
+    }
 }
 
 //////////////////////////////////////////////// Systems ////////////////////////////////////////////////
 
+/// Runs the physics systems unless [`PhysicsConfig::paused`] is set.
+fn physics_running(config: Res<PhysicsConfig>) -> ShouldRun {
+    match config.paused {
+        true => ShouldRun::No,
+        false => ShouldRun::Yes
+    }
+}
+
+/// Advances the physics tick and announces the step.
+fn begin_step(
+    config: Res<PhysicsConfig>,
+    clock: Option<Res<FixedClock>>,
+    mut tick: ResMut<PhysicsTick>,
+    mut step_writer: EventWriter<PhysicsStepEvent>
+) {
+    tick.0 += 1;
+    step_writer.send(PhysicsStepEvent {
+        tick: tick.0,
+        dt: clock.map(|clock| clock.step_seconds()).unwrap_or(1.0),
+        substeps: config.substeps
+    });
+}
+
+/// [`GravityVolume`]s sorted from highest to lowest priority, so the first one a point is in wins.
+/// Shared by [`apply_gravity`] and the debug gravity field, so both agree on what gravity is where.
+#[derive(Debug, Default)]
+pub(crate) struct SortedGravityVolumes(Vec<(AABB, GravityVolume)>);
+impl SortedGravityVolumes {
+    pub fn update<'a>(&mut self, volumes: impl IntoIterator<Item = (&'a GravityVolume, &'a CurrentTransform, &'a HalfExtents)>) {
+        self.0.clear();
+        self.0.extend(volumes.into_iter().map(|(volume, trans, extents)| (AABB::new(trans.0.translation, extents.0), *volume)));
+        self.0.sort_by_key(|(_, volume)| std::cmp::Reverse(volume.priority));
+    }
+
+    /// Gravity at a point before [`GravityScale`]: that of the highest priority volume it's in, or `global` otherwise.
+    pub fn gravity_at(&self, point: Vec3, global: Option<Vec3>) -> Option<Vec3> {
+        self.0
+            .iter()
+            .find(|(bounds, _)| bounds.contains_point(point))
+            .map(|(_, volume)| volume.gravity)
+            .or(global)
+    }
+}
+
 /// Applies gravity to all physics objects.
+/// [`LocalGravity`] overrides the gravity of the [`GravityVolume`] a body is in, or the global gravity otherwise, both of which get scaled by [`GravityScale`].
+#[allow(clippy::type_complexity)]
 fn apply_gravity(
     gravity: Option<Res<Gravity>>,
-    mut velocities: Query<&mut Velocity, Without<AntiGravity>>
+    volumes: Query<(&GravityVolume, &CurrentTransform, &HalfExtents)>,
+    mut velocities: Query<
+        (&mut Velocity, &CurrentTransform, Option<&LocalGravity>, Option<&GravityScale>),
+        (Without<AntiGravity>, Without<Kinematic>, Without<Sleeping>)
+    >,
+    mut sorted_volumes: Local<SortedGravityVolumes>
+) {
+    sorted_volumes.update(&volumes);
+    let gravity = gravity.map(|gravity| gravity.0);
+    for (mut vel, trans, local, scale) in &mut velocities {
+        let applied = match (local, sorted_volumes.gravity_at(trans.0.translation, gravity)) {
+            (Some(local), _) => local.0,
+            (None, Some(gravity)) => gravity * scale.map(|scale| scale.0).unwrap_or(1.0),
+            (None, None) => continue
+        };
+        vel.0 += applied;
+    }
+}
+
+/// Accelerates bodies inside of [`ForceVolume`]s.
+/// Without a [`FixedClock`], forces are applied as if a tick lasted one second.
+#[allow(clippy::type_complexity)]
+fn apply_force_volumes(
+    clock: Option<Res<FixedClock>>,
+    volumes: Query<(&ForceVolume, &CurrentTransform, &HalfExtents)>,
+    mut bodies: Query<
+        (&mut Velocity, &CurrentTransform, &CollisionConfig),
+        (Without<StaticBody>, Without<Kinematic>)
+    >
+) {
+    if volumes.is_empty() {
+        return;
+    }
+    let step = clock.map(|clock| clock.step_seconds()).unwrap_or(1.0);
+    for (mut vel, trans, config) in &mut bodies {
+        let position = trans.0.translation;
+        let force: Vec3 = volumes
+            .iter()
+            .filter(|(volume, volume_trans, extents)| {
+                volume.affects & config.groups != 0 && AABB::new(volume_trans.0.translation, extents.0).contains_point(position)
+            })
+            .map(|(volume, _, _)| volume.force)
+            .sum();
+
+        // Sleeping bodies wake up once their velocity isn't zero
+        if force != Vec3::ZERO {
+            vel.0 += force * step;
+        }
+    }
+}
+
+/// Applies buoyancy and drag to bodies inside of [`FluidVolume`]s.
+/// Drag is applied as if a tick lasted one second without a [`FixedClock`], while buoyancy is applied per tick like gravity.
+#[allow(clippy::type_complexity)]
+fn apply_fluid_volumes(
+    clock: Option<Res<FixedClock>>,
+    gravity: Option<Res<Gravity>>,
+    volumes: Query<(&FluidVolume, &CurrentTransform, &HalfExtents)>,
+    mut bodies: Query<
+        (&mut Velocity, &CurrentTransform, &HalfExtents, Option<&Weight>),
+        (Without<StaticBody>, Without<Kinematic>, Without<AntiGravity>, Without<Sleeping>)
+    >
 ) {
-    let gravity = match gravity {
-        Some(gravity) => gravity,
-        None => return
-    };
+    if volumes.is_empty() {
+        return;
+    }
+    let step = clock.map(|clock| clock.step_seconds()).unwrap_or(1.0);
+    let gravity = gravity.map(|gravity| gravity.0).unwrap_or(Vec3::ZERO);
+    for (mut vel, trans, extents, weight) in &mut bodies {
+        let bounds = AABB::new(trans.0.translation, extents.0);
+        let body_volume = bounds.volume();
+        if body_volume <= 0.0 {
+            continue;
+        }
+        let weight = weight.copied().unwrap_or_default().0;
+        let mut buoyancy = Vec3::ZERO;
+        let mut keep = 1.0;
+        for (fluid, fluid_trans, fluid_extents) in &volumes {
+            let submerged = bounds.overlap_volume(&AABB::new(fluid_trans.0.translation, fluid_extents.0));
+            if submerged <= 0.0 {
+                continue;
+            }
+            // Displaced fluid weighs as much as the push it gives, and drag grows with the submerged fraction
+            buoyancy -= gravity * fluid.density * submerged / weight;
+            keep *= (1.0 - fluid.drag.clamp(0.0, 1.0)).powf(submerged / body_volume * step);
+        }
+        if buoyancy != Vec3::ZERO || keep != 1.0 {
+            vel.0 = (vel.0 + buoyancy) * keep;
+        }
+    }
+}
+
+/// Clamps weights that were set to zero, a negative number or NaN, since they'd make collision responses blow up.
+fn validate_weights(mut weights: Query<(Entity, &mut Weight), Changed<Weight>>) {
+    for (entity, mut weight) in &mut weights {
+        if weight.0.is_nan() || weight.0 < Weight::MIN {
+            bevy_log::warn!("{entity:?} has invalid weight {}, clamping to {}", weight.0, Weight::MIN);
+            weight.0 = Weight::MIN;
+        }
+    }
+}
+
+/// Applies external forces and impulses to velocities.
+/// Without a [`FixedClock`], forces are applied as if a tick lasted one second.
+#[allow(clippy::type_complexity)]
+fn apply_forces(
+    clock: Option<Res<FixedClock>>,
+    mut entities: Query<
+        (&mut Velocity, Option<&ExternalForce>, Option<&mut ExternalImpulse>, Option<&Weight>),
+        (Or<(With<ExternalForce>, With<ExternalImpulse>)>, Without<Kinematic>, Without<Sleeping>)
+    >
+) {
+    let step = clock.map(|clock| clock.step_seconds()).unwrap_or(1.0);
+    for (mut vel, force, impulse, weight) in &mut entities {
+        let weight = weight.copied().unwrap_or_default();
+        if let Some(force) = force {
+            vel.0 += force.0 * step / weight.0;
+        }
+        if let Some(mut impulse) = impulse {
+            if impulse.0 != Vec3::ZERO {
+                vel.0 += impulse.0 / weight.0;
+                impulse.0 = Vec3::ZERO;
+            }
+        }
+    }
+}
+
+/// Snaps velocities slower than [`PhysicsConfig::rest_velocity_epsilon`] to zero.
+fn snap_resting_velocities(config: Res<PhysicsConfig>, mut velocities: Query<&mut Velocity>) {
+    if config.rest_velocity_epsilon <= 0.0 {
+        return;
+    }
+    let epsilon_squared = config.rest_velocity_epsilon * config.rest_velocity_epsilon;
     for mut vel in &mut velocities {
-        vel.0 += gravity.0;
+        if vel.0 != Vec3::ZERO && vel.0.length_squared() < epsilon_squared {
+            vel.0 = Vec3::ZERO;
+        }
     }
 }
 
-/// Dampens velocities by frictional value
-fn apply_friction(mut entities: Query<(&mut Velocity, &Friction)>) {
-    for (mut vel, fric) in &mut entities {
-        vel.0 *= fric.0;
+/// Clamps velocities to their limits, and resets velocities that became NaN or infinite.
+fn limit_velocities(config: Res<PhysicsConfig>, mut entities: Query<(Entity, &mut Velocity, Option<&VelocityLimit>)>) {
+    for (entity, mut vel, limit) in &mut entities {
+        if !vel.0.is_finite() {
+            bevy_log::warn!("{entity:?} has non-finite velocity {}, resetting it to zero", vel.0);
+            vel.0 = Vec3::ZERO;
+            continue;
+        }
+        let mut limited = vel.0;
+        if let Some(limit) = limit {
+            limited = limit.clamp(limited);
+        }
+        if let Some(max_speed) = config.max_speed {
+            limited = limited.clamp_length_max(max_speed);
+        }
+        if limited != vel.0 {
+            vel.0 = limited;
+        }
+    }
+}
+
+/// Dampens velocities by frictional value, using [`AirFriction`] for entities that weren't grounded last tick.
+/// Without a [`FixedClock`], friction is applied as if a tick lasted one second.
+#[allow(clippy::type_complexity)]
+fn apply_friction(
+    clock: Option<Res<FixedClock>>,
+    mut entities: Query<(
+        &mut Velocity,
+        &Friction,
+        Option<&AirFriction>,
+        Option<&GroundState>,
+        Option<&CharacterController>
+    ), Without<Sleeping>>
+) {
+    let step = clock.map(|clock| clock.step_seconds()).unwrap_or(1.0);
+    for (mut vel, fric, air_fric, ground, controller) in &mut entities {
+        let airborne = match (ground, controller) {
+            (None, None) => false,
+            _ => !ground.is_some_and(GroundState::is_grounded) && !controller.is_some_and(CharacterController::is_grounded)
+        };
+        let fric = match air_fric {
+            Some(air_fric) if airborne => air_fric.0,
+            _ => fric.0
+        };
+        vel.0 *= fric.powf(step);
     }
 }
 
 /// Moves entities with substeps, then applies collisions.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn update(
     config: Res<PhysicsConfig>,
     mut physics_objects: Query<(
+        Entity,
         &mut CurrentTransform,
         &mut Velocity,
         &mut HalfExtents,
         &Shape,
         &Weight,
         &CollisionConfig,
-        &mut CollisionResponse
-    )>
+        &mut CollisionResponse,
+        Option<&SurfaceTag>,
+        Option<&mut Contacts>,
+        Option<&CollisionExclusions>,
+        (Option<&Restitution>, Option<&RestitutionCombine>, Option<&ContactFriction>, Option<&FrictionCombine>),
+        Option<&mut GroundState>,
+        Option<&ResponseMode>
+    )>,
+    gravity: Option<Res<Gravity>>,
+    sensors: Query<(), With<Sensor>>,
+    sliders: Query<(), With<MoveAndSlide>>,
+    statics: Query<(), With<StaticBody>>,
+    kinematics: Query<(), With<Kinematic>>,
+    sleepers: Query<(), With<Sleeping>>,
+    carriers: Query<(), With<CarriesRiders>>,
+    one_ways: Query<&OneWay>,
+    joints: Query<(Entity, &DistanceJoint)>,
+    (mut collision_writer, mut sensor_writer): (EventWriter<CollisionEvent>, EventWriter<SensorEvent>),
+    (mut collided_pairs, mut sensed_pairs): (Local<HashSet<(Entity, Entity)>>, Local<HashSet<(Entity, Entity)>>),
+    mut broadphase: ResMut<Broadphase>,
+    (mut carrier_starts, mut riders): (Local<HashMap<Entity, Vec3>>, Local<HashMap<Entity, (Entity, Vec3)>>),
+    mut left_sleepers: Local<Vec<(Entity, Entity)>>
 ) {
 
+    // Nothing moves without time passing, so contacts and ground states are left as they were
+    if config.time_scale <= 0.0 {
+        return;
+    }
+
+    // Surfaces facing this way are ground
+    let up = -gravity.map_or(Gravity::default().0, |gravity| gravity.0).normalize_or_zero();
+
+    // Forgets contacts and collisions from the previous tick, and remembers where platforms started
+    collided_pairs.clear();
+    sensed_pairs.clear();
+    carrier_starts.clear();
+    broadphase.reset_counts();
+    // Sleeping bodies act like static ones until their velocity gets set. They remember what they stood on and touched.
+    // Bodies that start moving or get despawned stop touching the sleeping bodies they touched.
+    let asleep = |entity: Entity, vel: &Velocity| vel.0 == Vec3::ZERO && sleepers.contains(entity);
+    for (entity, _, vel, _, _, _, _, _, _, contacts, _, _, _, _) in &physics_objects {
+        if !asleep(entity, vel) {
+            continue;
+        }
+        for contact in contacts.iter().flat_map(|contacts| contacts.iter()) {
+            let still = physics_objects.get(contact.entity).is_ok_and(|(_, _, vel, ..)| vel.0 == Vec3::ZERO);
+            if !still {
+                left_sleepers.push((entity, contact.entity));
+            }
+        }
+    }
+    for (sleeper, other) in left_sleepers.drain(..) {
+        if let Ok((.., Some(mut contacts), _, _, _, _)) = physics_objects.get_mut(sleeper) {
+            contacts.remove(other);
+        }
+    }
+    for (entity, trans, vel, _, _, _, _, _, _, contacts, _, _, ground, _) in &mut physics_objects {
+        if carriers.contains(entity) {
+            carrier_starts.insert(entity, trans.0.translation);
+        }
+
+        // Sliding bodies already moved and reported what they hit this tick. They ride the platforms they landed on.
+        if sliders.contains(entity) {
+            for contact in contacts.iter().flat_map(|contacts| contacts.iter()) {
+                let pair = if entity.index() < contact.entity.index() { (entity, contact.entity) } else { (contact.entity, entity) };
+                collided_pairs.insert(pair);
+                if carriers.contains(contact.entity) && contact.normal.dot(up) >= config.ground_threshold {
+                    riders.insert(entity, (contact.entity, contact.normal));
+                }
+            }
+            continue;
+        }
+        if asleep(entity, &vel) {
+            continue;
+        }
+        if let Some(mut contacts) = contacts {
+            contacts.clear();
+        }
+        if let Some(mut ground) = ground {
+            ground.set(None);
+        }
+    }
+
     // For each substep...
     let steps = config.substeps as f32;
-    let inv_steps = 1.0 / steps;
+    let inv_steps = config.time_scale / steps;
     bevy_log::info!("---------------- Collision pass ---------------- ");
+    let keep = config.plane_lock.map_or(Vec3::ONE, |plane| Vec3::ONE - plane.normal());
     for i in 0..config.substeps {
         bevy_log::info!("---- Substep {} ----", i);
 
-        // Computes collisions between objects
-        let mut combinations = physics_objects.iter_combinations_mut();
-        while let Some([obj_a, obj_b]) = combinations.fetch_next() {
-            let (a_trans, a_vel, a_ext, a_shape, a_weight, a_cfg, mut a_resp) = obj_a;
-            let (b_trans, b_vel, b_ext, b_shape, b_weight, b_cfg, mut b_resp) = obj_b;
+        // Pulls jointed bodies back within range, before collisions push them back out of anything they got pulled into
+        for (entity, joint) in &joints {
+            let Ok([a, b]) = physics_objects.get_many_mut([entity, joint.other]) else { continue };
+            let (a_entity, mut a_trans, mut a_vel, _, _, a_weight, ..) = a;
+            let (b_entity, mut b_trans, mut b_vel, _, _, b_weight, ..) = b;
+            let inverse_weight = |entity: Entity, weight: &Weight| match statics.contains(entity) || kinematics.contains(entity) || sliders.contains(entity) {
+                true => 0.0,
+                false => 1.0 / weight.0
+            };
+            let a_inv = inverse_weight(a_entity, a_weight);
+            let b_inv = inverse_weight(b_entity, b_weight);
+            let Some((a_delta, b_delta)) = joint.corrections(a_trans.0.translation, b_trans.0.translation, a_inv, b_inv, config.substeps) else { continue };
+            a_trans.0.translation += a_delta * keep;
+            b_trans.0.translation += b_delta * keep;
+            a_vel.0 += a_delta * keep / inv_steps;
+            b_vel.0 += b_delta * keep / inv_steps;
+        }
+
+        // Finds pairs of objects whose paths could cross during the substep.
+        // Sliding bodies already moved, so they sit still like static ones.
+        let substep_vel = |entity: Entity, vel: &Velocity| match sliders.contains(entity) {
+            true => Vec3::ZERO,
+            false => vel.0 * inv_steps
+        };
+        broadphase.begin(config.broadphase_cell_size, config.verify_broadphase);
+        for (entity, trans, vel, ext, shape, _, cfg, _, _, _, _, _, _, _) in &physics_objects {
+            let mut aabb = AABB::new(trans.0.translation, ext.0);
+            if let Some(plane) = config.plane_lock {
+                aabb = flatten_to_plane(aabb, shape, plane);
+            }
+            let swept = aabb.union(&AABB::new(aabb.center + substep_vel(entity, vel), aabb.half_extents));
+            let is_static = statics.contains(entity) || asleep(entity, vel) || sliders.contains(entity);
+            broadphase.update(entity, *cfg, is_static, swept);
+        }
+
+        // Computes collisions between objects.
+        // Pairs come in entity index order, and ties between equally close responses keep the first, which keeps resolution deterministic.
+        for &(a, b) in broadphase.pairs() {
+            let Ok([obj_a, obj_b]) = physics_objects.get_many_mut([a, b]) else { continue };
+            let (a_entity, a_trans, a_vel, a_ext, a_shape, a_weight, a_cfg, mut a_resp, a_tag, a_contacts, a_excl, a_mat, a_ground, a_mode) = obj_a;
+            let (b_entity, b_trans, b_vel, b_ext, b_shape, b_weight, b_cfg, mut b_resp, b_tag, b_contacts, b_excl, b_mat, b_ground, b_mode) = obj_b;
 
-            // Quits early if neither object are affected by each other
-            let a_affected = a_cfg.affected_by(b_cfg.groups);
-            let b_affected = b_cfg.affected_by(a_cfg.groups);
+            // Quits early if neither object are affected by each other.
+            // Static and kinematic bodies act as if infinitely heavy, leaving the other object to take the whole response.
+            // So do sleeping and sliding bodies, unless a kinematic body pushes them.
+            let a_kinematic = kinematics.contains(a_entity);
+            let b_kinematic = kinematics.contains(b_entity);
+            let a_still = (asleep(a_entity, &a_vel) || sliders.contains(a_entity)) && !b_kinematic;
+            let b_still = (asleep(b_entity, &b_vel) || sliders.contains(b_entity)) && !a_kinematic;
+            let a_affected = a_cfg.affected_by(b_cfg.groups) && !statics.contains(a_entity) && !a_kinematic && !a_still;
+            let b_affected = b_cfg.affected_by(a_cfg.groups) && !statics.contains(b_entity) && !b_kinematic && !b_still;
             if !a_affected && !b_affected {
                 continue;
             }
+            if (a_excl.is_some() || b_excl.is_some()) && is_excluded(a_entity, a_excl, b_entity, b_excl) {
+                continue;
+            }
 
             // Computes collision between a and b
-            let coll = collide(
-                PhysObj {
-                    aabb: AABB::new(a_trans.0.translation, a_ext.0),
-                    shape: a_shape,
-                    vel: a_vel.0 * inv_steps
-                },
-                PhysObj {
-                    aabb: AABB::new(b_trans.0.translation, b_ext.0),
-                    shape: b_shape,
-                    vel: b_vel.0 * inv_steps
+            let mut a_aabb = AABB::new(a_trans.0.translation, a_ext.0);
+            let mut b_aabb = AABB::new(b_trans.0.translation, b_ext.0);
+            if let Some(plane) = config.plane_lock {
+                a_aabb = flatten_to_plane(a_aabb, a_shape, plane);
+                b_aabb = flatten_to_plane(b_aabb, b_shape, plane);
+            }
+            let a_obj = PhysObj {
+                aabb: a_aabb,
+                shape: a_shape,
+                vel: substep_vel(a_entity, &a_vel)
+            };
+            let b_obj = PhysObj {
+                aabb: b_aabb,
+                shape: b_shape,
+                vel: substep_vel(b_entity, &b_vel)
+            };
+
+            // Sensors only report what they're affected by, and never collide. Sliding bodies get sensed while they slide.
+            let a_sensor = sensors.contains(a_entity);
+            let b_sensor = sensors.contains(b_entity);
+            if a_sensor || b_sensor {
+                if sliders.contains(a_entity) || sliders.contains(b_entity) {
+                    continue;
                 }
-            );
+                let a_senses = a_sensor && a_affected && !sensed_pairs.contains(&(a_entity, b_entity));
+                let b_senses = b_sensor && b_affected && !sensed_pairs.contains(&(b_entity, a_entity));
+                if (a_senses || b_senses) && senses(a_obj, b_obj, config.axis_priority) {
+                    if a_senses {
+                        sensed_pairs.insert((a_entity, b_entity));
+                        sensor_writer.send(SensorEvent { sensor: a_entity, other: b_entity });
+                    }
+                    if b_senses {
+                        sensed_pairs.insert((b_entity, a_entity));
+                        sensor_writer.send(SensorEvent { sensor: b_entity, other: a_entity });
+                    }
+                }
+                continue;
+            }
+            let relative_vel = b_obj.vel - a_obj.vel;
+            let coll = collide(a_obj, b_obj, config.axis_priority);
+
+            // One-way bodies let objects through unless they come from the right side
+            let passes_one_way = |coll: &Collision| {
+                one_ways.get(a_entity).map_or(true, |one_way| one_way.blocks(coll.normal_a, relative_vel)) &&
+                one_ways.get(b_entity).map_or(true, |one_way| one_way.blocks(coll.normal_b, -relative_vel))
+            };
+            let coll = coll.filter(passes_one_way);
 
             // If collision found, distribute the response to a and b
-            if let Some(coll) = coll {
+            if let Some(mut coll) = coll {
 
+                // Applies the combined materials of a and b
+                let (a_rest, a_rest_rule, a_fric, a_fric_rule) = a_mat;
+                let (b_rest, b_rest_rule, b_fric, b_fric_rule) = b_mat;
+                let restitution = combine_material(
+                    a_rest.map(|r| r.0), a_rest_rule.map(|r| r.0),
+                    b_rest.map(|r| r.0), b_rest_rule.map(|r| r.0),
+                    config.restitution_combine
+                );
+                let friction = combine_material(
+                    a_fric.map(|f| f.0), a_fric_rule.map(|f| f.0),
+                    b_fric.map(|f| f.0), b_fric_rule.map(|f| f.0),
+                    config.friction_combine
+                );
+                apply_material(&mut coll, relative_vel, restitution, friction);
+
+                let a_mode = a_mode.copied().unwrap_or_default();
+                let b_mode = b_mode.copied().unwrap_or_default();
                 let (resp_a, resp_b) = match (a_affected, b_affected) {
                     (false, false) => continue,
-                    (false, true) => (CollisionResponse::Empty, CollisionResponse::for_b(&coll)),
-                    (true, false) => (CollisionResponse::for_a(&coll), CollisionResponse::Empty),
-                    (true, true) => CollisionResponse::weighted(&coll, a_weight.0, b_weight.0)
+                    (false, true) => (CollisionResponse::Empty, CollisionResponse::for_b(&coll, b_mode, relative_vel)),
+                    (true, false) => (CollisionResponse::for_a(&coll, a_mode, relative_vel), CollisionResponse::Empty),
+                    (true, true) => CollisionResponse::weighted(&coll, a_weight.0, b_weight.0, (a_mode, b_mode), relative_vel)
                 };
+                if collided_pairs.insert((a_entity, b_entity)) {
+                    collision_writer.send(CollisionEvent {
+                        entity_a: a_entity,
+                        entity_b: b_entity,
+                        normal: coll.normal_a,
+                        t: coll.t,
+                        point: coll.point,
+                        depth: coll.depth
+                    });
+                }
                 // bevy_log::debug!("Coll: {:?}", coll);
                 // bevy_log::debug!("A resp: {:?}", resp_a);
                 // bevy_log::debug!("B resp: {:?}", resp_a);
+                if let (Some(mut contacts), CollisionResponse::Value { surface_normal, .. }) = (a_contacts, resp_a) {
+                    contacts.add(Contact { entity: b_entity, normal: surface_normal, surface: b_tag.copied() });
+                }
+                if let (Some(mut contacts), CollisionResponse::Value { surface_normal, .. }) = (b_contacts, resp_b) {
+                    contacts.add(Contact { entity: a_entity, normal: surface_normal, surface: a_tag.copied() });
+                }
+                if let (Some(mut ground), CollisionResponse::Value { surface_normal, .. }) = (a_ground, resp_a) {
+                    if surface_normal.dot(up) >= config.ground_threshold {
+                        ground.set(Some(Grounded { normal: surface_normal, entity: b_entity }));
+                    }
+                }
+                if let (Some(mut ground), CollisionResponse::Value { surface_normal, .. }) = (b_ground, resp_b) {
+                    if surface_normal.dot(up) >= config.ground_threshold {
+                        ground.set(Some(Grounded { normal: surface_normal, entity: a_entity }));
+                    }
+                }
+                if let CollisionResponse::Value { surface_normal, .. } = resp_a {
+                    if carriers.contains(b_entity) && surface_normal.dot(up) >= config.ground_threshold {
+                        riders.insert(a_entity, (b_entity, surface_normal));
+                    }
+                }
+                if let CollisionResponse::Value { surface_normal, .. } = resp_b {
+                    if carriers.contains(a_entity) && surface_normal.dot(up) >= config.ground_threshold {
+                        riders.insert(b_entity, (a_entity, surface_normal));
+                    }
+                }
                 if resp_a.is_closer(&a_resp) {
                     *a_resp = resp_a;
                 }
@@ -368,29 +1350,635 @@ fn update(
         }
 
         // Applies collision responses and updates velocities
-        for (mut trans, mut vel, _, _, _, _, mut resp) in &mut physics_objects {
+        for (entity, mut trans, mut vel, _, _, _, _, mut resp, _, _, _, _, _, _) in &mut physics_objects {
+            let motion = substep_vel(entity, &vel);
             match *resp {
                 CollisionResponse::Empty => {
-                    trans.0.translation += vel.0 * inv_steps;
+                    trans.0.translation += motion;
                 },
                 CollisionResponse::Value { position_delta, velocity_delta, .. } => {
-                    trans.0.translation += vel.0 * inv_steps + position_delta;
-                    vel.0 += velocity_delta * steps;
+                    trans.0.translation += motion + position_delta * keep;
+                    vel.0 += velocity_delta * keep / inv_steps;
                     *resp = CollisionResponse::Empty;
                 }
             }
         }
     }
+
+    // Moves riders along with the platforms they stood on.
+    // Motion into or away from the platform is left out, since collisions already handle it.
+    for (rider, (platform, normal)) in riders.drain() {
+        let Some(start) = carrier_starts.get(&platform) else { continue };
+        let Ok((_, platform_trans, ..)) = physics_objects.get(platform) else { continue };
+        let displacement = platform_trans.0.translation - *start;
+        let Ok((_, mut rider_trans, ..)) = physics_objects.get_mut(rider) else { continue };
+        rider_trans.0.translation += (displacement - normal * displacement.dot(normal)) * keep;
+    }
+}
+
+/// Collects the flags of voxels overlapped by entities with an [`OverlappedVoxelFlags`] component.
+fn detect_voxel_overlaps(
+    config: Res<PhysicsConfig>,
+    mut bodies: Query<(&CurrentTransform, &HalfExtents, &mut OverlappedVoxelFlags)>,
+    chunks: Query<(&CurrentTransform, &HalfExtents, &Shape)>
+) {
+    for (trans, extents, mut overlapped) in &mut bodies {
+        let mut bounds = AABB::new(trans.0.translation, extents.0);
+        if let Some(plane) = config.plane_lock {
+            bounds = flatten_to_plane(bounds, &Shape::Cuboid, plane);
+        }
+        let mut flags = VoxelFlags::empty();
+        for (chunk_trans, chunk_extents, shape) in &chunks {
+            let mut chunk_bounds = AABB::new(chunk_trans.0.translation, chunk_extents.0);
+            if let Some(plane) = config.plane_lock {
+                chunk_bounds = flatten_to_plane(chunk_bounds, shape, plane);
+            }
+            if let Shape::VoxelChunk(chunk) = shape {
+                if chunk_bounds.intersects(&bounds) {
+                    flags = overlapped_voxels(chunk_bounds, chunk, bounds).fold(flags, |flags, data| flags | data.flags);
+                }
+            }
+        }
+        if overlapped.0 != flags {
+            overlapped.0 = flags;
+        }
+    }
 }
 
 /// Configuration for the physics engine
-#[derive(Resource, Copy, Clone, PartialEq)]
+#[derive(Resource, Copy, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
 pub struct PhysicsConfig {
-    pub substeps: usize
+    pub substeps: usize,
+    /// Rule used to combine the [`Restitution`] of objects without a [`RestitutionCombine`].
+    pub restitution_combine: CombineRule,
+    /// Rule used to combine the [`ContactFriction`] of objects without a [`FrictionCombine`].
+    pub friction_combine: CombineRule,
+    /// Plane to run physics in, for 2D games. Bodies never move perpendicular to it, and collide regardless of their depth.
+    /// Voxel chunks act as if only their first layer of voxels along the plane's normal existed.
+    /// Collisions between boxes are only swept along X and Y, so [`PlaneAxis::XY`] is the plane to use.
+    pub plane_lock: Option<PlaneAxis>,
+    /// How collisions that happen at the same time on different axes get resolved
+    pub axis_priority: AxisPriority,
+    /// Size of the cells objects are sorted into before colliding them. Only objects sharing a cell get collided.
+    /// Works best around the size of the objects that move the most, like characters.
+    pub broadphase_cell_size: f32,
+    /// Checks every substep that the broadphase, which only rehashes objects that moved, finds the same pairs as rebuilding it would.
+    /// Only checked in debug builds, and slow, so meant for tracking down missed collisions.
+    pub verify_broadphase: bool,
+    /// Minimum dot product between a surface's normal and the direction opposite [`Gravity`] for it to count as ground in [`GroundState`]
+    pub ground_threshold: f32,
+    /// Maximum number of surfaces a [`MoveAndSlide`] body can slide off of per tick
+    pub slide_iterations: usize,
+    /// Speed no object can exceed, regardless of its [`VelocityLimit`]. Guards against simulations blowing up.
+    pub max_speed: Option<f32>,
+    /// Stops every physics system while set, like for pause menus. Interpolation keeps showing the last state.
+    pub paused: bool,
+    /// Scales how far objects move by their velocity each tick, like for bullet time. Zero or less stops movement.
+    /// Doesn't affect gravity or friction.
+    /// While stopped, [`Contacts`], [`GroundState`]s and [`TouchingPairs`] keep what they had on the last tick that moved.
+    pub time_scale: f32,
+    /// Speed bodies must stay under for [`PhysicsConfig::sleep_ticks`] ticks to become [`Sleeping`]. Zero disables sleeping.
+    pub sleep_threshold: f32,
+    /// Number of ticks a body must stay under [`PhysicsConfig::sleep_threshold`] to become [`Sleeping`]
+    pub sleep_ticks: u32,
+    /// Velocities slower than this get snapped to zero after friction, so resting bodies don't jitter. Zero disables snapping.
+    pub rest_velocity_epsilon: f32
 }
 
 impl Default for PhysicsConfig {
     fn default() -> Self {
-        Self { substeps: 4 }
+        Self {
+            substeps: 4,
+            restitution_combine: CombineRule::Average,
+            friction_combine: CombineRule::Average,
+            plane_lock: None,
+            axis_priority: AxisPriority::YFirst,
+            broadphase_cell_size: 4.0,
+            verify_broadphase: false,
+            ground_threshold: 0.7,
+            slide_iterations: 4,
+            max_speed: None,
+            paused: false,
+            time_scale: 1.0,
+            sleep_threshold: 0.0,
+            sleep_ticks: 60,
+            rest_velocity_epsilon: 0.0001
+        }
+    }
+}
+
+/// Creates an app where each update runs a single physics tick.
+#[cfg(test)]
+pub(crate) fn physics_test_app() -> App {
+    let mut app = App::new();
+    app
+        .add_stage_after(CoreStage::Update, Phase::PostUpdate.stage(), SystemStage::parallel())
+        .add_plugin(PhysicsPlugin);
+    app
+}
+
+/// Spawns immovable terrain for tests to stand bodies on.
+#[cfg(test)]
+pub(crate) fn spawn_static_floor(world: &mut World, transform: Transform, extents: HalfExtents, shape: Shape) -> bevy_ecs::world::EntityMut<'_> {
+    let mut floor = world.spawn(PhysicsBundle {
+        config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE),
+        ..PhysicsBundle::new(transform, extents, shape)
+    });
+    floor.insert(AntiGravity);
+    floor
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use bevy_ecs::prelude::*;
+    use bevy_math::prelude::*;
+    use vidya_fixed_timestep::SimRng;
+
+    use crate::*;
+
+    #[test]
+    fn gravity_overrides_apply_in_priority_order() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -1.0, 0.0)));
+        let local = LocalGravity(Vec3::new(1.0, 0.0, 0.0));
+        let plain = app.world.spawn(PhysicsBundle::default()).id();
+        let feather = app.world.spawn(PhysicsBundle::default()).insert(GravityScale(0.1)).id();
+        let anvil = app.world.spawn(PhysicsBundle::default()).insert(GravityScale(2.0)).id();
+        let walker = app.world.spawn(PhysicsBundle::default()).insert((local, GravityScale(2.0))).id();
+        let floating = app.world.spawn(PhysicsBundle::default()).insert((AntiGravity, local)).id();
+        app.update();
+        let vel = |entity| app.world.get::<Velocity>(entity).unwrap().0;
+        assert_eq!(Vec3::new(0.0, -1.0, 0.0), vel(plain));
+        assert_eq!(Vec3::new(0.0, -0.1, 0.0), vel(feather));
+        assert_eq!(Vec3::new(0.0, -2.0, 0.0), vel(anvil));
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), vel(walker));
+        assert_eq!(Vec3::ZERO, vel(floating));
+    }
+
+    #[test]
+    fn gravity_volumes_override_gravity() {
+        use bevy_transform::prelude::*;
+
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+
+        // Upward gravity pool below y = 0, inside of a heavy gravity room it takes priority over
+        let pool = AABB::new(Vec3::new(0.0, -15.0, 0.0), Vec3::new(10.0, 15.0, 10.0));
+        let room = AABB::new(Vec3::ZERO, Vec3::splat(20.0));
+        app.world.spawn((GravityVolume::new(Vec3::new(0.0, 0.3, 0.0)).with_priority(1), CurrentTransform(Transform::from_translation(pool.center)), HalfExtents(pool.half_extents)));
+        app.world.spawn((GravityVolume::new(Vec3::new(0.0, -0.2, 0.0)), CurrentTransform(Transform::from_translation(room.center)), HalfExtents(room.half_extents)));
+        let ball = app.world
+            .spawn(PhysicsBundle::new(Transform::from_xyz(0.0, 25.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Sphere))
+            .id();
+
+        // Falls under global gravity, then faster in the room, until the pool pushes it back up
+        app.update();
+        assert_eq!(Vec3::new(0.0, -0.1, 0.0), app.world.get::<Velocity>(ball).unwrap().0);
+        let mut lowest = f32::MAX;
+        let mut rose = false;
+        for _ in 0..200 {
+            let previous = app.world.get::<CurrentTransform>(ball).unwrap().0.translation.y;
+            app.update();
+            let y = app.world.get::<CurrentTransform>(ball).unwrap().0.translation.y;
+            lowest = lowest.min(y);
+            rose |= y > previous && previous < 0.0;
+        }
+        assert!(rose);
+        assert!(lowest < 0.0 && lowest > -30.0, "{lowest}");
+    }
+
+    #[test]
+    fn gravity_at_picks_highest_priority_volume() {
+        let volume = |gravity: f32, priority: i32, center: Vec3| (
+            GravityVolume::new(Vec3::new(0.0, gravity, 0.0)).with_priority(priority),
+            CurrentTransform(Transform::from_translation(center)),
+            HalfExtents(Vec3::splat(2.0))
+        );
+        let volumes = [volume(-0.5, 0, Vec3::ZERO), volume(0.3, 2, Vec3::new(3.0, 0.0, 0.0)), volume(-0.2, 1, Vec3::new(3.0, 0.0, 0.0))];
+        let mut sorted = SortedGravityVolumes::default();
+        sorted.update(volumes.iter().map(|(volume, trans, extents)| (volume, trans, extents)));
+        let global = Some(Vec3::new(0.0, -0.1, 0.0));
+        assert_eq!(Some(Vec3::new(0.0, -0.5, 0.0)), sorted.gravity_at(Vec3::new(-1.0, 0.0, 0.0), global));
+        assert_eq!(Some(Vec3::new(0.0, 0.3, 0.0)), sorted.gravity_at(Vec3::new(1.5, 0.0, 0.0), global));
+        assert_eq!(Some(Vec3::new(0.0, -0.1, 0.0)), sorted.gravity_at(Vec3::new(0.0, 5.0, 0.0), global));
+        assert_eq!(None, sorted.gravity_at(Vec3::new(0.0, 5.0, 0.0), None));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn force_volumes_push_bodies_inside() {
+        use bevy_transform::prelude::*;
+
+        let mut app = physics_test_app();
+        app.insert_resource(PhysicsConfig { sleep_threshold: 0.001, sleep_ticks: 5, ..PhysicsConfig::default() });
+        let zone = AABB::new(Vec3::new(5.0, 0.0, 0.0), Vec3::new(5.0, 5.0, 5.0));
+        app.world.spawn((
+            ForceVolume::new(Vec3::new(0.2, 0.0, 0.0)).not_affecting(GROUP_PARTICLES),
+            CurrentTransform(Transform::from_translation(zone.center)),
+            HalfExtents(zone.half_extents)
+        ));
+        let spawn = |app: &mut App, x: f32, z: f32, groups: CollisionGroups, vel: Vec3| app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(groups, GROUP_NONE),
+                ..PhysicsBundle::new(Transform::from_xyz(x, 0.0, z), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(vel))
+                    .with_friction(Friction(Vec3::splat(0.9)))
+            })
+            .insert(AntiGravity)
+            .id();
+        let drifting = spawn(&mut app, -3.0, 0.0, GROUP_BASIC, Vec3::new(0.5, 0.0, 0.0));
+        let particle = spawn(&mut app, 5.0, -2.0, GROUP_PARTICLES, Vec3::ZERO);
+        let sleeper = spawn(&mut app, 5.0, 2.0, GROUP_BASIC, Vec3::ZERO);
+        app.world.entity_mut(sleeper).insert(Sleeping);
+
+        // Box slows down on its way in, and speeds up inside
+        let mut slowest_inside = f32::MAX;
+        let mut fastest_inside = 0.0_f32;
+        for _ in 0..300 {
+            app.update();
+            let x = app.world.get::<CurrentTransform>(drifting).unwrap().0.translation.x;
+            let vel = app.world.get::<Velocity>(drifting).unwrap().0.x;
+            if zone.contains_point(Vec3::new(x, 0.0, 0.0)) {
+                slowest_inside = slowest_inside.min(vel);
+                fastest_inside = fastest_inside.max(vel);
+            }
+        }
+        assert!(fastest_inside > slowest_inside * 2.0, "{slowest_inside} {fastest_inside}");
+
+        // Friction stops it after it drifts out
+        let x = app.world.get::<CurrentTransform>(drifting).unwrap().0.translation.x;
+        assert!(x > 10.0);
+        assert!(app.world.get::<Velocity>(drifting).unwrap().0.x < 0.001);
+
+        // Particles are excluded, sleeping boxes get woken up and blown out
+        assert_eq!(Vec3::new(5.0, 0.0, -2.0), app.world.get::<CurrentTransform>(particle).unwrap().0.translation);
+        assert!(app.world.get::<CurrentTransform>(sleeper).unwrap().0.translation.x > 10.0);
+    }
+
+    #[test]
+    fn light_boxes_float_and_heavy_ones_sink() {
+        use bevy_transform::prelude::*;
+
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.05, 0.0)));
+        let water = AABB::new(Vec3::new(0.0, -10.0, 0.0), Vec3::new(10.0, 10.0, 10.0));
+        app.world.spawn((FluidVolume::new(1.0, 0.1), CurrentTransform(Transform::from_translation(water.center)), HalfExtents(water.half_extents)));
+        let spawn = |app: &mut App, x: f32, weight: f32| app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_NONE),
+                ..PhysicsBundle::new(Transform::from_xyz(x, 3.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_weight(Weight(weight))
+            })
+            .id();
+        let light = spawn(&mut app, -2.0, 0.5);
+        let heavy = spawn(&mut app, 2.0, 2.0);
+
+        // Light box sinks in past where it floats, bobs back up, and settles half submerged
+        let mut lowest = f32::MAX;
+        let mut rebound = f32::MIN;
+        for _ in 0..500 {
+            app.update();
+            let y = app.world.get::<CurrentTransform>(light).unwrap().0.translation.y;
+            lowest = lowest.min(y);
+            if lowest < -0.1 {
+                rebound = rebound.max(y);
+            }
+        }
+        let y = app.world.get::<CurrentTransform>(light).unwrap().0.translation.y;
+        assert!(lowest < -0.1, "{lowest}");
+        assert!(rebound > 0.0, "{rebound}");
+        assert!(y.abs() < 0.01, "{y}");
+        assert!(app.world.get::<CurrentTransform>(heavy).unwrap().0.translation.y < -5.0);
+    }
+
+    #[test]
+    fn tiny_velocities_get_snapped_to_zero() {
+        let mut app = physics_test_app();
+        let spawn = |app: &mut App, vel: Vec3| app.world
+            .spawn(PhysicsBundle::default().with_velocity(Velocity(vel)))
+            .insert(AntiGravity)
+            .id();
+        let creeping = spawn(&mut app, Vec3::new(0.00001, 0.0, 0.0));
+        let slow = spawn(&mut app, Vec3::new(0.01, 0.0, 0.0));
+        app.update();
+        assert_eq!(Vec3::ZERO, app.world.get::<Velocity>(creeping).unwrap().0);
+        assert_eq!(Vec3::ZERO, app.world.get::<CurrentTransform>(creeping).unwrap().0.translation);
+        assert_eq!(Vec3::new(0.01, 0.0, 0.0), app.world.get::<Velocity>(slow).unwrap().0);
+    }
+
+    #[test]
+    fn velocities_get_limited() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -1.0, 0.0)));
+        app.insert_resource(PhysicsConfig { max_speed: Some(10.0), ..PhysicsConfig::default() });
+        let falling = app.world.spawn(PhysicsBundle::default()).insert(VelocityLimit::Linear(3.0)).id();
+        let per_axis = app.world
+            .spawn(PhysicsBundle::default().with_velocity(Velocity(Vec3::new(5.0, 0.0, -5.0))))
+            .insert((AntiGravity, VelocityLimit::PerAxis(Vec3::new(1.0, 1.0, 2.0))))
+            .id();
+        let fast = app.world
+            .spawn(PhysicsBundle::default().with_velocity(Velocity(Vec3::new(0.0, 0.0, 50.0))))
+            .insert(AntiGravity)
+            .id();
+        let broken = app.world
+            .spawn(PhysicsBundle::default().with_velocity(Velocity(Vec3::new(f32::NAN, 0.0, 0.0))))
+            .insert(AntiGravity)
+            .id();
+        for _ in 0..5 {
+            app.update();
+        }
+        let vel = |entity| app.world.get::<Velocity>(entity).unwrap().0;
+        assert!(vel(falling).abs_diff_eq(Vec3::new(0.0, -3.0, 0.0), 0.0001));
+        assert_eq!(Vec3::new(1.0, 0.0, -2.0), vel(per_axis));
+        assert!(vel(fast).abs_diff_eq(Vec3::new(0.0, 0.0, 10.0), 0.0001));
+        assert_eq!(Vec3::ZERO, vel(broken));
+    }
+
+    #[test]
+    fn friction_is_timestep_independent() {
+        let speed_after_one_second = |step: Duration| {
+            let mut app = physics_test_app();
+            app.insert_resource(FixedClock::new(step));
+            let entity = app.world
+                .spawn(PhysicsBundle::default()
+                    .with_velocity(Velocity(Vec3::new(8.0, 0.0, 0.0)))
+                    .with_friction(Friction::new(0.25))
+                )
+                .insert(AntiGravity)
+                .id();
+            let ticks = (1.0 / step.as_secs_f32()).round() as usize;
+            for _ in 0..ticks {
+                app.update();
+            }
+            app.world.get::<Velocity>(entity).unwrap().0.x
+        };
+        let slow = speed_after_one_second(Duration::from_secs_f64(1.0 / 20.0));
+        let fast = speed_after_one_second(Duration::from_secs_f64(1.0 / 120.0));
+        assert!((slow - 2.0).abs() < 0.001);
+        assert!((fast - 2.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn air_friction_applies_while_airborne() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(100.0, 1.0, 100.0), Shape::Cuboid).insert(StaticBody);
+        let spawn_body = |app: &mut App, y: f32| app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, y, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_friction(Friction(Vec3::new(0.5, 1.0, 0.5)))
+            })
+            .insert((AirFriction(Vec3::new(0.9, 1.0, 0.9)), GroundState::default()))
+            .id();
+        let grounded = spawn_body(&mut app, 0.5);
+        let airborne = spawn_body(&mut app, 50.0);
+
+        // Lets the grounded body settle, then pushes both sideways
+        app.update();
+        for body in [grounded, airborne] {
+            app.world.get_mut::<Velocity>(body).unwrap().0.x = 1.0;
+        }
+        app.update();
+        assert!((app.world.get::<Velocity>(grounded).unwrap().0.x - 0.5).abs() < 0.001);
+        assert!((app.world.get::<Velocity>(airborne).unwrap().0.x - 0.9).abs() < 0.001);
+    }
+
+    #[test]
+    fn paused_physics_leaves_transforms_alone() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -1.0, 0.0)));
+        app.insert_resource(PhysicsConfig { paused: true, ..PhysicsConfig::default() });
+        let transform = Transform::from_xyz(1.0, 2.0, 3.0);
+        let entity = app.world
+            .spawn(PhysicsBundle::new(transform, HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                .with_velocity(Velocity(Vec3::new(1.0, 0.0, 0.0)))
+            )
+            .id();
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(transform, app.world.get::<CurrentTransform>(entity).unwrap().0);
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), app.world.get::<Velocity>(entity).unwrap().0);
+
+        // Unpausing takes effect on the next tick
+        app.world.resource_mut::<PhysicsConfig>().paused = false;
+        app.update();
+        assert_ne!(transform, app.world.get::<CurrentTransform>(entity).unwrap().0);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn physics_components_round_trip_through_ron() {
+        use bevy_transform::prelude::*;
+
+        let mut chunk = VoxelChunk::new(UVec3::new(2, 1, 2));
+        chunk.set_voxel(UVec3::ZERO, VoxelData::new(Voxel::Slope));
+        let body = (
+            CurrentTransform(Transform::from_xyz(1.0, 2.0, 3.0)),
+            PreviousTransform(Transform::from_xyz(1.0, 2.5, 3.0)),
+            Velocity(Vec3::new(0.0, -0.5, 0.0)),
+            HalfExtents::new(2.0, 1.0, 2.0),
+            Friction(Vec3::splat(0.5)),
+            Weight(3.0),
+            CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+            Shape::VoxelChunk(chunk.clone())
+        );
+        let serialized = ron::to_string(&body).unwrap();
+        let (current, previous, vel, extents, friction, weight, config, shape) = ron::from_str(&serialized).unwrap();
+        assert_eq!((body.0, body.1, body.2, body.3, body.4, body.5, body.6), (current, previous, vel, extents, friction, weight, config));
+        assert!(matches!(shape, Shape::VoxelChunk(deserialized) if deserialized == chunk));
+    }
+
+    #[test]
+    fn simulation_is_deterministic() {
+        let simulate = || {
+            let mut app = physics_test_app();
+            app.insert_resource(Gravity(Vec3::new(0.0, -0.05, 0.0)));
+            spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(20.0, 1.0, 20.0), Shape::Cuboid).insert(StaticBody);
+            let mut rng = SimRng::new(3);
+            let bodies: Vec<Entity> = (0..50)
+                .map(|_| {
+                    let position = Vec3::new(rng.gen_range(-4.0..4.0), rng.gen_range(1.0..10.0), rng.gen_range(-4.0..4.0));
+                    let vel = Vec3::new(rng.gen_range(-0.2..0.2), 0.0, rng.gen_range(-0.2..0.2));
+                    app.world
+                        .spawn(PhysicsBundle {
+                            config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                            ..PhysicsBundle::new(Transform::from_translation(position), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                                .with_velocity(Velocity(vel))
+                        })
+                        .id()
+                })
+                .collect();
+            for _ in 0..120 {
+                app.update();
+            }
+            bodies
+                .iter()
+                .map(|body| app.world.get::<CurrentTransform>(*body).unwrap().0.translation.to_array().map(f32::to_bits))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(simulate(), simulate());
+    }
+
+    #[test]
+    fn steps_advance_tick_and_fire_events() {
+        let mut app = physics_test_app();
+        app.insert_resource(FixedClock::new(Duration::from_millis(50)));
+        for _ in 0..2 {
+            app.update();
+        }
+        app.world.resource_mut::<PhysicsConfig>().paused = true;
+        app.update();
+        app.world.resource_mut::<PhysicsConfig>().paused = false;
+        app.update();
+        assert_eq!(PhysicsTick(3), *app.world.resource::<PhysicsTick>());
+        let events: Vec<PhysicsStepEvent> = app.world
+            .resource::<Events<PhysicsStepEvent>>()
+            .iter_current_update_events()
+            .copied()
+            .collect();
+        assert_eq!(vec![PhysicsStepEvent { tick: 3, dt: 0.05, substeps: 4 }], events);
+    }
+
+    #[test]
+    fn time_scale_slows_movement() {
+        let mut app = physics_test_app();
+        app.insert_resource(PhysicsConfig { time_scale: 0.25, ..PhysicsConfig::default() });
+        let entity = app.world
+            .spawn(PhysicsBundle::default().with_velocity(Velocity(Vec3::new(4.0, 0.0, 0.0))))
+            .insert(AntiGravity)
+            .id();
+        app.update();
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), app.world.get::<CurrentTransform>(entity).unwrap().0.translation);
+        assert_eq!(Vec3::new(4.0, 0.0, 0.0), app.world.get::<Velocity>(entity).unwrap().0);
+    }
+
+    #[test]
+    fn stopped_time_keeps_touching_pairs() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        let floor = spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(10.0, 1.0, 10.0), Shape::Cuboid).id();
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+        app.update();
+        assert!(app.world.resource::<TouchingPairs>().contains(floor, body));
+
+        // Stopping time doesn't end the collision, nor does starting it again
+        for time_scale in [0.0, 0.0, 0.0, 1.0] {
+            app.world.resource_mut::<PhysicsConfig>().time_scale = time_scale;
+            app.update();
+            let ended = app.world.resource::<Events<CollisionEnded>>().iter_current_update_events().count();
+            let started = app.world.resource::<Events<CollisionStarted>>().iter_current_update_events().count();
+            assert_eq!((0, 0), (ended, started), "{time_scale}");
+            assert!(app.world.resource::<TouchingPairs>().contains(floor, body));
+            assert!(app.world.get::<Contacts>(body).unwrap().get(floor).is_some());
+        }
+    }
+
+    #[test]
+    fn impulses_are_consumed_after_one_tick() {
+        let mut app = physics_test_app();
+        let entity = app.world
+            .spawn(PhysicsBundle::default().with_weight(Weight(2.0)))
+            .insert((AntiGravity, ExternalImpulse(Vec3::new(4.0, 0.0, 0.0))))
+            .id();
+        app.update();
+        assert_eq!(Vec3::new(2.0, 0.0, 0.0), app.world.get::<Velocity>(entity).unwrap().0);
+        assert_eq!(Vec3::ZERO, app.world.get::<ExternalImpulse>(entity).unwrap().0);
+        app.update();
+        assert_eq!(Vec3::new(2.0, 0.0, 0.0), app.world.get::<Velocity>(entity).unwrap().0);
+    }
+
+    #[test]
+    fn constant_forces_grow_velocity_linearly() {
+        let mut app = physics_test_app();
+        let entity = app.world
+            .spawn(PhysicsBundle::default().with_weight(Weight(4.0)))
+            .insert((AntiGravity, ExternalForce(Vec3::new(0.0, 0.0, 2.0))))
+            .id();
+        for tick in 1..=5 {
+            app.update();
+            let vel = app.world.get::<Velocity>(entity).unwrap().0;
+            assert_eq!(Vec3::new(0.0, 0.0, 0.5 * tick as f32), vel);
+        }
+    }
+
+    #[test]
+    fn invalid_weights_get_clamped() {
+        let mut app = physics_test_app();
+        let weights = [Weight(0.0), Weight(-2.0), Weight(f32::NAN), Weight(0.5), Weight::IMMOVABLE];
+        let entities: Vec<Entity> = weights
+            .iter()
+            .map(|weight| app.world.spawn(PhysicsBundle::default().with_weight(*weight)).id())
+            .collect();
+        app.update();
+        let clamped: Vec<f32> = entities.iter().map(|entity| app.world.get::<Weight>(*entity).unwrap().0).collect();
+        assert_eq!(vec![Weight::MIN, Weight::MIN, Weight::MIN, 0.5, f32::INFINITY], clamped);
+    }
+
+    fn unit_box(x: f32, y: f32, z: f32) -> AABB {
+        AABB::new(Vec3::new(x, y, z), Vec3::splat(0.5))
+    }
+
+    #[test]
+    fn planar_intersections() {
+        let a = unit_box(0.0, 0.0, 0.0);
+
+        // Offset along one axis only overlaps in the planes without that axis
+        let b = unit_box(0.0, 0.0, 5.0);
+        assert!(a.intersects_xy(&b));
+        assert!(!a.intersects_yz(&b));
+        assert!(!a.intersects_xz(&b));
+        let b = unit_box(5.0, 0.0, 0.0);
+        assert!(!a.intersects_xy(&b));
+        assert!(a.intersects_yz(&b));
+        assert!(!a.intersects_xz(&b));
+        let b = unit_box(0.0, 5.0, 0.0);
+        assert!(!a.intersects_xy(&b));
+        assert!(!a.intersects_yz(&b));
+        assert!(a.intersects_xz(&b));
+
+        // Touching edges don't count, while the slightest overlap does
+        let touching = unit_box(1.0, 1.0, 1.0);
+        assert!(!a.intersects_xy(&touching));
+        assert!(!a.intersects_yz(&touching));
+        assert!(!a.intersects_xz(&touching));
+        assert!(!a.intersects(&touching));
+        let overlapping = unit_box(0.99, 0.99, 0.99);
+        assert!(a.intersects_xy(&overlapping));
+        assert!(a.intersects_yz(&overlapping));
+        assert!(a.intersects_xz(&overlapping));
+        assert!(a.intersects(&overlapping));
+    }
+
+    #[test]
+    fn contains_point() {
+        let a = unit_box(1.0, 1.0, 1.0);
+        assert!(a.contains_point(Vec3::ONE));
+        assert!(a.contains_point(Vec3::new(1.5, 0.5, 1.5)));
+        assert!(!a.contains_point(Vec3::new(1.5, 0.49, 1.5)));
+    }
+
+    #[test]
+    fn union_and_expand() {
+        let a = unit_box(0.0, 0.0, 0.0);
+        let b = AABB::new(Vec3::new(3.0, 1.0, 0.0), Vec3::new(0.5, 1.0, 0.5));
+        let union = a.union(&b);
+        assert_eq!(AABB::new(Vec3::new(1.5, 0.75, 0.0), Vec3::new(2.0, 1.25, 0.5)), union);
+        assert_eq!(union, b.union(&a));
+        assert_eq!(a, a.union(&a));
+
+        // Expanding by the gap between touching boxes makes them overlap
+        let touching = unit_box(1.0, 0.0, 0.0);
+        assert!(!a.intersects(&touching));
+        assert!(a.expanded_by(Vec3::splat(0.01)).intersects(&touching));
+        assert_eq!(Vec3::ZERO, a.expanded_by(Vec3::splat(-1.0)).half_extents);
+    }
+}
@@ -0,0 +1,216 @@
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_reflect::prelude::*;
+
+use crate::Collision;
+
+/// How bouncy an object is when it hits another.
+/// 0.0 means no bounce, 1.0 means a perfectly elastic bounce.
+#[derive(Component, Copy, Clone, PartialEq, Debug, Default, Reflect)]
+pub struct Restitution(pub f32);
+
+/// Fraction of the sliding velocity an object loses when it hits another.
+/// Unlike [`crate::Friction`], only applies while touching something.
+#[derive(Component, Copy, Clone, PartialEq, Debug, Default, Reflect)]
+pub struct ContactFriction(pub f32);
+
+/// Overrides the [`CombineRule`] used for an object's [`Restitution`].
+#[derive(Component, Copy, Clone, Eq, PartialEq, Debug, Default, Reflect)]
+pub struct RestitutionCombine(pub CombineRule);
+
+/// Overrides the [`CombineRule`] used for an object's [`ContactFriction`].
+#[derive(Component, Copy, Clone, Eq, PartialEq, Debug, Default, Reflect)]
+pub struct FrictionCombine(pub CombineRule);
+
+/// How an object's velocity changes when it hits something, like particles bouncing, characters sliding and crates stopping.
+/// Objects without one use [`ResponseMode::Stop`].
+#[derive(Component, Copy, Clone, PartialEq, Debug, Default, Reflect)]
+pub enum ResponseMode {
+    /// Cancels the velocity into the surface hit, with the combined [`Restitution`] and [`ContactFriction`] of both objects applied
+    #[default]
+    Stop,
+    /// Cancels only the velocity into the surface hit, keeping the velocity along it. Materials are ignored.
+    Slide,
+    /// Reflects the velocity into the surface hit, scaled by `restitution`, keeping the velocity along it. Materials are ignored.
+    Bounce { restitution: f32 }
+}
+
+impl ResponseMode {
+
+    /// Change in velocity of object B in a collision, where B moved at `relative_vel` compared to A.
+    pub(crate) fn velocity_delta(self, coll: &Collision, relative_vel: Vec3) -> Vec3 {
+        let normal = coll.normal_a;
+        let into = relative_vel.dot(normal).min(0.0);
+        match self {
+            ResponseMode::Stop => coll.velocity_delta,
+            ResponseMode::Slide => -normal * into,
+            ResponseMode::Bounce { restitution } => -normal * into * (1.0 + restitution)
+        }
+    }
+}
+
+/// Rule for combining the material values of two colliding objects.
+/// When the two objects use different rules, the one with the higher priority wins (Max > Multiply > Average > Min).
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Reflect)]
+pub enum CombineRule {
+    Min,
+    #[default]
+    Average,
+    Multiply,
+    Max
+}
+
+impl CombineRule {
+    pub fn priority(self) -> u8 {
+        match self {
+            Self::Min => 0,
+            Self::Average => 1,
+            Self::Multiply => 2,
+            Self::Max => 3
+        }
+    }
+
+    /// Picks the rule with the higher priority.
+    pub fn resolve(self, other: Self) -> Self {
+        if other.priority() > self.priority() { other } else { self }
+    }
+
+    pub fn combine(self, a: f32, b: f32) -> f32 {
+        match self {
+            Self::Min => a.min(b),
+            Self::Average => (a + b) * 0.5,
+            Self::Multiply => a * b,
+            Self::Max => a.max(b)
+        }
+    }
+}
+
+/// Combines the material values of two objects.
+/// If only one object defines a value, that value is used as is.
+/// Objects without a rule override use the rule specified.
+pub fn combine_material(
+    a: Option<f32>,
+    a_rule: Option<CombineRule>,
+    b: Option<f32>,
+    b_rule: Option<CombineRule>,
+    rule: CombineRule
+) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let rule = a_rule.unwrap_or(rule).resolve(b_rule.unwrap_or(rule));
+            Some(rule.combine(a, b))
+        },
+        (a, b) => a.or(b)
+    }
+}
+
+/// Adds bounce and sliding friction to a collision.
+/// `relative_vel` is the velocity of object B relative to object A during the substep.
+pub(crate) fn apply_material(coll: &mut Collision, relative_vel: Vec3, restitution: Option<f32>, friction: Option<f32>) {
+    if let Some(restitution) = restitution {
+        coll.velocity_delta *= 1.0 + restitution;
+    }
+    if let Some(friction) = friction {
+        let normal = coll.normal_a;
+        let tangent = relative_vel - normal * relative_vel.dot(normal);
+        coll.velocity_delta -= tangent * friction.clamp(0.0, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+    use vidya_fixed_timestep::CurrentTransform;
+
+    use crate::*;
+    use crate::CombineRule::*;
+
+    #[test]
+    fn combine_rules() {
+        let cases = [
+            (Min, Min, 0.0),
+            (Average, Average, 0.45),
+            (Multiply, Multiply, 0.0),
+            (Max, Max, 0.9),
+            (Min, Average, 0.45),
+            (Average, Multiply, 0.0),
+            (Multiply, Max, 0.9),
+            (Max, Min, 0.9),
+            (Min, Multiply, 0.0)
+        ];
+        for (a_rule, b_rule, expected) in cases {
+            let combined = combine_material(Some(0.9), Some(a_rule), Some(0.0), Some(b_rule), Min);
+            assert_eq!(Some(expected), combined, "{a_rule:?} with {b_rule:?}");
+            let combined = combine_material(Some(0.0), Some(b_rule), Some(0.9), Some(a_rule), Min);
+            assert_eq!(Some(expected), combined, "{b_rule:?} with {a_rule:?}");
+        }
+        assert_eq!(Some(0.05), combine_material(Some(0.5), None, Some(0.1), None, Multiply));
+        assert_eq!(Some(0.5), combine_material(Some(0.5), Some(Max), Some(0.1), None, Min));
+        assert_eq!(Some(0.3), combine_material(None, Some(Min), Some(0.3), None, Average));
+        assert_eq!(None, combine_material(None, None, None, Some(Max), Average));
+    }
+
+    #[test]
+    fn ball_bounces_off_floor() {
+        let mut app = physics_test_app();
+        app.insert_resource(PhysicsConfig { substeps: 1, ..PhysicsConfig::default() });
+        spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, 0.0, 0.0), HalfExtents::new(10.0, 1.0, 10.0), Shape::Cuboid)
+            .insert((Restitution(0.0), RestitutionCombine(Max), ContactFriction(0.05)));
+        let ball = app.world
+            .spawn((
+                PhysicsBundle {
+                    config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                    ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.5, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                        .with_velocity(Velocity(Vec3::new(1.0, -1.0, 0.0)))
+                },
+                Restitution(0.9)
+            ))
+            .id();
+        app.update();
+
+        // Max wins over the default Average, and only the floor defines friction
+        let vel = app.world.get::<Velocity>(ball).unwrap().0;
+        assert!((vel.y - 0.9).abs() < 0.0001, "{vel}");
+        assert!((vel.x - 0.95).abs() < 0.0001, "{vel}");
+        let y = app.world.get::<CurrentTransform>(ball).unwrap().0.translation.y;
+        assert!((y - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn response_modes_against_wall() {
+        let cases = [
+            (None, Vec3::new(0.0, 0.0, 0.25)),
+            (Some(ResponseMode::Stop), Vec3::new(0.0, 0.0, 0.25)),
+            (Some(ResponseMode::Slide), Vec3::new(0.0, 0.0, 0.5)),
+            (Some(ResponseMode::Bounce { restitution: 0.5 }), Vec3::new(-0.5, 0.0, 0.5))
+        ];
+        for (mode, expected) in cases {
+            let mut app = physics_test_app();
+            app.insert_resource(PhysicsConfig { substeps: 1, ..PhysicsConfig::default() });
+            spawn_static_floor(&mut app.world, Transform::from_xyz(2.0, 0.0, 0.0), HalfExtents::new(2.0, 10.0, 10.0), Shape::Cuboid)
+                .insert(ContactFriction(0.5));
+            let body = app.world
+                .spawn((
+                    PhysicsBundle {
+                        config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                        ..PhysicsBundle::new(Transform::from_xyz(0.0, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                            .with_velocity(Velocity(Vec3::new(1.0, 0.0, 0.5)))
+                    },
+                    AntiGravity
+                ))
+                .id();
+            if let Some(mode) = mode {
+                app.world.entity_mut(body).insert(mode);
+            }
+            app.update();
+
+            // Stopping loses velocity to the wall's friction, while sliding and bouncing ignore it
+            let vel = app.world.get::<Velocity>(body).unwrap().0;
+            assert!((vel - expected).length() < 0.0001, "{mode:?}: {vel}");
+            let x = app.world.get::<CurrentTransform>(body).unwrap().0.translation.x;
+            assert!((x - 0.5).abs() < 0.0001, "{mode:?}: {x}");
+        }
+    }
+}
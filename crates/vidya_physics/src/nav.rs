@@ -0,0 +1,358 @@
+//! Walkable cell grids derived from voxel chunks, for AI pathfinding.
+//! Cells use global voxel coordinates: the chunk at chunk coordinates `c` covers the cells starting at `c * chunk_size`,
+//! which matches how the [`crate::ChunkStreamingPlugin`] places chunks.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::ReadOnlyWorldQuery;
+use bevy_math::prelude::*;
+
+use crate::{Shape, Voxel, VoxelChunk, VoxelChunkMap};
+
+const DIRECTIONS: [IVec3; 4] = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+
+/// Builds a [`WalkableGrid`] from all chunks loaded in the [`VoxelChunkMap`].
+/// `agent_height` is the number of empty cells an agent needs to stand in, and `step_height`
+/// the number of cells it can step up or down between neighboring cells.
+pub fn build_walkable_grid<F: ReadOnlyWorldQuery>(
+    chunks: &VoxelChunkMap,
+    query: &Query<&Shape, F>,
+    agent_height: u32,
+    step_height: u32
+) -> WalkableGrid {
+    WalkableGrid::from_chunks(loaded_chunks(chunks, query), agent_height, step_height)
+}
+
+/// Rebuilds the cells of a [`WalkableGrid`] covered by the dirty chunks specified, like after they were edited.
+pub fn update_walkable_grid<F: ReadOnlyWorldQuery>(
+    grid: &mut WalkableGrid,
+    chunks: &VoxelChunkMap,
+    query: &Query<&Shape, F>,
+    dirty: impl IntoIterator<Item = IVec3>
+) {
+    grid.update_chunks(loaded_chunks(chunks, query), dirty);
+}
+
+fn loaded_chunks<'a, F: ReadOnlyWorldQuery>(
+    chunks: &VoxelChunkMap,
+    query: &'a Query<&Shape, F>
+) -> Vec<(IVec3, &'a VoxelChunk)> {
+    chunks.iter()
+        .filter_map(|(coords, entity)| match query.get(entity) {
+            Ok(Shape::VoxelChunk(chunk)) => Some((coords, chunk)),
+            _ => None
+        })
+        .collect()
+}
+
+/// Grid of cells an agent can stand in, stored as bits.
+/// A cell is walkable if it has solid ground below it (or is a slope the agent stands on)
+/// and enough empty cells above it for the agent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalkableGrid {
+    min: IVec3,
+    size: UVec3,
+    chunk_size: UVec3,
+    agent_height: u32,
+    step_height: u32,
+    solid: BitGrid,
+    ramps: BitGrid,
+    walkable: BitGrid
+}
+
+impl WalkableGrid {
+
+    /// Builds a grid from chunks and their chunk coordinates.
+    /// All chunks are expected to be the same size. The grid covers the box surrounding the chunks.
+    pub fn from_chunks<'a>(
+        chunks: impl IntoIterator<Item = (IVec3, &'a VoxelChunk)>,
+        agent_height: u32,
+        step_height: u32
+    ) -> Self {
+        let chunks: HashMap<IVec3, &VoxelChunk> = chunks.into_iter().collect();
+        let chunk_size = chunks.values().next().map(|chunk| chunk.size()).unwrap_or(UVec3::ONE);
+        let (min, max) = chunks.keys().fold(
+            (IVec3::splat(i32::MAX), IVec3::splat(i32::MIN)),
+            |(min, max), coords| (min.min(*coords), max.max(*coords + 1))
+        );
+        let (min, size) = if chunks.is_empty() {
+            (IVec3::ZERO, UVec3::ZERO)
+        }
+        else {
+            (min * chunk_size.as_ivec3(), ((max - min) * chunk_size.as_ivec3()).as_uvec3())
+        };
+        let len = (size.x * size.y * size.z) as usize;
+        let mut grid = Self {
+            min,
+            size,
+            chunk_size,
+            agent_height: agent_height.max(1),
+            step_height,
+            solid: BitGrid::new(len),
+            ramps: BitGrid::new(len),
+            walkable: BitGrid::new(len)
+        };
+        for (coords, chunk) in &chunks {
+            grid.write_chunk(*coords, Some(chunk));
+        }
+        grid.update_walkable(min, min + size.as_ivec3());
+        grid
+    }
+
+    /// Rebuilds the cells covered by the dirty chunks specified.
+    /// Dirty chunks missing from `chunks` are treated as empty. Chunks outside of the grid are ignored,
+    /// so the grid should be rebuilt from scratch when the loaded area changes.
+    pub fn update_chunks<'a>(
+        &mut self,
+        chunks: impl IntoIterator<Item = (IVec3, &'a VoxelChunk)>,
+        dirty: impl IntoIterator<Item = IVec3>
+    ) {
+        let chunks: HashMap<IVec3, &VoxelChunk> = chunks.into_iter().collect();
+        for coords in dirty {
+            let start = coords * self.chunk_size.as_ivec3();
+            if !self.in_bounds(start) {
+                continue;
+            }
+            self.write_chunk(coords, chunks.get(&coords).copied());
+
+            // Cells below the chunk can lose headroom, and the cells above it can gain or lose ground
+            let below = IVec3::Y * self.agent_height as i32;
+            self.update_walkable(start - below, start + self.chunk_size.as_ivec3() + IVec3::Y);
+        }
+    }
+
+    /// True if an agent can stand in the cell specified.
+    pub fn is_walkable(&self, cell: IVec3) -> bool {
+        self.index(cell).map(|idx| self.walkable.get(idx)).unwrap_or(false)
+    }
+
+    /// Finds the first walkable cell at or below the cell specified within the grid.
+    pub fn walkable_below(&self, cell: IVec3) -> Option<IVec3> {
+        let top = cell.y.min(self.min.y + self.size.y as i32 - 1);
+        (self.min.y..=top).rev()
+            .map(|y| IVec3::new(cell.x, y, cell.z))
+            .find(|cell| self.is_walkable(*cell))
+    }
+
+    /// Iterates over the walkable cells an agent can move to from the cell specified.
+    /// Neighbors are the four horizontal directions, stepping up or down at most `step_height` cells.
+    /// Slopes count as ramps, allowing a step of one cell even if `step_height` is zero.
+    pub fn neighbors(&self, cell: IVec3) -> impl Iterator<Item = IVec3> + '_ {
+        let step = self.step_height as i32;
+        DIRECTIONS.into_iter().flat_map(move |dir| {
+            (-step.max(1)..=step.max(1))
+                .map(move |dy| cell + dir + IVec3::Y * dy)
+                .filter(move |next| self.can_move(cell, *next))
+        })
+    }
+
+    fn can_move(&self, from: IVec3, to: IVec3) -> bool {
+        if !self.is_walkable(from) || !self.is_walkable(to) {
+            return false;
+        }
+        let dy = to.y - from.y;
+        let is_ramp = |cell: IVec3| self.index(cell).map(|idx| self.ramps.get(idx)).unwrap_or(false);
+        let max_step = if is_ramp(from) || is_ramp(to) {
+            self.step_height.max(1)
+        }
+        else {
+            self.step_height
+        };
+        if dy.unsigned_abs() > max_step {
+            return false;
+        }
+
+        // The lower of the two cells needs headroom up to the higher cell's head
+        let (low, high) = if dy > 0 { (from, to) } else { (to, from) };
+        let height = self.agent_height as i32;
+        (low.y + height..high.y + height).all(|y| !self.is_solid(IVec3::new(low.x, y, low.z)))
+    }
+
+    fn is_solid(&self, cell: IVec3) -> bool {
+        self.index(cell).map(|idx| self.solid.get(idx)).unwrap_or(false)
+    }
+
+    fn in_bounds(&self, cell: IVec3) -> bool {
+        let max = self.min + self.size.as_ivec3();
+        cell.cmpge(self.min).all() && cell.cmplt(max).all()
+    }
+
+    fn index(&self, cell: IVec3) -> Option<usize> {
+        if !self.in_bounds(cell) {
+            return None;
+        }
+        let local = (cell - self.min).as_uvec3();
+        Some((local.x + self.size.x * (local.y + self.size.y * local.z)) as usize)
+    }
+
+    /// Copies the solid and ramp bits of a chunk into the grid.
+    fn write_chunk(&mut self, coords: IVec3, chunk: Option<&VoxelChunk>) {
+        let start = coords * self.chunk_size.as_ivec3();
+        for z in 0..self.chunk_size.z {
+            for y in 0..self.chunk_size.y {
+                for x in 0..self.chunk_size.x {
+                    let local = UVec3::new(x, y, z);
+                    if let Some(idx) = self.index(start + local.as_ivec3()) {
+                        let data = chunk.and_then(|chunk| chunk.get_voxel(local));
+                        let solid = data.map(|data| data.is_solid()).unwrap_or(false);
+                        let ramp = solid && data.map(|data| data.voxel == Voxel::Slope).unwrap_or(false);
+                        self.solid.set(idx, solid);
+                        self.ramps.set(idx, ramp);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Recomputes the walkable bits of cells from `start` (inclusive) to `end` (exclusive).
+    fn update_walkable(&mut self, start: IVec3, end: IVec3) {
+        let start = start.max(self.min);
+        let end = end.min(self.min + self.size.as_ivec3());
+        for z in start.z..end.z {
+            for y in start.y..end.y {
+                for x in start.x..end.x {
+                    let cell = IVec3::new(x, y, z);
+                    let walkable = self.compute_walkable(cell);
+                    if let Some(idx) = self.index(cell) {
+                        self.walkable.set(idx, walkable);
+                    }
+                }
+            }
+        }
+    }
+
+    fn compute_walkable(&self, cell: IVec3) -> bool {
+        let is_ramp = |cell: IVec3| self.index(cell).map(|idx| self.ramps.get(idx)).unwrap_or(false);
+        let grounded = is_ramp(cell) ||
+            (!self.is_solid(cell) && self.is_solid(cell - IVec3::Y) && !is_ramp(cell - IVec3::Y));
+        grounded && (1..self.agent_height as i32).all(|dy| !self.is_solid(cell + IVec3::Y * dy))
+    }
+}
+
+/// Fixed size set of bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitGrid(Vec<u64>);
+impl BitGrid {
+    fn new(len: usize) -> Self {
+        Self(vec![0; len.div_ceil(64)])
+    }
+    fn get(&self, idx: usize) -> bool {
+        self.0[idx / 64] & (1 << (idx % 64)) != 0
+    }
+    fn set(&mut self, idx: usize, value: bool) {
+        let bit = 1 << (idx % 64);
+        if value {
+            self.0[idx / 64] |= bit;
+        }
+        else {
+            self.0[idx / 64] &= !bit;
+        }
+    }
+}
+
+/// Finds the shortest path between two walkable cells with A*.
+/// The path includes both the start and the goal. Returns None if the goal can't be reached.
+pub fn find_path(grid: &WalkableGrid, start: IVec3, goal: IVec3) -> Option<Vec<IVec3>> {
+    if !grid.is_walkable(start) || !grid.is_walkable(goal) {
+        return None;
+    }
+    let heuristic = |cell: IVec3| (cell.x - goal.x).unsigned_abs() + (cell.z - goal.z).unsigned_abs();
+    let mut open = BinaryHeap::from([Reverse((heuristic(start), 0, start.to_array()))]);
+    let mut came_from: HashMap<IVec3, IVec3> = HashMap::new();
+    let mut costs = HashMap::from([(start, 0)]);
+    while let Some(Reverse((_, cost, cell))) = open.pop() {
+        let cell = IVec3::from_array(cell);
+        if cell == goal {
+            let mut path = vec![goal];
+            while let Some(prev) = came_from.get(path.last().unwrap()) {
+                path.push(*prev);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if costs.get(&cell).is_some_and(|best| cost > *best) {
+            continue;
+        }
+        for next in grid.neighbors(cell) {
+            let next_cost = cost + 1;
+            let is_better = match costs.get(&next) {
+                Some(best) => next_cost < *best,
+                None => true
+            };
+            if is_better {
+                costs.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push(Reverse((next_cost + heuristic(next), next_cost, next.to_array())));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_math::prelude::*;
+
+    use crate::*;
+    use crate::nav::*;
+
+    /// 8x4x8 chunk with a floor at y = 0
+    fn floor_chunk() -> VoxelChunk {
+        let mut chunk = VoxelChunk::new(UVec3::new(8, 4, 8));
+        chunk.set_voxel_box(UVec3::ZERO, UVec3::new(8, 1, 8), VoxelData::new(Voxel::Cuboid));
+        chunk
+    }
+
+    #[test]
+    fn paths_around_wall() {
+        let mut chunk = floor_chunk();
+        chunk.set_voxel_box(UVec3::new(4, 1, 0), UVec3::new(5, 3, 7), VoxelData::new(Voxel::Cuboid));
+        let grid = WalkableGrid::from_chunks([(IVec3::ZERO, &chunk)], 2, 1);
+        assert!(grid.is_walkable(IVec3::new(0, 1, 0)));
+        assert!(!grid.is_walkable(IVec3::new(0, 2, 0)));
+        assert!(!grid.is_walkable(IVec3::new(4, 1, 0)));
+        assert_eq!(Some(IVec3::new(2, 1, 3)), grid.walkable_below(IVec3::new(2, 3, 3)));
+
+        // Wall is too tall to step over, so the path goes around its end at z = 7
+        let path = find_path(&grid, IVec3::new(2, 1, 0), IVec3::new(6, 1, 0)).unwrap();
+        assert_eq!(IVec3::new(2, 1, 0), path[0]);
+        assert_eq!(IVec3::new(6, 1, 0), *path.last().unwrap());
+        assert!(path.contains(&IVec3::new(4, 1, 7)));
+        assert_eq!(19, path.len());
+    }
+
+    #[test]
+    fn steps_and_ramps() {
+        let mut chunk = floor_chunk();
+        chunk.set_voxel(UVec3::new(3, 1, 0), VoxelData::new(Voxel::Cuboid));
+        chunk.set_voxel(UVec3::new(3, 1, 4), VoxelData::new(Voxel::Slope));
+        chunk.set_voxel(UVec3::new(4, 1, 4), VoxelData::new(Voxel::Cuboid));
+
+        // Can't step onto the block without a step height, but can walk up the ramp onto the next one
+        let grid = WalkableGrid::from_chunks([(IVec3::ZERO, &chunk)], 2, 0);
+        assert!(grid.is_walkable(IVec3::new(3, 1, 4)));
+        assert!(!grid.is_walkable(IVec3::new(3, 2, 4)));
+        assert!(!grid.neighbors(IVec3::new(2, 1, 0)).any(|cell| cell == IVec3::new(3, 2, 0)));
+        assert!(grid.neighbors(IVec3::new(3, 1, 4)).any(|cell| cell == IVec3::new(4, 2, 4)));
+        let grid = WalkableGrid::from_chunks([(IVec3::ZERO, &chunk)], 2, 1);
+        assert!(grid.neighbors(IVec3::new(2, 1, 0)).any(|cell| cell == IVec3::new(3, 2, 0)));
+    }
+
+    #[test]
+    fn updates_dirty_chunks() {
+        let chunk = floor_chunk();
+        let neighbor = floor_chunk();
+        let mut grid = WalkableGrid::from_chunks([(IVec3::ZERO, &chunk), (IVec3::X, &neighbor)], 2, 1);
+        assert!(find_path(&grid, IVec3::new(0, 1, 0), IVec3::new(15, 1, 0)).is_some());
+
+        // Cuts the neighbor off with a wall
+        let mut edited = neighbor.clone();
+        edited.set_voxel_box(UVec3::new(0, 1, 0), UVec3::new(1, 4, 8), VoxelData::new(Voxel::Cuboid));
+        grid.update_chunks([(IVec3::ZERO, &chunk), (IVec3::X, &edited)], [IVec3::X]);
+        assert!(find_path(&grid, IVec3::new(0, 1, 0), IVec3::new(15, 1, 0)).is_none());
+        assert_eq!(grid, WalkableGrid::from_chunks([(IVec3::ZERO, &chunk), (IVec3::X, &edited)], 2, 1));
+    }
+}
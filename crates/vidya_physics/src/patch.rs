@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+
+use bevy_math::prelude::*;
+
+use crate::{Error, VoxelChunk, VoxelData};
+
+/// Change of a single voxel in a [`ChunkPatch`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VoxelChange {
+    pub coords: UVec3,
+    pub old: VoxelData,
+    pub new: VoxelData
+}
+
+/// Voxels that differ between two versions of a [`VoxelChunk`], like before and after an edit.
+/// Can be applied to the old version to get the new one, or reverted on the new version to get the old one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkPatch {
+    size: UVec3,
+    changes: Vec<VoxelChange>
+}
+
+impl ChunkPatch {
+
+    /// Size of the chunks this patch applies to.
+    pub fn size(&self) -> UVec3 {
+        self.size
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &VoxelChange> {
+        self.changes.iter()
+    }
+
+    /// Patch that undoes this one.
+    pub fn inverse(&self) -> Self {
+        Self {
+            size: self.size,
+            changes: self.changes
+                .iter()
+                .map(|change| VoxelChange { coords: change.coords, old: change.new, new: change.old })
+                .collect()
+        }
+    }
+
+    /// Combines this patch with one applied after it, such that applying the result is the same as applying both in order.
+    /// Voxels that end up unchanged are dropped.
+    /// Panics if the patches have different sizes. See [`ChunkPatch::try_merge`].
+    pub fn merge(&mut self, later: &ChunkPatch) -> &mut Self {
+        self.try_merge(later).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Combines this patch with one applied after it, such that applying the result is the same as applying both in order.
+    /// Voxels that end up unchanged are dropped.
+    /// Fails if the patches have different sizes.
+    pub fn try_merge(&mut self, later: &ChunkPatch) -> Result<&mut Self, Error> {
+        check_size(self.size, later.size)?;
+        let mut changes: BTreeMap<[u32; 3], VoxelChange> = self.changes
+            .iter()
+            .map(|change| (sort_key(change.coords), *change))
+            .collect();
+        for change in &later.changes {
+            changes
+                .entry(sort_key(change.coords))
+                .and_modify(|existing| existing.new = change.new)
+                .or_insert(*change);
+        }
+        self.changes = changes
+            .into_values()
+            .filter(|change| change.old != change.new)
+            .collect();
+        Ok(self)
+    }
+}
+
+impl VoxelChunk {
+
+    /// Computes the patch that turns this chunk into `other`.
+    /// Panics if the chunks have different sizes. See [`VoxelChunk::try_diff`].
+    pub fn diff(&self, other: &VoxelChunk) -> ChunkPatch {
+        self.try_diff(other).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Computes the patch that turns this chunk into `other`.
+    /// Fails if the chunks have different sizes.
+    pub fn try_diff(&self, other: &VoxelChunk) -> Result<ChunkPatch, Error> {
+        check_size(self.size(), other.size())?;
+        let changes = self.iter()
+            .zip(other.iter())
+            .filter(|((old, _), (new, _))| old != new)
+            .map(|((old, coords), (new, _))| VoxelChange { coords, old: *old, new: *new })
+            .collect();
+        Ok(ChunkPatch { size: self.size(), changes })
+    }
+
+    /// Sets the voxels changed by a patch to their new values.
+    /// Panics if the patch has a different size. See [`VoxelChunk::try_apply_patch`].
+    pub fn apply_patch(&mut self, patch: &ChunkPatch) -> &mut Self {
+        self.try_apply_patch(patch).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Sets the voxels changed by a patch to their new values.
+    /// Fails if the patch has a different size.
+    pub fn try_apply_patch(&mut self, patch: &ChunkPatch) -> Result<&mut Self, Error> {
+        check_size(self.size(), patch.size)?;
+        for change in &patch.changes {
+            self.try_set_voxel(change.coords, change.new)?;
+        }
+        Ok(self)
+    }
+
+    /// Sets the voxels changed by a patch back to their old values.
+    /// Panics if the patch has a different size. See [`VoxelChunk::try_revert_patch`].
+    pub fn revert_patch(&mut self, patch: &ChunkPatch) -> &mut Self {
+        self.try_revert_patch(patch).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Sets the voxels changed by a patch back to their old values.
+    /// Fails if the patch has a different size.
+    pub fn try_revert_patch(&mut self, patch: &ChunkPatch) -> Result<&mut Self, Error> {
+        check_size(self.size(), patch.size)?;
+        for change in patch.changes.iter().rev() {
+            self.try_set_voxel(change.coords, change.old)?;
+        }
+        Ok(self)
+    }
+}
+
+fn check_size(expected: UVec3, found: UVec3) -> Result<(), Error> {
+    match expected == found {
+        true => Ok(()),
+        false => Err(Error::SizeMismatch { expected, found })
+    }
+}
+
+/// Orders voxels the same way they are stored in a chunk.
+fn sort_key(coords: UVec3) -> [u32; 3] {
+    [coords.z, coords.y, coords.x]
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_math::prelude::*;
+    use rand::prelude::*;
+
+    use crate::*;
+
+    /// Makes a random edit to a chunk
+    fn random_edit(chunk: &mut VoxelChunk, rng: &mut StdRng) {
+        let size = chunk.size();
+        let coords = UVec3::new(rng.gen_range(0..size.x), rng.gen_range(0..size.y), rng.gen_range(0..size.z));
+        let voxel = [Voxel::Empty, Voxel::Cuboid, Voxel::Slope][rng.gen_range(0..3)];
+        let data = VoxelData::new(voxel)
+            .with_orientation(Orientation::ZERO.with_y_rot(Degree::Ninty))
+            .with_flags(VoxelFlags::from_bits_truncate(rng.gen()));
+        if rng.gen_bool(0.5) {
+            chunk.set_voxel(coords, data);
+        }
+        else {
+            let dest = (coords + UVec3::new(rng.gen_range(1..3), 1, rng.gen_range(1..3))).min(size);
+            chunk.set_voxel_box(coords, dest, data);
+        }
+    }
+
+    #[test]
+    fn undo_random_edits() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let original = VoxelChunk::new(UVec3::new(6, 4, 5));
+        let mut chunk = original.clone();
+        let mut patches = Vec::new();
+        let mut merged = chunk.diff(&chunk);
+        for _ in 0..50 {
+            let before = chunk.clone();
+            random_edit(&mut chunk, &mut rng);
+            let patch = before.diff(&chunk);
+            assert_eq!(chunk, before.clone().apply_patch(&patch).clone());
+            merged.merge(&patch);
+            patches.push(patch);
+        }
+        assert_eq!(original.diff(&chunk), merged);
+
+        // Undoes every edit in reverse, then redoes them all at once
+        let edited = chunk.clone();
+        for patch in patches.iter().rev() {
+            chunk.revert_patch(patch);
+        }
+        assert_eq!(original, chunk);
+        chunk.apply_patch(&merged);
+        assert_eq!(edited, chunk);
+        chunk.apply_patch(&merged.inverse());
+        assert_eq!(original, chunk);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serialization_round_trip() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut chunk = VoxelChunk::new(UVec3::new(4, 4, 4));
+        let before = chunk.clone();
+        for _ in 0..10 {
+            random_edit(&mut chunk, &mut rng);
+        }
+        let patch = before.diff(&chunk);
+        let serialized = ron::to_string(&patch).unwrap();
+        let deserialized: ChunkPatch = ron::from_str(&serialized).unwrap();
+        assert_eq!(patch, deserialized);
+        assert_eq!(chunk, before.clone().apply_patch(&deserialized).clone());
+    }
+
+    #[test]
+    fn size_mismatch() {
+        let a = VoxelChunk::new(UVec3::new(2, 2, 2));
+        let b = VoxelChunk::new(UVec3::new(2, 3, 2));
+        assert_eq!(
+            Err(Error::SizeMismatch { expected: UVec3::new(2, 2, 2), found: UVec3::new(2, 3, 2) }),
+            a.try_diff(&b)
+        );
+    }
+}
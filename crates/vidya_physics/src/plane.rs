@@ -0,0 +1,90 @@
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+
+use crate::{PhysicsConfig, PlaneAxis, Shape, Velocity, AABB};
+
+/// Zeroes velocity perpendicular to the plane physics is locked to, if any.
+pub(crate) fn lock_velocities_to_plane(config: Res<PhysicsConfig>, mut velocities: Query<&mut Velocity>) {
+    let Some(plane) = config.plane_lock else { return };
+    let keep = Vec3::ONE - plane.normal();
+    for mut vel in &mut velocities {
+        if vel.0 * keep != vel.0 {
+            vel.0 *= keep;
+        }
+    }
+}
+
+/// Bounds a shape collides with when physics is locked to a plane.
+/// Everything gets moved onto the plane, so bodies collide regardless of their depth.
+/// Chunks are placed so that only their first layer of voxels along the plane's normal lines up with bodies,
+/// which makes them act one voxel deep.
+pub(crate) fn flatten_to_plane(bounds: AABB, shape: &Shape, plane: PlaneAxis) -> AABB {
+    let normal = plane.normal();
+    let keep = Vec3::ONE - normal;
+    let (center, half_extent) = match shape {
+        Shape::VoxelChunk(chunk) => {
+            let depth = chunk.size().as_vec3().dot(normal);
+            (depth / 2.0 - 0.5, depth / 2.0)
+        },
+        _ if bounds.half_extents.dot(normal) > 0.0 => (0.0, 0.5),
+        _ => (0.0, 0.0)
+    };
+    AABB::new(bounds.center * keep + normal * center, bounds.half_extents * keep + normal * half_extent)
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    fn plane_app() -> App {
+        let mut app = physics_test_app();
+        app
+            .insert_resource(PhysicsConfig { plane_lock: Some(PlaneAxis::XY), ..Default::default() })
+            .insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        app
+    }
+
+    #[test]
+    fn bodies_stay_on_their_plane() {
+        let mut app = plane_app();
+        let body = app.world
+            .spawn(PhysicsBundle::new(Transform::from_xyz(0.0, 0.0, 3.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                .with_velocity(Velocity(Vec3::new(0.5, 0.0, 0.5)))
+            )
+            .id();
+        for _ in 0..5 {
+            app.update();
+        }
+        let translation = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert_eq!(3.0, translation.z);
+        assert!(translation.x > 2.0);
+        assert_eq!(0.0, app.world.get::<Velocity>(body).unwrap().0.z);
+    }
+
+    #[test]
+    fn collides_regardless_of_depth() {
+        let mut app = plane_app();
+
+        // Chunk whose only solid voxels are in its first layer, far from the body in Z
+        let mut chunk = VoxelChunk::new(UVec3::new(4, 1, 4));
+        chunk.set_voxel_plane(0, UVec2::ZERO, UVec2::new(4, 1), PlaneAxis::XY, VoxelData::new(Voxel::Cuboid));
+        spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, 0.0, -20.0), HalfExtents::new(4.0, 1.0, 4.0), Shape::VoxelChunk(chunk));
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.1, 5.0), HalfExtents::new(1.0, 1.0, 0.2), Shape::Cuboid)
+            })
+            .id();
+        for _ in 0..5 {
+            app.update();
+        }
+        let translation = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert!((translation.y - 1.0).abs() < 0.001);
+        assert_eq!(5.0, translation.z);
+    }
+}
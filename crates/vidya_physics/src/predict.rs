@@ -0,0 +1,167 @@
+//! Dead reckoning helpers that predict where a physics object will be in the future.
+//! Predictions integrate in the same order as the engine (gravity, friction, then substepped movement).
+
+use bevy_math::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::{collide_cuboid_cuboid, PhysicsConfig, AABB};
+
+/// Predicts the positions of an object for each of the next `ticks` ticks, assuming no collisions.
+/// `damping` is the velocity kept per tick, which is its [`Friction`](crate::Friction) raised to the timestep in seconds.
+/// Uses the default [`PhysicsConfig`].
+pub fn predict_transform(
+    current: &Transform,
+    vel: Vec3,
+    gravity: Vec3,
+    damping: Vec3,
+    ticks: u32
+) -> Vec<Vec3> {
+    predict_transform_with_config(current, vel, gravity, damping, ticks, &PhysicsConfig::default())
+}
+
+/// Same as [`predict_transform`], but with the substeps and [`PhysicsConfig::time_scale`] of the config specified.
+pub fn predict_transform_with_config(
+    current: &Transform,
+    mut vel: Vec3,
+    gravity: Vec3,
+    damping: Vec3,
+    ticks: u32,
+    config: &PhysicsConfig
+) -> Vec<Vec3> {
+    if config.time_scale <= 0.0 {
+        return vec![current.translation; ticks as usize];
+    }
+    let inv_steps = config.time_scale / config.substeps as f32;
+    let mut pos = current.translation;
+    let mut positions = Vec::with_capacity(ticks as usize);
+    for _ in 0..ticks {
+        vel += gravity;
+        vel *= damping;
+        for _ in 0..config.substeps {
+            pos += vel * inv_steps;
+        }
+        positions.push(pos);
+    }
+    positions
+}
+
+/// Predicts the positions of a cuboid object for each of the next `ticks` ticks,
+/// stopping at the first substep where it would hit one of the colliders specified.
+/// The last position returned is the position the object would be pushed to during that collision.
+pub fn predict_until_collision(
+    body: AABB,
+    mut vel: Vec3,
+    gravity: Vec3,
+    damping: Vec3,
+    ticks: u32,
+    colliders: &[AABB],
+    config: &PhysicsConfig
+) -> Vec<Vec3> {
+    if config.time_scale <= 0.0 {
+        return vec![body.center; ticks as usize];
+    }
+    let inv_steps = config.time_scale / config.substeps as f32;
+    let mut pos = body.center;
+    let mut positions = Vec::with_capacity(ticks as usize);
+    for _ in 0..ticks {
+        vel += gravity;
+        vel *= damping;
+        for _ in 0..config.substeps {
+            let step = vel * inv_steps;
+            let aabb = AABB::new(pos, body.half_extents);
+            let coll = colliders
+                .iter()
+                .filter_map(|collider| collide_cuboid_cuboid(*collider, aabb, step))
+                .min_by(|a, b| a.t.total_cmp(&b.t));
+            if let Some(coll) = coll {
+                positions.push(pos + step + coll.position_delta);
+                return positions;
+            }
+            pos += step;
+        }
+        positions.push(pos);
+    }
+    positions
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+    use vidya_fixed_timestep::CurrentTransform;
+
+    use crate::*;
+    use crate::predict::*;
+
+    #[test]
+    fn matches_simulation() {
+        let gravity = Vec3::new(0.0, -0.01, 0.0);
+        let damping = Vec3::new(0.99, 0.98, 0.97);
+        let vel = Vec3::new(0.05, 0.2, -0.03);
+        let transform = Transform::from_xyz(1.0, 5.0, -2.0);
+
+        // Simulates a free-falling box
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(gravity));
+        let entity = app.world
+            .spawn(PhysicsBundle::new(transform, HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                .with_velocity(Velocity(vel))
+                .with_friction(Friction(damping))
+            )
+            .id();
+        let mut simulated = Vec::new();
+        for _ in 0..30 {
+            app.update();
+            simulated.push(app.world.get::<CurrentTransform>(entity).unwrap().0.translation);
+        }
+
+        let predicted = predict_transform(&transform, vel, gravity, damping, 30);
+        assert_eq!(simulated, predicted);
+    }
+
+    #[test]
+    fn matches_simulation_in_bullet_time() {
+        let gravity = Vec3::new(0.0, -0.01, 0.0);
+        let vel = Vec3::new(0.05, 0.2, -0.03);
+        let transform = Transform::from_xyz(1.0, 5.0, -2.0);
+        let config = PhysicsConfig { time_scale: 0.3, substeps: 3, ..PhysicsConfig::default() };
+
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(gravity)).insert_resource(config);
+        let entity = app.world
+            .spawn(PhysicsBundle::new(transform, HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                .with_velocity(Velocity(vel))
+                .with_friction(Friction(Vec3::ONE))
+            )
+            .id();
+        let mut simulated = Vec::new();
+        for _ in 0..30 {
+            app.update();
+            simulated.push(app.world.get::<CurrentTransform>(entity).unwrap().0.translation);
+        }
+
+        let predicted = predict_transform_with_config(&transform, vel, gravity, Vec3::ONE, 30, &config);
+        assert_eq!(simulated, predicted);
+
+        // Nothing moves while time is stopped
+        let stopped = PhysicsConfig { time_scale: 0.0, ..config };
+        assert_eq!(vec![transform.translation; 5], predict_transform_with_config(&transform, vel, gravity, Vec3::ONE, 5, &stopped));
+    }
+
+    #[test]
+    fn stops_at_collision() {
+        let floor = AABB::new(Vec3::ZERO, Vec3::new(5.0, 0.5, 5.0));
+        let positions = predict_until_collision(
+            AABB::new(Vec3::new(0.0, 3.0, 0.0), Vec3::splat(0.5)),
+            Vec3::ZERO,
+            Vec3::new(0.0, -0.1, 0.0),
+            Vec3::ONE,
+            100,
+            &[floor],
+            &PhysicsConfig::default()
+        );
+        assert!(positions.len() < 100);
+        assert!((positions.last().unwrap().y - 1.0).abs() < 0.0001);
+    }
+}
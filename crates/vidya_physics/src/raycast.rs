@@ -0,0 +1,427 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+use bevy_math::prelude::*;
+
+use crate::{
+    collide, contains, overlaps, AxisPriority, CollisionConfig, CollisionGroups, CurrentTransform, HalfExtents, PhysObj, Sensor, Shape, Voxel,
+    VoxelChunk, AABB
+};
+
+/// What a ray cast with [`PhysicsWorld::raycast`] ran into.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RayHit {
+    /// Entity that was hit
+    pub entity: Entity,
+    /// Distance from the ray's origin to the hit
+    pub distance: f32,
+    /// Point where the ray entered the entity
+    pub point: Vec3,
+    /// Normal of the surface hit, facing against the ray
+    pub normal: Vec3
+}
+
+/// What a box swept with [`PhysicsWorld::shape_cast`] ran into.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ShapeCastHit {
+    /// Entity that was hit
+    pub entity: Entity,
+    /// Distance the box traveled before hitting the entity
+    pub distance: f32,
+    /// Normal of the surface hit, facing against the box's movement
+    pub normal: Vec3
+}
+
+/// Voxel a ray cast with [`VoxelChunk::raycast`] ran into.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VoxelRayHit {
+    /// Coordinates of the voxel hit
+    pub coords: UVec3,
+    /// Normal of the face the ray entered the voxel through. Zero if the ray started inside it.
+    pub normal: Vec3,
+    /// Distance from the ray's origin to the hit
+    pub distance: f32
+}
+
+/// System param for querying the physics objects of the world, like for hitscan weapons and ground probes.
+/// Reads their positions as of the last tick. Sensors are never hit.
+#[derive(SystemParam)]
+pub struct PhysicsWorld<'w, 's> {
+    objects: Query<'w, 's, (Entity, &'static CurrentTransform, &'static HalfExtents, &'static Shape, &'static CollisionConfig), Without<Sensor>>
+}
+
+impl<'w, 's> PhysicsWorld<'w, 's> {
+
+    /// Casts a ray from `origin` in direction `dir`, returning the closest hit within `max_dist`.
+    /// Only objects in at least one of the groups of `filter` get hit. Rays starting inside an object hit it at distance 0.
+    /// Boxes and voxel chunks can be hit, with slopes hit like full voxels. Capsules and spheres are skipped for now.
+    /// Every object is checked, so this is best kept to a few casts per tick.
+    pub fn raycast(&self, origin: Vec3, dir: Vec3, max_dist: f32, filter: CollisionGroups) -> Option<RayHit> {
+        let dir = dir.try_normalize()?;
+        let mut closest: Option<RayHit> = None;
+        for (entity, trans, extents, shape, config) in &self.objects {
+            if config.groups & filter == 0 {
+                continue;
+            }
+
+            // Only looks as far as the closest hit so far
+            let max_dist = closest.map_or(max_dist, |hit| hit.distance);
+            let bounds = AABB::new(trans.0.translation, extents.0);
+            let hit = match shape {
+                Shape::Cuboid => raycast_aabb(origin, dir, max_dist, bounds),
+                Shape::VoxelChunk(chunk) => chunk
+                    .raycast_world(bounds, origin, dir, max_dist)
+                    .map(|hit| (hit.distance, if hit.normal == Vec3::ZERO { -dir } else { hit.normal })),
+                _ => None
+            };
+            if let Some((distance, normal)) = hit {
+                closest = Some(RayHit { entity, distance, point: origin + dir * distance, normal });
+            }
+        }
+        closest
+    }
+
+    /// Sweeps box `aabb` along `dir`, returning the first object it runs into within `max_dist`, like for ground probes and ledge checks.
+    /// Only objects in at least one of the groups of `filter` get hit. Uses the same sweeps as the collision update,
+    /// so hits line up with what a box moving the same way would collide with.
+    /// Boxes starting inside an object hit it at distance 0, with a normal facing against `dir`.
+    pub fn shape_cast(&self, aabb: AABB, dir: Vec3, max_dist: f32, filter: CollisionGroups) -> Option<ShapeCastHit> {
+        let dir = dir.try_normalize()?;
+        let mut closest: Option<ShapeCastHit> = None;
+        for (entity, trans, extents, shape, config) in &self.objects {
+            if config.groups & filter == 0 {
+                continue;
+            }
+            let bounds = AABB::new(trans.0.translation, extents.0);
+            let hit = if overlaps(bounds, shape, aabb) {
+                Some((0.0, -dir))
+            }
+            else {
+                let object = PhysObj { aabb: bounds, shape, vel: Vec3::ZERO };
+                let caster = PhysObj { aabb, shape: &Shape::Cuboid, vel: dir * max_dist };
+                collide(object, caster, AxisPriority::default()).map(|coll| (coll.t * max_dist, coll.normal_a))
+            };
+            let Some((distance, normal)) = hit else { continue };
+            if !closest.is_some_and(|hit| hit.distance <= distance) {
+                closest = Some(ShapeCastHit { entity, distance, normal });
+            }
+        }
+        closest
+    }
+
+    /// Every object overlapping box `aabb`, like for an explosion damaging everything within it.
+    /// Only objects in at least one of the groups of `filter` are included. Objects that only touch the box aren't.
+    /// Voxel chunks only overlap the box if one of their solid voxels does.
+    pub fn overlaps_aabb(&self, aabb: AABB, filter: CollisionGroups) -> Vec<Entity> {
+        self.objects
+            .iter()
+            .filter(|(_, trans, extents, shape, config)| {
+                config.groups & filter != 0 && overlaps(AABB::new(trans.0.translation, extents.0), shape, aabb)
+            })
+            .map(|(entity, ..)| entity)
+            .collect()
+    }
+
+    /// Every object containing the point specified, surface included.
+    /// Only objects in at least one of the groups of `filter` are included.
+    /// Voxel chunks only contain the point if one of their solid voxels does.
+    pub fn contains_point(&self, point: Vec3, filter: CollisionGroups) -> Vec<Entity> {
+        self.objects
+            .iter()
+            .filter(|(_, trans, extents, shape, config)| {
+                config.groups & filter != 0 && contains(AABB::new(trans.0.translation, extents.0), shape, point)
+            })
+            .map(|(entity, ..)| entity)
+            .collect()
+    }
+}
+
+impl VoxelChunk {
+
+    /// Casts a ray through the voxels of this chunk, returning the first voxel that isn't [`Voxel::Empty`] within `max_dist`.
+    /// Measured in voxels, with voxel (0, 0, 0) spanning (0, 0, 0) to (1, 1, 1).
+    /// Walks the voxels the ray passes through in order, so only those get checked.
+    pub fn raycast(&self, local_origin: Vec3, dir: Vec3, max_dist: f32) -> Option<VoxelRayHit> {
+        self.traverse(local_origin, dir.try_normalize()?, max_dist)
+    }
+
+    /// Same as [`VoxelChunk::raycast`], but for a chunk filling `bounds`, with the ray and distances in world space.
+    pub fn raycast_world(&self, bounds: AABB, origin: Vec3, dir: Vec3, max_dist: f32) -> Option<VoxelRayHit> {
+        let voxel_size = bounds.size() / self.size().as_vec3();
+        let local_origin = (origin - bounds.center + bounds.half_extents) / voxel_size;
+        self.traverse(local_origin, dir.try_normalize()? / voxel_size, max_dist)
+    }
+
+    /// Amanatides-Woo traversal of the voxels along a ray, with distances measured in lengths of `dir`.
+    fn traverse(&self, origin: Vec3, dir: Vec3, max_dist: f32) -> Option<VoxelRayHit> {
+
+        // Starts where the ray enters the chunk, in the voxel on the inner side of the face entered through
+        let size = self.size().as_vec3();
+        let (mut distance, entered) = raycast_aabb(origin, dir, max_dist, AABB::new(size / 2.0, size / 2.0))?;
+        let mut normal = if distance > 0.0 { entered } else { Vec3::ZERO };
+        let mut start = (origin + dir * distance).floor().clamp(Vec3::ZERO, size - 1.0);
+        for axis in 0..3 {
+            if normal[axis] != 0.0 {
+                start[axis] = if normal[axis] < 0.0 { 0.0 } else { size[axis] - 1.0 };
+            }
+        }
+        let mut coords = start.as_uvec3();
+
+        // Distance to the next voxel boundary along each axis, and between boundaries
+        let mut next = Vec3::splat(f32::INFINITY);
+        let mut delta = Vec3::splat(f32::INFINITY);
+        for axis in 0..3 {
+            if dir[axis] > 0.0 {
+                next[axis] = (coords[axis] as f32 + 1.0 - origin[axis]) / dir[axis];
+                delta[axis] = 1.0 / dir[axis];
+            }
+            else if dir[axis] < 0.0 {
+                next[axis] = (coords[axis] as f32 - origin[axis]) / dir[axis];
+                delta[axis] = -1.0 / dir[axis];
+            }
+        }
+        loop {
+            if self.get_voxel(coords)?.voxel != Voxel::Empty {
+                return Some(VoxelRayHit { coords, normal, distance });
+            }
+
+            // Steps into the neighbor across the closest boundary
+            let axis = if next.x < next.y && next.x < next.z { 0 } else if next.y < next.z { 1 } else { 2 };
+            distance = next[axis];
+            if distance > max_dist {
+                return None;
+            }
+            normal = Vec3::ZERO;
+            if dir[axis] > 0.0 {
+                coords[axis] += 1;
+                normal[axis] = -1.0;
+            }
+            else {
+                coords[axis] = coords[axis].checked_sub(1)?;
+                normal[axis] = 1.0;
+            }
+            next[axis] += delta[axis];
+        }
+    }
+}
+
+/// Distance along a ray to where it enters box `aabb`, in lengths of `dir`, and the normal of the face it enters through.
+/// Uses slab intersection, clipping the ray against the pair of planes bounding the box along each axis.
+/// Rays starting inside the box hit it at distance 0, with a normal facing against the ray.
+pub(crate) fn raycast_aabb(origin: Vec3, dir: Vec3, max_dist: f32, aabb: AABB) -> Option<(f32, Vec3)> {
+    let (min, max) = (aabb.center - aabb.half_extents, aabb.center + aabb.half_extents);
+    let mut enter = 0.0;
+    let mut exit = max_dist;
+    let mut normal = -dir;
+    for axis in 0..3 {
+
+        // Parallel to the slab, so either always in it or never
+        if dir[axis] == 0.0 {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let near = if dir[axis] > 0.0 { min[axis] } else { max[axis] };
+        let far = if dir[axis] > 0.0 { max[axis] } else { min[axis] };
+        let near_t = (near - origin[axis]) / dir[axis];
+        let far_t = (far - origin[axis]) / dir[axis];
+        if near_t > enter {
+            enter = near_t;
+            normal = Vec3::ZERO;
+            normal[axis] = -dir[axis].signum();
+        }
+        exit = exit.min(far_t);
+        if enter > exit {
+            return None;
+        }
+    }
+    Some((enter, normal))
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+    use bevy_ecs::system::SystemState;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    /// Spawns a wall in front of a floor, and returns both
+    fn spawn_level(app: &mut App) -> (Entity, Entity) {
+        let floor = spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(20.0, 1.0, 20.0), Shape::Cuboid).id();
+        let wall = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_NONE),
+                ..PhysicsBundle::new(Transform::from_xyz(5.0, 1.0, 0.0), HalfExtents::new(2.0, 2.0, 2.0), Shape::Cuboid)
+            })
+            .id();
+        app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(2.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert(Sensor);
+        (floor, wall)
+    }
+
+    fn raycast(app: &mut App, origin: Vec3, dir: Vec3, max_dist: f32, filter: CollisionGroups) -> Option<RayHit> {
+        let mut state = SystemState::<PhysicsWorld>::new(&mut app.world);
+        state.get(&app.world).raycast(origin, dir, max_dist, filter)
+    }
+
+    #[test]
+    fn hits_closest_box() {
+        let mut app = App::new();
+        let (floor, wall) = spawn_level(&mut app);
+
+        // Passes through the sensor and hits the wall behind it
+        let hit = raycast(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::new(2.0, 0.0, 0.0), 10.0, GROUP_ALL);
+        assert_eq!(Some(RayHit { entity: wall, distance: 4.0, point: Vec3::new(4.0, 1.0, 0.0), normal: Vec3::NEG_X }), hit);
+
+        // Probes for the ground diagonally
+        let hit = raycast(&mut app, Vec3::new(0.0, 2.0, 0.0), Vec3::new(-1.0, -1.0, 0.0), 10.0, GROUP_ALL).unwrap();
+        assert_eq!((floor, Vec3::Y), (hit.entity, hit.normal));
+        assert!((hit.point - Vec3::new(-2.0, 0.0, 0.0)).length() < 0.0001);
+        assert!((hit.distance - 8.0_f32.sqrt()).abs() < 0.0001);
+
+        // Starts inside the wall
+        let hit = raycast(&mut app, Vec3::new(5.0, 1.0, 0.0), Vec3::X, 10.0, GROUP_ALL);
+        assert_eq!(Some(RayHit { entity: wall, distance: 0.0, point: Vec3::new(5.0, 1.0, 0.0), normal: Vec3::NEG_X }), hit);
+    }
+
+    #[test]
+    fn misses() {
+        let mut app = App::new();
+        spawn_level(&mut app);
+        assert_eq!(None, raycast(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::X, 3.0, GROUP_ALL));
+        assert_eq!(None, raycast(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::Y, 10.0, GROUP_ALL));
+        assert_eq!(None, raycast(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 0.0, 1.0), 10.0, GROUP_ALL));
+        assert_eq!(None, raycast(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::ZERO, 10.0, GROUP_ALL));
+    }
+
+    /// Floor with a wall of two voxels on it
+    fn test_chunk() -> VoxelChunk {
+        let mut chunk = VoxelChunk::new(UVec3::new(4, 4, 4));
+        chunk
+            .set_voxel_box(UVec3::ZERO, UVec3::new(4, 1, 4), VoxelData::new(Voxel::Cuboid))
+            .set_voxel_box(UVec3::new(1, 1, 1), UVec3::new(3, 2, 2), VoxelData::new(Voxel::Cuboid));
+        chunk
+    }
+
+    #[test]
+    fn shape_casts() {
+        let mut app = App::new();
+        let (floor, wall) = spawn_level(&mut app);
+        let cast = |app: &mut App, center: Vec3, dir: Vec3, max_dist: f32| {
+            let mut state = SystemState::<PhysicsWorld>::new(&mut app.world);
+            state.get(&app.world).shape_cast(AABB::new(center, Vec3::splat(0.5)), dir, max_dist, GROUP_ALL)
+        };
+
+        // Probes for floor below, then passes through the sensor into the wall
+        assert_eq!(Some(ShapeCastHit { entity: floor, distance: 0.5, normal: Vec3::Y }), cast(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::NEG_Y, 1.0));
+        assert_eq!(Some(ShapeCastHit { entity: wall, distance: 3.5, normal: Vec3::NEG_X }), cast(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::X, 10.0));
+
+        // Falls short of the floor, casts into empty space, and walks off a ledge
+        assert_eq!(None, cast(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::NEG_Y, 0.4));
+        assert_eq!(None, cast(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::Y, 10.0));
+        assert_eq!(None, cast(&mut app, Vec3::new(11.0, 1.0, 0.0), Vec3::NEG_Y, 1.0));
+
+        // Starts inside the wall
+        assert_eq!(Some(ShapeCastHit { entity: wall, distance: 0.0, normal: Vec3::NEG_Y }), cast(&mut app, Vec3::new(5.0, 1.0, 0.0), Vec3::Y, 1.0));
+    }
+
+    #[test]
+    fn finds_overlapping_objects() {
+        let mut app = App::new();
+        let (floor, wall) = spawn_level(&mut app);
+        let chunk = spawn_static_floor(&mut app.world, Transform::from_xyz(-6.0, 2.0, -6.0), HalfExtents::new(4.0, 4.0, 4.0), Shape::VoxelChunk(test_chunk())).id();
+        let mut state = SystemState::<PhysicsWorld>::new(&mut app.world);
+        let world = state.get(&app.world);
+        let overlapping = |center: Vec3, filter: CollisionGroups| {
+            let mut entities = world.overlaps_aabb(AABB::new(center, Vec3::splat(1.0)), filter);
+            entities.sort();
+            entities
+        };
+        let containing = |point: Vec3| {
+            let mut entities = world.contains_point(point, GROUP_ALL);
+            entities.sort();
+            entities
+        };
+
+        // Skips the sensor, objects only touching the box, and objects outside of the filter
+        assert_eq!(vec![floor], overlapping(Vec3::new(2.0, 0.5, 0.0), GROUP_ALL));
+        assert_eq!(vec![floor, wall], overlapping(Vec3::new(3.5, 0.5, 0.0), GROUP_ALL));
+        assert_eq!(vec![wall], overlapping(Vec3::new(3.5, 0.5, 0.0), GROUP_BASIC));
+        assert_eq!(Vec::<Entity>::new(), overlapping(Vec3::new(0.0, 10.0, 0.0), GROUP_ALL));
+
+        // Only overlaps the chunk where its voxels are
+        assert_eq!(vec![floor, chunk], overlapping(Vec3::new(-6.0, 0.5, -6.0), GROUP_ALL));
+        assert_eq!(Vec::<Entity>::new(), overlapping(Vec3::new(-6.0, 3.0, -6.0), GROUP_ALL));
+        assert_eq!(vec![chunk], overlapping(Vec3::new(-6.0, 1.5, -6.0), GROUP_ALL));
+
+        // Contains points on surfaces, and inside solid voxels
+        assert_eq!(vec![floor, wall], containing(Vec3::new(4.0, 0.0, 1.0)));
+        assert_eq!(vec![wall], containing(Vec3::new(5.0, 1.0, 0.0)));
+        assert_eq!(vec![chunk], containing(Vec3::new(-6.5, 1.5, -6.5)));
+        assert_eq!(Vec::<Entity>::new(), containing(Vec3::new(-6.5, 1.5, -5.5)));
+        assert_eq!(vec![chunk], containing(Vec3::new(-4.0, 0.5, -4.0)));
+    }
+
+    #[test]
+    fn raycasts_through_chunk_voxels() {
+        let chunk = test_chunk();
+        let hit = |origin: Vec3, dir: Vec3, max_dist: f32| chunk
+            .raycast(origin, dir, max_dist)
+            .map(|hit| (hit.coords, hit.normal, hit.distance));
+
+        // Hits the wall's outer faces, not the faces shared by its voxels
+        assert_eq!(Some((UVec3::new(2, 1, 1), Vec3::X, 7.0)), hit(Vec3::new(10.0, 1.5, 1.5), Vec3::NEG_X, 20.0));
+        assert_eq!(Some((UVec3::new(1, 1, 1), Vec3::NEG_X, 0.5)), hit(Vec3::new(0.5, 1.5, 1.5), Vec3::X, 20.0));
+        assert_eq!(Some((UVec3::new(2, 1, 1), Vec3::NEG_Z, 0.5)), hit(Vec3::new(2.5, 1.5, 0.5), Vec3::Z, 20.0));
+        assert_eq!(Some((UVec3::new(1, 1, 1), Vec3::Y, 1.0)), hit(Vec3::new(1.5, 3.0, 1.5), Vec3::NEG_Y, 20.0));
+
+        // Walks diagonally down to the floor
+        let (coords, normal, distance) = hit(Vec3::new(0.25, 3.5, 3.5), Vec3::new(1.0, -1.0, 0.0), 20.0).unwrap();
+        assert_eq!((UVec3::new(2, 0, 3), Vec3::Y), (coords, normal));
+        assert!((distance - 2.5 * 2.0_f32.sqrt()).abs() < 0.0001, "{distance}");
+
+        // Enters the chunk from outside, right onto the floor
+        assert_eq!(Some((UVec3::new(3, 0, 2), Vec3::X, 1.0)), hit(Vec3::new(5.0, 0.5, 2.5), Vec3::NEG_X, 20.0));
+
+        // Starts inside a voxel
+        assert_eq!(Some((UVec3::new(0, 0, 0), Vec3::ZERO, 0.0)), hit(Vec3::new(0.5, 0.5, 0.5), Vec3::Y, 20.0));
+
+        // Misses
+        assert_eq!(None, hit(Vec3::new(10.0, 1.5, 1.5), Vec3::NEG_X, 6.0));
+        assert_eq!(None, hit(Vec3::new(0.5, 1.5, 3.5), Vec3::X, 20.0));
+        assert_eq!(None, hit(Vec3::new(0.5, 3.5, 3.5), Vec3::Y, 20.0));
+        assert_eq!(None, hit(Vec3::new(-1.0, 0.5, 0.5), Vec3::NEG_X, 20.0));
+    }
+
+    #[test]
+    fn raycasts_chunks_in_world() {
+        let mut app = App::new();
+        let chunk = spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, 4.0, 0.0), HalfExtents::new(8.0, 8.0, 8.0), Shape::VoxelChunk(test_chunk())).id();
+
+        // Voxels are twice as big as in the chunk's local space
+        let hit = raycast(&mut app, Vec3::new(-1.0, 10.0, -1.0), Vec3::NEG_Y, 20.0, GROUP_ALL);
+        assert_eq!(Some(RayHit { entity: chunk, distance: 6.0, point: Vec3::new(-1.0, 4.0, -1.0), normal: Vec3::Y }), hit);
+        let hit = raycast(&mut app, Vec3::new(10.0, 3.0, -1.0), Vec3::NEG_X, 20.0, GROUP_ALL);
+        assert_eq!(Some(RayHit { entity: chunk, distance: 8.0, point: Vec3::new(2.0, 3.0, -1.0), normal: Vec3::X }), hit);
+        let bounds = AABB::new(Vec3::new(0.0, 4.0, 0.0), Vec3::splat(4.0));
+        let hit = test_chunk().raycast_world(bounds, Vec3::new(10.0, 3.0, -1.0), Vec3::NEG_X, 20.0).unwrap();
+        assert_eq!((UVec3::new(2, 1, 1), 8.0), (hit.coords, hit.distance));
+    }
+
+    #[test]
+    fn skips_filtered_out_groups() {
+        let mut app = App::new();
+        let (floor, _) = spawn_level(&mut app);
+        assert_eq!(None, raycast(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::X, 10.0, GROUP_STATIC_TERRAIN));
+        let hit = raycast(&mut app, Vec3::new(5.0, 5.0, 0.0), Vec3::NEG_Y, 10.0, GROUP_STATIC_TERRAIN).unwrap();
+        assert_eq!((floor, 5.0), (hit.entity, hit.distance));
+    }
+}
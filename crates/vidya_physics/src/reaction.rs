@@ -0,0 +1,167 @@
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+
+use crate::{Contacts, Velocity};
+
+/// Built-in reaction an [`Entity`] has to touching something.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CollisionReaction {
+    /// Despawns the entity. Reactions listed after this one don't run.
+    DespawnSelf,
+    /// Fires a [`NamedCollisionEvent`] with the name specified for every entity touched.
+    EmitNamedEvent(String),
+    /// Bounces the entity off of the surfaces touched, like the ball in a brick breaker.
+    /// Speed into a surface is reversed and multiplied by the restitution factor specified.
+    ReflectVelocity(f32)
+}
+
+/// Reactions an [`Entity`] has to touching something, run in order on every tick it has [`Contacts`].
+/// Saves filtering a global event stream for entity-specific behavior.
+#[derive(Component, Debug, Clone, PartialEq, Default)]
+pub struct CollisionReactions {
+    pub reactions: Vec<CollisionReaction>,
+    /// Velocity before the physics update, which [`CollisionReaction::ReflectVelocity`] reflects
+    incoming_velocity: Vec3
+}
+impl CollisionReactions {
+    pub fn new(reactions: impl IntoIterator<Item = CollisionReaction>) -> Self {
+        Self {
+            reactions: reactions.into_iter().collect(),
+            incoming_velocity: Vec3::ZERO
+        }
+    }
+    pub fn with_reaction(mut self, reaction: CollisionReaction) -> Self {
+        self.reactions.push(reaction);
+        self
+    }
+}
+
+/// Event fired by [`CollisionReaction::EmitNamedEvent`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NamedCollisionEvent {
+    pub name: String,
+    /// Entity with the reaction
+    pub entity: Entity,
+    /// Entity touched
+    pub other: Entity
+}
+
+/// Remembers velocities before the physics update, since collisions cancel out speed into surfaces.
+pub(crate) fn record_incoming_velocities(mut reacting: Query<(&mut CollisionReactions, &Velocity)>) {
+    for (mut reactions, vel) in &mut reacting {
+        reactions.incoming_velocity = vel.0;
+    }
+}
+
+/// Runs the reactions of entities that touched something this tick.
+pub(crate) fn run_collision_reactions(
+    mut commands: Commands,
+    mut reacting: Query<(Entity, &CollisionReactions, &Contacts, Option<&mut Velocity>)>,
+    mut event_writer: EventWriter<NamedCollisionEvent>
+) {
+    for (entity, reactions, contacts, mut vel) in &mut reacting {
+        if contacts.is_empty() {
+            continue;
+        }
+        for reaction in &reactions.reactions {
+            match reaction {
+                CollisionReaction::DespawnSelf => {
+                    commands.entity(entity).despawn();
+                    break;
+                },
+                CollisionReaction::EmitNamedEvent(name) => {
+                    for contact in contacts.iter() {
+                        event_writer.send(NamedCollisionEvent { name: name.clone(), entity, other: contact.entity });
+                    }
+                },
+                CollisionReaction::ReflectVelocity(restitution) => {
+                    if let Some(vel) = &mut vel {
+                        vel.0 = reflect(reactions.incoming_velocity, contacts, *restitution);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reflects a velocity off of every surface it moves into.
+fn reflect(mut vel: Vec3, contacts: &Contacts, restitution: f32) -> Vec3 {
+    for contact in contacts.iter() {
+        let speed_into = vel.dot(contact.normal);
+        if speed_into < 0.0 {
+            vel -= contact.normal * speed_into * (1.0 + restitution);
+        }
+    }
+    vel
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::event::Events;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    /// App with a wall at x = 0, returning the wall
+    fn wall_app() -> (App, Entity) {
+        let mut app = physics_test_app();
+        let wall = spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(1.0, 10.0, 10.0), Shape::Cuboid).id();
+        (app, wall)
+    }
+
+    /// Spawns a ball moving towards the wall
+    fn spawn_ball(app: &mut App, reactions: CollisionReactions) -> Entity {
+        app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(1.5, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(-1.0, 0.5, 0.0)))
+            })
+            .insert((AntiGravity, reactions))
+            .id()
+    }
+
+    #[test]
+    fn reflect_velocity() {
+        let (mut app, _) = wall_app();
+        let ball = spawn_ball(&mut app, CollisionReactions::new([CollisionReaction::ReflectVelocity(0.5)]));
+        app.update();
+        assert_eq!(Vec3::new(0.5, 0.5, 0.0), app.world.get::<Velocity>(ball).unwrap().0);
+    }
+
+    #[test]
+    fn emit_named_event() {
+        let (mut app, wall) = wall_app();
+        let ball = spawn_ball(&mut app, CollisionReactions::default()
+            .with_reaction(CollisionReaction::EmitNamedEvent("hit".into()))
+            .with_reaction(CollisionReaction::EmitNamedEvent("bonk".into()))
+        );
+        app.update();
+        let events = app.world.resource::<Events<NamedCollisionEvent>>();
+        let names: Vec<&str> = events.iter_current_update_events().map(|event| event.name.as_str()).collect();
+        assert_eq!(vec!["hit", "bonk"], names);
+        assert!(events.iter_current_update_events().all(|event| event.entity == ball && event.other == wall));
+    }
+
+    #[test]
+    fn despawn_stops_later_reactions() {
+        let (mut app, _) = wall_app();
+        let ball = spawn_ball(&mut app, CollisionReactions::new([
+            CollisionReaction::EmitNamedEvent("before".into()),
+            CollisionReaction::DespawnSelf,
+            CollisionReaction::EmitNamedEvent("after".into()),
+            CollisionReaction::DespawnSelf
+        ]));
+        app.update();
+        assert!(app.world.get_entity(ball).is_none());
+        let events = app.world.resource::<Events<NamedCollisionEvent>>();
+        let names: Vec<&str> = events.iter_current_update_events().map(|event| event.name.as_str()).collect();
+        assert_eq!(vec!["before"], names);
+
+        // Keeps running after the entity is gone
+        app.update();
+    }
+}
@@ -0,0 +1,156 @@
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_reflect::prelude::*;
+use vidya_fixed_timestep::CurrentTransform;
+
+use crate::{overlaps, CollisionConfig, HalfExtents, Shape, AABB};
+
+/// How far a resized box may sink into terrain before growing gets blocked.
+const TOLERANCE: f32 = 0.001;
+
+/// Point of an [`Entity`]'s bounds that stays fixed in world space while it gets resized.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Reflect)]
+pub enum BoundsAnchor {
+    /// Keeps the feet in place. Useful for crouching.
+    #[default]
+    Bottom,
+    Center,
+    Top
+}
+
+/// Smoothly resizes an [`Entity`]'s [`HalfExtents`] toward a target over fixed ticks,
+/// moving its [`CurrentTransform`] so that the anchor stays in place.
+/// Growing gets blocked while the expanded bounds would overlap something the entity is affected by,
+/// in which case a [`ResizeBlocked`] event is fired.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct ResizeBounds {
+    /// Half extents to resize to
+    pub target: Vec3,
+    pub anchor: BoundsAnchor,
+    /// Maximum change in half extents per tick
+    pub speed: f32
+}
+impl ResizeBounds {
+    pub fn new(target: HalfExtents, speed: f32) -> Self {
+        Self {
+            target: target.0,
+            anchor: BoundsAnchor::default(),
+            speed
+        }
+    }
+    pub fn with_anchor(mut self, anchor: BoundsAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+}
+impl Default for ResizeBounds {
+    fn default() -> Self {
+        Self {
+            target: Vec3::splat(0.5),
+            anchor: BoundsAnchor::default(),
+            speed: 0.05
+        }
+    }
+}
+
+/// Event fired every tick an [`Entity`] fails to grow toward its [`ResizeBounds`] target.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ResizeBlocked {
+    pub entity: Entity
+}
+
+/// Moves half extents toward their targets, keeping anchors in place.
+pub(crate) fn resize_bounds(
+    mut resizing: Query<(Entity, &mut CurrentTransform, &mut HalfExtents, &ResizeBounds, &CollisionConfig)>,
+    obstacles: Query<(&CurrentTransform, &HalfExtents, &Shape, &CollisionConfig), Without<ResizeBounds>>,
+    mut blocked_writer: EventWriter<ResizeBlocked>
+) {
+    for (entity, mut trans, mut extents, resize, config) in &mut resizing {
+        if extents.0 == resize.target {
+            continue;
+        }
+
+        // Steps toward target
+        let delta = (resize.target - extents.0).clamp(Vec3::splat(-resize.speed), Vec3::splat(resize.speed));
+        let new_extents = extents.0 + delta;
+        let offset = match resize.anchor {
+            BoundsAnchor::Bottom => Vec3::new(0.0, delta.y, 0.0),
+            BoundsAnchor::Center => Vec3::ZERO,
+            BoundsAnchor::Top => Vec3::new(0.0, -delta.y, 0.0)
+        };
+        let new_center = trans.0.translation + offset;
+
+        // Blocks growing into obstacles
+        if delta.cmpgt(Vec3::ZERO).any() {
+            let bounds = AABB::new(new_center, (new_extents - TOLERANCE).max(Vec3::ZERO));
+            let blocked = obstacles.iter().any(|(obs_trans, obs_extents, obs_shape, obs_config)| {
+                config.affected_by(obs_config.groups) &&
+                overlaps(AABB::new(obs_trans.0.translation, obs_extents.0), obs_shape, bounds)
+            });
+            if blocked {
+                blocked_writer.send(ResizeBlocked { entity });
+                continue;
+            }
+        }
+        extents.0 = new_extents;
+        trans.0.translation = new_center;
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+    use vidya_fixed_timestep::CurrentTransform;
+
+    use crate::*;
+
+    fn spawn_crouched(app: &mut App) -> Entity {
+        app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.5, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert(ResizeBounds::new(HalfExtents::new(1.0, 2.0, 1.0), 0.1))
+            .id()
+    }
+
+    #[test]
+    fn keeps_bottom_in_place() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(10.0, 2.0, 10.0), Shape::Cuboid);
+        let body = spawn_crouched(&mut app);
+
+        for _ in 0..10 {
+            app.update();
+            let trans = app.world.get::<CurrentTransform>(body).unwrap().0;
+            let extents = app.world.get::<HalfExtents>(body).unwrap().0;
+            assert!((trans.translation.y - extents.y - 1.0).abs() < 0.0001);
+        }
+        assert_eq!(Vec3::new(0.5, 1.0, 0.5), app.world.get::<HalfExtents>(body).unwrap().0);
+        assert!(app.world.resource::<Events<ResizeBlocked>>().is_empty());
+    }
+
+    #[test]
+    fn blocked_under_overhang() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(10.0, 2.0, 10.0), Shape::Cuboid);
+
+        // Overhang half a unit above the crouched body's head
+        let mut chunk = VoxelChunk::new(UVec3::new(4, 1, 4));
+        chunk.set_voxel(UVec3::new(1, 0, 1), VoxelData::new(Voxel::Cuboid));
+        spawn_static_floor(&mut app.world, Transform::from_xyz(1.0, 3.0, 1.0), HalfExtents::new(4.0, 1.0, 4.0), Shape::VoxelChunk(chunk));
+        let body = spawn_crouched(&mut app);
+
+        for _ in 0..10 {
+            app.update();
+        }
+        let extents = app.world.get::<HalfExtents>(body).unwrap().0;
+        assert!(extents.y < 0.75);
+        assert!(!app.world.resource::<Events<ResizeBlocked>>().is_empty());
+    }
+}
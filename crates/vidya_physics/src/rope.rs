@@ -0,0 +1,226 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
+use bevy_math::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::{distance_corrections, CollisionConfig, CollisionGroups, CurrentTransform, ParticleBundle, Velocity, GROUP_NONE, GROUP_PARTICLES};
+
+/// What the end of a [`Rope`] is attached to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RopeAnchor {
+    /// Fixed point in space
+    Point(Vec3),
+    /// Position of an entity with a [`CurrentTransform`]. The rope follows the entity without pulling on it.
+    Entity(Entity)
+}
+
+/// Chain of point particles kept within a fixed distance of each other, like a hanging cable.
+/// Spawned with [`spawn_rope`], which lays the segments out between the anchors, or straight down from the start if there's no end anchor.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Rope {
+    pub start: RopeAnchor,
+    pub end: Option<RopeAnchor>,
+    /// Maximum distance between neighboring segments
+    pub segment_length: f32,
+    /// Number of times constraints are relaxed per tick. More iterations stretch less.
+    pub iterations: usize,
+    /// Collision config of the segments
+    pub config: CollisionConfig,
+    segment_count: usize,
+    segments: Vec<Entity>
+}
+
+impl Rope {
+    pub fn new(start: RopeAnchor, segment_count: usize, segment_length: f32) -> Self {
+        Self {
+            start,
+            end: None,
+            segment_length,
+            iterations: 4,
+            config: CollisionConfig::new(GROUP_PARTICLES, GROUP_NONE),
+            segment_count,
+            segments: Vec::new()
+        }
+    }
+    pub fn with_end(mut self, end: RopeAnchor) -> Self {
+        self.end = Some(end);
+        self
+    }
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+    /// Makes segments collide with the groups specified, like [`crate::GROUP_STATIC_TERRAIN`].
+    pub fn with_collision(mut self, affected_by: CollisionGroups) -> Self {
+        self.config = CollisionConfig::new(GROUP_PARTICLES, affected_by);
+        self
+    }
+
+    /// Segment entities, from the start of the rope to the end. Empty until the rope is spawned.
+    pub fn segments(&self) -> &[Entity] {
+        &self.segments
+    }
+}
+
+/// Spawns a rope and its segments, returning the rope's entity.
+/// Segments are spawned when commands are applied, after which they can be found with [`Rope::segments`].
+pub fn spawn_rope(commands: &mut Commands, rope: Rope) -> Entity {
+    let entity = commands.spawn_empty().id();
+    commands.add(SpawnRope { entity, rope });
+    entity
+}
+
+struct SpawnRope {
+    entity: Entity,
+    rope: Rope
+}
+impl Command for SpawnRope {
+    fn write(mut self, world: &mut World) {
+        let Some(start) = anchor_position(world, self.rope.start) else { return };
+        let end = self.rope.end.and_then(|end| anchor_position(world, end));
+        let count = self.rope.segment_count;
+        self.rope.segments = (0..count)
+            .map(|i| {
+                let position = match end {
+                    Some(end) => start.lerp(end, (i + 1) as f32 / (count + 1) as f32),
+                    None => start - Vec3::Y * self.rope.segment_length * (i + 1) as f32
+                };
+                let mut segment = ParticleBundle::new(position);
+                segment.physics.config = self.rope.config;
+                world.spawn((segment, TransformBundle::from_transform(Transform::from_translation(position)))).id()
+            })
+            .collect();
+        if let Some(mut entity) = world.get_entity_mut(self.entity) {
+            entity.insert(self.rope);
+        }
+    }
+}
+
+fn anchor_position(world: &World, anchor: RopeAnchor) -> Option<Vec3> {
+    match anchor {
+        RopeAnchor::Point(point) => Some(point),
+        RopeAnchor::Entity(entity) => world.get::<CurrentTransform>(entity).map(|trans| trans.0.translation)
+    }
+}
+
+/// Pulls rope segments back within range of their neighbors after they moved.
+/// Corrections are added to velocities as well, so ropes swing instead of stretching and snapping back.
+pub(crate) fn solve_ropes(
+    ropes: Query<&Rope>,
+    mut bodies: Query<(&mut CurrentTransform, Option<&mut Velocity>)>,
+    mut points: Local<Vec<(Vec3, f32)>>
+) {
+    'ropes: for rope in &ropes {
+
+        // Gathers anchors and segments as points with inverse weights, so anchors never move
+        let anchor = |anchor: RopeAnchor| match anchor {
+            RopeAnchor::Point(point) => Some(point),
+            RopeAnchor::Entity(entity) => bodies.get(entity).ok().map(|(trans, _)| trans.0.translation)
+        };
+        points.clear();
+        if let Some(start) = anchor(rope.start) {
+            points.push((start, 0.0));
+        }
+        let first_segment = points.len();
+        for segment in &rope.segments {
+            let Ok((trans, _)) = bodies.get(*segment) else { continue 'ropes };
+            points.push((trans.0.translation, 1.0));
+        }
+        if let Some(end) = rope.end.and_then(anchor) {
+            points.push((end, 0.0));
+        }
+
+        // Relaxes each link in turn, like a fully stiff distance joint
+        for _ in 0..rope.iterations {
+            for i in 1..points.len() {
+                let (a, a_inv) = points[i - 1];
+                let (b, b_inv) = points[i];
+                let Some((a_delta, b_delta)) = distance_corrections(a, b, a_inv, b_inv, rope.segment_length, 1.0) else { continue };
+                points[i - 1].0 += a_delta;
+                points[i].0 += b_delta;
+            }
+        }
+
+        // Moves segments to their corrected positions
+        for (segment, (position, _)) in rope.segments.iter().zip(&points[first_segment..]) {
+            let Ok((mut trans, vel)) = bodies.get_mut(*segment) else { continue };
+            let delta = *position - trans.0.translation;
+            trans.0.translation = *position;
+            if let Some(mut vel) = vel {
+                vel.0 += delta;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    /// Spawns a rope and returns its segments
+    fn spawn(app: &mut App, rope: Rope) -> Vec<Entity> {
+        let mut queue = bevy_ecs::system::CommandQueue::default();
+        let entity = spawn_rope(&mut Commands::new(&mut queue, &app.world), rope);
+        queue.apply(&mut app.world);
+        app.world.get::<Rope>(entity).unwrap().segments().to_vec()
+    }
+
+    fn positions(app: &App, segments: &[Entity]) -> Vec<Vec3> {
+        segments.iter().map(|segment| app.world.get::<CurrentTransform>(*segment).unwrap().0.translation).collect()
+    }
+
+    /// Length of the path from the anchor through every segment
+    fn length(anchor: Vec3, positions: &[Vec3]) -> f32 {
+        let mut previous = anchor;
+        positions.iter().map(|position| {
+            let length = position.distance(previous);
+            previous = *position;
+            length
+        }).sum()
+    }
+
+    #[test]
+    fn hangs_without_stretching() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+        let anchor = Vec3::new(0.0, 10.0, 0.0);
+        let segments = spawn(&mut app, Rope::new(RopeAnchor::Point(anchor), 10, 0.5));
+
+        // Settles hanging straight down from the anchor
+        for _ in 0..100 {
+            app.update();
+            let positions = positions(&app, &segments);
+            assert!(length(anchor, &positions) <= 5.0 * 1.1, "{}", length(anchor, &positions));
+        }
+        let positions = positions(&app, &segments);
+        let bottom = positions.last().unwrap();
+        assert!(bottom.y > 10.0 - 5.0 * 1.1 && bottom.y <= 5.0, "{bottom}");
+    }
+
+    #[test]
+    fn holds_up_load() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        let start = Vec3::new(-3.0, 0.0, 0.0);
+        let end = app.world
+            .spawn(PhysicsBundle::new(Transform::from_xyz(3.0, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid))
+            .insert(AntiGravity)
+            .id();
+        let segments = spawn(&mut app, Rope::new(RopeAnchor::Point(start), 9, 1.0).with_end(RopeAnchor::Entity(end)));
+        for _ in 0..100 {
+            app.update();
+        }
+
+        // Sags, but stays within 10% of its full length
+        let mut positions = positions(&app, &segments);
+        positions.push(Vec3::new(3.0, 0.0, 0.0));
+        let length = length(start, &positions);
+        assert!(positions[4].y < -1.0);
+        assert!(length <= 10.0 * 1.1, "{length}");
+    }
+}
@@ -0,0 +1,95 @@
+use bevy_ecs::prelude::*;
+
+use crate::{collide, overlaps, AxisPriority, PhysObj, Shape};
+
+/// Marker for a physics object that detects overlaps without colliding, like a checkpoint, pickup or damage zone.
+/// Sensors fire a [`SensorEvent`] for every object they're affected by, according to their [`crate::CollisionConfig`].
+/// Neither the sensor nor the objects it detects receive a [`crate::CollisionResponse`].
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct Sensor;
+
+/// Event fired once per tick for every object a [`Sensor`] overlaps or passes through.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SensorEvent {
+    pub sensor: Entity,
+    pub other: Entity
+}
+
+/// True if two objects overlap, or would touch during their movement.
+pub(crate) fn senses(a: PhysObj<'_>, b: PhysObj<'_>, priority: AxisPriority) -> bool {
+    let overlapping = match b.shape {
+        Shape::VoxelChunk(_) => overlaps(b.aabb, b.shape, a.aabb),
+        _ => overlaps(a.aabb, a.shape, b.aabb)
+    };
+    overlapping || collide(a, b, priority).is_some()
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::event::Events;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    fn sensed(app: &App) -> Vec<SensorEvent> {
+        app.world.resource::<Events<SensorEvent>>().iter_current_update_events().copied().collect()
+    }
+
+    #[test]
+    fn checkpoint() {
+        let mut app = physics_test_app();
+        let checkpoint = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_BASIC),
+                ..PhysicsBundle::new(Transform::default(), HalfExtents::new(2.0, 2.0, 2.0), Shape::Cuboid)
+            })
+            .insert((AntiGravity, Sensor))
+            .id();
+        let player = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(-2.0, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(0.5, 0.0, 0.0)))
+            })
+            .insert(AntiGravity)
+            .id();
+
+        // Walks into the checkpoint and through it without slowing down
+        app.update();
+        assert!(sensed(&app).is_empty());
+        for _ in 0..6 {
+            app.update();
+            assert_eq!(vec![SensorEvent { sensor: checkpoint, other: player }], sensed(&app));
+            assert_eq!(Vec3::new(0.5, 0.0, 0.0), app.world.get::<Velocity>(player).unwrap().0);
+        }
+        app.update();
+        assert!(sensed(&app).is_empty());
+        assert_eq!(Vec3::new(2.0, 0.0, 0.0), app.world.get::<CurrentTransform>(player).unwrap().0.translation);
+        assert_eq!(Vec3::ZERO, app.world.get::<CurrentTransform>(checkpoint).unwrap().0.translation);
+        assert!(app.world.resource::<Events<CollisionEvent>>().is_empty());
+    }
+
+    #[test]
+    fn respects_groups() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(10.0, 1.0, 10.0), Shape::Cuboid);
+        let sensor = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_PARTICLES),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert(Sensor)
+            .id();
+
+        // Falls through the floor without firing, since it only senses particles
+        for _ in 0..5 {
+            app.update();
+            assert!(sensed(&app).is_empty());
+        }
+        assert!(app.world.get::<CurrentTransform>(sensor).unwrap().0.translation.y < 0.0);
+    }
+}
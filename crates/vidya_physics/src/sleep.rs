@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_reflect::prelude::*;
+
+use crate::{CollisionEvent, ExternalImpulse, Kinematic, MoveAndSlide, PhysicsConfig, StaticBody, Velocity};
+
+/// Marker the engine puts on bodies that stayed slower than [`PhysicsConfig::sleep_threshold`] for [`PhysicsConfig::sleep_ticks`] ticks.
+/// Sleeping bodies skip gravity, friction and forces, and act like static bodies to other bodies until they wake up.
+/// They wake up when struck by a moving body or pushed by a [`Kinematic`] one, or when their [`Velocity`] or [`ExternalImpulse`] gets set.
+/// Each body sleeps on its own rather than as part of an island of touching bodies.
+/// Dynamic bodies stacked on each other keep jostling, so stacks rarely fall asleep by themselves. Inserting this puts them to sleep.
+#[derive(Component, Debug, Copy, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct Sleeping;
+
+/// Puts bodies that stayed slow for long enough to sleep, and wakes up sleeping bodies that got struck or pushed.
+/// Bodies woken by a collision wake the sleeping bodies they fall or get pushed into on the following ticks, so whole stacks wake up.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_sleeping(
+    mut commands: Commands,
+    config: Res<PhysicsConfig>,
+    mut bodies: Query<
+        (Entity, &mut Velocity, Option<&ExternalImpulse>, Option<&Sleeping>),
+        (Without<StaticBody>, Without<Kinematic>, Without<MoveAndSlide>)
+    >,
+    mut collision_reader: EventReader<CollisionEvent>,
+    mut idle_ticks: Local<HashMap<Entity, u32>>,
+    mut struck: Local<HashSet<Entity>>
+) {
+    if config.sleep_threshold <= 0.0 {
+        collision_reader.clear();
+        return;
+    }
+
+    // Finds sleeping bodies struck by bodies that were moving before this tick
+    let is_moving = |entity: Entity| {
+        matches!(bodies.get(entity), Ok((_, _, _, None))) && idle_ticks.get(&entity).copied().unwrap_or(0) == 0
+    };
+    let is_sleeping = |entity: Entity| matches!(bodies.get(entity), Ok((_, _, _, Some(_))));
+    struck.clear();
+    for event in collision_reader.iter() {
+        if is_sleeping(event.entity_a) && is_moving(event.entity_b) {
+            struck.insert(event.entity_a);
+        }
+        if is_sleeping(event.entity_b) && is_moving(event.entity_a) {
+            struck.insert(event.entity_b);
+        }
+    }
+
+    // Wakes up sleeping bodies that got struck or pushed, and puts bodies that stayed slow to sleep
+    idle_ticks.retain(|entity, _| bodies.contains(*entity));
+    for (entity, mut vel, impulse, sleeping) in &mut bodies {
+        let idle = idle_ticks.entry(entity).or_default();
+        if sleeping.is_some() {
+            let pushed = vel.0 != Vec3::ZERO || impulse.is_some_and(|impulse| impulse.0 != Vec3::ZERO);
+            if pushed || struck.contains(&entity) {
+                commands.entity(entity).remove::<Sleeping>();
+                *idle = 0;
+            }
+        }
+        else if vel.0.length() < config.sleep_threshold {
+            *idle += 1;
+            if *idle >= config.sleep_ticks {
+                commands.entity(entity).insert(Sleeping);
+                if vel.0 != Vec3::ZERO {
+                    vel.0 = Vec3::ZERO;
+                }
+            }
+        }
+        else {
+            *idle = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_ecs::event::Events;
+    use bevy_ecs::prelude::*;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    fn sleepy_app() -> App {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        app.insert_resource(PhysicsConfig { sleep_threshold: 0.001, sleep_ticks: 5, ..PhysicsConfig::default() });
+        spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(20.0, 1.0, 20.0), Shape::Cuboid).insert(StaticBody);
+        app
+    }
+
+    fn spawn_box(app: &mut App, x: f32, y: f32, vel: Vec3) -> Entity {
+        app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(x, y, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(vel))
+            })
+            .id()
+    }
+
+    fn is_sleeping(app: &App, entity: Entity) -> bool {
+        app.world.get::<Sleeping>(entity).is_some()
+    }
+
+    #[test]
+    fn settled_box_sleeps_and_wakes_when_struck() {
+        let mut app = sleepy_app();
+        let resting = spawn_box(&mut app, 0.0, 0.5, Vec3::ZERO);
+        for _ in 0..6 {
+            app.update();
+        }
+        assert!(is_sleeping(&app, resting));
+
+        // Sleeping bodies stay put
+        let position = app.world.get::<CurrentTransform>(resting).unwrap().0.translation;
+        app.update();
+        assert_eq!(position, app.world.get::<CurrentTransform>(resting).unwrap().0.translation);
+        assert_eq!(Vec3::ZERO, app.world.get::<Velocity>(resting).unwrap().0);
+
+        // Gets struck by a sliding box
+        let striker = spawn_box(&mut app, -3.0, 0.5, Vec3::new(0.5, 0.0, 0.0));
+        for _ in 0..6 {
+            app.update();
+        }
+        assert!(!is_sleeping(&app, resting));
+        assert!(app.world.get::<CurrentTransform>(striker).unwrap().0.translation.x <= -1.0 + 0.001);
+    }
+
+    #[test]
+    fn setting_velocity_wakes_sleeping_box() {
+        let mut app = sleepy_app();
+        let body = spawn_box(&mut app, 0.0, 0.5, Vec3::ZERO);
+        for _ in 0..6 {
+            app.update();
+        }
+        assert!(is_sleeping(&app, body));
+        app.world.get_mut::<Velocity>(body).unwrap().0 = Vec3::new(0.0, 1.0, 0.0);
+        app.update();
+        assert!(!is_sleeping(&app, body));
+        assert!(app.world.get::<CurrentTransform>(body).unwrap().0.translation.y > 1.0);
+    }
+
+    #[test]
+    fn struck_stacks_wake_up() {
+        let mut app = sleepy_app();
+        let bottom = spawn_box(&mut app, 0.0, 0.5, Vec3::ZERO);
+        let top = spawn_box(&mut app, 0.0, 1.5, Vec3::ZERO);
+        app.world.entity_mut(bottom).insert(Sleeping);
+        app.world.entity_mut(top).insert(Sleeping);
+        app.update();
+        assert!(is_sleeping(&app, bottom) && is_sleeping(&app, top));
+
+        // Landing on the stack wakes the top box, which wakes the bottom one once it falls onto it
+        spawn_box(&mut app, 0.0, 3.0, Vec3::new(0.0, -0.5, 0.0));
+        let mut woke_up = (false, false);
+        for _ in 0..6 {
+            app.update();
+            woke_up.0 |= !is_sleeping(&app, top);
+            woke_up.1 |= !is_sleeping(&app, bottom);
+        }
+        assert_eq!((true, true), woke_up);
+    }
+
+    #[test]
+    fn sleeping_bodies_keep_touching() {
+        let mut app = sleepy_app();
+        let floor = app.world.query_filtered::<Entity, With<StaticBody>>().single(&app.world);
+        let bottom = spawn_box(&mut app, 0.0, 0.5, Vec3::ZERO);
+        let ended = |app: &App| app.world.resource::<Events<CollisionEnded>>().iter_current_update_events().count();
+
+        // Falling asleep doesn't end collisions or forget contacts
+        for _ in 0..10 {
+            app.update();
+            assert_eq!(0, ended(&app));
+        }
+        assert!(is_sleeping(&app, bottom));
+        assert!(app.world.resource::<TouchingPairs>().contains(floor, bottom));
+        assert!(app.world.get::<Contacts>(bottom).unwrap().get(floor).is_some());
+
+        // Box stacked on top, which wakes the bottom one, then both go back to sleep
+        let top = spawn_box(&mut app, 0.0, 1.5, Vec3::ZERO);
+        app.update();
+        for body in [bottom, top] {
+            app.world.entity_mut(body).insert(Sleeping);
+            app.world.get_mut::<Velocity>(body).unwrap().0 = Vec3::ZERO;
+        }
+        for _ in 0..3 {
+            app.update();
+            assert_eq!(0, ended(&app));
+        }
+        assert!(is_sleeping(&app, top));
+        assert!(app.world.resource::<TouchingPairs>().contains(bottom, top));
+        assert!(app.world.get::<Contacts>(top).unwrap().get(bottom).is_some());
+
+        // Jumping off the bottom box ends that collision only
+        app.world.get_mut::<Velocity>(top).unwrap().0 = Vec3::new(0.0, 1.0, 0.0);
+        app.update();
+        assert_eq!(1, ended(&app));
+        assert!(!app.world.resource::<TouchingPairs>().contains(bottom, top));
+        assert!(app.world.resource::<TouchingPairs>().contains(floor, bottom));
+        assert!(app.world.get::<Contacts>(top).unwrap().is_empty());
+        assert!(app.world.get::<Contacts>(bottom).unwrap().get(floor).is_some());
+    }
+}
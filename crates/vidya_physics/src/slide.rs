@@ -0,0 +1,474 @@
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_reflect::prelude::*;
+use smallvec::SmallVec;
+
+use crate::{
+    collide, is_excluded, senses, AxisPriority, Collision, CollisionConfig, CollisionEvent, CollisionExclusions, Contact, Contacts, CurrentTransform, HalfExtents, OneWay,
+    PhysObj, PhysicsConfig, Sensor, SensorEvent, Shape, SurfaceTag, Velocity, AABB
+};
+
+/// Marker for bodies moved with [`move_and_slide`] instead of the regular collision update, like player characters.
+/// They slide along whatever they run into, without pushing it. Other bodies collide with them as if they were static,
+/// except for [`crate::Kinematic`] bodies which push them, and [`crate::CarriesRiders`] platforms carry them.
+#[derive(Component, Debug, Copy, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct MoveAndSlide;
+
+/// Height of the ledges a [`MoveAndSlide`] body steps onto instead of getting stopped by, like stairs or lips in the terrain.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Default, Reflect)]
+#[reflect(Component)]
+pub struct StepHeight(pub f32);
+
+/// Surface hit during [`move_and_slide`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SlideHit {
+    /// Entity hit
+    pub entity: Entity,
+    /// Normal of the surface hit
+    pub normal: Vec3,
+    /// Value between 0 and 1 describing when during the move the surface was hit
+    pub t: f32,
+    /// World space point where the body touched the surface
+    pub point: Vec3,
+    /// How deep the body would have ended up inside the surface without sliding
+    pub depth: f32
+}
+
+/// Moves a box by its velocity, sliding along the surfaces it hits instead of stopping dead.
+/// Each hit removes the part of the remaining motion and of the velocity that goes into the surface,
+/// then the rest of the motion gets swept again, up to `iterations` times.
+/// Walls no taller than `step_height` are stepped onto instead, as long as there's ground to land on past them.
+/// `colliders` lists the entities that can be hit along with their bounds, shapes and [`OneWay`]s, and gets called once per iteration.
+/// Returns the surfaces hit, in order.
+pub fn move_and_slide<'a, I>(
+    trans: &mut CurrentTransform,
+    vel: &mut Velocity,
+    extents: &HalfExtents,
+    colliders: impl Fn() -> I,
+    iterations: usize,
+    step_height: f32,
+    priority: AxisPriority
+) -> SmallVec<[SlideHit; 4]>
+where
+    I: IntoIterator<Item = (Entity, AABB, &'a Shape, Option<&'a OneWay>)>
+{
+    let mut hits = SmallVec::new();
+    let mut motion = vel.0;
+    let mut elapsed = 0.0;
+    for _ in 0..iterations {
+        if motion == Vec3::ZERO {
+            return hits;
+        }
+
+        // Finds the first surface hit
+        let body = PhysObj { aabb: AABB::new(trans.0.translation, extents.0), shape: &Shape::Cuboid, vel: motion };
+        let Some((entity, coll)) = sweep(body, &colliders, priority) else {
+            trans.0.translation += motion;
+            return hits;
+        };
+
+        // Steps onto low walls, landing on top of them
+        let normal = coll.normal_a;
+        elapsed += (1.0 - elapsed) * coll.t;
+        if step_height > 0.0 && normal.y == 0.0 {
+            let start = AABB::new(trans.0.translation + motion * coll.t, extents.0);
+            let rest = motion * (1.0 - coll.t);
+            if let Some((landing, ground)) = step(start, Vec3::new(rest.x, 0.0, rest.z), step_height, &colliders, priority) {
+                trans.0.translation = landing;
+                vel.0 -= ground.normal * vel.0.dot(ground.normal).min(0.0);
+                hits.push(SlideHit { t: elapsed, ..ground });
+                return hits;
+            }
+        }
+
+        // Moves up to the surface, pushing out of it if overlapping, and keeps the rest of the motion that runs along it
+        let rest = motion * (1.0 - coll.t) + coll.position_delta;
+        let push = normal * rest.dot(normal);
+        trans.0.translation += motion * coll.t + push;
+        motion = rest - push;
+        vel.0 -= normal * vel.0.dot(normal).min(0.0);
+        hits.push(SlideHit { entity, normal, t: elapsed, point: coll.point, depth: coll.depth });
+    }
+    hits
+}
+
+/// First collision a moving body has with any of the colliders.
+fn sweep<'a, I>(body: PhysObj<'_>, colliders: &impl Fn() -> I, priority: AxisPriority) -> Option<(Entity, Collision)>
+where
+    I: IntoIterator<Item = (Entity, AABB, &'a Shape, Option<&'a OneWay>)>
+{
+    colliders()
+        .into_iter()
+        .filter_map(|(entity, aabb, shape, one_way)| {
+            let collider = PhysObj { aabb, shape, vel: Vec3::ZERO };
+            let coll = collide(collider, body.clone(), priority)?;
+
+            // One-way colliders let the body through unless it comes from the right side
+            match one_way {
+                Some(one_way) if !one_way.blocks(coll.normal_a, body.vel) => None,
+                _ => Some((entity, coll))
+            }
+        })
+        .min_by(|(_, a), (_, b)| a.t.total_cmp(&b.t))
+}
+
+/// Lifts a box by `height`, moves it horizontally, then drops it back down.
+/// Returns where it lands and the ground it lands on, or None if anything is in the way or there's no ground to land on.
+fn step<'a, I>(
+    start: AABB,
+    motion: Vec3,
+    height: f32,
+    colliders: &impl Fn() -> I,
+    priority: AxisPriority
+) -> Option<(Vec3, SlideHit)>
+where
+    I: IntoIterator<Item = (Entity, AABB, &'a Shape, Option<&'a OneWay>)>
+{
+    if motion == Vec3::ZERO {
+        return None;
+    }
+    let lift = Vec3::Y * height;
+    let moving = |center: Vec3, vel: Vec3| PhysObj { aabb: AABB::new(center, start.half_extents), shape: &Shape::Cuboid, vel };
+    if sweep(moving(start.center, lift), colliders, priority).is_some() {
+        return None;
+    }
+    let lifted = start.center + lift;
+    if sweep(moving(lifted, motion), colliders, priority).is_some() {
+        return None;
+    }
+    let moved = lifted + motion;
+    let (entity, coll) = sweep(moving(moved, -lift), colliders, priority)?;
+    if coll.normal_a.y <= 0.0 {
+        return None;
+    }
+    let ground = SlideHit { entity, normal: coll.normal_a, t: 1.0, point: coll.point, depth: coll.depth };
+    Some((moved - lift + coll.position_delta, ground))
+}
+
+/// Moves [`MoveAndSlide`] bodies, recording what they hit in their [`Contacts`] and as [`CollisionEvent`]s.
+/// [`Sensor`]s overlapping the path between where a body started and ended the tick fire a [`SensorEvent`].
+#[allow(clippy::type_complexity)]
+pub(crate) fn slide_bodies(
+    config: Res<PhysicsConfig>,
+    mut bodies: Query<
+        (
+            Entity,
+            &mut CurrentTransform,
+            &mut Velocity,
+            &HalfExtents,
+            &CollisionConfig,
+            Option<&CollisionExclusions>,
+            Option<&StepHeight>,
+            Option<&mut Contacts>
+        ),
+        With<MoveAndSlide>
+    >,
+    colliders: Query<
+        (Entity, &CurrentTransform, &HalfExtents, &Shape, &CollisionConfig, Option<&CollisionExclusions>, Option<&SurfaceTag>, Option<&OneWay>),
+        (Without<MoveAndSlide>, Without<Sensor>)
+    >,
+    sensors: Query<
+        (Entity, &CurrentTransform, &HalfExtents, &Shape, &CollisionConfig, Option<&CollisionExclusions>),
+        (With<Sensor>, Without<MoveAndSlide>)
+    >,
+    mut collision_writer: EventWriter<CollisionEvent>,
+    mut sensor_writer: EventWriter<SensorEvent>
+) {
+
+    // Nothing moves without time passing, so contacts are left as they were
+    if config.time_scale <= 0.0 {
+        return;
+    }
+
+    for (entity, mut trans, mut vel, extents, body_cfg, body_excl, step_height, contacts) in &mut bodies {
+        let start = AABB::new(trans.0.translation, extents.0);
+        let hittable = || colliders
+            .iter()
+            .filter(|(other, _, _, _, cfg, excl, _, _)| body_cfg.affected_by(cfg.groups) && !is_excluded(entity, body_excl, *other, *excl))
+            .map(|(entity, trans, extents, shape, _, _, _, one_way)| (entity, AABB::new(trans.0.translation, extents.0), shape, one_way));
+        let step_height = step_height.map_or(0.0, |step_height| step_height.0);
+
+        // Slides as far as the time scale lets the body move. Hits only cancel parts of the velocity, so scaling it back keeps them canceled.
+        vel.0 *= config.time_scale;
+        let hits = move_and_slide(&mut trans, &mut vel, extents, hittable, config.slide_iterations, step_height, config.axis_priority);
+        vel.0 /= config.time_scale;
+
+        // Reports each surface hit once, with the surface as entity A like in the regular collision update
+        for (i, hit) in hits.iter().enumerate() {
+            if hits[..i].iter().any(|earlier| earlier.entity == hit.entity) {
+                continue;
+            }
+            collision_writer.send(CollisionEvent {
+                entity_a: hit.entity,
+                entity_b: entity,
+                normal: hit.normal,
+                t: hit.t,
+                point: hit.point,
+                depth: hit.depth
+            });
+        }
+        if let Some(mut contacts) = contacts {
+            contacts.clear();
+            for hit in &hits {
+                let surface = colliders.get(hit.entity).ok().and_then(|(_, _, _, _, _, _, tag, _)| tag.copied());
+                contacts.add(Contact { entity: hit.entity, normal: hit.normal, surface });
+            }
+        }
+
+        // Fires the sensors passed through, as if the body had moved in a straight line
+        let body = PhysObj { aabb: start, shape: &Shape::Cuboid, vel: trans.0.translation - start.center };
+        for (sensor, sensor_trans, sensor_extents, sensor_shape, sensor_cfg, sensor_excl) in &sensors {
+            if !sensor_cfg.affected_by(body_cfg.groups) || is_excluded(sensor, sensor_excl, entity, body_excl) {
+                continue;
+            }
+            let sensor_obj = PhysObj { aabb: AABB::new(sensor_trans.0.translation, sensor_extents.0), shape: sensor_shape, vel: Vec3::ZERO };
+            if senses(sensor_obj, body.clone(), config.axis_priority) {
+                sensor_writer.send(SensorEvent { sensor, other: entity });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::event::Events;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    /// Spawns a floor with a wall on its right, and returns the wall
+    fn spawn_level(app: &mut App) -> Entity {
+        spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(20.0, 1.0, 20.0), Shape::Cuboid);
+        spawn_static_floor(&mut app.world, Transform::from_xyz(3.0, 2.5, 0.0), HalfExtents::new(2.0, 4.0, 20.0), Shape::Cuboid).id()
+    }
+
+    #[test]
+    fn slides_along_walls() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+        let wall = spawn_level(&mut app);
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert((MoveAndSlide, CharacterController::default()))
+            .id();
+
+        // Walks diagonally into the wall, keeping the speed that runs along it
+        for _ in 0..20 {
+            app.world.get_mut::<Velocity>(body).unwrap().0 = Vec3::new(0.1, app.world.get::<Velocity>(body).unwrap().0.y, 0.1);
+            app.update();
+        }
+        let pos = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert!(pos.abs_diff_eq(Vec3::new(1.5, 1.0, 2.0), 0.0001), "{pos}");
+        let controller = app.world.get::<CharacterController>(body).unwrap();
+        assert!(controller.is_grounded());
+        assert!(controller.on_wall());
+        assert!(!controller.on_ceiling());
+        assert!(app.world.get::<Contacts>(body).unwrap().get(wall).is_some());
+    }
+
+    #[test]
+    fn climbs_stairs() {
+        for (step_height, expected) in [(Some(StepHeight(0.3)), Vec3::new(5.5, 1.75, 0.0)), (None, Vec3::new(1.75, 0.75, 0.0))] {
+            let mut app = physics_test_app();
+            app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+
+            // Floor with 4 steps a quarter unit high and a unit deep
+            let solid = VoxelData::new(Voxel::Cuboid);
+            let mut chunk = VoxelChunk::new(UVec3::new(32, 8, 4));
+            chunk.set_voxel_box(UVec3::ZERO, UVec3::new(32, 1, 4), solid);
+            for step in 1..=4 {
+                chunk.set_voxel_box(UVec3::new(4 * (step + 1), 1, 0), UVec3::new(32, step + 1, 4), solid);
+            }
+            spawn_static_floor(&mut app.world, Transform::from_xyz(4.0, 1.0, 0.0), HalfExtents::new(8.0, 2.0, 1.0), Shape::VoxelChunk(chunk));
+            let mut body = app.world.spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.5, 0.75, 0.0), HalfExtents::new(0.5, 1.0, 0.5), Shape::Cuboid)
+            });
+            body.insert((MoveAndSlide, CharacterController::default()));
+            if let Some(step_height) = step_height {
+                body.insert(step_height);
+            }
+            let body = body.id();
+
+            // Walks up the stairs without ever leaving the ground, or gets stopped by the first step without a step height
+            for _ in 0..100 {
+                app.world.get_mut::<Velocity>(body).unwrap().0.x = 0.05;
+                app.update();
+                if step_height.is_some() {
+                    assert!(app.world.get::<CharacterController>(body).unwrap().is_grounded());
+                }
+            }
+            let pos = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+            assert!(pos.abs_diff_eq(expected, 0.0001), "{pos}");
+        }
+    }
+
+    #[test]
+    fn bumps_into_ceilings() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+        spawn_level(&mut app);
+        spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, 3.0, 0.0), HalfExtents::new(4.0, 1.0, 4.0), Shape::Cuboid);
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(0.0, 1.5, 0.0)))
+            })
+            .insert((MoveAndSlide, CharacterController::default()))
+            .id();
+
+        // Stops at the ceiling instead of passing through it
+        app.update();
+        let pos = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert!(pos.abs_diff_eq(Vec3::new(0.0, 2.0, 0.0), 0.0001), "{pos}");
+        assert!(app.world.get::<Velocity>(body).unwrap().0.y <= 0.0);
+        let controller = app.world.get::<CharacterController>(body).unwrap();
+        assert!(controller.on_ceiling());
+        assert!(!controller.is_grounded());
+    }
+
+    #[test]
+    fn jumps_through_one_way_platforms() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        spawn_level(&mut app);
+        let platform = spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, 3.0, 0.0), HalfExtents::new(4.0, 0.2, 4.0), Shape::Cuboid)
+            .insert(OneWay::default())
+            .id();
+        let body = spawn_character(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+        // Passes through from below, then lands on top
+        let mut highest = 0.0_f32;
+        for _ in 0..40 {
+            app.update();
+            highest = highest.max(app.world.get::<CurrentTransform>(body).unwrap().0.translation.y);
+        }
+        assert!(highest > 5.0, "{highest}");
+        let y = app.world.get::<CurrentTransform>(body).unwrap().0.translation.y;
+        assert!((y - 3.6).abs() < 0.0001, "{y}");
+        assert!(app.world.get::<Contacts>(body).unwrap().get(platform).is_some());
+        assert!(app.world.get::<CharacterController>(body).unwrap().is_grounded());
+    }
+
+    #[test]
+    fn time_scale_slows_sliding() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+        spawn_level(&mut app);
+        let body = spawn_character(&mut app, Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.5, 0.0, 0.0));
+
+        // Covers a quarter of the distance, keeping its velocity
+        app.world.resource_mut::<PhysicsConfig>().time_scale = 0.25;
+        app.update();
+        let pos = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert!(pos.abs_diff_eq(Vec3::new(0.125, 1.0, 0.0), 0.0001), "{pos}");
+        assert_eq!(0.5, app.world.get::<Velocity>(body).unwrap().0.x);
+
+        // Stands still while time is stopped, still touching the floor
+        app.world.resource_mut::<PhysicsConfig>().time_scale = 0.0;
+        for _ in 0..4 {
+            app.update();
+        }
+        let pos = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert!(pos.abs_diff_eq(Vec3::new(0.125, 1.0, 0.0), 0.0001), "{pos}");
+        assert!(app.world.get::<CharacterController>(body).unwrap().is_grounded());
+
+        // Runs into the wall once time flows again
+        app.world.resource_mut::<PhysicsConfig>().time_scale = 1.0;
+        for _ in 0..4 {
+            app.update();
+        }
+        let pos = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert!(pos.abs_diff_eq(Vec3::new(1.5, 1.0, 0.0), 0.0001), "{pos}");
+        assert_eq!(0.0, app.world.get::<Velocity>(body).unwrap().0.x);
+    }
+
+    fn spawn_character(app: &mut App, position: Vec3, vel: Vec3) -> Entity {
+        app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_translation(position), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(vel))
+            })
+            .insert((MoveAndSlide, CharacterController::default()))
+            .id()
+    }
+
+    #[test]
+    fn fires_sensors_and_collision_events() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+        let wall = spawn_level(&mut app);
+        let checkpoint = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_NONE, GROUP_BASIC),
+                ..PhysicsBundle::new(Transform::from_xyz(-4.0, 2.0, 0.0), HalfExtents::new(0.5, 4.0, 4.0), Shape::Cuboid)
+            })
+            .insert((AntiGravity, Sensor))
+            .id();
+        let body = spawn_character(&mut app, Vec3::new(-8.0, 1.0, 0.0), Vec3::new(3.0, 0.0, 0.0));
+
+        // Passes through the checkpoint within a single tick, then runs into the wall
+        let mut sensed = Vec::new();
+        let mut started = Vec::new();
+        for _ in 0..4 {
+            app.update();
+            sensed.extend(app.world.resource::<Events<SensorEvent>>().iter_current_update_events().copied());
+            started.extend(app.world.resource::<Events<CollisionStarted>>().iter_current_update_events().copied());
+        }
+        assert_eq!(vec![SensorEvent { sensor: checkpoint, other: body }], sensed);
+        assert!(started.iter().any(|started| [started.0, started.1].contains(&wall)), "{started:?}");
+        assert!(app.world.resource::<TouchingPairs>().contains(wall, body));
+        let controller = app.world.get::<CharacterController>(body).unwrap();
+        assert!(controller.on_wall() && controller.is_grounded());
+    }
+
+    #[test]
+    fn other_bodies_collide_with_sliding_bodies() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.01, 0.0)));
+        spawn_level(&mut app);
+        let spawn_mover = |app: &mut App, position: Vec3, extents: HalfExtents, vel: Vec3| app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_MOVING_TERRAIN, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_translation(position), extents, Shape::Cuboid)
+                    .with_velocity(Velocity(vel))
+            })
+            .insert(Kinematic)
+            .id();
+
+        // Crate dropped onto a character, a character in the way of a door, and one standing on a moving platform
+        let under_crate = spawn_character(&mut app, Vec3::new(-4.0, 1.0, 0.0), Vec3::ZERO);
+        let crate_box = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(-4.0, 3.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+        let pushed = spawn_character(&mut app, Vec3::new(-6.0, 1.0, -5.0), Vec3::ZERO);
+        let door = spawn_mover(&mut app, Vec3::new(-8.0, 1.0, -5.0), HalfExtents::new(1.0, 1.0, 1.0), Vec3::new(0.1, 0.0, 0.0));
+        let platform = spawn_mover(&mut app, Vec3::new(-5.0, 3.0, 5.0), HalfExtents::new(4.0, 1.0, 4.0), Vec3::new(0.1, 0.0, 0.0));
+        app.world.entity_mut(platform).insert(CarriesRiders);
+        let rider = spawn_character(&mut app, Vec3::new(-5.0, 4.0, 5.0), Vec3::ZERO);
+        for _ in 0..30 {
+            app.update();
+        }
+        let position = |entity| app.world.get::<CurrentTransform>(entity).unwrap().0.translation;
+
+        // Crate rests on the character's head, the door shoves its character along, and the platform carries its rider
+        assert!((position(crate_box).y - 2.0).abs() < 0.001, "{}", position(crate_box));
+        assert!(position(under_crate).abs_diff_eq(Vec3::new(-4.0, 1.0, 0.0), 0.001), "{}", position(under_crate));
+        assert!(position(pushed).x >= position(door).x + 1.0 - 0.001, "{} {}", position(pushed), position(door));
+        assert!(position(pushed).x > -5.0, "{}", position(pushed));
+        assert!((position(rider).x - position(platform).x).abs() < 0.001, "{} {}", position(rider), position(platform));
+        assert!((position(rider).x + 2.0).abs() < 0.001, "{}", position(rider));
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_transform::prelude::*;
+use vidya_fixed_timestep::SimRng;
+
+use crate::{CharacterController, CollisionResponse, CurrentTransform, ExternalImpulse, GroundState, PhysicsTick, PreviousTransform, Sleeping, Velocity};
+
+/// Physics state of a single body, as captured by [`snapshot`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BodySnapshot {
+    pub current_transform: Transform,
+    pub previous_transform: Transform,
+    pub velocity: Vec3,
+    /// Impulse waiting to be applied on the next tick
+    pub impulse: Option<Vec3>,
+    pub ground: Option<GroundState>,
+    pub sleeping: bool,
+    /// Whole controller, so coyote time, buffered jumps, air jumps and material blending replay too
+    pub controller: Option<CharacterController>
+}
+
+/// Physics state of every body in a [`World`], keyed by entity. See [`snapshot`] and [`restore`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhysicsSnapshot {
+    pub tick: PhysicsTick,
+    /// State of the [`SimRng`], if there was one, so random draws replay along with the bodies
+    pub rng: Option<SimRng>,
+    bodies: HashMap<Entity, BodySnapshot>
+}
+impl PhysicsSnapshot {
+    /// State of the body specified, if it was captured.
+    pub fn get(&self, entity: Entity) -> Option<&BodySnapshot> {
+        self.bodies.get(&entity)
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &BodySnapshot)> {
+        self.bodies.iter().map(|(entity, body)| (*entity, body))
+    }
+    pub fn len(&self) -> usize {
+        self.bodies.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bodies.is_empty()
+    }
+}
+
+/// Captures the state of every physics body that a tick depends on, along with the [`PhysicsTick`] and [`SimRng`].
+/// Best taken between ticks, like in a system before or after the fixed timestep stages.
+pub fn snapshot(world: &mut World) -> PhysicsSnapshot {
+    let mut query = world.query::<(
+        Entity,
+        &CurrentTransform,
+        &PreviousTransform,
+        &Velocity,
+        Option<&ExternalImpulse>,
+        Option<&GroundState>,
+        Option<&Sleeping>,
+        Option<&CharacterController>
+    )>();
+    let bodies = query
+        .iter(world)
+        .map(|(entity, current, previous, vel, impulse, ground, sleeping, controller)| {
+            let body = BodySnapshot {
+                current_transform: current.0,
+                previous_transform: previous.0,
+                velocity: vel.0,
+                impulse: impulse.map(|impulse| impulse.0),
+                ground: ground.copied(),
+                sleeping: sleeping.is_some(),
+                controller: controller.copied()
+            };
+            (entity, body)
+        })
+        .collect();
+    PhysicsSnapshot {
+        tick: world.get_resource::<PhysicsTick>().copied().unwrap_or_default(),
+        rng: world.get_resource::<SimRng>().cloned(),
+        bodies
+    }
+}
+
+/// Writes the state captured by [`snapshot`] back to the bodies that still exist.
+/// Bodies spawned since the snapshot are left alone. Pending collision responses get cleared,
+/// and [`PreviousTransform`]s are restored along with [`CurrentTransform`]s so interpolation doesn't smear across the jump.
+pub fn restore(world: &mut World, snap: &PhysicsSnapshot) {
+    if let Some(mut tick) = world.get_resource_mut::<PhysicsTick>() {
+        *tick = snap.tick;
+    }
+    if let Some(rng) = &snap.rng {
+        world.insert_resource(rng.clone());
+    }
+    for (entity, body) in snap.iter() {
+        let mut entity = match world.get_entity_mut(entity) {
+            Some(entity) => entity,
+            None => continue
+        };
+        entity.insert((
+            CurrentTransform(body.current_transform),
+            PreviousTransform(body.previous_transform),
+            Velocity(body.velocity),
+            CollisionResponse::Empty
+        ));
+        match body.impulse {
+            Some(impulse) => { entity.insert(ExternalImpulse(impulse)); },
+            None => { entity.remove::<ExternalImpulse>(); }
+        }
+        if let Some(ground) = body.ground {
+            entity.insert(ground);
+        }
+        match body.sleeping {
+            true => { entity.insert(Sleeping); },
+            false => { entity.remove::<Sleeping>(); }
+        }
+        if let Some(controller) = body.controller {
+            entity.insert(controller);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_ecs::prelude::*;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+    use vidya_fixed_timestep::SimRng;
+
+    use crate::*;
+
+    /// Nudges bodies in random directions, like gusts of wind
+    fn gusts(mut rng: ResMut<SimRng>, mut bodies: Query<&mut Velocity, Without<StaticBody>>) {
+        for mut vel in &mut bodies {
+            vel.0.x += rng.gen_range(-0.02..0.02);
+        }
+    }
+
+    fn positions(app: &mut App, bodies: &[Entity]) -> Vec<Vec3> {
+        bodies
+            .iter()
+            .map(|body| app.world.get::<CurrentTransform>(*body).unwrap().0.translation)
+            .collect()
+    }
+
+    #[test]
+    fn restoring_replays_the_same_trajectory() {
+        let mut app = physics_test_app();
+        app
+            .insert_resource(Gravity(Vec3::new(0.0, -0.05, 0.0)))
+            .insert_resource(SimRng::new(5))
+            .add_system_to_stage(Phase::PostUpdate.stage(), gusts.before(PhysicsSystems::BeginStep));
+        spawn_static_floor(&mut app.world, Transform::from_xyz(0.0, -0.5, 0.0), HalfExtents::new(20.0, 1.0, 20.0), Shape::Cuboid).insert(StaticBody);
+        let bodies: Vec<Entity> = (0..4)
+            .map(|i| {
+                let x = i as f32 * 1.5 - 2.0;
+                app.world
+                    .spawn(PhysicsBundle {
+                        config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                        ..PhysicsBundle::new(Transform::from_xyz(x, 1.0 + i as f32, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                            .with_velocity(Velocity(Vec3::new(0.1 - i as f32 * 0.07, 0.0, 0.02)))
+                    })
+                    .insert((Restitution(0.5), GroundState::default()))
+                    .id()
+            })
+            .collect();
+
+        // Character falling from high up, with a jump buffered until it lands
+        let character = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(6.0, 8.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .insert(CharacterController::default().with_jump_buffer_ticks(20))
+            .id();
+        let bodies: Vec<Entity> = bodies.into_iter().chain([character]).collect();
+
+        for _ in 0..10 {
+            app.update();
+        }
+        let mut controller = app.world.get_mut::<CharacterController>(character).unwrap();
+        assert!(!controller.is_grounded());
+        assert!(!controller.try_jump(0.5));
+        let snap = snapshot(&mut app.world);
+        assert_eq!(bodies.len() + 1, snap.len());
+        assert_eq!(Some(*app.world.get::<CharacterController>(character).unwrap()), snap.get(character).unwrap().controller);
+        let mut trajectory = Vec::new();
+        let mut jumped = false;
+        for _ in 0..10 {
+            app.update();
+            trajectory.push(positions(&mut app, &bodies));
+            jumped |= app.world.get::<Velocity>(character).unwrap().0.y > 0.0;
+        }
+        assert!(jumped);
+
+        restore(&mut app.world, &snap);
+        assert_eq!(snap.tick, *app.world.resource::<PhysicsTick>());
+        assert_eq!(snap.rng.as_ref(), Some(app.world.resource::<SimRng>()));
+        for (i, expected) in trajectory.into_iter().enumerate() {
+            app.update();
+            assert_eq!(expected, positions(&mut app, &bodies), "Diverged on tick {i}");
+        }
+    }
+}
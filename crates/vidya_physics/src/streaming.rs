@@ -0,0 +1,315 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_transform::prelude::*;
+use vidya_fixed_timestep::{AppExt, CurrentTransform, Phase};
+
+use crate::{
+    PhysicsBundle, HalfExtents, Shape, VoxelChunk, AntiGravity,
+    CollisionConfig, GROUP_STATIC_TERRAIN, GROUP_NONE
+};
+
+/// Plugin that spawns [`VoxelChunk`] entities around [`ChunkFocus`] entities, and despawns them once out of range.
+/// Chunk data is supplied by a user-defined [`ChunkProvider`].
+pub struct ChunkStreamingPlugin {
+    provider: Arc<dyn ChunkProvider>,
+    config: ChunkStreamingConfig
+}
+impl ChunkStreamingPlugin {
+    /// Creates the plugin with the provider chunks are loaded from.
+    pub fn new(provider: impl ChunkProvider, config: ChunkStreamingConfig) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            config
+        }
+    }
+}
+impl Plugin for ChunkStreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(self.config)
+            .insert_resource(ChunkSource(self.provider.clone()))
+            .init_resource::<VoxelChunkMap>()
+            .add_event::<ChunkLoaded>()
+            .add_event::<ChunkUnloaded>()
+            .add_phase_system(Phase::Update, stream_chunks);
+    }
+}
+
+/// Source of chunk data for the [`ChunkStreamingPlugin`].
+pub trait ChunkProvider: Send + Sync + 'static {
+    /// Loads the chunk at the chunk coordinates specified.
+    /// Returns None if there is no chunk at those coordinates.
+    fn load(&self, coords: IVec3) -> Option<VoxelChunk>;
+}
+
+/// Configuration for the [`ChunkStreamingPlugin`].
+#[derive(Resource, Debug, Copy, Clone, PartialEq)]
+pub struct ChunkStreamingConfig {
+    /// Number of chunks kept loaded around a focus along each axis.
+    pub radius: UVec3,
+    /// Size of each chunk measured in voxels.
+    pub chunk_size: UVec3,
+    /// Size of a single voxel in units.
+    pub voxel_size: Vec3,
+    /// Maximum number of chunks requested from the provider per scan.
+    pub loads_per_tick: usize,
+    /// Number of fixed ticks between each scan.
+    pub interval: u32,
+    /// Collision config given to spawned chunks.
+    pub collision_config: CollisionConfig
+}
+impl ChunkStreamingConfig {
+    /// Size of a chunk in units.
+    pub fn chunk_extents(&self) -> Vec3 {
+        self.chunk_size.as_vec3() * self.voxel_size
+    }
+    /// Coordinates of the chunk containing the position specified.
+    pub fn chunk_coords(&self, position: Vec3) -> IVec3 {
+        (position / self.chunk_extents()).floor().as_ivec3()
+    }
+    /// Center of the chunk at the coordinates specified.
+    pub fn chunk_center(&self, coords: IVec3) -> Vec3 {
+        (coords.as_vec3() + 0.5) * self.chunk_extents()
+    }
+}
+impl Default for ChunkStreamingConfig {
+    fn default() -> Self {
+        Self {
+            radius: UVec3::new(2, 1, 2),
+            chunk_size: UVec3::new(16, 16, 16),
+            voxel_size: Vec3::ONE,
+            loads_per_tick: 4,
+            interval: 1,
+            collision_config: CollisionConfig::new(GROUP_STATIC_TERRAIN, GROUP_NONE)
+        }
+    }
+}
+
+/// Resource that stores the chunk entities currently loaded by the [`ChunkStreamingPlugin`], keyed by chunk coordinates.
+#[derive(Resource, Debug, Default)]
+pub struct VoxelChunkMap {
+    chunks: HashMap<IVec3, Entity>,
+    /// Coordinates the provider had no chunk for
+    empty: HashSet<IVec3>
+}
+impl VoxelChunkMap {
+    /// Entity of the chunk loaded at the coordinates specified.
+    pub fn get(&self, coords: IVec3) -> Option<Entity> {
+        self.chunks.get(&coords).copied()
+    }
+    pub fn contains(&self, coords: IVec3) -> bool {
+        self.chunks.contains_key(&coords)
+    }
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+    /// Iterates over the coordinates and entities of all loaded chunks.
+    pub fn iter(&self) -> impl Iterator<Item = (IVec3, Entity)> + '_ {
+        self.chunks.iter().map(|(coords, entity)| (*coords, *entity))
+    }
+}
+
+/// Marker component for entities that chunks should be loaded around.
+#[derive(Component, Debug, Copy, Clone, Default)]
+pub struct ChunkFocus;
+
+/// Component added to chunk entities spawned by the [`ChunkStreamingPlugin`].
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct StreamedChunk(pub IVec3);
+
+/// Event fired when a chunk entity gets spawned.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ChunkLoaded {
+    pub coords: IVec3,
+    pub entity: Entity
+}
+
+/// Event fired when a chunk entity gets despawned.
+/// Carries the chunk's data at the time of despawning so that edits can be persisted.
+#[derive(Debug, Clone)]
+pub struct ChunkUnloaded {
+    pub coords: IVec3,
+    pub entity: Entity,
+    pub chunk: VoxelChunk
+}
+
+#[derive(Resource)]
+struct ChunkSource(Arc<dyn ChunkProvider>);
+
+/// Despawns chunks out of range of every focus, then spawns missing chunks nearest first.
+#[allow(clippy::too_many_arguments)]
+fn stream_chunks(
+    mut commands: Commands,
+    config: Res<ChunkStreamingConfig>,
+    source: Res<ChunkSource>,
+    mut map: ResMut<VoxelChunkMap>,
+    mut ticks: Local<u32>,
+    focuses: Query<&CurrentTransform, With<ChunkFocus>>,
+    chunks: Query<&Shape, With<StreamedChunk>>,
+    mut loaded_writer: EventWriter<ChunkLoaded>,
+    mut unloaded_writer: EventWriter<ChunkUnloaded>
+) {
+    // Throttles scans
+    *ticks += 1;
+    if *ticks < config.interval {
+        return;
+    }
+    *ticks = 0;
+
+    // Forgets chunks that were despawned by something other than this plugin
+    map.chunks.retain(|_, entity| chunks.contains(*entity));
+
+    let radius = config.radius.as_ivec3();
+    let centers: Vec<IVec3> = focuses
+        .iter()
+        .map(|trans| config.chunk_coords(trans.0.translation))
+        .collect();
+    let in_range = |coords: IVec3| centers
+        .iter()
+        .any(|center| (coords - *center).abs().cmple(radius).all());
+
+    // Unloads chunks out of range
+    let out_of_range: Vec<(IVec3, Entity)> = map
+        .iter()
+        .filter(|(coords, _)| !in_range(*coords))
+        .collect();
+    for (coords, entity) in out_of_range {
+        map.chunks.remove(&coords);
+        if let Ok(Shape::VoxelChunk(chunk)) = chunks.get(entity) {
+            unloaded_writer.send(ChunkUnloaded { coords, entity, chunk: chunk.clone() });
+        }
+        commands.entity(entity).despawn();
+    }
+    map.empty.retain(|coords| in_range(*coords));
+
+    // Collects missing chunks, nearest first
+    let mut missing = Vec::new();
+    for center in &centers {
+        for z in -radius.z..=radius.z {
+            for y in -radius.y..=radius.y {
+                for x in -radius.x..=radius.x {
+                    let coords = *center + IVec3::new(x, y, z);
+                    if !map.chunks.contains_key(&coords) && !map.empty.contains(&coords) {
+                        missing.push(coords);
+                    }
+                }
+            }
+        }
+    }
+    let distance = |coords: IVec3| centers
+        .iter()
+        .map(|center| (coords - *center).as_vec3().length_squared())
+        .fold(f32::INFINITY, f32::min);
+    missing.sort_by(|a, b| distance(*a)
+        .total_cmp(&distance(*b))
+        .then(a.to_array().cmp(&b.to_array()))
+    );
+    missing.dedup();
+
+    // Loads chunks within budget
+    for coords in missing.into_iter().take(config.loads_per_tick) {
+        let chunk = match source.0.load(coords) {
+            Some(chunk) => chunk,
+            None => {
+                map.empty.insert(coords);
+                continue;
+            }
+        };
+        let transform = Transform::from_translation(config.chunk_center(coords));
+        let bounds = HalfExtents(config.chunk_extents() / 2.0);
+        let entity = commands
+            .spawn(PhysicsBundle {
+                config: config.collision_config,
+                ..PhysicsBundle::new(transform, bounds, Shape::VoxelChunk(chunk))
+            })
+            .insert((AntiGravity, StreamedChunk(coords)))
+            .id();
+        map.chunks.insert(coords, entity);
+        loaded_writer.send(ChunkLoaded { coords, entity });
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+    use vidya_fixed_timestep::{CurrentTransform, Phase};
+
+    use crate::*;
+
+    /// Provides a floor one chunk thick at y = 0
+    struct FloorProvider;
+    impl ChunkProvider for FloorProvider {
+        fn load(&self, coords: IVec3) -> Option<VoxelChunk> {
+            (coords.y == 0).then(|| VoxelChunk::new(UVec3::new(4, 1, 4)))
+        }
+    }
+
+    fn app(config: ChunkStreamingConfig) -> App {
+        let mut app = App::new();
+        app
+            .add_stage_after(CoreStage::Update, Phase::Update.stage(), SystemStage::parallel())
+            .add_plugin(ChunkStreamingPlugin::new(FloorProvider, config));
+        app
+    }
+
+    #[test]
+    fn streams_around_focus() {
+        let config = ChunkStreamingConfig {
+            radius: UVec3::new(1, 1, 1),
+            chunk_size: UVec3::new(4, 1, 4),
+            loads_per_tick: 27,
+            ..Default::default()
+        };
+        let mut app = app(config);
+        let focus = app.world
+            .spawn((ChunkFocus, CurrentTransform(Transform::from_xyz(1.0, 0.5, 1.0))))
+            .id();
+
+        // Only the 9 floor chunks around the focus exist
+        app.update();
+        let map = app.world.resource::<VoxelChunkMap>();
+        assert_eq!(9, map.len());
+        assert!(map.contains(IVec3::new(-1, 0, -1)));
+        assert!(map.contains(IVec3::new(1, 0, 1)));
+        assert!(!map.contains(IVec3::new(2, 0, 0)));
+
+        // Moving two chunks over unloads the trailing column and loads the leading one
+        app.world.get_mut::<CurrentTransform>(focus).unwrap().0.translation.x += 8.0;
+        app.update();
+        let map = app.world.resource::<VoxelChunkMap>();
+        assert_eq!(9, map.len());
+        assert!(!map.contains(IVec3::new(0, 0, 0)));
+        assert!(map.contains(IVec3::new(3, 0, 0)));
+        assert_eq!(6, app.world.resource::<Events<ChunkUnloaded>>().len());
+    }
+
+    #[test]
+    fn loads_within_budget() {
+        let config = ChunkStreamingConfig {
+            radius: UVec3::new(1, 0, 1),
+            chunk_size: UVec3::new(4, 1, 4),
+            loads_per_tick: 4,
+            ..Default::default()
+        };
+        let mut app = app(config);
+        app.world.spawn((ChunkFocus, CurrentTransform::default()));
+
+        app.update();
+        assert_eq!(4, app.world.resource::<VoxelChunkMap>().len());
+        app.update();
+        assert_eq!(8, app.world.resource::<VoxelChunkMap>().len());
+        app.update();
+        assert_eq!(9, app.world.resource::<VoxelChunkMap>().len());
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+
+/// Tag describing what an [`Entity`]'s surface is made of, like stone or metal.
+/// Reported in [`crate::Contacts`] so that footstep and impact sounds can depend on what was touched.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Hash, Default, Reflect)]
+#[reflect(Component)]
+pub struct SurfaceTag(pub u16);
+
+/// Resource that maps [`SurfaceTag`]s to human-readable names for debugging.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SurfaceTags {
+    names: HashMap<SurfaceTag, String>
+}
+impl SurfaceTags {
+    /// Names a tag and returns self.
+    pub fn with_tag(mut self, tag: SurfaceTag, name: impl Into<String>) -> Self {
+        self.insert(tag, name);
+        self
+    }
+    /// Names a tag, replacing its previous name if any.
+    pub fn insert(&mut self, tag: SurfaceTag, name: impl Into<String>) {
+        self.names.insert(tag, name.into());
+    }
+    /// Name of the tag specified.
+    pub fn name(&self, tag: SurfaceTag) -> Option<&str> {
+        self.names.get(&tag).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    #[test]
+    fn contacts_report_surface() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        let floor = spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(10.0, 1.0, 10.0), Shape::Cuboid).insert(SurfaceTag(3)).id();
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+
+        // Resting on the floor touches it every tick
+        for _ in 0..5 {
+            app.update();
+            let contacts = app.world.get::<Contacts>(body).unwrap();
+            assert_eq!(1, contacts.len());
+            let contact = contacts.get(floor).unwrap();
+            assert_eq!(Some(SurfaceTag(3)), contact.surface);
+            assert_eq!(Vec3::Y, contact.normal);
+        }
+    }
+}
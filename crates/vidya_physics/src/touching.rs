@@ -0,0 +1,135 @@
+use std::collections::HashSet;
+
+use bevy_ecs::prelude::*;
+
+use crate::{CollisionEvent, Contacts, PhysicsConfig, Sleeping};
+
+/// Event fired on the first tick two physics objects collide after not touching.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CollisionStarted(pub Entity, pub Entity);
+
+/// Event fired on the first tick two physics objects that were touching stop colliding.
+/// Also fires when either of them is despawned, in which case that entity no longer exists.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CollisionEnded(pub Entity, pub Entity);
+
+/// Resource that stores the pairs of physics objects that collided during the last tick.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct TouchingPairs {
+    pairs: HashSet<(Entity, Entity)>,
+    previous: HashSet<(Entity, Entity)>
+}
+impl TouchingPairs {
+    /// True if the entities specified collided during the last tick, in either order.
+    pub fn contains(&self, a: Entity, b: Entity) -> bool {
+        self.pairs.contains(&pair(a, b))
+    }
+    /// Pairs of entities that collided during the last tick.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.pairs.iter().copied()
+    }
+}
+
+/// Same pair of entities regardless of the order they're specified in.
+fn pair(a: Entity, b: Entity) -> (Entity, Entity) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+/// Diffs the pairs that collided this tick against those of the last tick, firing events for pairs that started and stopped touching.
+/// [`Sleeping`] bodies don't collide, so they keep touching whatever is still in their [`Contacts`].
+pub(crate) fn track_touching_pairs(
+    config: Res<PhysicsConfig>,
+    mut touching: ResMut<TouchingPairs>,
+    sleepers: Query<&Contacts, With<Sleeping>>,
+    mut collision_reader: EventReader<CollisionEvent>,
+    mut started_writer: EventWriter<CollisionStarted>,
+    mut ended_writer: EventWriter<CollisionEnded>
+) {
+
+    // Nothing collides without time passing, so pairs are left as they were
+    if config.time_scale <= 0.0 {
+        collision_reader.clear();
+        return;
+    }
+
+    let touching = touching.as_mut();
+    std::mem::swap(&mut touching.pairs, &mut touching.previous);
+    touching.pairs.clear();
+    for event in collision_reader.iter() {
+        touching.pairs.insert(pair(event.entity_a, event.entity_b));
+    }
+    let still_touching = |sleeper: Entity, other: Entity| sleepers.get(sleeper).is_ok_and(|contacts| contacts.get(other).is_some());
+    for &(a, b) in &touching.previous {
+        if still_touching(a, b) || still_touching(b, a) {
+            touching.pairs.insert((a, b));
+        }
+    }
+    for (a, b) in touching.pairs.difference(&touching.previous) {
+        started_writer.send(CollisionStarted(*a, *b));
+    }
+    for (a, b) in touching.previous.difference(&touching.pairs) {
+        ended_writer.send(CollisionEnded(*a, *b));
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_app::prelude::*;
+    use bevy_ecs::event::Events;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    fn started(app: &App) -> Vec<CollisionStarted> {
+        app.world.resource::<Events<CollisionStarted>>().iter_current_update_events().copied().collect()
+    }
+
+    fn ended(app: &App) -> Vec<CollisionEnded> {
+        app.world.resource::<Events<CollisionEnded>>().iter_current_update_events().copied().collect()
+    }
+
+    #[test]
+    fn pressure_plate() {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        let plate = spawn_static_floor(&mut app.world, Transform::default(), HalfExtents::new(10.0, 1.0, 10.0), Shape::Cuboid).id();
+        let player = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(0.0, 1.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+            })
+            .id();
+        let (a, b) = if plate < player { (plate, player) } else { (player, plate) };
+
+        // Stepping on starts the collision once
+        app.update();
+        assert_eq!(vec![CollisionStarted(a, b)], started(&app));
+        for _ in 0..3 {
+            app.update();
+            assert!(started(&app).is_empty());
+            assert!(ended(&app).is_empty());
+            assert!(app.world.resource::<TouchingPairs>().contains(plate, player));
+        }
+
+        // Stepping off ends it once
+        app.world.get_mut::<Velocity>(player).unwrap().0 = Vec3::new(0.0, 1.0, 0.0);
+        app.world.insert_resource(Gravity(Vec3::ZERO));
+        app.update();
+        assert_eq!(vec![CollisionEnded(a, b)], ended(&app));
+        app.update();
+        assert!(ended(&app).is_empty());
+        assert!(!app.world.resource::<TouchingPairs>().contains(plate, player));
+
+        // Despawning while touching ends the collision
+        app.world.insert_resource(Gravity(Vec3::new(0.0, -0.1, 0.0)));
+        app.world.entity_mut(player).insert(CurrentTransform(Transform::from_xyz(0.0, 1.0, 0.0)));
+        app.world.get_mut::<Velocity>(player).unwrap().0 = Vec3::ZERO;
+        app.update();
+        assert_eq!(vec![CollisionStarted(a, b)], started(&app));
+        app.world.despawn(player);
+        app.update();
+        assert_eq!(vec![CollisionEnded(a, b)], ended(&app));
+    }
+}
@@ -0,0 +1,214 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
+
+use crate::{ChunkPatch, Shape, VoxelChunk};
+
+/// Resource that records chunk edits made with [`edit_chunk`] so they can be undone and redone.
+/// Edits are only recorded if this resource was added to the app.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct UndoStack {
+    undo: Vec<(Entity, ChunkPatch)>,
+    redo: Vec<(Entity, ChunkPatch)>,
+    /// Maximum number of edits remembered. Oldest edits are forgotten first. Unlimited if None.
+    pub capacity: Option<usize>
+}
+
+impl UndoStack {
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Records an edit made to a chunk entity. Clears the redo history.
+    pub fn push(&mut self, chunk: Entity, patch: ChunkPatch) {
+        if patch.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push((chunk, patch));
+        if let Some(capacity) = self.capacity {
+            let excess = self.undo.len().saturating_sub(capacity);
+            self.undo.drain(..excess);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+/// Edits the voxel chunk of an entity, recording the change in the [`UndoStack`] if there is one.
+/// Edits are applied through the entity's [`Shape`], so they count as a change.
+pub fn edit_chunk(commands: &mut Commands, chunk: Entity, edit: impl FnOnce(&mut VoxelChunk) + Send + Sync + 'static) {
+    commands.add(EditChunk { chunk, edit });
+}
+
+/// Reverts the most recent edit in the [`UndoStack`].
+/// Edits that no longer fit their chunk, like after it got resized, are skipped and forgotten.
+pub fn undo(commands: &mut Commands) {
+    commands.add(Undo);
+}
+
+/// Reapplies the most recently undone edit in the [`UndoStack`].
+/// Edits that no longer fit their chunk, like after it got resized, are skipped and forgotten.
+pub fn redo(commands: &mut Commands) {
+    commands.add(Redo);
+}
+
+struct EditChunk<F> {
+    chunk: Entity,
+    edit: F
+}
+impl<F: FnOnce(&mut VoxelChunk) + Send + Sync + 'static> Command for EditChunk<F> {
+    fn write(self, world: &mut World) {
+        let record = world.contains_resource::<UndoStack>();
+        let patch = {
+            let mut shape = match world.get_mut::<Shape>(self.chunk) {
+                Some(shape) => shape,
+                None => return
+            };
+            let chunk = match shape.as_mut() {
+                Shape::VoxelChunk(chunk) => chunk,
+                _ => return
+            };
+            let before = record.then(|| chunk.clone());
+            (self.edit)(chunk);
+            before.map(|before| before.diff(chunk))
+        };
+        if let Some(patch) = patch {
+            world.resource_mut::<UndoStack>().push(self.chunk, patch);
+        }
+    }
+}
+
+struct Undo;
+impl Command for Undo {
+    fn write(self, world: &mut World) {
+        let Some(mut stack) = world.get_resource_mut::<UndoStack>() else { return };
+        let Some((entity, patch)) = stack.undo.pop() else { return };
+        if let Some(Shape::VoxelChunk(chunk)) = world.get_mut::<Shape>(entity).as_deref_mut() {
+            if let Err(err) = chunk.try_revert_patch(&patch) {
+                bevy_log::warn!("Skipping undo of {entity:?}: {err}");
+                return;
+            }
+        }
+        world.resource_mut::<UndoStack>().redo.push((entity, patch));
+    }
+}
+
+struct Redo;
+impl Command for Redo {
+    fn write(self, world: &mut World) {
+        let Some(mut stack) = world.get_resource_mut::<UndoStack>() else { return };
+        let Some((entity, patch)) = stack.redo.pop() else { return };
+        if let Some(Shape::VoxelChunk(chunk)) = world.get_mut::<Shape>(entity).as_deref_mut() {
+            if let Err(err) = chunk.try_apply_patch(&patch) {
+                bevy_log::warn!("Skipping redo of {entity:?}: {err}");
+                return;
+            }
+        }
+        world.resource_mut::<UndoStack>().undo.push((entity, patch));
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_ecs::system::CommandQueue;
+    use bevy_math::prelude::*;
+
+    use crate::*;
+
+    fn apply(world: &mut World, command: impl FnOnce(&mut Commands)) {
+        let mut queue = CommandQueue::default();
+        command(&mut Commands::new(&mut queue, world));
+        queue.apply(world);
+    }
+
+    fn chunk_of(world: &World, entity: Entity) -> &VoxelChunk {
+        match world.get::<Shape>(entity).unwrap() {
+            Shape::VoxelChunk(chunk) => chunk,
+            _ => panic!("Not a chunk")
+        }
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut world = World::new();
+        world.init_resource::<UndoStack>();
+        let original = VoxelChunk::new(UVec3::new(4, 4, 4));
+        let entity = world.spawn(Shape::VoxelChunk(original.clone())).id();
+        apply(&mut world, |commands| edit_chunk(commands, entity, |chunk| {
+            chunk.set_voxel(UVec3::ZERO, VoxelData::new(Voxel::Cuboid));
+        }));
+        apply(&mut world, |commands| edit_chunk(commands, entity, |chunk| {
+            chunk.carve_sphere(Vec3::ZERO, 1.0);
+            chunk.set_voxel(UVec3::ONE, VoxelData::new(Voxel::Slope));
+        }));
+        let edited = chunk_of(&world, entity).clone();
+
+        // Undoing past the first edit does nothing
+        world.clear_trackers();
+        apply(&mut world, |commands| {
+            undo(commands);
+            undo(commands);
+            undo(commands);
+        });
+        assert_eq!(&original, chunk_of(&world, entity));
+        assert!(world.query_filtered::<(), Changed<Shape>>().get(&world, entity).is_ok());
+
+        // Redoes both edits, then a new edit clears the redo history
+        apply(&mut world, |commands| {
+            redo(commands);
+            redo(commands);
+        });
+        assert_eq!(&edited, chunk_of(&world, entity));
+        apply(&mut world, |commands| {
+            undo(commands);
+            edit_chunk(commands, entity, |chunk| { chunk.set_voxel(UVec3::new(3, 3, 3), VoxelData::new(Voxel::Cuboid)); });
+        });
+        assert!(!world.resource::<UndoStack>().can_redo());
+    }
+
+    #[test]
+    fn skips_edits_that_no_longer_fit() {
+        let mut world = World::new();
+        world.init_resource::<UndoStack>();
+        let entity = world.spawn(Shape::VoxelChunk(VoxelChunk::new(UVec3::new(4, 4, 4)))).id();
+        let edit = |commands: &mut Commands| edit_chunk(commands, entity, |chunk| {
+            chunk.set_voxel(UVec3::ZERO, VoxelData::new(Voxel::Cuboid));
+        });
+        let resize = |world: &mut World, size: UVec3| {
+            let mut resized = VoxelChunk::new(size);
+            resized.set_voxel(UVec3::ONE, VoxelData::new(Voxel::Slope));
+            world.entity_mut(entity).insert(Shape::VoxelChunk(resized.clone()));
+            resized
+        };
+
+        // Undoing an edit made before a resize leaves the chunk alone and forgets the edit
+        apply(&mut world, edit);
+        let resized = resize(&mut world, UVec3::new(8, 4, 4));
+        apply(&mut world, undo);
+        assert_eq!(&resized, chunk_of(&world, entity));
+        assert!(!world.resource::<UndoStack>().can_undo());
+        assert!(!world.resource::<UndoStack>().can_redo());
+
+        // So does redoing one
+        apply(&mut world, edit);
+        apply(&mut world, undo);
+        let resized = resize(&mut world, UVec3::new(8, 8, 8));
+        apply(&mut world, redo);
+        assert_eq!(&resized, chunk_of(&world, entity));
+        assert!(!world.resource::<UndoStack>().can_undo());
+        assert!(!world.resource::<UndoStack>().can_redo());
+    }
+}
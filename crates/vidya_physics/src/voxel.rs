@@ -4,6 +4,10 @@ use bevy_ecs::prelude::*;
 use bevy_math::prelude::*;
 use bevy_math::Vec3Swizzles;
 use bevy_reflect::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use serde::de::Error as _;
 
 use super::*;
 
@@ -11,6 +15,7 @@ use super::*;
 
 /// A collider stored in a [`VoxelChunk`].
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Voxel {
     /// No voxel
     #[default]
@@ -21,17 +26,37 @@ pub enum Voxel {
     Slope
 }
 
-/// Stores both a [`Voxel`] and its orientation.
+bitflags::bitflags! {
+    /// Behavioral flags of a voxel.
+    /// Flags can be set on [`Voxel::Empty`] cells too, like a climbable ladder cell that doesn't collide.
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct VoxelFlags: u16 {
+        /// Only collides with objects coming from above, like a platform that can be jumped through from below
+        const ONE_WAY_UP =      0b0000_0001;
+        /// Can be climbed by objects overlapping it
+        const CLIMBABLE =       0b0000_0010;
+        /// Top face never collides
+        const NO_COLLIDE_TOP =  0b0000_0100;
+        /// Never collides
+        const NO_COLLIDE =      0b0000_1000;
+    }
+}
+
+/// Stores a [`Voxel`], its orientation and its flags.
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VoxelData {
     pub voxel: Voxel,
-    pub orientation: Orientation
+    pub orientation: Orientation,
+    pub flags: VoxelFlags
 }
 impl VoxelData {
     pub fn new(voxel: Voxel) -> Self {
         Self {
             voxel,
-            orientation: Orientation::default()
+            orientation: Orientation::default(),
+            flags: VoxelFlags::empty()
         }
     }
 
@@ -39,10 +64,20 @@ impl VoxelData {
         self.orientation = orientation;
         self
     }
+
+    pub fn with_flags(mut self, flags: VoxelFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// True if this voxel can collide with anything.
+    pub fn is_solid(&self) -> bool {
+        self.voxel != Voxel::Empty && !self.flags.contains(VoxelFlags::NO_COLLIDE)
+    }
 }
 
 /// Represents a chunk of [`Voxel`]s stored in an [`Entity`].
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, PartialEq, Reflect)]
 pub struct VoxelChunk {
     size: UVec3,
     #[reflect(ignore)]
@@ -51,11 +86,23 @@ pub struct VoxelChunk {
 impl VoxelChunk {
 
     /// Allocates an empty voxel chunk.
+    /// Panics if the size is invalid. See [`VoxelChunk::try_new`].
     pub fn new(size: UVec3) -> Self {
-        Self {
+        Self::try_new(size).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Allocates an empty voxel chunk.
+    /// Fails if any dimension is zero, or if the voxel count does not fit in a u32.
+    pub fn try_new(size: UVec3) -> Result<Self, Error> {
+        let len = size.x
+            .checked_mul(size.y)
+            .and_then(|len| len.checked_mul(size.z))
+            .filter(|len| *len != 0)
+            .ok_or(Error::InvalidSize { size })?;
+        Ok(Self {
             size,
-            voxels: vec![VoxelData::default(); (size.x * size.y * size.z) as usize]
-        }
+            voxels: vec![VoxelData::default(); len as usize]
+        })
     }
 
     /// Size of the chunk measured in voxels
@@ -85,18 +132,22 @@ impl VoxelChunk {
 
     /// Sets the value of a voxel and returns self.
     /// Helpful when setting multiple voxels at once.
+    /// Panics if out of bounds. See [`VoxelChunk::try_set_voxel`].
     pub fn set_voxel(&mut self, coords: UVec3, voxel_data: VoxelData) -> &mut Self {
-        let idx = self.to_voxel_index(coords);
-        if !self.in_bounds(coords) {
-            panic!("Coordiantes out of bounds");
-        }
-        let current_voxel = self.voxels.get_mut(idx).expect("Voxel coordinates out of bounds");
-        *current_voxel = voxel_data;
-        self
+        self.try_set_voxel(coords, voxel_data).unwrap_or_else(|err| panic!("{err}"))
     }
 
     /// Sets the value of a voxel and returns self.
-    /// Helpful when setting multiple voxels at once.
+    /// Fails if out of bounds.
+    pub fn try_set_voxel(&mut self, coords: UVec3, voxel_data: VoxelData) -> Result<&mut Self, Error> {
+        self.check_bounds(coords)?;
+        let idx = self.to_voxel_index(coords);
+        self.voxels[idx] = voxel_data;
+        Ok(self)
+    }
+
+    /// Sets the value of every voxel in a plane between `src` (inclusive) and `dest` (exclusive) and returns self.
+    /// Panics if out of bounds. See [`VoxelChunk::try_set_voxel_plane`].
     pub fn set_voxel_plane(
         &mut self,
         xyz: u32,
@@ -105,28 +156,73 @@ impl VoxelChunk {
         axis: PlaneAxis,
         voxel_data: VoxelData
     ) -> &mut Self {
-        match axis {
-            PlaneAxis::XY => {
+        self.try_set_voxel_plane(xyz, src, dest, axis, voxel_data).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Sets the value of every voxel in a plane between `src` (inclusive) and `dest` (exclusive) and returns self.
+    /// Fails without setting any voxel if the plane is out of bounds.
+    pub fn try_set_voxel_plane(
+        &mut self,
+        xyz: u32,
+        src: UVec2,
+        dest: UVec2,
+        axis: PlaneAxis,
+        voxel_data: VoxelData
+    ) -> Result<&mut Self, Error> {
+        let (src, dest) = match axis {
+            PlaneAxis::XY => (UVec3::new(src.x, src.y, xyz), UVec3::new(dest.x, dest.y, xyz + 1)),
+            PlaneAxis::YZ => (UVec3::new(xyz, src.x, src.y), UVec3::new(xyz + 1, dest.x, dest.y)),
+            PlaneAxis::XZ => (UVec3::new(src.x, xyz, src.y), UVec3::new(dest.x, xyz + 1, dest.y))
+        };
+        self.try_set_voxel_box(src, dest, voxel_data)
+    }
+
+    /// Sets the value of every voxel between `src` (inclusive) and `dest` (exclusive) and returns self.
+    /// Panics if out of bounds. See [`VoxelChunk::try_set_voxel_box`].
+    pub fn set_voxel_box(&mut self, src: UVec3, dest: UVec3, voxel_data: VoxelData) -> &mut Self {
+        self.try_set_voxel_box(src, dest, voxel_data).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Sets the value of every voxel between `src` (inclusive) and `dest` (exclusive) and returns self.
+    /// Fails without setting any voxel if the box is out of bounds.
+    pub fn try_set_voxel_box(&mut self, src: UVec3, dest: UVec3, voxel_data: VoxelData) -> Result<&mut Self, Error> {
+        if src.cmpge(dest).any() {
+            return Ok(self);
+        }
+        self.check_bounds(dest - UVec3::ONE)?;
+        for z in src.z..dest.z {
+            for y in src.y..dest.y {
                 for x in src.x..dest.x {
-                    for y in src.y..dest.y {
-                        let z = xyz;
-                        self.set_voxel(UVec3::new(x, y, z), voxel_data);
-                    }
+                    let idx = self.to_voxel_index(UVec3::new(x, y, z));
+                    self.voxels[idx] = voxel_data;
                 }
-            },
-            PlaneAxis::YZ => {
-                for y in src.x..dest.x {
-                    for z in src.y..dest.y {
-                        let x = xyz;
-                        self.set_voxel(UVec3::new(x, y, z), voxel_data);
-                    }
-                }
-            },
-            PlaneAxis::XZ => {
-                for x in src.x..dest.x {
-                    for z in src.y..dest.y {
-                        let y = xyz;
-                        self.set_voxel(UVec3::new(x, y, z), voxel_data);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Empties every voxel whose center is within `radius` of `center`, and returns self.
+    /// Both are measured in voxels, with voxel (0, 0, 0) spanning (0, 0, 0) to (1, 1, 1).
+    pub fn carve_sphere(&mut self, center: Vec3, radius: f32) -> &mut Self {
+        self.set_voxel_sphere(center, radius, VoxelData::default())
+    }
+
+    /// Sets every voxel whose center is within `radius` of `center`, and returns self.
+    /// Both are measured in voxels, with voxel (0, 0, 0) spanning (0, 0, 0) to (1, 1, 1).
+    pub fn fill_sphere(&mut self, center: Vec3, radius: f32, voxel_data: VoxelData) -> &mut Self {
+        self.set_voxel_sphere(center, radius, voxel_data)
+    }
+
+    fn set_voxel_sphere(&mut self, center: Vec3, radius: f32, voxel_data: VoxelData) -> &mut Self {
+        let start = (center - radius).floor().max(Vec3::ZERO).as_uvec3();
+        let end = (center + radius).ceil().max(Vec3::ZERO).as_uvec3().min(self.size);
+        for z in start.z..end.z {
+            for y in start.y..end.y {
+                for x in start.x..end.x {
+                    let coords = UVec3::new(x, y, z);
+                    if (coords.as_vec3() + 0.5).distance_squared(center) <= radius * radius {
+                        let idx = self.to_voxel_index(coords);
+                        self.voxels[idx] = voxel_data;
                     }
                 }
             }
@@ -134,6 +230,13 @@ impl VoxelChunk {
         self
     }
 
+    fn check_bounds(&self, coords: UVec3) -> Result<(), Error> {
+        match self.in_bounds(coords) {
+            true => Ok(()),
+            false => Err(Error::OutOfBounds { coords, size: self.size })
+        }
+    }
+
     fn in_bounds(&self, coords: UVec3) -> bool {
         coords.x < self.size.x && coords.y < self.size.y && coords.z < self.size.z
     }
@@ -160,9 +263,85 @@ impl VoxelChunk {
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for VoxelChunk {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PackedChunk::pack(self).serialize(serializer)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for VoxelChunk {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        PackedChunk::deserialize(deserializer)?.unpack().map_err(D::Error::custom)
+    }
+}
+
+/// Serialized form of a [`VoxelChunk`].
+/// Each distinct voxel is stored once in a palette, and the voxels themselves as runs of palette indices in storage order.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct PackedChunk {
+    size: UVec3,
+    palette: Vec<VoxelData>,
+    /// Palette index and length of each run
+    runs: Vec<(u32, u32)>
+}
+#[cfg(feature = "serde")]
+impl PackedChunk {
+    fn pack(chunk: &VoxelChunk) -> Self {
+        let mut palette: Vec<VoxelData> = Vec::new();
+        let mut runs: Vec<(u32, u32)> = Vec::new();
+        for voxel in &chunk.voxels {
+            let index = match palette.iter().position(|entry| entry == voxel) {
+                Some(index) => index as u32,
+                None => {
+                    palette.push(*voxel);
+                    palette.len() as u32 - 1
+                }
+            };
+            match runs.last_mut() {
+                Some((run_index, len)) if *run_index == index => *len += 1,
+                _ => runs.push((index, 1))
+            }
+        }
+        Self { size: chunk.size, palette, runs }
+    }
+
+    fn unpack(self) -> Result<VoxelChunk, String> {
+        let mut chunk = VoxelChunk::try_new(self.size).map_err(|err| err.to_string())?;
+        let len = chunk.voxels.len();
+        let mut start = 0;
+        for (index, run_len) in self.runs {
+            let voxel = *self.palette
+                .get(index as usize)
+                .ok_or_else(|| format!("Palette index {index} out of bounds of palette with length {}", self.palette.len()))?;
+            let end = start + run_len as usize;
+            let run = chunk.voxels
+                .get_mut(start..end)
+                .ok_or_else(|| format!("Runs cover more than the {len} voxels of the chunk"))?;
+            run.fill(voxel);
+            start = end;
+        }
+        if start != len {
+            return Err(format!("Runs cover {start} of the {len} voxels of the chunk"));
+        }
+        Ok(chunk)
+    }
+}
+
 /// Axis an axis-aligned plane can sit on
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Reflect, FromReflect)]
 pub enum PlaneAxis { XY, YZ, XZ }
+impl PlaneAxis {
+    /// Unit vector along the axis perpendicular to the plane.
+    pub fn normal(self) -> Vec3 {
+        match self {
+            Self::XY => Vec3::Z,
+            Self::YZ => Vec3::X,
+            Self::XZ => Vec3::Y
+        }
+    }
+}
 
 pub struct VoxelChunkIterator<'a> {
     chunk: &'a VoxelChunk,
@@ -203,6 +382,7 @@ impl<'a> Iterator for VoxelChunkIterator<'a> {
 
 /// Similar to a euler rotation in the order of XYZ, except constrained to 90 degree angles
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Orientation {
     /// Rotation along x axis
     pub x_rot: Degree,
@@ -232,25 +412,54 @@ impl Orientation {
         self.z_rot = z_rot;
         self
     }
+
+    /// Rotation matrix equivalent to this orientation.
+    pub fn to_mat3(self) -> Mat3 {
+        Mat3::from_cols(self * Vec3::X, self * Vec3::Y, self * Vec3::Z)
+    }
+
+    /// Finds the orientation equivalent to a rotation matrix.
+    /// Always returns the same orientation for a given rotation, even though several combinations of degrees can represent it.
+    /// Returns None if the matrix is not one of the 24 rotations that orientations can represent.
+    pub fn from_mat3(mat: Mat3) -> Option<Self> {
+        Self::all().find(|orientation| orientation.to_mat3().abs_diff_eq(mat, 0.0001))
+    }
+
+    /// Orientation that applies `other`, then `self`.
+    /// `a.compose(b) * v == a * (b * v)`.
+    pub fn compose(self, other: Self) -> Self {
+        Self::from_mat3(self.to_mat3() * other.to_mat3()).expect("Composed orientations should be representable")
+    }
+
+    /// Orientation that undoes this one.
+    /// `a.inverse() * (a * v) == v`.
+    pub fn inverse(self) -> Self {
+        Self::from_mat3(self.to_mat3().transpose()).expect("Inverse orientation should be representable")
+    }
+
+    /// Every combination of degrees, including combinations that represent the same rotation.
+    fn all() -> impl Iterator<Item = Self> {
+        const DEGREES: [Degree; 4] = [Degree::Zero, Degree::Ninty, Degree::OneEighty, Degree::TwoSeventy];
+        DEGREES.into_iter().flat_map(|x_rot| DEGREES.into_iter().flat_map(move |y_rot| DEGREES
+            .into_iter()
+            .map(move |z_rot| Self::new(x_rot, y_rot, z_rot))
+        ))
+    }
 }
+/// Composes two orientations, which is NOT the same as adding each axis' [`Degree`] independently.
+/// `(a + b) * v == a * (b * v)`, so `b` gets applied first. See [`Orientation::compose`].
 impl Add for Orientation {
     type Output = Self;
     fn add(self, rhs: Self) -> Self {
-        Orientation {
-            x_rot: self.x_rot + rhs.x_rot,
-            y_rot: self.y_rot + rhs.y_rot,
-            z_rot: self.z_rot + rhs.z_rot,
-        }
+        self.compose(rhs)
     }
 }
+/// Orientation of `self` relative to `rhs`, such that `(a - b) + b` is equivalent to `a`.
+/// Like [`Add`], this does NOT subtract each axis' [`Degree`] independently.
 impl Sub for Orientation {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self {
-        Orientation {
-            x_rot: self.x_rot - rhs.x_rot,
-            y_rot: self.y_rot - rhs.y_rot,
-            z_rot: self.z_rot - rhs.z_rot,
-        }
+        self.compose(rhs.inverse())
     }
 }
 impl Mul<Vec3> for Orientation {
@@ -266,6 +475,7 @@ impl Mul<Vec3> for Orientation {
 
 /// Degree of an [`Orientation`] at perfect 90 degree angles.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Degree {
     #[default]
     Zero,
@@ -321,13 +531,11 @@ impl Degree {
     }
 
     fn from_num(num: usize) -> Degree {
-        let num = num % 4;
-        match num {
+        match num % 4 {
             0 => Degree::Zero,
             1 => Degree::Ninty,
             2 => Degree::OneEighty,
-            3 => Degree::TwoSeventy,
-            _ => panic!("This error is impossible")
+            _ => Degree::TwoSeventy
         }
     }
 }
@@ -413,6 +621,74 @@ mod orientation_tests {
             orientation * Vec3::new(0.0, 1.0, 0.0)
         );
     }
+
+    #[test]
+    fn compose() {
+        let vectors = [Vec3::X, Vec3::Y, Vec3::Z, Vec3::new(1.0, 2.0, 3.0)];
+        for a in Orientation::all() {
+            for b in Orientation::all() {
+                let composed = a.compose(b);
+                assert_eq!(composed, a + b);
+                for v in vectors {
+                    assert_eq!(composed * v, a * (b * v));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        for a in Orientation::all() {
+            assert_eq!(v, a.inverse() * (a * v));
+            assert_eq!(v, a * (a.inverse() * v));
+            for b in Orientation::all() {
+                assert_eq!((a - b) * (b * v), a * v);
+            }
+        }
+    }
+
+    #[test]
+    fn canonical() {
+        let rotations: std::collections::HashSet<Orientation> = Orientation::all()
+            .map(|orientation| Orientation::from_mat3(orientation.to_mat3()).unwrap())
+            .collect();
+        assert_eq!(24, rotations.len());
+        assert_eq!(None, Orientation::from_mat3(Mat3::from_diagonal(Vec3::new(-1.0, 1.0, 1.0))));
+    }
+}
+
+#[cfg(test)]
+mod voxel_flags_tests {
+
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    #[test]
+    fn overlapped_flags() {
+        let mut app = physics_test_app();
+        let ladder = VoxelData::new(Voxel::Empty).with_flags(VoxelFlags::CLIMBABLE);
+        let mut chunk = VoxelChunk::new(UVec3::new(4, 4, 4));
+        chunk.set_voxel_box(UVec3::new(0, 0, 0), UVec3::new(1, 4, 1), ladder);
+        app.world
+            .spawn(PhysicsBundle::new(Transform::default(), HalfExtents::new(4.0, 4.0, 4.0), Shape::VoxelChunk(chunk)))
+            .insert(AntiGravity);
+        let body = app.world
+            .spawn(PhysicsBundle::new(Transform::from_xyz(-1.5, 0.0, -1.5), HalfExtents::new(0.5, 0.5, 0.5), Shape::Cuboid))
+            .insert((AntiGravity, OverlappedVoxelFlags::default()))
+            .id();
+
+        // Inside the ladder column
+        app.update();
+        assert_eq!(VoxelFlags::CLIMBABLE, app.world.get::<OverlappedVoxelFlags>(body).unwrap().0);
+
+        // Beside the ladder column
+        app.world.get_mut::<CurrentTransform>(body).unwrap().0.translation.x = 1.5;
+        app.update();
+        assert_eq!(VoxelFlags::empty(), app.world.get::<OverlappedVoxelFlags>(body).unwrap().0);
+    }
 }
 
 #[cfg(test)]
@@ -420,7 +696,7 @@ mod voxel_chunk_tests {
 
     use bevy_math::UVec3;
 
-    use crate::{ Voxel, VoxelChunk, VoxelData };
+    use crate::{ Voxel, VoxelChunk, VoxelData, VoxelFlags };
 
     #[test]
     fn build() {
@@ -447,10 +723,54 @@ mod voxel_chunk_tests {
         assert_eq!(Some(&VoxelData::new(Voxel::Slope)), chunk.get_voxel(UVec3::new(15, 15, 15)));
         assert_eq!(Some(&VoxelData::new(Voxel::Cuboid)), chunk.get_voxel(UVec3::new(8, 8, 8)));
 
+        // Fills a box with flagged voxels
+        let ladder = VoxelData::new(Voxel::Empty).with_flags(VoxelFlags::CLIMBABLE);
+        chunk.set_voxel_box(UVec3::new(10, 0, 10), UVec3::new(11, 4, 11), ladder);
+        assert_eq!(Some(&ladder), chunk.get_voxel(UVec3::new(10, 3, 10)));
+        assert_eq!(Some(&VoxelData::default()), chunk.get_voxel(UVec3::new(10, 4, 10)));
+        assert!(!ladder.is_solid());
+
         // Checks out-of-bounds returns None
         assert_eq!(None, chunk.get_voxel(UVec3::new(16, 0, 0)));
         assert_eq!(None, chunk.get_voxel(UVec3::new(0, 16, 0)));
         assert_eq!(None, chunk.get_voxel(UVec3::new(0, 0, 16)));
         assert_eq!(None, chunk.get_voxel(UVec3::new(1337, 1337, 1337)));
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+
+    use bevy_math::prelude::*;
+
+    use crate::*;
+
+    #[test]
+    fn chunk_round_trips_through_ron() {
+        let mut chunk = VoxelChunk::new(UVec3::splat(16));
+        chunk
+            .set_voxel_box(UVec3::ZERO, UVec3::new(16, 2, 16), VoxelData::new(Voxel::Cuboid))
+            .set_voxel_box(UVec3::new(0, 2, 0), UVec3::new(16, 3, 4), VoxelData::new(Voxel::Slope).with_orientation(Orientation::ZERO.with_y_rot(Degree::Ninty)))
+            .set_voxel_box(UVec3::new(4, 6, 4), UVec3::new(8, 7, 8), VoxelData::new(Voxel::Cuboid).with_flags(VoxelFlags::ONE_WAY_UP))
+            .set_voxel_box(UVec3::new(12, 2, 12), UVec3::new(13, 10, 13), VoxelData::new(Voxel::Empty).with_flags(VoxelFlags::CLIMBABLE));
+        for i in 0..16 {
+            chunk.set_voxel(UVec3::new(i, 15, (i * 7) % 16), VoxelData::new(Voxel::Cuboid));
+        }
+        let serialized = ron::to_string(&chunk).unwrap();
+        let deserialized: VoxelChunk = ron::from_str(&serialized).unwrap();
+        assert_eq!(chunk, deserialized);
+
+        // Much smaller than a voxel per entry
+        assert!(serialized.len() < 4096, "{} bytes", serialized.len());
+
+        // Doesn't trust the runs it's given
+        let too_short = "(size: (2, 2, 2), palette: [(voxel: Cuboid, orientation: (x_rot: Zero, y_rot: Zero, z_rot: Zero), flags: (bits: 0))], runs: [(0, 7)])";
+        assert!(ron::from_str::<VoxelChunk>(too_short).is_err());
+        let too_long = too_short.replace("(0, 7)", "(0, 9)");
+        assert!(ron::from_str::<VoxelChunk>(&too_long).is_err());
+        let bad_index = too_short.replace("(0, 7)", "(1, 8)");
+        assert!(ron::from_str::<VoxelChunk>(&bad_index).is_err());
+        let valid = too_short.replace("(0, 7)", "(0, 8)");
+        assert_eq!(Some(Voxel::Cuboid), ron::from_str::<VoxelChunk>(&valid).unwrap().get_voxel(UVec3::ONE).map(|data| data.voxel));
+    }
+}
@@ -0,0 +1,147 @@
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::DespawnRecursiveExt;
+use bevy_math::prelude::*;
+
+use crate::{CurrentTransform, Velocity, AABB};
+
+/// What happens to bodies whose center leaves the [`WorldBounds`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum OutOfBoundsBehavior {
+    /// Despawns the body along with its children
+    #[default]
+    Despawn,
+    /// Moves the body back inside, and stops it from moving further out. Bodies with a non-finite position or velocity get reset to the center at rest.
+    Clamp,
+    /// Fires an [`OutOfBoundsEvent`] every tick the body is outside, and leaves the rest to the game
+    Event
+}
+
+/// Optional resource that keeps bodies from falling out of the level forever.
+/// Bodies with a non-finite position count as out of bounds too.
+#[derive(Resource, Debug, Copy, Clone, PartialEq)]
+pub struct WorldBounds {
+    pub aabb: AABB,
+    pub behavior: OutOfBoundsBehavior
+}
+impl WorldBounds {
+    pub fn new(aabb: AABB, behavior: OutOfBoundsBehavior) -> Self {
+        Self { aabb, behavior }
+    }
+
+    /// True if the point specified is inside the bounds.
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.aabb.contains_point(point)
+    }
+}
+
+/// Event fired for a body outside of the [`WorldBounds`] when using [`OutOfBoundsBehavior::Event`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct OutOfBoundsEvent(pub Entity);
+
+/// Despawns, clamps or reports bodies outside of the [`WorldBounds`], if any.
+pub(crate) fn enforce_world_bounds(
+    mut commands: Commands,
+    bounds: Option<Res<WorldBounds>>,
+    mut bodies: Query<(Entity, &mut CurrentTransform, &mut Velocity)>,
+    mut event_writer: EventWriter<OutOfBoundsEvent>
+) {
+    let Some(bounds) = bounds else { return };
+    for (entity, mut trans, mut vel) in &mut bodies {
+        let position = trans.0.translation;
+        if bounds.contains(position) {
+            continue;
+        }
+        match bounds.behavior {
+            OutOfBoundsBehavior::Despawn => commands.entity(entity).despawn_recursive(),
+            OutOfBoundsBehavior::Clamp => {
+                let min = bounds.aabb.center - bounds.aabb.half_extents;
+                let max = bounds.aabb.center + bounds.aabb.half_extents;
+                if !position.is_finite() || !vel.0.is_finite() {
+                    trans.0.translation = bounds.aabb.center;
+                    vel.0 = Vec3::ZERO;
+                    continue;
+                }
+                trans.0.translation = position.clamp(min, max);
+
+                // Keeps velocity along the bounds, but not out of them
+                let below = position.cmplt(min) & vel.0.cmplt(Vec3::ZERO);
+                let above = position.cmpgt(max) & vel.0.cmpgt(Vec3::ZERO);
+                vel.0 = Vec3::select(below | above, Vec3::ZERO, vel.0);
+            },
+            OutOfBoundsBehavior::Event => event_writer.send(OutOfBoundsEvent(entity))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use bevy_ecs::event::Events;
+    use bevy_ecs::prelude::*;
+    use bevy_hierarchy::BuildWorldChildren;
+    use bevy_math::prelude::*;
+    use bevy_transform::prelude::*;
+
+    use crate::*;
+
+    fn falling_app(behavior: OutOfBoundsBehavior) -> (App, Entity) {
+        let mut app = physics_test_app();
+        app.insert_resource(Gravity(Vec3::new(0.0, -0.5, 0.0)));
+        app.insert_resource(WorldBounds::new(AABB::new(Vec3::ZERO, Vec3::splat(10.0)), behavior));
+        let body = app.world
+            .spawn(PhysicsBundle {
+                config: CollisionConfig::new(GROUP_BASIC, GROUP_ALL),
+                ..PhysicsBundle::new(Transform::from_xyz(1.0, 0.0, 0.0), HalfExtents::new(1.0, 1.0, 1.0), Shape::Cuboid)
+                    .with_velocity(Velocity(Vec3::new(0.1, 0.0, 0.0)))
+            })
+            .id();
+        (app, body)
+    }
+
+    #[test]
+    fn out_of_bounds_bodies_get_despawned() {
+        let (mut app, body) = falling_app(OutOfBoundsBehavior::Despawn);
+        let child = app.world.spawn(Transform::default()).id();
+        app.world.entity_mut(body).push_children(&[child]);
+        for _ in 0..5 {
+            app.update();
+        }
+        assert!(app.world.get_entity(body).is_some());
+        for _ in 0..5 {
+            app.update();
+        }
+        assert!(app.world.get_entity(body).is_none());
+        assert!(app.world.get_entity(child).is_none());
+    }
+
+    #[test]
+    fn out_of_bounds_bodies_get_clamped() {
+        let (mut app, body) = falling_app(OutOfBoundsBehavior::Clamp);
+        for _ in 0..20 {
+            app.update();
+        }
+        let position = app.world.get::<CurrentTransform>(body).unwrap().0.translation;
+        assert_eq!(-10.0, position.y);
+        assert!(position.x > 2.0);
+
+        // Keeps sliding along the bottom without falling further
+        let vel = app.world.get::<Velocity>(body).unwrap().0;
+        assert_eq!(0.1, vel.x);
+        assert!(vel.y >= -0.5);
+    }
+
+    #[test]
+    fn out_of_bounds_bodies_fire_events() {
+        let (mut app, body) = falling_app(OutOfBoundsBehavior::Event);
+        let mut fired = Vec::new();
+        for tick in 0..10 {
+            app.update();
+            let events = app.world.resource::<Events<OutOfBoundsEvent>>();
+            if events.iter_current_update_events().any(|event| event.0 == body) {
+                fired.push(tick);
+            }
+        }
+        assert_eq!(vec![5, 6, 7, 8, 9], fired);
+        assert!(app.world.get::<CurrentTransform>(body).unwrap().0.translation.y < -10.0);
+    }
+}
@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use vidya_fixed_timestep::prelude::*;
+
+#[path = "../examples/platformer_starter/starter.rs"]
+mod starter;
+
+use starter::*;
+
+/// Runs the platformer starter without a window or renderer for 60 frames.
+#[test]
+fn platformer_starter_runs_headless() {
+    let mut app = App::new();
+    app
+        .add_plugins(MinimalPlugins)
+        .add_plugin(InputPlugin)
+        .add_plugin(StarterPlugin);
+    for _ in 0..60 {
+        std::thread::sleep(Duration::from_secs_f64(1.0/60.0));
+        app.update();
+    }
+
+    // Character fell onto the floor of the first level
+    let mut characters = app.world.query_filtered::<&CurrentTransform, With<Character>>();
+    let character = characters.single(&app.world).0.translation;
+    assert!((-0.1..0.1).contains(&character.y), "{character}");
+}